@@ -1,7 +1,12 @@
 use super::{
-    config::{Config, ResourceType},
+    config::{Config, Predicate, ResourceType},
     InMemoryCache,
 };
+use std::{sync::Arc, time::Duration};
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker},
+    Id,
+};
 
 /// Builder to configure and construct an [`InMemoryCache`].
 #[derive(Debug, Default)]
@@ -19,6 +24,97 @@ impl InMemoryCacheBuilder {
         InMemoryCache::new_with_config(self.0)
     }
 
+    /// Sets the number of messages to cache in a channel, overriding the
+    /// guild's and the default message cache size.
+    ///
+    /// A size of 0 disables caching messages in the channel.
+    pub fn channel_message_cache_size(
+        mut self,
+        channel_id: Id<ChannelMarker>,
+        message_cache_size: usize,
+    ) -> Self {
+        self.0
+            .channel_message_cache_sizes_mut()
+            .insert(channel_id, message_cache_size);
+
+        self
+    }
+
+    /// Sets a predicate deciding whether to cache the messages of a channel.
+    ///
+    /// Messages of channels the predicate returns `false` for aren't cached,
+    /// and neither are their reactions. Messages that are already cached are
+    /// still updated and removed.
+    ///
+    /// Defaults to caching the messages of all channels.
+    pub fn channel_message_filter(
+        mut self,
+        filter: impl Fn(Id<ChannelMarker>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.0.channel_message_filter = Some(Predicate(Arc::new(filter)));
+
+        self
+    }
+
+    /// Sets the number of messages to cache per channel of a guild, overriding
+    /// the default message cache size.
+    ///
+    /// A size of 0 disables caching messages in the guild.
+    pub fn guild_message_cache_size(
+        mut self,
+        guild_id: Id<GuildMarker>,
+        message_cache_size: usize,
+    ) -> Self {
+        self.0
+            .guild_message_cache_sizes_mut()
+            .insert(guild_id, message_cache_size);
+
+        self
+    }
+
+    /// Sets a predicate returning the resource types to cache for a guild.
+    ///
+    /// Resources of the guild, such as its channels, members, and presences,
+    /// are only cached if their type is returned by the predicate and is one
+    /// of the [`resource_types`] for all guilds. Resources that are already
+    /// cached are still removed when they're deleted, so the predicate can
+    /// change what it returns over time without leaving deleted resources in
+    /// the cache.
+    ///
+    /// Defaults to the resource types for all guilds.
+    ///
+    /// # Examples
+    ///
+    /// Only cache members and presences of premium guilds:
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    /// use twilight_cache_inmemory::{InMemoryCache, ResourceType};
+    /// use twilight_model::id::Id;
+    ///
+    /// let premium_guilds = HashSet::from([Id::new(1), Id::new(2)]);
+    ///
+    /// let cache = InMemoryCache::builder()
+    ///     .guild_resource_types(move |guild_id| {
+    ///         if premium_guilds.contains(&guild_id) {
+    ///             ResourceType::all()
+    ///         } else {
+    ///             ResourceType::all() - ResourceType::MEMBER - ResourceType::PRESENCE
+    ///         }
+    ///     })
+    ///     .build();
+    /// ```
+    ///
+    /// [`resource_types`]: Self::resource_types
+    pub fn guild_resource_types(
+        mut self,
+        resource_types: impl Fn(Id<GuildMarker>) -> ResourceType + Send + Sync + 'static,
+    ) -> Self {
+        self.0.guild_resource_types = Some(Predicate(Arc::new(resource_types)));
+
+        self
+    }
+
     /// Sets the list of resource types for the cache to handle.
     ///
     /// Defaults to all types.
@@ -30,12 +126,52 @@ impl InMemoryCacheBuilder {
 
     /// Sets the number of messages to cache per channel.
     ///
+    /// A size of 0 disables caching messages in channels without a guild or
+    /// channel message cache size.
+    ///
     /// Defaults to 100.
     pub const fn message_cache_size(mut self, message_cache_size: usize) -> Self {
         self.0.message_cache_size = message_cache_size;
 
         self
     }
+
+    /// Sets the approximate maximum number of bytes used by messages across
+    /// all channels.
+    ///
+    /// Once exceeded the least recently created or updated messages are
+    /// evicted, regardless of their channel.
+    ///
+    /// Defaults to no limit.
+    pub const fn message_cache_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.0.message_cache_max_bytes = Some(max_bytes);
+
+        self
+    }
+
+    /// Sets the maximum number of messages to cache across all channels.
+    ///
+    /// Once exceeded the least recently created or updated messages are
+    /// evicted, regardless of their channel.
+    ///
+    /// Defaults to no limit.
+    pub const fn message_cache_max_messages(mut self, max_messages: usize) -> Self {
+        self.0.message_cache_max_messages = Some(max_messages);
+
+        self
+    }
+
+    /// Sets the duration after which messages that haven't been created or
+    /// updated expire.
+    ///
+    /// Expired messages are evicted when a message is next cached.
+    ///
+    /// Defaults to no expiry.
+    pub const fn message_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.0.message_cache_ttl = Some(ttl);
+
+        self
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,339 @@
+//! Changes made to cached resources by events.
+
+use crate::{
+    model::{CachedGuild, CachedMember, CachedMessage, CachedVoiceState},
+    GuildResource, InMemoryCache,
+};
+use twilight_model::{
+    channel::Channel,
+    gateway::event::Event,
+    guild::Role,
+    id::{
+        marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Cached value of a resource before and after an event was processed.
+///
+/// A resource that was newly cached has no value before the event, and a
+/// resource that was removed from the cache has no value after it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change<T> {
+    /// Value after the event was processed, if any.
+    pub after: Option<T>,
+    /// Value before the event was processed, if any.
+    pub before: Option<T>,
+}
+
+impl<T> Change<T> {
+    /// Whether the resource was newly cached.
+    pub const fn is_created(&self) -> bool {
+        self.before.is_none() && self.after.is_some()
+    }
+
+    /// Whether the resource was removed from the cache.
+    pub const fn is_deleted(&self) -> bool {
+        self.before.is_some() && self.after.is_none()
+    }
+
+    /// Whether a cached resource was updated.
+    pub const fn is_updated(&self) -> bool {
+        self.before.is_some() && self.after.is_some()
+    }
+}
+
+/// Change to a cached resource made by an event.
+///
+/// Returned by [`InMemoryCache::update_with_changes`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum CacheChange {
+    /// Channel or thread was created, updated, or deleted.
+    Channel(Change<Channel>),
+    /// Guild was updated or deleted.
+    Guild(Change<CachedGuild>),
+    /// Member was added, updated, or removed.
+    Member(Change<CachedMember>),
+    /// Message was created, updated, deleted, or had its reactions changed.
+    Message(Change<CachedMessage>),
+    /// Role was created, updated, or deleted.
+    Role(Change<GuildResource<Role>>),
+    /// Voice state was created, updated, or removed.
+    VoiceState(Change<CachedVoiceState>),
+}
+
+/// Key of a resource that an event may change.
+#[derive(Clone, Copy, Debug)]
+enum ChangeKey {
+    Channel(Id<ChannelMarker>),
+    Guild(Id<GuildMarker>),
+    Member(Id<GuildMarker>, Id<UserMarker>),
+    Message(Id<MessageMarker>),
+    Role(Id<RoleMarker>),
+    VoiceState(Id<GuildMarker>, Id<UserMarker>),
+}
+
+impl ChangeKey {
+    /// Keys of the resources an event may change.
+    fn of(event: &Event) -> Vec<Self> {
+        match event {
+            Event::ChannelCreate(channel) => Vec::from([Self::Channel(channel.id)]),
+            Event::ChannelDelete(channel) => Vec::from([Self::Channel(channel.id)]),
+            Event::ChannelUpdate(channel) => Vec::from([Self::Channel(channel.id)]),
+            Event::GuildDelete(guild) => Vec::from([Self::Guild(guild.id)]),
+            Event::GuildUpdate(guild) => Vec::from([Self::Guild(guild.id)]),
+            Event::MemberAdd(member) => Vec::from([Self::Member(member.guild_id, member.user.id)]),
+            Event::MemberRemove(member) => {
+                Vec::from([Self::Member(member.guild_id, member.user.id)])
+            }
+            Event::MemberUpdate(member) => {
+                Vec::from([Self::Member(member.guild_id, member.user.id)])
+            }
+            Event::MessageCreate(message) => Vec::from([Self::Message(message.id)]),
+            Event::MessageDelete(message) => Vec::from([Self::Message(message.id)]),
+            Event::MessageDeleteBulk(messages) => {
+                messages.ids.iter().copied().map(Self::Message).collect()
+            }
+            Event::MessageUpdate(message) => Vec::from([Self::Message(message.id)]),
+            Event::ReactionAdd(reaction) => Vec::from([Self::Message(reaction.message_id)]),
+            Event::ReactionRemove(reaction) => Vec::from([Self::Message(reaction.message_id)]),
+            Event::ReactionRemoveAll(reaction) => Vec::from([Self::Message(reaction.message_id)]),
+            Event::ReactionRemoveEmoji(reaction) => Vec::from([Self::Message(reaction.message_id)]),
+            Event::RoleCreate(role) => Vec::from([Self::Role(role.role.id)]),
+            Event::RoleDelete(role) => Vec::from([Self::Role(role.role_id)]),
+            Event::RoleUpdate(role) => Vec::from([Self::Role(role.role.id)]),
+            Event::ThreadCreate(thread) => Vec::from([Self::Channel(thread.id)]),
+            Event::ThreadDelete(thread) => Vec::from([Self::Channel(thread.id)]),
+            Event::ThreadUpdate(thread) => Vec::from([Self::Channel(thread.id)]),
+            Event::VoiceStateUpdate(update) => update
+                .0
+                .guild_id
+                .map(|guild_id| Self::VoiceState(guild_id, update.0.user_id))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Cloned value of a resource in the cache.
+#[derive(Debug)]
+enum Snapshot {
+    Channel(Option<Channel>),
+    Guild(Option<CachedGuild>),
+    Member(Option<CachedMember>),
+    Message(Option<CachedMessage>),
+    Role(Option<GuildResource<Role>>),
+    VoiceState(Option<CachedVoiceState>),
+}
+
+impl Snapshot {
+    /// Clone the cached value of a resource.
+    fn take(cache: &InMemoryCache, key: ChangeKey) -> Self {
+        match key {
+            ChangeKey::Channel(id) => {
+                Self::Channel(cache.channels.get(&id).map(|r| r.value().clone()))
+            }
+            ChangeKey::Guild(id) => Self::Guild(cache.guilds.get(&id).map(|r| r.value().clone())),
+            ChangeKey::Member(guild_id, user_id) => Self::Member(
+                cache
+                    .members
+                    .get(&(guild_id, user_id))
+                    .map(|r| r.value().clone()),
+            ),
+            ChangeKey::Message(id) => {
+                Self::Message(cache.messages.get(&id).map(|r| r.value().clone()))
+            }
+            ChangeKey::Role(id) => Self::Role(cache.roles.get(&id).map(|r| r.value().clone())),
+            ChangeKey::VoiceState(guild_id, user_id) => Self::VoiceState(
+                cache
+                    .voice_states
+                    .get(&(guild_id, user_id))
+                    .map(|r| r.value().clone()),
+            ),
+        }
+    }
+
+    /// Combine the snapshots of a resource before and after an event into a
+    /// change, if the resource changed.
+    fn into_change(self, after: Self) -> Option<CacheChange> {
+        fn change<T: PartialEq>(before: Option<T>, after: Option<T>) -> Option<Change<T>> {
+            (before != after).then(|| Change { after, before })
+        }
+
+        match (self, after) {
+            (Self::Channel(before), Self::Channel(after)) => {
+                change(before, after).map(CacheChange::Channel)
+            }
+            (Self::Guild(before), Self::Guild(after)) => {
+                change(before, after).map(CacheChange::Guild)
+            }
+            (Self::Member(before), Self::Member(after)) => {
+                change(before, after).map(CacheChange::Member)
+            }
+            (Self::Message(before), Self::Message(after)) => {
+                change(before, after).map(CacheChange::Message)
+            }
+            (Self::Role(before), Self::Role(after)) => change(before, after).map(CacheChange::Role),
+            (Self::VoiceState(before), Self::VoiceState(after)) => {
+                change(before, after).map(CacheChange::VoiceState)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl InMemoryCache {
+    /// Update the cache with an event from the gateway, returning the changes
+    /// it made to cached resources.
+    ///
+    /// Changes include the cached value of a resource before the event was
+    /// processed and its value afterwards, such as the content of a message
+    /// before it was edited or the member that was removed. Resources that
+    /// weren't changed, such as those of a disabled [`ResourceType`], are
+    /// omitted.
+    ///
+    /// Changes are reported for channels, guilds, members, messages, roles,
+    /// and voice states directly referenced by the event. Resources removed
+    /// as a side effect, such as the channels of a deleted guild or messages
+    /// evicted from a full message cache, aren't reported.
+    ///
+    /// The values before and after are read without locking the cache for the
+    /// duration of the update, so changes are only exact when events are
+    /// processed one at a time. If another event concerning the same resource
+    /// is processed concurrently, such as by another task sharing the cache,
+    /// its effects may be included in the change as well.
+    ///
+    /// # Examples
+    ///
+    /// Log the previous content of edited messages:
+    ///
+    /// ```no_run
+    /// use twilight_cache_inmemory::{CacheChange, InMemoryCache};
+    /// # use twilight_model::gateway::event::Event;
+    /// # let event: Event = unimplemented!();
+    ///
+    /// let cache = InMemoryCache::new();
+    ///
+    /// for change in cache.update_with_changes(&event) {
+    ///     if let CacheChange::Message(change) = change {
+    ///         if let (Some(before), Some(after)) = (change.before, change.after) {
+    ///             println!("message edited from {:?} to {:?}", before.content(), after.content());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`ResourceType`]: crate::ResourceType
+    pub fn update_with_changes(&self, event: &Event) -> Vec<CacheChange> {
+        let keys = ChangeKey::of(event);
+        let before = keys
+            .iter()
+            .map(|key| Snapshot::take(self, *key))
+            .collect::<Vec<_>>();
+
+        self.update(event);
+
+        keys.into_iter()
+            .zip(before)
+            .filter_map(|(key, before)| before.into_change(Snapshot::take(self, key)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheChange, Change};
+    use crate::{test, InMemoryCache};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+    use twilight_model::{
+        gateway::{
+            event::Event,
+            payload::incoming::{MemberRemove, MessageDelete, RoleDelete, RoleUpdate},
+        },
+        id::Id,
+    };
+
+    assert_impl_all!(CacheChange: Clone, Debug, PartialEq, Send, Sync);
+    assert_impl_all!(Change<()>: Clone, Debug, Eq, PartialEq, Send, Sync);
+
+    #[test]
+    fn role_update_and_delete() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+        let role = test::role(Id::new(2));
+        cache.cache_roles(guild_id, [role.clone()]);
+
+        let mut renamed = role.clone();
+        renamed.name = "renamed".to_owned();
+        let changes = cache.update_with_changes(&Event::RoleUpdate(RoleUpdate {
+            guild_id,
+            role: renamed,
+        }));
+
+        assert_eq!(1, changes.len());
+        let change = match &changes[0] {
+            CacheChange::Role(change) => change,
+            other => panic!("expected role change, got {other:?}"),
+        };
+        assert!(change.is_updated());
+        assert_eq!(role.name, change.before.as_ref().unwrap().name);
+        assert_eq!("renamed", change.after.as_ref().unwrap().name);
+
+        let changes = cache.update_with_changes(&Event::RoleDelete(RoleDelete {
+            guild_id,
+            role_id: role.id,
+        }));
+        assert!(matches!(&changes[..], [CacheChange::Role(change)] if change.is_deleted()));
+
+        // Nothing left to change.
+        let changes = cache.update_with_changes(&Event::RoleDelete(RoleDelete {
+            guild_id,
+            role_id: role.id,
+        }));
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn member_remove() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+        let user_id = Id::new(2);
+        cache.cache_member(guild_id, test::member(user_id, guild_id));
+
+        let changes = cache.update_with_changes(&Event::MemberRemove(MemberRemove {
+            guild_id,
+            user: test::user(user_id),
+        }));
+
+        match &changes[..] {
+            [CacheChange::Member(change)] => {
+                assert!(change.is_deleted());
+                assert_eq!(user_id, change.before.as_ref().unwrap().user_id());
+            }
+            other => panic!("expected member change, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn message_delete() {
+        let cache = test::cache_with_message_and_reactions();
+        let message = cache.messages.iter().next().unwrap().value().clone();
+
+        let changes = cache.update_with_changes(&Event::MessageDelete(MessageDelete {
+            channel_id: message.channel_id(),
+            guild_id: message.guild_id(),
+            id: message.id(),
+        }));
+
+        assert_eq!(
+            Vec::from([CacheChange::Message(Change {
+                after: None,
+                before: Some(message),
+            })]),
+            changes
+        );
+    }
+}
@@ -1,4 +1,14 @@
 use bitflags::bitflags;
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    sync::Arc,
+    time::Duration,
+};
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker},
+    Id,
+};
 
 bitflags! {
     /// A set of bitflags which can be used to specify what resource to process
@@ -36,6 +46,41 @@ bitflags! {
         const INTEGRATION = 1 << 12;
         /// Information relating to guild stickers.
         const STICKER = 1 << 13;
+        /// Information relating to thread members.
+        const THREAD_MEMBER = 1 << 14;
+        /// Information relating to guild invites.
+        const INVITE = 1 << 15;
+        /// Information relating to auto moderation rules.
+        const AUTO_MODERATION_RULE = 1 << 16;
+    }
+}
+
+/// Predicate returning the resource types to cache for a guild.
+type GuildResourceTypes = dyn Fn(Id<GuildMarker>) -> ResourceType + Send + Sync;
+
+/// Predicate deciding whether to cache the messages of a channel.
+type ChannelMessageFilter = dyn Fn(Id<ChannelMarker>) -> bool + Send + Sync;
+
+/// Shared predicate function, compared by identity.
+pub(super) struct Predicate<F: ?Sized>(pub(super) Arc<F>);
+
+impl<F: ?Sized> Clone for Predicate<F> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> Debug for Predicate<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("Predicate")
+    }
+}
+
+impl<F: ?Sized> Eq for Predicate<F> {}
+
+impl<F: ?Sized> PartialEq for Predicate<F> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
     }
 }
 
@@ -44,6 +89,13 @@ bitflags! {
 /// [`InMemoryCache`]: crate::InMemoryCache
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Config {
+    pub(super) channel_message_cache_sizes: Option<HashMap<Id<ChannelMarker>, usize>>,
+    pub(super) channel_message_filter: Option<Predicate<ChannelMessageFilter>>,
+    pub(super) guild_message_cache_sizes: Option<HashMap<Id<GuildMarker>, usize>>,
+    pub(super) guild_resource_types: Option<Predicate<GuildResourceTypes>>,
+    pub(super) message_cache_max_bytes: Option<usize>,
+    pub(super) message_cache_max_messages: Option<usize>,
+    pub(super) message_cache_ttl: Option<Duration>,
     pub(super) resource_types: ResourceType,
     pub(super) message_cache_size: usize,
 }
@@ -54,11 +106,103 @@ impl Config {
     /// Refer to individual getters for their defaults.
     pub const fn new() -> Self {
         Self {
+            channel_message_cache_sizes: None,
+            channel_message_filter: None,
+            guild_message_cache_sizes: None,
+            guild_resource_types: None,
+            message_cache_max_bytes: None,
+            message_cache_max_messages: None,
+            message_cache_ttl: None,
             resource_types: ResourceType::all(),
             message_cache_size: 100,
         }
     }
 
+    /// Returns whether the messages of a channel are cached.
+    ///
+    /// Defaults to caching the messages of all channels.
+    pub fn channel_message_filter(&self, channel_id: Id<ChannelMarker>) -> bool {
+        self.channel_message_filter
+            .as_ref()
+            .map_or(true, |filter| (filter.0)(channel_id))
+    }
+
+    /// Returns the message cache size overriding the default for a channel,
+    /// if any.
+    ///
+    /// Defaults to no overrides.
+    pub fn channel_message_cache_size(&self, channel_id: Id<ChannelMarker>) -> Option<usize> {
+        self.channel_message_cache_sizes
+            .as_ref()
+            .and_then(|sizes| sizes.get(&channel_id))
+            .copied()
+    }
+
+    /// Returns a mutable reference to the message cache sizes overriding the
+    /// default for channels.
+    pub fn channel_message_cache_sizes_mut(&mut self) -> &mut HashMap<Id<ChannelMarker>, usize> {
+        self.channel_message_cache_sizes
+            .get_or_insert_with(HashMap::new)
+    }
+
+    /// Returns the message cache size overriding the default for the channels
+    /// of a guild, if any.
+    ///
+    /// Defaults to no overrides.
+    pub fn guild_message_cache_size(&self, guild_id: Id<GuildMarker>) -> Option<usize> {
+        self.guild_message_cache_sizes
+            .as_ref()
+            .and_then(|sizes| sizes.get(&guild_id))
+            .copied()
+    }
+
+    /// Returns a mutable reference to the message cache sizes overriding the
+    /// default for the channels of guilds.
+    pub fn guild_message_cache_sizes_mut(&mut self) -> &mut HashMap<Id<GuildMarker>, usize> {
+        self.guild_message_cache_sizes
+            .get_or_insert_with(HashMap::new)
+    }
+
+    /// Returns the resource types enabled for a guild.
+    ///
+    /// Defaults to the [resource types enabled] for all guilds.
+    ///
+    /// [resource types enabled]: Self::resource_types
+    pub fn guild_resource_types(&self, guild_id: Id<GuildMarker>) -> ResourceType {
+        self.guild_resource_types
+            .as_ref()
+            .map_or(self.resource_types, |resource_types| {
+                self.resource_types & (resource_types.0)(guild_id)
+            })
+    }
+
+    /// Returns the approximate maximum number of bytes used by messages across
+    /// all channels.
+    ///
+    /// Defaults to no limit.
+    pub const fn message_cache_max_bytes(&self) -> Option<usize> {
+        self.message_cache_max_bytes
+    }
+
+    /// Returns a mutable reference to the approximate maximum number of bytes
+    /// used by messages across all channels.
+    pub fn message_cache_max_bytes_mut(&mut self) -> &mut Option<usize> {
+        &mut self.message_cache_max_bytes
+    }
+
+    /// Returns the maximum number of messages across all channels.
+    ///
+    /// Defaults to no limit.
+    pub const fn message_cache_max_messages(&self) -> Option<usize> {
+        self.message_cache_max_messages
+    }
+
+    /// Returns a mutable reference to the maximum number of messages across
+    /// all channels.
+    pub fn message_cache_max_messages_mut(&mut self) -> &mut Option<usize> {
+        &mut self.message_cache_max_messages
+    }
+
     /// Returns an immutable reference to the message cache size.
     ///
     /// Defaults to 100.
@@ -70,6 +214,21 @@ impl Config {
     pub fn message_cache_size_mut(&mut self) -> &mut usize {
         &mut self.message_cache_size
     }
+
+    /// Returns the duration after which messages that haven't been created or
+    /// updated expire.
+    ///
+    /// Defaults to no expiry.
+    pub const fn message_cache_ttl(&self) -> Option<Duration> {
+        self.message_cache_ttl
+    }
+
+    /// Returns a mutable reference to the duration after which messages that
+    /// haven't been created or updated expire.
+    pub fn message_cache_ttl_mut(&mut self) -> &mut Option<Duration> {
+        &mut self.message_cache_ttl
+    }
+
     /// Returns an immutable reference to the resource types enabled.
     ///
     /// Defaults to all resource types.
@@ -83,6 +242,28 @@ impl Config {
     }
 }
 
+impl Config {
+    /// Number of messages to cache in a channel, taking overrides for the
+    /// channel and its guild into account.
+    pub(crate) fn message_cache_size_in(
+        &self,
+        guild_id: Option<Id<GuildMarker>>,
+        channel_id: Id<ChannelMarker>,
+    ) -> usize {
+        self.channel_message_cache_size(channel_id)
+            .or_else(|| guild_id.and_then(|guild_id| self.guild_message_cache_size(guild_id)))
+            .unwrap_or(self.message_cache_size)
+    }
+
+    /// Whether messages need to be tracked across channels to enforce a global
+    /// budget or expiry.
+    pub(crate) const fn tracks_messages(&self) -> bool {
+        self.message_cache_max_bytes.is_some()
+            || self.message_cache_max_messages.is_some()
+            || self.message_cache_ttl.is_some()
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self::new()
@@ -91,19 +272,85 @@ impl Default for Config {
 
 #[cfg(test)]
 mod tests {
-    use super::{Config, ResourceType};
+    use super::{Config, Predicate, ResourceType};
     use static_assertions::assert_fields;
+    use std::sync::Arc;
+    use twilight_model::id::Id;
 
-    assert_fields!(Config: resource_types, message_cache_size);
+    assert_fields!(
+        Config: channel_message_cache_sizes,
+        channel_message_filter,
+        guild_message_cache_sizes,
+        guild_resource_types,
+        message_cache_max_bytes,
+        message_cache_max_messages,
+        message_cache_ttl,
+        resource_types,
+        message_cache_size
+    );
 
     #[test]
     fn defaults() {
         let conf = Config {
+            channel_message_cache_sizes: None,
+            channel_message_filter: None,
+            guild_message_cache_sizes: None,
+            guild_resource_types: None,
+            message_cache_max_bytes: None,
+            message_cache_max_messages: None,
+            message_cache_ttl: None,
             resource_types: ResourceType::all(),
             message_cache_size: 100,
         };
         let default = Config::default();
         assert_eq!(conf.resource_types, default.resource_types);
         assert_eq!(conf.message_cache_size, default.message_cache_size);
+        assert_eq!(conf, default);
+    }
+
+    #[test]
+    fn message_cache_size_overrides() {
+        let mut config = Config::new();
+        config
+            .guild_message_cache_sizes_mut()
+            .insert(Id::new(1), 10);
+        config
+            .channel_message_cache_sizes_mut()
+            .insert(Id::new(2), 5);
+
+        assert_eq!(
+            5,
+            config.message_cache_size_in(Some(Id::new(1)), Id::new(2))
+        );
+        assert_eq!(
+            10,
+            config.message_cache_size_in(Some(Id::new(1)), Id::new(3))
+        );
+        assert_eq!(100, config.message_cache_size_in(None, Id::new(3)));
+    }
+
+    #[test]
+    fn predicates() {
+        let mut config = Config::new();
+        config.resource_types = ResourceType::GUILD | ResourceType::MEMBER;
+        config.guild_resource_types = Some(Predicate(Arc::new(|guild_id| {
+            if guild_id == Id::new(1) {
+                ResourceType::all()
+            } else {
+                ResourceType::GUILD
+            }
+        })));
+        config.channel_message_filter =
+            Some(Predicate(Arc::new(|channel_id| channel_id != Id::new(2))));
+
+        assert_eq!(
+            ResourceType::GUILD | ResourceType::MEMBER,
+            config.guild_resource_types(Id::new(1))
+        );
+        assert_eq!(ResourceType::GUILD, config.guild_resource_types(Id::new(3)));
+        assert!(!config.channel_message_filter(Id::new(2)));
+        assert!(config.channel_message_filter(Id::new(3)));
+        assert_eq!(config.clone(), config);
+        assert_ne!(Config::new(), config);
     }
 }
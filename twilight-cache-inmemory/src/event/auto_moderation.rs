@@ -0,0 +1,211 @@
+use crate::{config::ResourceType, InMemoryCache, UpdateCache};
+use twilight_model::{
+    gateway::payload::incoming::{
+        AutoModerationRuleCreate, AutoModerationRuleDelete, AutoModerationRuleUpdate,
+    },
+    guild::auto_moderation::AutoModerationRule,
+    id::{
+        marker::{AutoModerationRuleMarker, GuildMarker},
+        Id,
+    },
+};
+
+impl InMemoryCache {
+    /// Seed the cache with a guild's auto moderation rules.
+    ///
+    /// Auto moderation rules are not sent in [`GuildCreate`] events, so rules
+    /// that existed before the current session are only cached once they are
+    /// fetched, such as with `Client::auto_moderation_rules`. The provided
+    /// rules are treated as the guild's complete list: cached rules of the
+    /// guild that are not in it are removed. Rules belonging to other guilds
+    /// are ignored.
+    ///
+    /// Requires [`ResourceType::AUTO_MODERATION_RULE`] for the guild,
+    /// otherwise nothing is cached.
+    ///
+    /// [`GuildCreate`]: twilight_model::gateway::payload::incoming::GuildCreate
+    pub fn cache_auto_moderation_rules(
+        &self,
+        guild_id: Id<GuildMarker>,
+        rules: impl IntoIterator<Item = AutoModerationRule>,
+    ) {
+        if !self.wants_in(Some(guild_id), ResourceType::AUTO_MODERATION_RULE) {
+            return;
+        }
+
+        let rules = rules
+            .into_iter()
+            .filter(|rule| rule.guild_id == guild_id)
+            .collect::<Vec<_>>();
+
+        let stale = self
+            .guild_auto_moderation_rules
+            .get(&guild_id)
+            .map(|cached| {
+                cached
+                    .iter()
+                    .copied()
+                    .filter(|id| !rules.iter().any(|rule| rule.id == *id))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for rule_id in stale {
+            self.delete_auto_moderation_rule(rule_id);
+        }
+
+        self.guild_auto_moderation_rules
+            .entry(guild_id)
+            .or_default();
+
+        for rule in rules {
+            self.cache_auto_moderation_rule(rule);
+        }
+    }
+
+    fn cache_auto_moderation_rule(&self, rule: AutoModerationRule) {
+        self.guild_auto_moderation_rules
+            .entry(rule.guild_id)
+            .or_default()
+            .insert(rule.id);
+
+        self.auto_moderation_rules.insert(rule.id, rule);
+    }
+
+    fn delete_auto_moderation_rule(&self, rule_id: Id<AutoModerationRuleMarker>) {
+        if let Some((_, rule)) = self.auto_moderation_rules.remove(&rule_id) {
+            if let Some(mut rules) = self.guild_auto_moderation_rules.get_mut(&rule.guild_id) {
+                rules.remove(&rule_id);
+            }
+        }
+    }
+}
+
+impl UpdateCache for AutoModerationRuleCreate {
+    fn update(&self, cache: &InMemoryCache) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::AUTO_MODERATION_RULE) {
+            return;
+        }
+
+        cache.cache_auto_moderation_rule(self.0.clone());
+    }
+}
+
+impl UpdateCache for AutoModerationRuleDelete {
+    fn update(&self, cache: &InMemoryCache) {
+        if !cache.wants(ResourceType::AUTO_MODERATION_RULE) {
+            return;
+        }
+
+        cache.delete_auto_moderation_rule(self.id);
+    }
+}
+
+impl UpdateCache for AutoModerationRuleUpdate {
+    fn update(&self, cache: &InMemoryCache) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::AUTO_MODERATION_RULE) {
+            return;
+        }
+
+        cache.cache_auto_moderation_rule(self.0.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InMemoryCache;
+    use twilight_model::{
+        gateway::payload::incoming::{
+            AutoModerationRuleCreate, AutoModerationRuleDelete, AutoModerationRuleUpdate,
+            GuildDelete,
+        },
+        guild::auto_moderation::{
+            AutoModerationEventType, AutoModerationRule, AutoModerationTriggerMetadata,
+            AutoModerationTriggerType,
+        },
+        id::Id,
+    };
+
+    fn rule(id: u64) -> AutoModerationRule {
+        AutoModerationRule {
+            actions: Vec::new(),
+            creator_id: Id::new(3),
+            enabled: true,
+            event_type: AutoModerationEventType::MessageSend,
+            exempt_channels: Vec::new(),
+            exempt_roles: Vec::new(),
+            guild_id: Id::new(1),
+            id: Id::new(id),
+            name: "rule".to_owned(),
+            trigger_metadata: AutoModerationTriggerMetadata {
+                allow_list: None,
+                keyword_filter: Some(Vec::from(["keyword".to_owned()])),
+                presets: None,
+            },
+            trigger_type: AutoModerationTriggerType::Keyword,
+        }
+    }
+
+    #[test]
+    fn rule_create_update_delete() {
+        let cache = InMemoryCache::new();
+
+        cache.update(&AutoModerationRuleCreate(rule(2)));
+        assert!(cache
+            .guild_auto_moderation_rules(Id::new(1))
+            .unwrap()
+            .contains(&Id::new(2)));
+
+        let mut updated = rule(2);
+        updated.enabled = false;
+        cache.update(&AutoModerationRuleUpdate(updated));
+        assert!(!cache.auto_moderation_rule(Id::new(2)).unwrap().enabled);
+
+        cache.update(&AutoModerationRuleDelete(rule(2)));
+        assert!(cache.auto_moderation_rule(Id::new(2)).is_none());
+        assert!(cache
+            .guild_auto_moderation_rules(Id::new(1))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn seed_rules() {
+        let cache = InMemoryCache::new();
+        cache.update(&AutoModerationRuleCreate(rule(2)));
+
+        let mut other_guild = rule(5);
+        other_guild.guild_id = Id::new(9);
+        cache.cache_auto_moderation_rules(Id::new(1), [rule(3), rule(4), other_guild]);
+
+        let rules = cache.guild_auto_moderation_rules(Id::new(1)).unwrap();
+        assert_eq!(2, rules.len());
+        assert!(rules.contains(&Id::new(3)) && rules.contains(&Id::new(4)));
+        drop(rules);
+        assert!(cache.auto_moderation_rule(Id::new(2)).is_none());
+        assert!(cache.auto_moderation_rule(Id::new(5)).is_none());
+
+        cache.cache_auto_moderation_rules(Id::new(1), []);
+        assert!(cache
+            .guild_auto_moderation_rules(Id::new(1))
+            .unwrap()
+            .is_empty());
+        assert_eq!(0, cache.stats().auto_moderation_rules());
+    }
+
+    #[test]
+    fn guild_delete() {
+        let cache = InMemoryCache::new();
+        cache.update(&AutoModerationRuleCreate(rule(2)));
+        cache.update(&AutoModerationRuleCreate(rule(3)));
+        assert_eq!(2, cache.stats().auto_moderation_rules());
+
+        cache.update(&GuildDelete {
+            id: Id::new(1),
+            unavailable: false,
+        });
+
+        assert_eq!(0, cache.stats().auto_moderation_rules());
+        assert!(cache.guild_auto_moderation_rules(Id::new(1)).is_none());
+    }
+}
@@ -20,6 +20,7 @@ impl InMemoryCache {
                 .insert(channel.id);
         }
 
+        self.index_channel(channel.id, channel.parent_id);
         self.channels.insert(channel.id, channel);
     }
 
@@ -28,6 +29,9 @@ impl InMemoryCache {
     /// The guild channel data itself and the channel entry in its guild's list
     /// of channels will be deleted.
     pub(crate) fn delete_channel(&self, channel_id: Id<ChannelMarker>) {
+        self.index_channel(channel_id, None);
+        self.channel_children.remove(&channel_id);
+
         if let Some((_, channel)) = self.channels.remove(&channel_id) {
             if let Some(guild_id) = channel.guild_id {
                 let maybe_channels = self.guild_channels.get_mut(&guild_id);
@@ -42,7 +46,7 @@ impl InMemoryCache {
 
 impl UpdateCache for ChannelCreate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::CHANNEL) {
+        if !cache.wants_in(self.guild_id, ResourceType::CHANNEL) {
             return;
         }
 
@@ -62,7 +66,7 @@ impl UpdateCache for ChannelDelete {
 
 impl UpdateCache for ChannelPinsUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::CHANNEL) {
+        if !cache.wants_in(self.guild_id, ResourceType::CHANNEL) {
             return;
         }
 
@@ -74,7 +78,7 @@ impl UpdateCache for ChannelPinsUpdate {
 
 impl UpdateCache for ChannelUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::CHANNEL) {
+        if !cache.wants_in(self.guild_id, ResourceType::CHANNEL) {
             return;
         }
 
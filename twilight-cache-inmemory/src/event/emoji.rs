@@ -62,7 +62,7 @@ impl InMemoryCache {
 
 impl UpdateCache for GuildEmojisUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::EMOJI) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::EMOJI) {
             return;
         }
 
@@ -4,7 +4,7 @@ use crate::{
     InMemoryCache, UpdateCache,
 };
 use dashmap::DashMap;
-use std::{collections::HashSet, hash::Hash};
+use std::{collections::HashSet, hash::Hash, mem};
 use twilight_model::{
     gateway::payload::incoming::{GuildCreate, GuildDelete, GuildUpdate},
     guild::Guild,
@@ -12,66 +12,31 @@ use twilight_model::{
 };
 
 impl InMemoryCache {
-    #[allow(clippy::too_many_lines)]
-    fn cache_guild(&self, guild: Guild) {
-        let Guild {
-            afk_channel_id,
-            afk_timeout,
-            application_id,
-            approximate_member_count: _,
-            approximate_presence_count: _,
-            banner,
-            channels,
-            default_message_notifications,
-            description,
-            discovery_splash,
-            emojis,
-            explicit_content_filter,
-            features,
-            icon,
-            id,
-            joined_at,
-            large,
-            max_members,
-            max_presences,
-            max_video_channel_users,
-            member_count,
-            members,
-            mfa_level,
-            name,
-            nsfw_level,
-            owner_id,
-            owner,
-            permissions,
-            preferred_locale,
-            premium_progress_bar_enabled,
-            premium_subscription_count,
-            premium_tier,
-            presences,
-            roles,
-            rules_channel_id,
-            splash,
-            stage_instances,
-            stickers,
-            system_channel_flags,
-            system_channel_id,
-            threads,
-            unavailable,
-            vanity_url_code,
-            verification_level,
-            voice_states,
-            widget_channel_id,
-            widget_enabled,
-        } = guild;
+    fn cache_guild(&self, mut guild: Guild) {
+        let id = guild.id;
+        let mut channels = mem::take(&mut guild.channels);
+        let emojis = mem::take(&mut guild.emojis);
+        let members = mem::take(&mut guild.members);
+        let presences = mem::take(&mut guild.presences);
+        let roles = mem::take(&mut guild.roles);
+        let stage_instances = mem::take(&mut guild.stage_instances);
+        let stickers = mem::take(&mut guild.stickers);
+        let mut threads = mem::take(&mut guild.threads);
+        let voice_states = mem::take(&mut guild.voice_states);
+
+        if self.wants_in(Some(id), ResourceType::THREAD_MEMBER) {
+            for thread in &threads {
+                if let Some(thread_member) = &thread.member {
+                    self.cache_thread_member(thread.id, thread_member);
+                }
+            }
+        }
 
         // The map and set creation needs to occur first, so caching states and
         // objects always has a place to put them.
-        if self.wants(ResourceType::CHANNEL) {
+        if self.wants_in(Some(id), ResourceType::CHANNEL) {
             self.guild_channels.insert(id, HashSet::new());
 
-            let mut channels = channels;
-            let mut threads = threads;
-
             for channel in &mut channels {
                 channel.guild_id = Some(id);
             }
@@ -84,79 +49,42 @@ impl InMemoryCache {
             self.cache_channels(threads);
         }
 
-        if self.wants(ResourceType::EMOJI) {
+        if self.wants_in(Some(id), ResourceType::EMOJI) {
             self.guild_emojis.insert(id, HashSet::new());
             self.cache_emojis(id, emojis);
         }
 
-        if self.wants(ResourceType::MEMBER) {
+        if self.wants_in(Some(id), ResourceType::MEMBER) {
             self.guild_members.insert(id, HashSet::new());
             self.cache_members(id, members);
         }
 
-        if self.wants(ResourceType::PRESENCE) {
+        if self.wants_in(Some(id), ResourceType::PRESENCE) {
             self.guild_presences.insert(id, HashSet::new());
             self.cache_presences(id, presences.into_iter().map(CachedPresence::from));
         }
 
-        if self.wants(ResourceType::ROLE) {
+        if self.wants_in(Some(id), ResourceType::ROLE) {
             self.guild_roles.insert(id, HashSet::new());
             self.cache_roles(id, roles);
         }
 
-        if self.wants(ResourceType::STICKER) {
+        if self.wants_in(Some(id), ResourceType::STICKER) {
             self.guild_stage_instances.insert(id, HashSet::new());
             self.cache_stickers(id, stickers);
         }
 
-        if self.wants(ResourceType::VOICE_STATE) {
+        if self.wants_in(Some(id), ResourceType::VOICE_STATE) {
             self.voice_state_guilds.insert(id, HashSet::new());
             self.cache_voice_states(voice_states);
         }
 
-        if self.wants(ResourceType::STAGE_INSTANCE) {
+        if self.wants_in(Some(id), ResourceType::STAGE_INSTANCE) {
             self.guild_stage_instances.insert(id, HashSet::new());
             self.cache_stage_instances(id, stage_instances);
         }
 
-        let guild = CachedGuild {
-            afk_channel_id,
-            afk_timeout,
-            application_id,
-            banner,
-            default_message_notifications,
-            description,
-            discovery_splash,
-            explicit_content_filter,
-            features,
-            icon,
-            id,
-            joined_at,
-            large,
-            max_members,
-            max_presences,
-            max_video_channel_users,
-            member_count,
-            mfa_level,
-            name,
-            nsfw_level,
-            owner_id,
-            owner,
-            permissions,
-            preferred_locale,
-            premium_progress_bar_enabled,
-            premium_subscription_count,
-            premium_tier,
-            rules_channel_id,
-            splash,
-            system_channel_id,
-            system_channel_flags,
-            unavailable,
-            vanity_url_code,
-            verification_level,
-            widget_channel_id,
-            widget_enabled,
-        };
+        let guild = CachedGuild::from_model(guild);
 
         self.unavailable_guilds.remove(&guild.id());
         self.guilds.insert(guild.id(), guild);
@@ -187,14 +115,45 @@ impl InMemoryCache {
             self.guilds.remove(&id);
         }
 
+        if self.wants(ResourceType::THREAD_MEMBER) {
+            let thread_ids = self
+                .guild_channels
+                .get(&id)
+                .map(|channel_ids| channel_ids.iter().copied().collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            for thread_id in thread_ids {
+                self.delete_thread_members(thread_id);
+            }
+        }
+
+        if self.wants(ResourceType::AUTO_MODERATION_RULE) {
+            remove_ids(
+                &self.guild_auto_moderation_rules,
+                &self.auto_moderation_rules,
+                id,
+            );
+        }
+
         if self.wants(ResourceType::CHANNEL) {
-            remove_ids(&self.guild_channels, &self.channels, id);
+            if let Some((_, ids)) = self.guild_channels.remove(&id) {
+                for channel_id in ids {
+                    self.channels.remove(&channel_id);
+                    self.channel_children.remove(&channel_id);
+                }
+            }
         }
 
         if self.wants(ResourceType::EMOJI) {
             remove_ids(&self.guild_emojis, &self.emojis, id);
         }
 
+        if self.wants(ResourceType::INVITE) {
+            remove_ids(&self.guild_invites, &self.invites, id);
+            self.deleted_invites.remove(&id);
+            self.invite_synced_guilds.remove(&id);
+        }
+
         if self.wants(ResourceType::ROLE) {
             remove_ids(&self.guild_roles, &self.roles, id);
         }
@@ -211,9 +170,13 @@ impl InMemoryCache {
         if self.wants(ResourceType::MEMBER) {
             if let Some((_, ids)) = self.guild_members.remove(&id) {
                 for user_id in ids {
-                    self.members.remove(&(id, user_id));
+                    if let Some((_, member)) = self.members.remove(&(id, user_id)) {
+                        self.index_member(id, user_id, member.roles());
+                    }
                 }
             }
+
+            self.guild_member_names.remove(&id);
         }
 
         if self.wants(ResourceType::PRESENCE) {
@@ -228,7 +191,7 @@ impl InMemoryCache {
 
 impl UpdateCache for GuildCreate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::GUILD) {
+        if !cache.wants_in(Some(self.0.id), ResourceType::GUILD) {
             return;
         }
 
@@ -244,7 +207,7 @@ impl UpdateCache for GuildDelete {
 
 impl UpdateCache for GuildUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::GUILD) {
+        if !cache.wants_in(Some(self.0.id), ResourceType::GUILD) {
             return;
         }
 
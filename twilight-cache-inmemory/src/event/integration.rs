@@ -38,7 +38,7 @@ impl InMemoryCache {
 
 impl UpdateCache for IntegrationCreate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::INTEGRATION) {
+        if !cache.wants_in(self.guild_id, ResourceType::INTEGRATION) {
             return;
         }
 
@@ -65,7 +65,7 @@ impl UpdateCache for IntegrationDelete {
 
 impl UpdateCache for IntegrationUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::INTEGRATION) {
+        if !cache.wants_in(self.guild_id, ResourceType::INTEGRATION) {
             return;
         }
 
@@ -7,7 +7,7 @@ use twilight_model::{
 impl UpdateCache for InteractionCreate {
     fn update(&self, cache: &InMemoryCache) {
         // Cache interaction member
-        if cache.wants(ResourceType::MEMBER) {
+        if cache.wants_in(self.guild_id, ResourceType::MEMBER) {
             if let (Some(member), Some(guild_id)) = (&self.member, self.guild_id) {
                 if let Some(user) = &member.user {
                     cache.cache_user(Cow::Borrowed(user), self.guild_id);
@@ -33,7 +33,9 @@ impl UpdateCache for InteractionCreate {
                         cache.cache_user(Cow::Borrowed(u), self.guild_id);
                     }
 
-                    if !cache.wants(ResourceType::MEMBER) || self.guild_id.is_none() {
+                    if !cache.wants_in(self.guild_id, ResourceType::MEMBER)
+                        || self.guild_id.is_none()
+                    {
                         continue;
                     }
 
@@ -49,7 +51,7 @@ impl UpdateCache for InteractionCreate {
                 }
 
                 // Cache resolved roles
-                if cache.wants(ResourceType::ROLE) {
+                if cache.wants_in(self.guild_id, ResourceType::ROLE) {
                     if let Some(guild_id) = self.guild_id {
                         cache.cache_roles(guild_id, resolved.roles.iter().map(|(_, v)| v).cloned());
                     }
@@ -0,0 +1,92 @@
+use crate::{config::ResourceType, model::CachedInvite, InMemoryCache, UpdateCache};
+use std::borrow::Cow;
+use twilight_model::gateway::payload::incoming::{InviteCreate, InviteDelete};
+
+impl InMemoryCache {
+    pub(crate) fn cache_invite(&self, invite: CachedInvite) {
+        self.guild_invites
+            .entry(invite.guild_id())
+            .or_default()
+            .insert(invite.code().to_owned());
+
+        self.invites.insert(invite.code().to_owned(), invite);
+    }
+
+    pub(crate) fn delete_invite(&self, code: &str) {
+        if let Some((_, invite)) = self.invites.remove(code) {
+            if let Some(mut codes) = self.guild_invites.get_mut(&invite.guild_id()) {
+                codes.remove(code);
+            }
+
+            self.record_deleted_invite(invite);
+        }
+    }
+}
+
+impl UpdateCache for InviteCreate {
+    fn update(&self, cache: &InMemoryCache) {
+        if let (Some(inviter), true) = (&self.inviter, cache.wants(ResourceType::USER)) {
+            cache.cache_user(Cow::Borrowed(inviter), Some(self.guild_id));
+        }
+
+        if !cache.wants_in(Some(self.guild_id), ResourceType::INVITE) {
+            return;
+        }
+
+        cache.cache_invite(CachedInvite::from_create(self));
+    }
+}
+
+impl UpdateCache for InviteDelete {
+    fn update(&self, cache: &InMemoryCache) {
+        if !cache.wants(ResourceType::INVITE) {
+            return;
+        }
+
+        cache.delete_invite(&self.code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InMemoryCache;
+    use twilight_model::{
+        gateway::payload::incoming::{InviteCreate, InviteDelete},
+        id::Id,
+        util::Timestamp,
+    };
+
+    #[test]
+    fn invite_create_and_delete() {
+        let cache = InMemoryCache::new();
+
+        cache.update(&InviteCreate {
+            channel_id: Id::new(2),
+            code: "code".to_owned(),
+            created_at: Timestamp::from_secs(1_632_072_645).expect("non zero"),
+            guild_id: Id::new(1),
+            inviter: None,
+            max_age: 0,
+            max_uses: 0,
+            target_user_type: None,
+            target_user: None,
+            temporary: false,
+            uses: 3,
+        });
+
+        let invite = cache.invite("code").unwrap();
+        assert_eq!(Id::new(2), invite.channel_id());
+        assert_eq!(3, invite.uses());
+        drop(invite);
+        assert!(cache.guild_invites(Id::new(1)).unwrap().contains("code"));
+
+        cache.update(&InviteDelete {
+            channel_id: Id::new(2),
+            code: "code".to_owned(),
+            guild_id: Id::new(1),
+        });
+
+        assert!(cache.invite("code").is_none());
+        assert!(cache.guild_invites(Id::new(1)).unwrap().is_empty());
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     model::{member::ComputedInteractionMemberFields, CachedMember},
     InMemoryCache, UpdateCache,
 };
-use std::borrow::Cow;
+use std::{borrow::Cow, mem};
 use twilight_model::{
     application::interaction::application_command::InteractionMember,
     gateway::payload::incoming::{MemberAdd, MemberChunk, MemberRemove, MemberUpdate},
@@ -29,11 +29,11 @@ impl InMemoryCache {
         let member_id = member.user.id;
         let id = (guild_id, member_id);
 
-        if let Some(m) = self.members.get(&id) {
-            if *m == member {
-                return;
-            }
-        }
+        let old_roles = match self.members.get(&id) {
+            Some(m) if *m == member => return,
+            Some(m) => m.roles.clone(),
+            None => Vec::new(),
+        };
 
         self.cache_user(Cow::Borrowed(&member.user), Some(guild_id));
         let cached = CachedMember::from_model(member);
@@ -42,6 +42,7 @@ impl InMemoryCache {
             .entry(guild_id)
             .or_default()
             .insert(member_id);
+        self.index_member(guild_id, member_id, &old_roles);
     }
 
     pub(crate) fn cache_borrowed_partial_member(
@@ -52,11 +53,11 @@ impl InMemoryCache {
     ) {
         let id = (guild_id, user_id);
 
-        if let Some(m) = self.members.get(&id) {
-            if &*m == member {
-                return;
-            }
-        }
+        let old_roles = match self.members.get(&id) {
+            Some(m) if &*m == member => return,
+            Some(m) => m.roles.clone(),
+            None => Vec::new(),
+        };
 
         self.guild_members
             .entry(guild_id)
@@ -65,6 +66,7 @@ impl InMemoryCache {
 
         let cached = CachedMember::from_partial_member(guild_id, user_id, member.clone());
         self.members.insert(id, cached);
+        self.index_member(guild_id, user_id, &old_roles);
     }
 
     pub(crate) fn cache_borrowed_interaction_member(
@@ -75,10 +77,10 @@ impl InMemoryCache {
     ) {
         let id = (guild_id, user_id);
 
-        let (avatar, deaf, mute) = match self.members.get(&id) {
+        let (avatar, deaf, mute, old_roles) = match self.members.get(&id) {
             Some(m) if &*m == member => return,
-            Some(m) => (m.avatar(), m.deaf(), m.mute()),
-            None => (None, None, None),
+            Some(m) => (m.avatar(), m.deaf(), m.mute(), m.roles.clone()),
+            None => (None, None, None, Vec::new()),
         };
 
         self.guild_members
@@ -94,18 +96,19 @@ impl InMemoryCache {
         );
 
         self.members.insert(id, cached);
+        self.index_member(guild_id, user_id, &old_roles);
     }
 }
 
 impl UpdateCache for MemberAdd {
     fn update(&self, cache: &InMemoryCache) {
-        if cache.wants(ResourceType::GUILD) {
+        if cache.wants_in(Some(self.guild_id), ResourceType::GUILD) {
             if let Some(mut guild) = cache.guilds.get_mut(&self.guild_id) {
                 guild.member_count = guild.member_count.map(|count| count + 1);
             }
         }
 
-        if !cache.wants(ResourceType::MEMBER) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::MEMBER) {
             return;
         }
 
@@ -121,7 +124,7 @@ impl UpdateCache for MemberAdd {
 
 impl UpdateCache for MemberChunk {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::MEMBER) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::MEMBER) {
             return;
         }
 
@@ -147,7 +150,9 @@ impl UpdateCache for MemberRemove {
             return;
         }
 
-        cache.members.remove(&(self.guild_id, self.user.id));
+        if let Some((_, member)) = cache.members.remove(&(self.guild_id, self.user.id)) {
+            cache.index_member(self.guild_id, self.user.id, member.roles());
+        }
 
         if let Some(mut members) = cache.guild_members.get_mut(&self.guild_id) {
             members.remove(&self.user.id);
@@ -171,7 +176,7 @@ impl UpdateCache for MemberRemove {
 
 impl UpdateCache for MemberUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::MEMBER) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::MEMBER) {
             return;
         }
 
@@ -186,11 +191,15 @@ impl UpdateCache for MemberUpdate {
         member.avatar = self.avatar;
         member.deaf = self.deaf.or_else(|| member.deaf());
         member.mute = self.mute.or_else(|| member.mute());
-        member.nick = self.nick.clone();
-        member.roles = self.roles.clone();
+        member.nick.clone_from(&self.nick);
+        let old_roles = mem::replace(&mut member.roles, self.roles.clone());
         member.joined_at = self.joined_at;
         member.pending = self.pending;
         member.communication_disabled_until = self.communication_disabled_until;
+
+        // Release the lock on the member before it's indexed.
+        drop(member);
+        cache.index_member(self.guild_id, self.user.id, &old_roles);
     }
 }
 
@@ -13,31 +13,54 @@ impl UpdateCache for MessageCreate {
         if let (Some(member), Some(guild_id), true) = (
             &self.member,
             self.guild_id,
-            cache.wants(ResourceType::MEMBER),
+            cache.wants_in(self.guild_id, ResourceType::MEMBER),
         ) {
             cache.cache_borrowed_partial_member(guild_id, member, self.author.id);
         }
 
-        if !cache.wants(ResourceType::MESSAGE) {
+        if !cache.wants_messages_in(self.guild_id, self.channel_id) {
             return;
         }
 
-        let mut channel_messages = cache.channel_messages.entry(self.0.channel_id).or_default();
+        let message_cache_size = cache
+            .config
+            .message_cache_size_in(self.0.guild_id, self.0.channel_id);
+
+        // If the channel has as many messages as the cache size the user has
+        // requested then we pop message IDs out. Once we have the popped IDs
+        // we can remove them from the message cache. This prevents the cache
+        // from filling up with old messages that aren't in any channel cache.
+        let mut popped_ids = Vec::new();
+
+        {
+            let mut channel_messages = cache.channel_messages.entry(self.0.channel_id).or_default();
 
-        // If the channel has more messages than the cache size the user has
-        // requested then we pop a message ID out. Once we have the popped ID we
-        // can remove it from the message cache. This prevents the cache from
-        // filling up with old messages that aren't in any channel cache.
-        if channel_messages.len() >= cache.config.message_cache_size() {
-            if let Some(popped_id) = channel_messages.pop_back() {
-                cache.messages.remove(&popped_id);
+            while channel_messages.len() >= message_cache_size {
+                match channel_messages.pop_back() {
+                    Some(popped_id) => popped_ids.push(popped_id),
+                    None => break,
+                }
             }
+
+            if message_cache_size > 0 {
+                channel_messages.push_front(self.0.id);
+            }
+        }
+
+        for popped_id in &popped_ids {
+            cache.messages.remove(popped_id);
+        }
+
+        cache.untrack_messages(popped_ids, true);
+
+        if message_cache_size == 0 {
+            return;
         }
 
-        channel_messages.push_front(self.0.id);
         cache
             .messages
             .insert(self.0.id, CachedMessage::from(self.0.clone()));
+        cache.track_message(self.0.id);
     }
 }
 
@@ -49,11 +72,15 @@ impl UpdateCache for MessageDelete {
 
         cache.messages.remove(&self.id);
 
-        let mut channel_messages = cache.channel_messages.entry(self.channel_id).or_default();
+        {
+            let mut channel_messages = cache.channel_messages.entry(self.channel_id).or_default();
 
-        if let Some(idx) = channel_messages.iter().position(|id| *id == self.id) {
-            channel_messages.remove(idx);
+            if let Some(idx) = channel_messages.iter().position(|id| *id == self.id) {
+                channel_messages.remove(idx);
+            }
         }
+
+        cache.untrack_messages([self.id], false);
     }
 }
 
@@ -63,18 +90,22 @@ impl UpdateCache for MessageDeleteBulk {
             return;
         }
 
-        let mut channel_messages = cache.channel_messages.entry(self.channel_id).or_default();
+        {
+            let mut channel_messages = cache.channel_messages.entry(self.channel_id).or_default();
 
-        for id in &self.ids {
-            cache.messages.remove(id);
+            for id in &self.ids {
+                cache.messages.remove(id);
 
-            if let Some(idx) = channel_messages
-                .iter()
-                .position(|message_id| message_id == id)
-            {
-                channel_messages.remove(idx);
+                if let Some(idx) = channel_messages
+                    .iter()
+                    .position(|message_id| message_id == id)
+                {
+                    channel_messages.remove(idx);
+                }
             }
         }
+
+        cache.untrack_messages(self.ids.iter().copied(), false);
     }
 }
 
@@ -124,13 +155,17 @@ impl UpdateCache for MessageUpdate {
             if let Some(tts) = self.tts {
                 message.tts = tts;
             }
+        } else {
+            return;
         }
+
+        cache.track_message(self.id);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{InMemoryCache, ResourceType};
+    use crate::{test, InMemoryCache, ResourceType};
     use twilight_model::{
         channel::message::{Message, MessageFlags, MessageType},
         gateway::payload::incoming::MessageCreate,
@@ -237,4 +272,21 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn message_create_cache_size_zero() {
+        let cache = InMemoryCache::builder()
+            .message_cache_size(0)
+            .channel_message_cache_size(Id::new(2), 1)
+            .build();
+
+        cache.update(&MessageCreate(test::message(Id::new(1), Id::new(3))));
+        assert!(cache.message(Id::new(3)).is_none());
+        assert!(cache
+            .channel_messages(Id::new(1))
+            .map_or(true, |messages| messages.is_empty()));
+
+        cache.update(&MessageCreate(test::message(Id::new(2), Id::new(4))));
+        assert!(cache.message(Id::new(4)).is_some());
+    }
 }
@@ -1,8 +1,10 @@
+pub mod auto_moderation;
 pub mod channel;
 pub mod emoji;
 pub mod guild;
 pub mod integration;
 pub mod interaction;
+pub mod invite;
 pub mod member;
 pub mod message;
 pub mod presence;
@@ -14,7 +16,7 @@ pub mod thread;
 pub mod voice_state;
 
 use crate::{config::ResourceType, InMemoryCache, UpdateCache};
-use std::{borrow::Cow, collections::BTreeSet};
+use std::borrow::Cow;
 use twilight_model::{
     gateway::payload::incoming::{Ready, UnavailableGuild, UserUpdate},
     id::{marker::GuildMarker, Id},
@@ -45,13 +47,30 @@ impl InMemoryCache {
 
         let user = user.into_owned();
         let user_id = user.id;
+        let renamed = self
+            .users
+            .get(&user_id)
+            .map_or(true, |cached_user| cached_user.name != user.name);
 
         self.users.insert(user_id, user);
 
         if let Some(guild_id) = guild_id {
-            let mut guild_id_set = BTreeSet::new();
-            guild_id_set.insert(guild_id);
-            self.user_guilds.insert(user_id, guild_id_set);
+            self.user_guilds
+                .entry(user_id)
+                .or_default()
+                .insert(guild_id);
+        }
+
+        if renamed {
+            let guild_ids = self
+                .user_guilds
+                .get(&user_id)
+                .map(|guild_ids| guild_ids.iter().copied().collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            for guild_id in guild_ids {
+                self.index_member(guild_id, user_id, &[]);
+            }
         }
     }
 
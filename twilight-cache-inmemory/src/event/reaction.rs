@@ -8,7 +8,7 @@ use twilight_model::{
 
 impl UpdateCache for ReactionAdd {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::REACTION) {
+        if !cache.wants_in(self.guild_id, ResourceType::REACTION) {
             return;
         }
 
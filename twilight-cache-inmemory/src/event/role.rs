@@ -31,6 +31,8 @@ impl InMemoryCache {
     }
 
     fn delete_role(&self, role_id: Id<RoleMarker>) {
+        self.role_members.remove(&role_id);
+
         if let Some((_, role)) = self.roles.remove(&role_id) {
             if let Some(mut roles) = self.guild_roles.get_mut(&role.guild_id) {
                 roles.remove(&role_id);
@@ -41,7 +43,7 @@ impl InMemoryCache {
 
 impl UpdateCache for RoleCreate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::ROLE) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::ROLE) {
             return;
         }
 
@@ -61,7 +63,7 @@ impl UpdateCache for RoleDelete {
 
 impl UpdateCache for RoleUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::ROLE) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::ROLE) {
             return;
         }
 
@@ -46,7 +46,7 @@ impl InMemoryCache {
 
 impl UpdateCache for StageInstanceCreate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::STAGE_INSTANCE) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::STAGE_INSTANCE) {
             return;
         }
 
@@ -66,7 +66,7 @@ impl UpdateCache for StageInstanceDelete {
 
 impl UpdateCache for StageInstanceUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::STAGE_INSTANCE) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::STAGE_INSTANCE) {
             return;
         }
 
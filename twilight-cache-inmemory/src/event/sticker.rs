@@ -69,7 +69,7 @@ impl InMemoryCache {
 
 impl UpdateCache for GuildStickersUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::STICKER) {
+        if !cache.wants_in(Some(self.guild_id), ResourceType::STICKER) {
             return;
         }
 
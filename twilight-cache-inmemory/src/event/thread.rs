@@ -1,11 +1,116 @@
-use crate::{config::ResourceType, InMemoryCache, UpdateCache};
-use twilight_model::gateway::payload::incoming::{
-    ThreadCreate, ThreadDelete, ThreadListSync, ThreadUpdate,
+use crate::{
+    config::ResourceType,
+    model::{CachedPresence, CachedThreadMember},
+    InMemoryCache, UpdateCache,
 };
+use twilight_model::{
+    channel::thread::ThreadMember,
+    gateway::payload::incoming::{
+        ThreadCreate, ThreadDelete, ThreadListSync, ThreadMemberUpdate, ThreadMembersUpdate,
+        ThreadUpdate,
+    },
+    id::{
+        marker::{ChannelMarker, UserMarker},
+        Id,
+    },
+};
+
+impl InMemoryCache {
+    /// Cache a member of a thread.
+    ///
+    /// Thread members of the current user may omit the user's ID, in which
+    /// case the ID of the cached current user is used.
+    pub(crate) fn cache_thread_member(
+        &self,
+        thread_id: Id<ChannelMarker>,
+        thread_member: &ThreadMember,
+    ) {
+        let user_id = match thread_member.user_id.or_else(|| {
+            self.current_user
+                .lock()
+                .expect("current user poisoned")
+                .as_ref()
+                .map(|user| user.id)
+        }) {
+            Some(user_id) => user_id,
+            None => return,
+        };
+
+        if let Some(member) = &thread_member.member {
+            if self.wants_in(Some(member.guild_id), ResourceType::MEMBER) {
+                self.cache_member(member.guild_id, member.clone());
+            }
+        }
+
+        if let Some(presence) = &thread_member.presence {
+            if self.wants_in(Some(presence.guild_id), ResourceType::PRESENCE) {
+                self.cache_presences(
+                    presence.guild_id,
+                    [CachedPresence::from_model(presence.clone())],
+                );
+            }
+        }
+
+        self.thread_member_threads
+            .entry(thread_id)
+            .or_default()
+            .insert(user_id);
+        self.thread_member_users
+            .entry(user_id)
+            .or_default()
+            .insert(thread_id);
+        self.thread_members.insert(
+            (thread_id, user_id),
+            CachedThreadMember::from_model(thread_member, thread_id, user_id),
+        );
+    }
+
+    /// Remove a member of a thread.
+    fn delete_thread_member(&self, thread_id: Id<ChannelMarker>, user_id: Id<UserMarker>) {
+        self.thread_members.remove(&(thread_id, user_id));
+
+        if let Some(mut user_ids) = self.thread_member_threads.get_mut(&thread_id) {
+            user_ids.remove(&user_id);
+        }
+
+        let user_is_empty = self
+            .thread_member_users
+            .get_mut(&user_id)
+            .map(|mut thread_ids| {
+                thread_ids.remove(&thread_id);
+
+                thread_ids.is_empty()
+            });
+
+        if user_is_empty == Some(true) {
+            self.thread_member_users
+                .remove_if(&user_id, |_, thread_ids| thread_ids.is_empty());
+        }
+    }
+
+    /// Remove all members of a thread, such as when it's archived or deleted.
+    pub(crate) fn delete_thread_members(&self, thread_id: Id<ChannelMarker>) {
+        let user_ids = match self.thread_member_threads.remove(&thread_id) {
+            Some((_, user_ids)) => user_ids,
+            None => return,
+        };
+
+        for user_id in user_ids {
+            self.delete_thread_member(thread_id, user_id);
+        }
+    }
+}
 
 impl UpdateCache for ThreadCreate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::CHANNEL) {
+        if let (Some(thread_member), true) = (
+            &self.member,
+            cache.wants_in(self.guild_id, ResourceType::THREAD_MEMBER),
+        ) {
+            cache.cache_thread_member(self.id, thread_member);
+        }
+
+        if !cache.wants_in(self.guild_id, ResourceType::CHANNEL) {
             return;
         }
 
@@ -15,6 +120,10 @@ impl UpdateCache for ThreadCreate {
 
 impl UpdateCache for ThreadDelete {
     fn update(&self, cache: &InMemoryCache) {
+        if cache.wants(ResourceType::THREAD_MEMBER) {
+            cache.delete_thread_members(self.id);
+        }
+
         if !cache.wants(ResourceType::CHANNEL) {
             return;
         }
@@ -25,7 +134,15 @@ impl UpdateCache for ThreadDelete {
 
 impl UpdateCache for ThreadListSync {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::CHANNEL) {
+        if cache.wants_in(Some(self.guild_id), ResourceType::THREAD_MEMBER) {
+            for thread_member in &self.members {
+                if let Some(thread_id) = thread_member.id {
+                    cache.cache_thread_member(thread_id, thread_member);
+                }
+            }
+        }
+
+        if !cache.wants_in(Some(self.guild_id), ResourceType::CHANNEL) {
             return;
         }
 
@@ -33,12 +150,216 @@ impl UpdateCache for ThreadListSync {
     }
 }
 
+impl UpdateCache for ThreadMemberUpdate {
+    fn update(&self, cache: &InMemoryCache) {
+        let thread_id = match self.id {
+            Some(thread_id) => thread_id,
+            None => return,
+        };
+
+        // The event doesn't include the guild, so fall back to that of the
+        // cached thread.
+        let guild_id = self
+            .member
+            .as_ref()
+            .map(|member| member.guild_id)
+            .or_else(|| {
+                cache
+                    .channels
+                    .get(&thread_id)
+                    .and_then(|thread| thread.guild_id)
+            });
+
+        if !cache.wants_in(guild_id, ResourceType::THREAD_MEMBER) {
+            return;
+        }
+
+        cache.cache_thread_member(thread_id, &self.0);
+    }
+}
+
+impl UpdateCache for ThreadMembersUpdate {
+    fn update(&self, cache: &InMemoryCache) {
+        if !cache.wants(ResourceType::THREAD_MEMBER) {
+            return;
+        }
+
+        if cache.wants_in(Some(self.guild_id), ResourceType::THREAD_MEMBER) {
+            for thread_member in &self.added_members {
+                cache.cache_thread_member(thread_member.id.unwrap_or(self.id), thread_member);
+            }
+        }
+
+        for user_id in &self.removed_member_ids {
+            cache.delete_thread_member(self.id, *user_id);
+        }
+    }
+}
+
 impl UpdateCache for ThreadUpdate {
     fn update(&self, cache: &InMemoryCache) {
-        if !cache.wants(ResourceType::CHANNEL) {
+        if cache.wants(ResourceType::THREAD_MEMBER) {
+            let archived = self
+                .thread_metadata
+                .as_ref()
+                .map_or(false, |metadata| metadata.archived);
+
+            // Member updates aren't sent for archived threads, so their
+            // members would otherwise go stale.
+            if archived {
+                cache.delete_thread_members(self.id);
+            } else if let (Some(thread_member), true) = (
+                &self.member,
+                cache.wants_in(self.guild_id, ResourceType::THREAD_MEMBER),
+            ) {
+                cache.cache_thread_member(self.id, thread_member);
+            }
+        }
+
+        if !cache.wants_in(self.guild_id, ResourceType::CHANNEL) {
             return;
         }
 
         cache.cache_channel(self.0.clone());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test, InMemoryCache, ResourceType};
+    use twilight_model::{
+        channel::{
+            thread::{AutoArchiveDuration, ThreadMember, ThreadMetadata},
+            Channel, ChannelType,
+        },
+        gateway::payload::incoming::{
+            ThreadDelete, ThreadMemberUpdate, ThreadMembersUpdate, ThreadUpdate,
+        },
+        id::{
+            marker::{ChannelMarker, UserMarker},
+            Id,
+        },
+        util::Timestamp,
+    };
+
+    fn thread_member(
+        thread_id: Option<Id<ChannelMarker>>,
+        user_id: Option<Id<UserMarker>>,
+    ) -> ThreadMember {
+        ThreadMember {
+            flags: 0,
+            id: thread_id,
+            join_timestamp: Timestamp::from_secs(1_632_072_645).expect("non zero"),
+            member: None,
+            presence: None,
+            user_id,
+        }
+    }
+
+    fn thread(id: Id<ChannelMarker>, archived: bool) -> Channel {
+        let (guild_id, parent_id, mut channel) = test::guild_channel_text();
+        channel.guild_id = Some(guild_id);
+        channel.id = id;
+        channel.kind = ChannelType::GuildPublicThread;
+        channel.parent_id = Some(parent_id);
+        channel.thread_metadata = Some(ThreadMetadata {
+            archived,
+            auto_archive_duration: AutoArchiveDuration::Day,
+            archive_timestamp: Timestamp::from_secs(1_632_072_645).expect("non zero"),
+            create_timestamp: None,
+            invitable: None,
+            locked: false,
+        });
+
+        channel
+    }
+
+    #[test]
+    fn members_update() {
+        let cache = InMemoryCache::new();
+        let thread_id = Id::new(10);
+
+        cache.update(&ThreadMembersUpdate {
+            added_members: Vec::from([
+                thread_member(Some(thread_id), Some(Id::new(1))),
+                thread_member(Some(thread_id), Some(Id::new(2))),
+            ]),
+            guild_id: Id::new(100),
+            id: thread_id,
+            member_count: 2,
+            removed_member_ids: Vec::new(),
+        });
+
+        assert_eq!(2, cache.thread_members(thread_id).unwrap().len());
+        assert!(cache.user_threads(Id::new(1)).unwrap().contains(&thread_id));
+        assert_eq!(Some(2), cache.stats().channel_thread_members(thread_id));
+
+        cache.update(&ThreadMembersUpdate {
+            added_members: Vec::new(),
+            guild_id: Id::new(100),
+            id: thread_id,
+            member_count: 1,
+            removed_member_ids: Vec::from([Id::new(1)]),
+        });
+
+        assert!(cache.thread_member(thread_id, Id::new(1)).is_none());
+        assert!(cache.user_threads(Id::new(1)).is_none());
+        assert!(cache.thread_member(thread_id, Id::new(2)).is_some());
+
+        cache.update(&ThreadDelete {
+            guild_id: Id::new(100),
+            id: thread_id,
+            kind: ChannelType::GuildPublicThread,
+            parent_id: Id::new(1),
+        });
+
+        assert!(cache.thread_members(thread_id).is_none());
+        assert!(cache.user_threads(Id::new(2)).is_none());
+        assert_eq!(0, cache.stats().thread_members());
+    }
+
+    #[test]
+    fn current_user_member_update() {
+        let cache = InMemoryCache::new();
+        cache.update(&twilight_model::gateway::payload::incoming::UserUpdate(
+            test::current_user(5),
+        ));
+        let thread_id = Id::new(10);
+
+        // The current user's ID is omitted when its own member is updated.
+        cache.update(&ThreadMemberUpdate(thread_member(Some(thread_id), None)));
+
+        let member = cache.thread_member(thread_id, Id::new(5)).unwrap();
+        assert_eq!(thread_id, member.thread_id());
+    }
+
+    #[test]
+    fn archived_thread_clears_members() {
+        let cache = InMemoryCache::new();
+        let thread_id = Id::new(10);
+        let mut channel = thread(thread_id, false);
+        channel.member = Some(thread_member(Some(thread_id), Some(Id::new(5))));
+
+        cache.update(&ThreadUpdate(channel));
+        assert!(cache.thread_member(thread_id, Id::new(5)).is_some());
+
+        cache.update(&ThreadUpdate(thread(thread_id, true)));
+        assert!(cache.thread_members(thread_id).is_none());
+        assert!(cache.channel(thread_id).is_some());
+    }
+
+    #[test]
+    fn resource_type_disabled() {
+        let cache = InMemoryCache::builder()
+            .resource_types(ResourceType::CHANNEL)
+            .build();
+        let thread_id = Id::new(10);
+
+        cache.update(&ThreadMemberUpdate(thread_member(
+            Some(thread_id),
+            Some(Id::new(1)),
+        )));
+
+        assert!(cache.thread_member(thread_id, Id::new(1)).is_none());
+    }
+}
@@ -0,0 +1,537 @@
+//! Secondary indexes of the cache and the queries using them.
+
+use crate::{model::CachedMember, InMemoryCache};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    mem,
+    ops::Bound,
+};
+use twilight_model::{
+    channel::Channel,
+    id::{
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Lowercased names and nicknames of the members of a guild.
+#[derive(Debug, Default)]
+pub(crate) struct MemberNames {
+    /// Mapping of names and the users with them.
+    names: BTreeMap<String, HashSet<Id<UserMarker>>>,
+    /// Mapping of users and their indexed names.
+    users: HashMap<Id<UserMarker>, Vec<String>>,
+}
+
+impl MemberNames {
+    /// Replace the indexed names of a user.
+    fn insert(&mut self, user_id: Id<UserMarker>, names: Vec<String>) {
+        self.remove(user_id);
+
+        for name in &names {
+            self.names.entry(name.clone()).or_default().insert(user_id);
+        }
+
+        self.users.insert(user_id, names);
+    }
+
+    /// Remove the indexed names of a user.
+    fn remove(&mut self, user_id: Id<UserMarker>) {
+        let names = match self.users.remove(&user_id) {
+            Some(names) => names,
+            None => return,
+        };
+
+        for name in names {
+            if let Some(user_ids) = self.names.get_mut(&name) {
+                user_ids.remove(&user_id);
+
+                if user_ids.is_empty() {
+                    self.names.remove(&name);
+                }
+            }
+        }
+    }
+
+    /// Approximate number of bytes used by the names.
+    pub(crate) fn estimated_size(&self) -> usize {
+        let names = self
+            .names
+            .iter()
+            .map(|(name, user_ids)| {
+                mem::size_of::<String>()
+                    + name.len()
+                    + mem::size_of::<HashSet<Id<UserMarker>>>()
+                    + user_ids.len() * mem::size_of::<Id<UserMarker>>()
+            })
+            .sum::<usize>();
+
+        let users = self
+            .users
+            .values()
+            .map(|names| {
+                mem::size_of::<Id<UserMarker>>()
+                    + mem::size_of::<Vec<String>>()
+                    + names
+                        .iter()
+                        .map(|name| mem::size_of::<String>() + name.len())
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+
+        mem::size_of::<Self>() + names + users
+    }
+
+    /// IDs of users with a name starting with a lowercased prefix, ordered by
+    /// name.
+    fn prefixed(&self, prefix: &str) -> Vec<Id<UserMarker>> {
+        let mut seen = HashSet::new();
+
+        self.names
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .flat_map(|(_, user_ids)| {
+                let mut user_ids = user_ids.iter().copied().collect::<Vec<_>>();
+                user_ids.sort_unstable();
+
+                user_ids
+            })
+            .filter(|user_id| seen.insert(*user_id))
+            .collect()
+    }
+}
+
+impl InMemoryCache {
+    /// Update the indexes of a channel before it's cached or removed.
+    ///
+    /// The parent of the channel is looked up in the cached channel, if any,
+    /// and replaced with `parent_id`.
+    pub(crate) fn index_channel(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        parent_id: Option<Id<ChannelMarker>>,
+    ) {
+        let old_parent_id = self
+            .channels
+            .get(&channel_id)
+            .and_then(|channel| channel.parent_id);
+
+        if old_parent_id == parent_id {
+            return;
+        }
+
+        if let Some(old_parent_id) = old_parent_id {
+            let is_empty = self
+                .channel_children
+                .get_mut(&old_parent_id)
+                .map(|mut channel_ids| {
+                    channel_ids.remove(&channel_id);
+
+                    channel_ids.is_empty()
+                });
+
+            if is_empty == Some(true) {
+                self.channel_children
+                    .remove_if(&old_parent_id, |_, channel_ids| channel_ids.is_empty());
+            }
+        }
+
+        if let Some(parent_id) = parent_id {
+            self.channel_children
+                .entry(parent_id)
+                .or_default()
+                .insert(channel_id);
+        }
+    }
+
+    /// Update the indexes of a member after it was cached, updated, or
+    /// removed, given the roles it had before.
+    pub(crate) fn index_member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        old_roles: &[Id<RoleMarker>],
+    ) {
+        let key = (guild_id, user_id);
+
+        for role_id in old_roles {
+            let is_empty = self.role_members.get_mut(role_id).map(|mut members| {
+                members.remove(&key);
+
+                members.is_empty()
+            });
+
+            if is_empty == Some(true) {
+                self.role_members
+                    .remove_if(role_id, |_, members| members.is_empty());
+            }
+        }
+
+        let member = self
+            .members
+            .get(&key)
+            .map(|member| (member.nick.clone(), member.roles.clone()));
+
+        let (nick, roles) = if let Some(member) = member {
+            member
+        } else {
+            if let Some(mut names) = self.guild_member_names.get_mut(&guild_id) {
+                names.remove(user_id);
+            }
+
+            return;
+        };
+
+        for role_id in roles {
+            self.role_members.entry(role_id).or_default().insert(key);
+        }
+
+        let mut names = Vec::with_capacity(2);
+
+        if let Some(user) = self.users.get(&user_id) {
+            names.push(user.name.to_lowercase());
+        }
+
+        if let Some(nick) = nick {
+            let nick = nick.to_lowercase();
+
+            if !names.contains(&nick) {
+                names.push(nick);
+            }
+        }
+
+        self.guild_member_names
+            .entry(guild_id)
+            .or_default()
+            .insert(user_id, names);
+    }
+
+    /// Rebuild the indexes from the cached channels and members, such as
+    /// after restoring a snapshot.
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn rebuild_indexes(&self) {
+        self.channel_children.clear();
+        self.guild_member_names.clear();
+        self.role_members.clear();
+
+        let children = self
+            .channels
+            .iter()
+            .filter_map(|channel| Some((channel.parent_id?, channel.id)))
+            .collect::<Vec<_>>();
+
+        for (parent_id, channel_id) in children {
+            self.channel_children
+                .entry(parent_id)
+                .or_default()
+                .insert(channel_id);
+        }
+
+        let members = self
+            .members
+            .iter()
+            .map(|member| *member.key())
+            .collect::<Vec<_>>();
+
+        for (guild_id, user_id) in members {
+            self.index_member(guild_id, user_id, &[]);
+        }
+    }
+
+    /// Channels whose parent is a channel, such as the channels in a category
+    /// or the threads of a text channel.
+    ///
+    /// Channels are ordered by their position and then by their ID, with
+    /// channels without a position, such as threads, last. The channels are
+    /// cloned out of the cache.
+    ///
+    /// Requires [`ResourceType::CHANNEL`].
+    ///
+    /// [`ResourceType::CHANNEL`]: crate::ResourceType::CHANNEL
+    pub fn channel_children(&self, parent_id: Id<ChannelMarker>) -> Vec<Channel> {
+        let channel_ids = match self.channel_children.get(&parent_id) {
+            Some(channel_ids) => channel_ids.iter().copied().collect::<Vec<_>>(),
+            None => return Vec::new(),
+        };
+
+        let mut channels = channel_ids
+            .into_iter()
+            .filter_map(|channel_id| self.channels.get(&channel_id).map(|c| c.value().clone()))
+            .collect::<Vec<_>>();
+
+        channels.sort_by_key(|channel| (channel.position.is_none(), channel.position, channel.id));
+
+        channels
+    }
+
+    /// Members of a guild whose username or nickname starts with a prefix,
+    /// ignoring case.
+    ///
+    /// Members are ordered by their matching name. The members are cloned out
+    /// of the cache.
+    ///
+    /// Requires [`ResourceType::MEMBER`]. Usernames are only matched for
+    /// members whose user is cached.
+    ///
+    /// # Examples
+    ///
+    /// Autocomplete a member argument of a command:
+    ///
+    /// ```
+    /// use twilight_cache_inmemory::InMemoryCache;
+    /// use twilight_model::id::Id;
+    ///
+    /// let cache = InMemoryCache::new();
+    /// let guild_id = Id::new(1);
+    ///
+    /// let choices = cache
+    ///     .members_by_name_prefix(guild_id, "vi")
+    ///     .into_iter()
+    ///     .take(25)
+    ///     .collect::<Vec<_>>();
+    /// # assert!(choices.is_empty());
+    /// ```
+    ///
+    /// [`ResourceType::MEMBER`]: crate::ResourceType::MEMBER
+    pub fn members_by_name_prefix(
+        &self,
+        guild_id: Id<GuildMarker>,
+        prefix: &str,
+    ) -> Vec<CachedMember> {
+        let user_ids = match self.guild_member_names.get(&guild_id) {
+            Some(names) => names.prefixed(&prefix.to_lowercase()),
+            None => return Vec::new(),
+        };
+
+        user_ids
+            .into_iter()
+            .filter_map(|user_id| {
+                self.members
+                    .get(&(guild_id, user_id))
+                    .map(|member| member.value().clone())
+            })
+            .collect()
+    }
+
+    /// Members that have a role.
+    ///
+    /// The `@everyone` role, whose ID is the guild's ID, matches all of the
+    /// guild's members. Members are ordered by their user ID. The members are
+    /// cloned out of the cache.
+    ///
+    /// Requires [`ResourceType::MEMBER`].
+    ///
+    /// [`ResourceType::MEMBER`]: crate::ResourceType::MEMBER
+    pub fn members_with_role(&self, role_id: Id<RoleMarker>) -> Vec<CachedMember> {
+        let guild_id = role_id.cast();
+
+        let mut keys = if let Some(user_ids) = self.guild_members.get(&guild_id) {
+            user_ids
+                .iter()
+                .map(|user_id| (guild_id, *user_id))
+                .collect::<Vec<_>>()
+        } else if let Some(keys) = self.role_members.get(&role_id) {
+            keys.iter().copied().collect()
+        } else {
+            return Vec::new();
+        };
+
+        keys.sort_unstable();
+
+        keys.into_iter()
+            .filter_map(|key| self.members.get(&key).map(|member| member.value().clone()))
+            .collect()
+    }
+
+    /// Members of a user in the guilds it shares with the current user.
+    ///
+    /// Members are ordered by their guild ID. The members are cloned out of
+    /// the cache.
+    ///
+    /// Requires [`ResourceType::MEMBER`].
+    ///
+    /// [`ResourceType::MEMBER`]: crate::ResourceType::MEMBER
+    pub fn mutual_guild_members(&self, user_id: Id<UserMarker>) -> Vec<CachedMember> {
+        let guild_ids = match self.user_guilds.get(&user_id) {
+            Some(guild_ids) => guild_ids.iter().copied().collect::<Vec<_>>(),
+            None => return Vec::new(),
+        };
+
+        guild_ids
+            .into_iter()
+            .filter_map(|guild_id| {
+                self.members
+                    .get(&(guild_id, user_id))
+                    .map(|member| member.value().clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test, InMemoryCache};
+    use std::borrow::Cow;
+    use twilight_model::{
+        channel::ChannelType,
+        gateway::payload::incoming::{
+            ChannelDelete, ChannelUpdate, GuildDelete, MemberAdd, MemberRemove, MemberUpdate,
+            RoleDelete,
+        },
+        id::Id,
+    };
+
+    #[test]
+    fn members_with_role() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+        let role_id = Id::new(10);
+
+        let mut member = test::member(Id::new(2), guild_id);
+        member.roles = Vec::from([role_id]);
+        cache.update(&MemberAdd(member.clone()));
+        cache.update(&MemberAdd(test::member(Id::new(3), guild_id)));
+
+        let members = cache.members_with_role(role_id);
+        assert_eq!(1, members.len());
+        assert_eq!(Id::new(2), members[0].user_id());
+        assert_eq!(2, cache.members_with_role(guild_id.cast()).len());
+
+        cache.update(&MemberUpdate {
+            avatar: None,
+            communication_disabled_until: None,
+            guild_id,
+            deaf: None,
+            joined_at: member.joined_at,
+            mute: None,
+            nick: None,
+            pending: false,
+            premium_since: None,
+            roles: Vec::new(),
+            user: member.user.clone(),
+        });
+        assert!(cache.members_with_role(role_id).is_empty());
+        assert!(cache.role_members.get(&role_id).is_none());
+
+        cache.update(&MemberAdd(member.clone()));
+        cache.update(&RoleDelete { guild_id, role_id });
+        assert!(cache.members_with_role(role_id).is_empty());
+
+        cache.update(&MemberAdd(member));
+        cache.update(&GuildDelete {
+            id: guild_id,
+            unavailable: false,
+        });
+        assert!(cache.role_members.is_empty());
+        assert!(cache.guild_member_names.is_empty());
+    }
+
+    #[test]
+    fn members_by_name_prefix() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+
+        let mut member = test::member(Id::new(2), guild_id);
+        member.user.name = "Violet".to_owned();
+        member.nick = Some("Vi".to_owned());
+        cache.update(&MemberAdd(member.clone()));
+
+        let mut other = test::member(Id::new(3), guild_id);
+        other.user.name = "Victor".to_owned();
+        cache.update(&MemberAdd(other.clone()));
+
+        let names = |prefix| {
+            cache
+                .members_by_name_prefix(guild_id, prefix)
+                .into_iter()
+                .map(|member| member.user_id().get())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(Vec::from([2, 3]), names("VI"));
+        assert_eq!(Vec::from([3]), names("vic"));
+        assert!(names("x").is_empty());
+
+        // Renames are picked up from any event caching the user.
+        other.user.name = "Xavier".to_owned();
+        cache.cache_user(Cow::Owned(other.user.clone()), Some(guild_id));
+        assert_eq!(Vec::from([3]), names("x"));
+        assert_eq!(Vec::from([2]), names("vi"));
+
+        cache.update(&MemberRemove {
+            guild_id,
+            user: member.user,
+        });
+        assert!(names("vi").is_empty());
+    }
+
+    #[test]
+    fn channel_children() {
+        let cache = InMemoryCache::new();
+        let (guild_id, category_id, mut category) = test::guild_channel_text();
+        category.kind = ChannelType::GuildCategory;
+        cache.update(&ChannelUpdate(category.clone()));
+
+        for (id, position) in [(10, Some(2)), (11, Some(1)), (12, None), (13, Some(1))] {
+            let (_, _, mut channel) = test::guild_channel_text();
+            channel.id = Id::new(id);
+            channel.guild_id = Some(guild_id);
+            channel.parent_id = Some(category_id);
+            channel.position = position;
+            cache.update(&ChannelUpdate(channel));
+        }
+
+        let ids = |cache: &InMemoryCache| {
+            cache
+                .channel_children(category_id)
+                .into_iter()
+                .map(|channel| channel.id.get())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(Vec::from([11, 13, 10, 12]), ids(&cache));
+
+        let mut moved = cache.channel(Id::new(10)).unwrap().clone();
+        moved.parent_id = None;
+        cache.update(&ChannelUpdate(moved.clone()));
+        assert_eq!(Vec::from([11, 13, 12]), ids(&cache));
+
+        cache.update(&ChannelDelete(category));
+        assert!(cache.channel_children(category_id).is_empty());
+    }
+
+    #[test]
+    fn mutual_guild_members() {
+        let cache = InMemoryCache::new();
+        let user_id = Id::new(2);
+
+        cache.update(&MemberAdd(test::member(user_id, Id::new(20))));
+        cache.update(&MemberAdd(test::member(user_id, Id::new(10))));
+
+        let guild_ids = cache
+            .mutual_guild_members(user_id)
+            .into_iter()
+            .map(|member| member.guild_id().get())
+            .collect::<Vec<_>>();
+        assert_eq!(Vec::from([10, 20]), guild_ids);
+        assert!(cache.mutual_guild_members(Id::new(3)).is_empty());
+    }
+
+    #[test]
+    fn mutual_guild_members_user_changed() {
+        let cache = InMemoryCache::new();
+        let user_id = Id::new(2);
+
+        cache.update(&MemberAdd(test::member(user_id, Id::new(10))));
+
+        let mut member = test::member(user_id, Id::new(20));
+        member.user.name = "renamed".to_owned();
+        cache.update(&MemberAdd(member));
+
+        let guild_ids = cache
+            .mutual_guild_members(user_id)
+            .into_iter()
+            .map(|member| member.guild_id().get())
+            .collect::<Vec<_>>();
+        assert_eq!(Vec::from([10, 20]), guild_ids);
+    }
+}
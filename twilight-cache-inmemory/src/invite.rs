@@ -0,0 +1,365 @@
+//! Inferring which invite a member used to join a guild.
+
+use crate::{config::ResourceType, model::CachedInvite, InMemoryCache};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+    invite::Invite,
+};
+
+/// Invite that was likely used to join a guild, inferred from the change in
+/// use counts of its invites.
+///
+/// Returned by [`InMemoryCache::sync_invites`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InviteUse {
+    /// Uses of multiple invites increased, such as when multiple members
+    /// joined between syncs.
+    Ambiguous(Vec<CachedInvite>),
+    /// Uses of a single invite increased.
+    Invite(CachedInvite),
+    /// Uses of no invites increased.
+    ///
+    /// The member may have joined via the vanity URL, an invite that was
+    /// deleted before it could be synced, or without an invite, such as when
+    /// added by a bot.
+    Unknown,
+}
+
+impl InviteUse {
+    /// ID of the user who created the used invite, if it's known.
+    pub const fn inviter_id(&self) -> Option<Id<UserMarker>> {
+        match self {
+            Self::Invite(invite) => invite.inviter_id(),
+            Self::Ambiguous(_) | Self::Unknown => None,
+        }
+    }
+}
+
+/// Reason a cached invite was likely deleted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum InviteDeletion {
+    /// Invite was revoked or expired.
+    Revoked,
+    /// Invite was one use away from its maximum number of uses, so it was
+    /// deleted upon being used.
+    UsedUp,
+}
+
+/// Invite deleted from a synced guild, kept so that the next sync of the
+/// guild can consider its last use.
+#[derive(Clone, Debug)]
+pub(crate) struct DeletedInvite {
+    /// When the invite was deleted.
+    deleted_at: Instant,
+    pub(crate) invite: CachedInvite,
+    reason: InviteDeletion,
+}
+
+impl DeletedInvite {
+    /// Duration deleted invites are kept for if their guild isn't synced.
+    pub(crate) const RETENTION: Duration = Duration::from_secs(5 * 60);
+
+    /// Record the deletion of an invite, inferring why it was deleted.
+    pub(crate) fn new(invite: CachedInvite) -> Self {
+        let max_uses = invite.max_uses().unwrap_or_default();
+
+        let reason = if max_uses > 0 && invite.uses() + 1 >= max_uses {
+            InviteDeletion::UsedUp
+        } else {
+            InviteDeletion::Revoked
+        };
+
+        Self {
+            deleted_at: Instant::now(),
+            invite,
+            reason,
+        }
+    }
+
+    /// Whether the deletion is recent enough to be considered by a sync.
+    fn is_recent(&self) -> bool {
+        self.deleted_at.elapsed() < Self::RETENTION
+    }
+
+    /// The invite with its last use, if it was used up.
+    fn into_used(mut self) -> Option<CachedInvite> {
+        (self.reason == InviteDeletion::UsedUp).then(|| {
+            self.invite.uses += 1;
+
+            self.invite
+        })
+    }
+}
+
+impl InMemoryCache {
+    /// Record the deletion of an invite of a guild whose invites have been
+    /// synced, pruning the guild's deletions that are no longer recent.
+    pub(crate) fn record_deleted_invite(&self, invite: CachedInvite) {
+        let guild_id = invite.guild_id();
+
+        if !self.invite_synced_guilds.contains(&guild_id) {
+            return;
+        }
+
+        let mut deleted = self.deleted_invites.entry(guild_id).or_default();
+        deleted.retain(DeletedInvite::is_recent);
+        deleted.push(DeletedInvite::new(invite));
+    }
+
+    /// Replace the cached invites of a guild with a fresh list, returning the
+    /// invite whose use count increased since they were last cached.
+    ///
+    /// Call this when a member joins with the guild's current invites to
+    /// infer which invite they used, and when the guild becomes available to
+    /// seed the cache. The first sync of a guild, including after restoring a
+    /// snapshot, only seeds the cache and returns [`InviteUse::Unknown`], since
+    /// there are no previous use counts to compare with.
+    ///
+    /// Invites that were used up and deleted are included if they were one
+    /// use away from their maximum, whether they're missing from the list or
+    /// were deleted via an [`InviteDelete`] event in the last few minutes.
+    ///
+    /// Requires [`ResourceType::INVITE`] for the guild, otherwise nothing is
+    /// cached and [`InviteUse::Unknown`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// Attribute new members to the user who invited them:
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use twilight_cache_inmemory::InMemoryCache;
+    /// use twilight_model::gateway::event::Event;
+    /// # use twilight_model::invite::Invite;
+    /// # let event: Event = unimplemented!();
+    /// # async fn fetch_invites(
+    /// #     guild_id: twilight_model::id::Id<twilight_model::id::marker::GuildMarker>,
+    /// # ) -> Result<Vec<Invite>, Box<dyn std::error::Error>> { unimplemented!() }
+    ///
+    /// let cache = InMemoryCache::new();
+    /// cache.update(&event);
+    ///
+    /// if let Event::MemberAdd(member) = event {
+    ///     // Fetched via the HTTP API, such as with `Client::guild_invites`.
+    ///     let invites = fetch_invites(member.guild_id).await?;
+    ///
+    ///     if let Some(inviter_id) = cache.sync_invites(member.guild_id, &invites).inviter_id() {
+    ///         println!("{} was invited by {inviter_id}", member.user.id);
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// [`InviteDelete`]: twilight_model::gateway::payload::incoming::InviteDelete
+    pub fn sync_invites(&self, guild_id: Id<GuildMarker>, invites: &[Invite]) -> InviteUse {
+        if !self.wants_in(Some(guild_id), ResourceType::INVITE) {
+            return InviteUse::Unknown;
+        }
+
+        let seeding = self.invite_synced_guilds.insert(guild_id);
+        let fresh = invites
+            .iter()
+            .filter_map(|invite| CachedInvite::from_model(guild_id, invite))
+            .collect::<Vec<_>>();
+        let fresh_codes = fresh.iter().map(CachedInvite::code).collect::<HashSet<_>>();
+
+        let mut used = fresh
+            .iter()
+            .filter(|invite| {
+                let uses = self
+                    .invites
+                    .get(invite.code())
+                    .map_or(0, |cached| cached.uses());
+
+                invite.uses() > uses
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let cached_codes = self
+            .guild_invites
+            .get(&guild_id)
+            .map(|codes| codes.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let mut deleted = self
+            .deleted_invites
+            .remove(&guild_id)
+            .map(|(_, deleted)| deleted)
+            .unwrap_or_default();
+
+        for code in cached_codes {
+            if !fresh_codes.contains(code.as_str()) {
+                if let Some((_, invite)) = self.invites.remove(&code) {
+                    deleted.push(DeletedInvite::new(invite));
+                }
+            }
+        }
+
+        used.extend(
+            deleted
+                .into_iter()
+                .filter(DeletedInvite::is_recent)
+                .filter_map(DeletedInvite::into_used),
+        );
+
+        self.guild_invites.remove(&guild_id);
+
+        for invite in fresh {
+            self.cache_invite(invite);
+        }
+
+        if seeding {
+            return InviteUse::Unknown;
+        }
+
+        match used.len() {
+            0 => InviteUse::Unknown,
+            1 => InviteUse::Invite(used.remove(0)),
+            _ => InviteUse::Ambiguous(used),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InviteUse;
+    use crate::{test, InMemoryCache};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+    use twilight_model::{
+        channel::ChannelType,
+        gateway::payload::incoming::{InviteCreate, InviteDelete},
+        id::Id,
+        invite::{Invite, InviteChannel},
+        util::Timestamp,
+    };
+
+    assert_impl_all!(InviteUse: Clone, Debug, Eq, PartialEq, Send, Sync);
+
+    fn invite(code: &str, inviter: u64, uses: u64, max_uses: u64) -> Invite {
+        Invite {
+            approximate_member_count: None,
+            approximate_presence_count: None,
+            channel: Some(InviteChannel {
+                id: Id::new(2),
+                name: None,
+                kind: ChannelType::GuildText,
+            }),
+            code: code.to_owned(),
+            created_at: None,
+            expires_at: None,
+            guild: None,
+            inviter: Some(test::user(Id::new(inviter))),
+            max_age: Some(0),
+            max_uses: Some(max_uses),
+            target_type: None,
+            target_user: None,
+            temporary: Some(false),
+            uses: Some(uses),
+        }
+    }
+
+    #[test]
+    fn single_use() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+
+        // The first sync has no previous use counts to compare with.
+        let seeded = cache.sync_invites(guild_id, &[invite("a", 10, 0, 0), invite("b", 20, 5, 0)]);
+        assert_eq!(InviteUse::Unknown, seeded);
+        assert_eq!(5, cache.invite("b").unwrap().uses());
+
+        let used = cache.sync_invites(guild_id, &[invite("a", 10, 1, 0), invite("b", 20, 5, 0)]);
+        assert_eq!(Some(Id::new(10)), used.inviter_id());
+        assert_eq!(1, cache.invite("a").unwrap().uses());
+    }
+
+    #[test]
+    fn ambiguous_and_unknown() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+        let invites = [invite("a", 10, 0, 0), invite("b", 20, 0, 0)];
+        cache.sync_invites(guild_id, &invites);
+
+        assert_eq!(InviteUse::Unknown, cache.sync_invites(guild_id, &invites));
+
+        let used = cache.sync_invites(guild_id, &[invite("a", 10, 1, 0), invite("b", 20, 1, 0)]);
+        assert!(matches!(&used, InviteUse::Ambiguous(invites) if invites.len() == 2));
+        assert_eq!(None, used.inviter_id());
+    }
+
+    #[test]
+    fn used_up_invite() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+        cache.sync_invites(guild_id, &[invite("a", 10, 1, 2), invite("b", 20, 0, 0)]);
+
+        // The invite was deleted after reaching its maximum uses.
+        let used = cache.sync_invites(guild_id, &[invite("b", 20, 0, 0)]);
+
+        assert_eq!(Some(Id::new(10)), used.inviter_id());
+        assert!(cache.invite("a").is_none());
+        assert_eq!(1, cache.guild_invites(guild_id).unwrap().len());
+    }
+
+    #[test]
+    fn deleted_invite() {
+        let cache = InMemoryCache::new();
+        let guild_id = Id::new(1);
+        let delete = |code: &str| {
+            cache.update(&InviteDelete {
+                channel_id: Id::new(2),
+                code: code.to_owned(),
+                guild_id,
+            });
+        };
+        cache.sync_invites(
+            guild_id,
+            &[
+                invite("a", 10, 1, 2),
+                invite("b", 20, 0, 0),
+                invite("c", 30, 0, 0),
+            ],
+        );
+
+        // The invite was used up and its deletion received before the sync.
+        delete("a");
+        assert!(cache.invite("a").is_none());
+        let used = cache.sync_invites(guild_id, &[invite("b", 20, 0, 0), invite("c", 30, 0, 0)]);
+        assert_eq!(Some(Id::new(10)), used.inviter_id());
+        assert!(cache.deleted_invites.get(&guild_id).is_none());
+
+        // Revoked invites weren't used.
+        delete("c");
+        assert_eq!(
+            InviteUse::Unknown,
+            cache.sync_invites(guild_id, &[invite("b", 20, 0, 0)])
+        );
+
+        // Deletions in guilds that haven't been synced aren't kept.
+        cache.update(&InviteCreate {
+            channel_id: Id::new(4),
+            code: "d".to_owned(),
+            created_at: Timestamp::from_secs(1_632_072_645).expect("non zero"),
+            guild_id: Id::new(3),
+            inviter: None,
+            max_age: 0,
+            max_uses: 1,
+            target_user_type: None,
+            target_user: None,
+            temporary: false,
+            uses: 0,
+        });
+        delete("d");
+        assert!(cache.deleted_invites.is_empty());
+    }
+}
@@ -14,8 +14,8 @@
 
 use crate::{
     model::{
-        CachedEmoji, CachedGuild, CachedMember, CachedMessage, CachedPresence, CachedSticker,
-        CachedVoiceState,
+        CachedEmoji, CachedGuild, CachedInvite, CachedMember, CachedMessage, CachedPresence,
+        CachedSticker, CachedThreadMember, CachedVoiceState,
     },
     GuildResource, InMemoryCache,
 };
@@ -23,11 +23,11 @@ use dashmap::{iter::Iter, mapref::multiple::RefMulti};
 use std::{hash::Hash, ops::Deref};
 use twilight_model::{
     channel::{Channel, StageInstance},
-    guild::{GuildIntegration, Role},
+    guild::{auto_moderation::AutoModerationRule, GuildIntegration, Role},
     id::{
         marker::{
-            ChannelMarker, EmojiMarker, GuildMarker, IntegrationMarker, MessageMarker, RoleMarker,
-            StageMarker, StickerMarker, UserMarker,
+            AutoModerationRuleMarker, ChannelMarker, EmojiMarker, GuildMarker, IntegrationMarker,
+            MessageMarker, RoleMarker, StageMarker, StickerMarker, UserMarker,
         },
         Id,
     },
@@ -144,6 +144,13 @@ impl<'a> InMemoryCacheIter<'a> {
         self.0
     }
 
+    /// Create an iterator over the auto moderation rules in the cache.
+    pub fn auto_moderation_rules(
+        &self,
+    ) -> ResourceIter<'a, Id<AutoModerationRuleMarker>, AutoModerationRule> {
+        ResourceIter::new(self.0.auto_moderation_rules.iter())
+    }
+
     /// Create an iterator over the channels in the cache.
     pub fn channels(&self) -> ResourceIter<'a, Id<ChannelMarker>, Channel> {
         ResourceIter::new(self.0.channels.iter())
@@ -167,6 +174,11 @@ impl<'a> InMemoryCacheIter<'a> {
         ResourceIter::new(self.0.integrations.iter())
     }
 
+    /// Create an iterator over the invites across all guilds in the cache.
+    pub fn invites(&self) -> ResourceIter<'a, String, CachedInvite> {
+        ResourceIter::new(self.0.invites.iter())
+    }
+
     /// Create an iterator over the members across all guilds in the cache.
     pub fn members(&self) -> ResourceIter<'a, (Id<GuildMarker>, Id<UserMarker>), CachedMember> {
         ResourceIter::new(self.0.members.iter())
@@ -199,6 +211,13 @@ impl<'a> InMemoryCacheIter<'a> {
         ResourceIter::new(self.0.stickers.iter())
     }
 
+    /// Create an iterator over the thread members in the cache.
+    pub fn thread_members(
+        &self,
+    ) -> ResourceIter<'a, (Id<ChannelMarker>, Id<UserMarker>), CachedThreadMember> {
+        ResourceIter::new(self.0.thread_members.iter())
+    }
+
     /// Create an iterator over the users in the cache.
     pub fn users(&self) -> ResourceIter<'a, Id<UserMarker>, User> {
         ResourceIter::new(self.0.users.iter())
@@ -26,8 +26,17 @@ pub mod model;
 pub mod permission;
 
 mod builder;
+mod change;
 mod config;
 mod event;
+mod index;
+mod invite;
+mod memory;
+mod message_budget;
+#[cfg(feature = "http")]
+mod resolver;
+#[cfg(feature = "snapshot")]
+mod snapshot;
 mod stats;
 
 #[cfg(test)]
@@ -35,24 +44,36 @@ mod test;
 
 pub use self::{
     builder::InMemoryCacheBuilder,
+    change::{CacheChange, Change},
     config::{Config, ResourceType},
+    invite::InviteUse,
     stats::InMemoryCacheStats,
 };
 
 #[cfg(feature = "permission-calculator")]
 pub use self::permission::InMemoryCachePermissions;
 
+#[cfg(feature = "http")]
+pub use self::resolver::{InMemoryCacheResolver, ResolveError, ResolveErrorType};
+
+#[cfg(feature = "snapshot")]
+pub use self::snapshot::{SnapshotError, SnapshotErrorType};
+
 use self::{
+    index::MemberNames,
+    invite::DeletedInvite,
     iter::InMemoryCacheIter,
+    message_budget::MessageBudget,
     model::{
-        CachedEmoji, CachedGuild, CachedMember, CachedMessage, CachedPresence, CachedSticker,
-        CachedVoiceState,
+        CachedEmoji, CachedGuild, CachedInvite, CachedMember, CachedMessage, CachedPresence,
+        CachedSticker, CachedThreadMember, CachedVoiceState,
     },
 };
 use dashmap::{
     mapref::{entry::Entry, one::Ref},
     DashMap, DashSet,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeSet, HashSet, VecDeque},
     fmt::{Debug, Formatter, Result as FmtResult},
@@ -63,11 +84,11 @@ use std::{
 use twilight_model::{
     channel::{Channel, StageInstance},
     gateway::event::Event,
-    guild::{GuildIntegration, Role},
+    guild::{auto_moderation::AutoModerationRule, GuildIntegration, Role},
     id::{
         marker::{
-            ChannelMarker, EmojiMarker, GuildMarker, IntegrationMarker, MessageMarker, RoleMarker,
-            StageMarker, StickerMarker, UserMarker,
+            AutoModerationRuleMarker, ChannelMarker, EmojiMarker, GuildMarker, IntegrationMarker,
+            MessageMarker, RoleMarker, StageMarker, StickerMarker, UserMarker,
         },
         Id,
     },
@@ -79,7 +100,7 @@ use twilight_model::{
 /// This is used when a resource does not itself include its associated guild's
 /// ID. In lieu of the resource itself storing its guild's ID this relation
 /// includes it.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct GuildResource<T> {
     guild_id: Id<GuildMarker>,
     value: T,
@@ -197,15 +218,24 @@ fn upsert_guild_item<K: Eq + Hash, V: PartialEq>(
 #[derive(Debug, Default)]
 pub struct InMemoryCache {
     config: Config,
+    auto_moderation_rules: DashMap<Id<AutoModerationRuleMarker>, AutoModerationRule>,
+    /// Mapping of channels and the channels whose parent they are.
+    channel_children: DashMap<Id<ChannelMarker>, HashSet<Id<ChannelMarker>>>,
     channels: DashMap<Id<ChannelMarker>, Channel>,
     channel_messages: DashMap<Id<ChannelMarker>, VecDeque<Id<MessageMarker>>>,
     // So long as the lock isn't held across await or panic points this is fine.
     current_user: Mutex<Option<CurrentUser>>,
+    /// Invites recently deleted from guilds whose invites have been synced.
+    deleted_invites: DashMap<Id<GuildMarker>, Vec<DeletedInvite>>,
     emojis: DashMap<Id<EmojiMarker>, GuildResource<CachedEmoji>>,
     guilds: DashMap<Id<GuildMarker>, CachedGuild>,
+    guild_auto_moderation_rules: DashMap<Id<GuildMarker>, HashSet<Id<AutoModerationRuleMarker>>>,
     guild_channels: DashMap<Id<GuildMarker>, HashSet<Id<ChannelMarker>>>,
     guild_emojis: DashMap<Id<GuildMarker>, HashSet<Id<EmojiMarker>>>,
     guild_integrations: DashMap<Id<GuildMarker>, HashSet<Id<IntegrationMarker>>>,
+    guild_invites: DashMap<Id<GuildMarker>, HashSet<String>>,
+    /// Mapping of guilds and the names of their members.
+    guild_member_names: DashMap<Id<GuildMarker>, MemberNames>,
     guild_members: DashMap<Id<GuildMarker>, HashSet<Id<UserMarker>>>,
     guild_presences: DashMap<Id<GuildMarker>, HashSet<Id<UserMarker>>>,
     guild_roles: DashMap<Id<GuildMarker>, HashSet<Id<RoleMarker>>>,
@@ -213,12 +243,26 @@ pub struct InMemoryCache {
     guild_stickers: DashMap<Id<GuildMarker>, HashSet<Id<StickerMarker>>>,
     integrations:
         DashMap<(Id<GuildMarker>, Id<IntegrationMarker>), GuildResource<GuildIntegration>>,
+    /// Guilds whose invites have been synced.
+    invite_synced_guilds: DashSet<Id<GuildMarker>>,
+    invites: DashMap<String, CachedInvite>,
     members: DashMap<(Id<GuildMarker>, Id<UserMarker>), CachedMember>,
+    // Never lock while holding a reference into one of the maps.
+    message_budget: Mutex<MessageBudget>,
     messages: DashMap<Id<MessageMarker>, CachedMessage>,
     presences: DashMap<(Id<GuildMarker>, Id<UserMarker>), CachedPresence>,
+    /// Mapping of roles and the members that have them.
+    #[allow(clippy::type_complexity)]
+    role_members: DashMap<Id<RoleMarker>, HashSet<(Id<GuildMarker>, Id<UserMarker>)>>,
     roles: DashMap<Id<RoleMarker>, GuildResource<Role>>,
     stage_instances: DashMap<Id<StageMarker>, GuildResource<StageInstance>>,
     stickers: DashMap<Id<StickerMarker>, GuildResource<CachedSticker>>,
+    /// Mapping of threads and the users that are members of them.
+    thread_member_threads: DashMap<Id<ChannelMarker>, HashSet<Id<UserMarker>>>,
+    /// Mapping of users and the threads they are members of.
+    thread_member_users: DashMap<Id<UserMarker>, HashSet<Id<ChannelMarker>>>,
+    /// Mapping of thread ID and user ID pairs to their thread members.
+    thread_members: DashMap<(Id<ChannelMarker>, Id<UserMarker>), CachedThreadMember>,
     unavailable_guilds: DashSet<Id<GuildMarker>>,
     users: DashMap<Id<UserMarker>, User>,
     user_guilds: DashMap<Id<UserMarker>, BTreeSet<Id<GuildMarker>>>,
@@ -258,29 +302,44 @@ impl InMemoryCache {
     ///
     /// This is equal to creating a new empty cache.
     pub fn clear(&self) {
+        self.auto_moderation_rules.clear();
+        self.channel_children.clear();
         self.channels.clear();
         self.channel_messages.clear();
         self.current_user
             .lock()
             .expect("current user poisoned")
             .take();
+        self.deleted_invites.clear();
         self.emojis.clear();
         self.guilds.clear();
+        self.guild_auto_moderation_rules.clear();
         self.guild_channels.clear();
         self.guild_emojis.clear();
         self.guild_integrations.clear();
+        self.guild_invites.clear();
+        self.guild_member_names.clear();
         self.guild_members.clear();
         self.guild_presences.clear();
         self.guild_roles.clear();
         self.guild_stage_instances.clear();
         self.guild_stickers.clear();
         self.integrations.clear();
+        self.invite_synced_guilds.clear();
+        self.invites.clear();
         self.members.clear();
+        *self.message_budget.lock().expect("message budget poisoned") = MessageBudget::default();
         self.messages.clear();
         self.presences.clear();
+        self.role_members.clear();
         self.roles.clear();
+        self.stage_instances.clear();
         self.stickers.clear();
+        self.thread_member_threads.clear();
+        self.thread_member_users.clear();
+        self.thread_members.clear();
         self.unavailable_guilds.clear();
+        self.user_guilds.clear();
         self.users.clear();
         self.voice_state_channels.clear();
         self.voice_state_guilds.clear();
@@ -383,6 +442,18 @@ impl InMemoryCache {
             .clone()
     }
 
+    /// Gets an auto moderation rule by ID.
+    ///
+    /// This requires the [`AUTO_MODERATION_CONFIGURATION`] intent.
+    ///
+    /// [`AUTO_MODERATION_CONFIGURATION`]: ::twilight_model::gateway::Intents::AUTO_MODERATION_CONFIGURATION
+    pub fn auto_moderation_rule(
+        &self,
+        rule_id: Id<AutoModerationRuleMarker>,
+    ) -> Option<Reference<'_, Id<AutoModerationRuleMarker>, AutoModerationRule>> {
+        self.auto_moderation_rules.get(&rule_id).map(Reference::new)
+    }
+
     /// Gets a channel by ID.
     pub fn channel(
         &self,
@@ -428,6 +499,23 @@ impl InMemoryCache {
         self.guilds.get(&guild_id).map(Reference::new)
     }
 
+    /// Gets the set of auto moderation rules in a guild.
+    ///
+    /// Rules created before the cache started aren't included, as they aren't
+    /// sent when a guild becomes available.
+    ///
+    /// This requires the [`AUTO_MODERATION_CONFIGURATION`] intent.
+    ///
+    /// [`AUTO_MODERATION_CONFIGURATION`]: ::twilight_model::gateway::Intents::AUTO_MODERATION_CONFIGURATION
+    pub fn guild_auto_moderation_rules(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Option<Reference<'_, Id<GuildMarker>, HashSet<Id<AutoModerationRuleMarker>>>> {
+        self.guild_auto_moderation_rules
+            .get(&guild_id)
+            .map(Reference::new)
+    }
+
     /// Gets the set of channels in a guild.
     ///
     /// This requires the [`GUILDS`] intent.
@@ -467,6 +555,20 @@ impl InMemoryCache {
         self.guild_integrations.get(&guild_id).map(Reference::new)
     }
 
+    /// Gets the set of invite codes in a guild.
+    ///
+    /// This requires the [`GUILD_INVITES`] intent. Invites created before the
+    /// cache started are only known once synced via [`sync_invites`].
+    ///
+    /// [`GUILD_INVITES`]: ::twilight_model::gateway::Intents::GUILD_INVITES
+    /// [`sync_invites`]: Self::sync_invites
+    pub fn guild_invites(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Option<Reference<'_, Id<GuildMarker>, HashSet<String>>> {
+        self.guild_invites.get(&guild_id).map(Reference::new)
+    }
+
     /// Gets the set of members in a guild.
     ///
     /// This list may be incomplete if not all members have been cached.
@@ -569,6 +671,15 @@ impl InMemoryCache {
             .map(Reference::new)
     }
 
+    /// Gets an invite by code.
+    ///
+    /// This requires the [`GUILD_INVITES`] intent.
+    ///
+    /// [`GUILD_INVITES`]: ::twilight_model::gateway::Intents::GUILD_INVITES
+    pub fn invite(&self, code: &str) -> Option<Reference<'_, String, CachedInvite>> {
+        self.invites.get(code).map(Reference::new)
+    }
+
     /// Gets a member by guild ID and user ID.
     ///
     /// This requires the [`GUILD_MEMBERS`] intent.
@@ -650,6 +761,38 @@ impl InMemoryCache {
         self.stickers.get(&sticker_id).map(Reference::new)
     }
 
+    /// Gets a thread member by thread ID and user ID.
+    ///
+    /// Thread members other than the current user are only received with the
+    /// [`GUILD_MEMBERS`] intent.
+    ///
+    /// [`GUILD_MEMBERS`]: ::twilight_model::gateway::Intents::GUILD_MEMBERS
+    #[allow(clippy::type_complexity)]
+    pub fn thread_member(
+        &self,
+        thread_id: Id<ChannelMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Option<Reference<'_, (Id<ChannelMarker>, Id<UserMarker>), CachedThreadMember>> {
+        self.thread_members
+            .get(&(thread_id, user_id))
+            .map(Reference::new)
+    }
+
+    /// Gets the set of members of a thread.
+    ///
+    /// Thread members other than the current user are only received with the
+    /// [`GUILD_MEMBERS`] intent.
+    ///
+    /// [`GUILD_MEMBERS`]: ::twilight_model::gateway::Intents::GUILD_MEMBERS
+    pub fn thread_members(
+        &self,
+        thread_id: Id<ChannelMarker>,
+    ) -> Option<Reference<'_, Id<ChannelMarker>, HashSet<Id<UserMarker>>>> {
+        self.thread_member_threads
+            .get(&thread_id)
+            .map(Reference::new)
+    }
+
     /// Gets a user by ID.
     ///
     /// This requires the [`GUILD_MEMBERS`] intent.
@@ -659,6 +802,19 @@ impl InMemoryCache {
         self.users.get(&user_id).map(Reference::new)
     }
 
+    /// Gets the set of threads a user is a member of.
+    ///
+    /// Thread members other than the current user are only received with the
+    /// [`GUILD_MEMBERS`] intent.
+    ///
+    /// [`GUILD_MEMBERS`]: ::twilight_model::gateway::Intents::GUILD_MEMBERS
+    pub fn user_threads(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Option<Reference<'_, Id<UserMarker>, HashSet<Id<ChannelMarker>>>> {
+        self.thread_member_users.get(&user_id).map(Reference::new)
+    }
+
     /// Gets the voice states within a voice channel.
     ///
     /// This requires both the [`GUILDS`] and [`GUILD_VOICE_STATES`] intents.
@@ -737,26 +893,54 @@ impl InMemoryCache {
     const fn wants(&self, resource_type: ResourceType) -> bool {
         self.config.resource_types().contains(resource_type)
     }
+
+    /// Determine whether the configured cache wants a specific resource of a
+    /// guild, if any, to be processed.
+    fn wants_in(&self, guild_id: Option<Id<GuildMarker>>, resource_type: ResourceType) -> bool {
+        match guild_id {
+            Some(guild_id) => self
+                .config
+                .guild_resource_types(guild_id)
+                .contains(resource_type),
+            None => self.wants(resource_type),
+        }
+    }
+
+    /// Determine whether the configured cache wants the messages of a
+    /// channel, and the guild it's in if any, to be processed.
+    fn wants_messages_in(
+        &self,
+        guild_id: Option<Id<GuildMarker>>,
+        channel_id: Id<ChannelMarker>,
+    ) -> bool {
+        self.wants_in(guild_id, ResourceType::MESSAGE)
+            && self.config.channel_message_filter(channel_id)
+    }
 }
 
 mod private {
     use twilight_model::gateway::{
         event::Event,
         payload::incoming::{
+            AutoModerationRuleCreate, AutoModerationRuleDelete, AutoModerationRuleUpdate,
             ChannelCreate, ChannelDelete, ChannelPinsUpdate, ChannelUpdate, GuildCreate,
             GuildDelete, GuildEmojisUpdate, GuildStickersUpdate, GuildUpdate, IntegrationCreate,
-            IntegrationDelete, IntegrationUpdate, InteractionCreate, MemberAdd, MemberChunk,
-            MemberRemove, MemberUpdate, MessageCreate, MessageDelete, MessageDeleteBulk,
-            MessageUpdate, PresenceUpdate, ReactionAdd, ReactionRemove, ReactionRemoveAll,
-            ReactionRemoveEmoji, Ready, RoleCreate, RoleDelete, RoleUpdate, StageInstanceCreate,
-            StageInstanceDelete, StageInstanceUpdate, ThreadCreate, ThreadDelete, ThreadListSync,
-            ThreadUpdate, UnavailableGuild, UserUpdate, VoiceStateUpdate,
+            IntegrationDelete, IntegrationUpdate, InteractionCreate, InviteCreate, InviteDelete,
+            MemberAdd, MemberChunk, MemberRemove, MemberUpdate, MessageCreate, MessageDelete,
+            MessageDeleteBulk, MessageUpdate, PresenceUpdate, ReactionAdd, ReactionRemove,
+            ReactionRemoveAll, ReactionRemoveEmoji, Ready, RoleCreate, RoleDelete, RoleUpdate,
+            StageInstanceCreate, StageInstanceDelete, StageInstanceUpdate, ThreadCreate,
+            ThreadDelete, ThreadListSync, ThreadMemberUpdate, ThreadMembersUpdate, ThreadUpdate,
+            UnavailableGuild, UserUpdate, VoiceStateUpdate,
         },
     };
 
     pub trait Sealed {}
 
     impl Sealed for Event {}
+    impl Sealed for AutoModerationRuleCreate {}
+    impl Sealed for AutoModerationRuleDelete {}
+    impl Sealed for AutoModerationRuleUpdate {}
     impl Sealed for ChannelCreate {}
     impl Sealed for ChannelDelete {}
     impl Sealed for ChannelPinsUpdate {}
@@ -770,6 +954,8 @@ mod private {
     impl Sealed for IntegrationDelete {}
     impl Sealed for IntegrationUpdate {}
     impl Sealed for InteractionCreate {}
+    impl Sealed for InviteCreate {}
+    impl Sealed for InviteDelete {}
     impl Sealed for MemberAdd {}
     impl Sealed for MemberChunk {}
     impl Sealed for MemberRemove {}
@@ -793,6 +979,8 @@ mod private {
     impl Sealed for ThreadCreate {}
     impl Sealed for ThreadDelete {}
     impl Sealed for ThreadListSync {}
+    impl Sealed for ThreadMemberUpdate {}
+    impl Sealed for ThreadMembersUpdate {}
     impl Sealed for ThreadUpdate {}
     impl Sealed for UnavailableGuild {}
     impl Sealed for UserUpdate {}
@@ -838,6 +1026,9 @@ impl UpdateCache for Event {
     #[allow(clippy::cognitive_complexity, clippy::explicit_deref_methods)]
     fn update(&self, c: &InMemoryCache) {
         match self {
+            Event::AutoModerationRuleCreate(v) => c.update(v.deref()),
+            Event::AutoModerationRuleDelete(v) => c.update(v.deref()),
+            Event::AutoModerationRuleUpdate(v) => c.update(v.deref()),
             Event::ChannelCreate(v) => c.update(v.deref()),
             Event::ChannelDelete(v) => c.update(v.deref()),
             Event::ChannelPinsUpdate(v) => c.update(v),
@@ -851,6 +1042,8 @@ impl UpdateCache for Event {
             Event::IntegrationDelete(v) => c.update(v.deref()),
             Event::IntegrationUpdate(v) => c.update(v.deref()),
             Event::InteractionCreate(v) => c.update(v.deref()),
+            Event::InviteCreate(v) => c.update(v.deref()),
+            Event::InviteDelete(v) => c.update(v),
             Event::MemberAdd(v) => c.update(v.deref()),
             Event::MemberRemove(v) => c.update(v),
             Event::MemberUpdate(v) => c.update(v.deref()),
@@ -875,14 +1068,13 @@ impl UpdateCache for Event {
             Event::ThreadUpdate(v) => c.update(v.deref()),
             Event::ThreadDelete(v) => c.update(v),
             Event::ThreadListSync(v) => c.update(v),
+            Event::ThreadMembersUpdate(v) => c.update(v),
+            Event::ThreadMemberUpdate(v) => c.update(v.deref()),
             Event::UnavailableGuild(v) => c.update(v),
             Event::UserUpdate(v) => c.update(v),
             Event::VoiceStateUpdate(v) => c.update(v.deref()),
             // Ignored events.
             Event::AutoModerationActionExecution(_)
-            | Event::AutoModerationRuleCreate(_)
-            | Event::AutoModerationRuleDelete(_)
-            | Event::AutoModerationRuleUpdate(_)
             | Event::BanAdd(_)
             | Event::BanRemove(_)
             | Event::CommandPermissionsUpdate(_)
@@ -898,8 +1090,6 @@ impl UpdateCache for Event {
             | Event::GuildScheduledEventUpdate(_)
             | Event::GuildScheduledEventUserAdd(_)
             | Event::GuildScheduledEventUserRemove(_)
-            | Event::InviteCreate(_)
-            | Event::InviteDelete(_)
             | Event::PresencesReplace
             | Event::Resumed
             | Event::ShardConnected(_)
@@ -909,8 +1099,6 @@ impl UpdateCache for Event {
             | Event::ShardPayload(_)
             | Event::ShardReconnecting(_)
             | Event::ShardResuming(_)
-            | Event::ThreadMembersUpdate(_)
-            | Event::ThreadMemberUpdate(_)
             | Event::TypingStart(_)
             | Event::VoiceServerUpdate(_)
             | Event::WebhooksUpdate(_) => {}
@@ -920,9 +1108,9 @@ impl UpdateCache for Event {
 
 #[cfg(test)]
 mod tests {
-    use crate::{test, InMemoryCache};
+    use crate::{test, InMemoryCache, ResourceType};
     use twilight_model::{
-        gateway::payload::incoming::RoleDelete,
+        gateway::payload::incoming::{GuildCreate, MemberChunk, MessageCreate, RoleDelete},
         guild::{Member, Permissions, Role},
         id::Id,
         util::Timestamp,
@@ -947,6 +1135,45 @@ mod tests {
         assert!(cache.members.is_empty());
     }
 
+    #[test]
+    fn guild_resource_types() {
+        let cache = InMemoryCache::builder()
+            .guild_resource_types(|guild_id| {
+                if guild_id == Id::new(1) {
+                    ResourceType::all()
+                } else {
+                    ResourceType::GUILD
+                }
+            })
+            .channel_message_filter(|channel_id| channel_id != Id::new(4))
+            .build();
+
+        for guild_id in [Id::new(1), Id::new(2)] {
+            let mut guild = test::guild(guild_id, Some(1));
+            guild.members = Vec::from([test::member(Id::new(3), guild_id)]);
+            cache.update(&GuildCreate(guild));
+            cache.update(&MemberChunk {
+                chunk_count: 1,
+                chunk_index: 0,
+                guild_id,
+                members: Vec::from([test::member(Id::new(5), guild_id)]),
+                nonce: None,
+                not_found: Vec::new(),
+                presences: Vec::new(),
+            });
+        }
+
+        assert!(cache.guild(Id::new(2)).is_some());
+        assert_eq!(Some(2), cache.stats().guild_members(Id::new(1)));
+        assert!(cache.member(Id::new(2), Id::new(3)).is_none());
+        assert!(cache.member(Id::new(2), Id::new(5)).is_none());
+
+        cache.update(&MessageCreate(test::message(Id::new(4), Id::new(6))));
+        cache.update(&MessageCreate(test::message(Id::new(7), Id::new(8))));
+        assert!(cache.message(Id::new(6)).is_none());
+        assert!(cache.message(Id::new(8)).is_some());
+    }
+
     #[test]
     fn highest_role() {
         let joined_at = Timestamp::from_secs(1_632_072_645).expect("non zero");
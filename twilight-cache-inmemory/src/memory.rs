@@ -0,0 +1,508 @@
+//! Estimating the memory used by the resources in the cache.
+
+use crate::{
+    config::ResourceType,
+    index::MemberNames,
+    model::{
+        CachedEmoji, CachedGuild, CachedMember, CachedMessage, CachedPresence, CachedSticker,
+        CachedThreadMember, CachedVoiceState,
+    },
+    GuildResource, InMemoryCache,
+};
+use dashmap::DashMap;
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    hash::Hash,
+    mem,
+};
+use twilight_model::{
+    channel::{permission_overwrite::PermissionOverwrite, Channel, StageInstance},
+    guild::{
+        auto_moderation::{
+            AutoModerationAction, AutoModerationKeywordPresetType, AutoModerationRule,
+        },
+        GuildIntegration, Role,
+    },
+    id::{
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+    user::{CurrentUser, User},
+};
+
+/// Length of an optional string.
+fn len(value: Option<&str>) -> usize {
+    value.map_or(0, str::len)
+}
+
+/// Approximate number of bytes used by an auto moderation rule.
+fn auto_moderation_rule(rule: &AutoModerationRule) -> usize {
+    let metadata = &rule.trigger_metadata;
+    let strings = |strings: Option<&Vec<String>>| {
+        strings.map_or(0, |strings| {
+            strings
+                .iter()
+                .map(|string| mem::size_of::<String>() + string.len())
+                .sum()
+        })
+    };
+
+    mem::size_of::<AutoModerationRule>()
+        + rule.actions.len() * mem::size_of::<AutoModerationAction>()
+        + rule.exempt_channels.len() * mem::size_of::<Id<ChannelMarker>>()
+        + rule.exempt_roles.len() * mem::size_of::<Id<RoleMarker>>()
+        + rule.name.len()
+        + strings(metadata.allow_list.as_ref())
+        + strings(metadata.keyword_filter.as_ref())
+        + metadata.presets.as_ref().map_or(0, |presets| {
+            presets.len() * mem::size_of::<AutoModerationKeywordPresetType>()
+        })
+}
+
+/// Approximate number of bytes used by a channel.
+fn channel(channel: &Channel) -> usize {
+    mem::size_of::<Channel>()
+        + len(channel.name.as_deref())
+        + channel
+            .permission_overwrites
+            .as_ref()
+            .map_or(0, |overwrites| {
+                overwrites.len() * mem::size_of::<PermissionOverwrite>()
+            })
+        + channel
+            .recipients
+            .as_ref()
+            .map_or(0, |recipients| recipients.iter().map(user).sum())
+        + len(channel.rtc_region.as_deref())
+        + len(channel.topic.as_deref())
+}
+
+/// Approximate number of bytes used by the current user.
+fn current_user(current_user: &CurrentUser) -> usize {
+    mem::size_of::<CurrentUser>()
+        + len(current_user.email.as_deref())
+        + len(current_user.locale.as_deref())
+        + current_user.name.len()
+}
+
+/// Approximate number of bytes used by a guild integration.
+fn integration(integration: &GuildIntegration) -> usize {
+    mem::size_of::<GuildIntegration>()
+        + integration.account.id.len()
+        + integration.account.name.len()
+        + integration.kind.len()
+        + integration.name.len()
+        + integration.user.as_ref().map_or(0, user)
+}
+
+/// Approximate number of bytes used by a role.
+fn role(role: &Role) -> usize {
+    mem::size_of::<Role>() + role.name.len() + len(role.unicode_emoji.as_deref())
+}
+
+/// Approximate number of bytes used by a stage instance.
+fn stage_instance(stage_instance: &StageInstance) -> usize {
+    mem::size_of::<StageInstance>() + stage_instance.topic.len()
+}
+
+/// Approximate number of bytes used by a user.
+fn user(user: &User) -> usize {
+    mem::size_of::<User>()
+        + len(user.email.as_deref())
+        + len(user.locale.as_deref())
+        + user.name.len()
+}
+
+/// Approximate number of bytes used by a resource associated with a guild.
+fn guild_resource<T>(resource: &GuildResource<T>, size: impl Fn(&T) -> usize) -> usize {
+    mem::size_of::<Id<GuildMarker>>() + size(resource.resource())
+}
+
+/// Collection of IDs in an index.
+trait Collection {
+    /// Approximate number of bytes used by the collection.
+    fn estimated_size(&self) -> usize;
+}
+
+impl<T> Collection for BTreeSet<T> {
+    fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>() + self.len() * mem::size_of::<T>()
+    }
+}
+
+impl<T> Collection for HashSet<T> {
+    fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>() + self.len() * mem::size_of::<T>()
+    }
+}
+
+impl<T> Collection for VecDeque<T> {
+    fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>() + self.len() * mem::size_of::<T>()
+    }
+}
+
+/// Approximate number of bytes used by the entries of a map.
+fn entries<K: Eq + Hash, V>(map: &DashMap<K, V>, size: impl Fn(&V) -> usize) -> usize {
+    map.iter()
+        .map(|entry| mem::size_of::<K>() + size(entry.value()))
+        .sum()
+}
+
+/// Approximate number of bytes used by the entries of an index.
+fn index<K: Eq + Hash, C: Collection>(map: &DashMap<K, C>) -> usize {
+    entries(map, Collection::estimated_size)
+}
+
+/// Approximate number of bytes used by some entries of a map.
+fn some_entries<K: Eq + Hash, V>(
+    map: &DashMap<K, V>,
+    keys: impl IntoIterator<Item = K>,
+    size: impl Fn(&V) -> usize,
+) -> usize {
+    keys.into_iter()
+        .filter_map(|key| {
+            map.get(&key)
+                .map(|entry| mem::size_of::<K>() + size(entry.value()))
+        })
+        .sum()
+}
+
+/// Approximate number of bytes used by an entry of an index, and the IDs in
+/// it.
+fn index_entry<K: Eq + Hash, T: Clone>(map: &DashMap<K, HashSet<T>>, key: &K) -> (usize, Vec<T>) {
+    map.get(key).map_or((0, Vec::new()), |ids| {
+        (
+            mem::size_of::<K>() + ids.estimated_size(),
+            ids.iter().cloned().collect(),
+        )
+    })
+}
+
+impl InMemoryCache {
+    /// Approximate number of bytes used by resources of some types.
+    pub(crate) fn memory_usage(&self, resource_types: ResourceType) -> usize {
+        let mut size = 0;
+
+        if resource_types.contains(ResourceType::AUTO_MODERATION_RULE) {
+            size += entries(&self.auto_moderation_rules, auto_moderation_rule)
+                + index(&self.guild_auto_moderation_rules);
+        }
+
+        if resource_types.contains(ResourceType::CHANNEL) {
+            size += entries(&self.channels, channel)
+                + index(&self.channel_children)
+                + index(&self.guild_channels);
+        }
+
+        if resource_types.contains(ResourceType::EMOJI) {
+            size += entries(&self.emojis, |emoji| {
+                guild_resource(emoji, CachedEmoji::estimated_size)
+            }) + index(&self.guild_emojis);
+        }
+
+        if resource_types.contains(ResourceType::GUILD) {
+            size += entries(&self.guilds, CachedGuild::estimated_size)
+                + self.unavailable_guilds.len() * mem::size_of::<Id<GuildMarker>>();
+        }
+
+        if resource_types.contains(ResourceType::INTEGRATION) {
+            size += entries(&self.integrations, |integration| {
+                guild_resource(integration, self::integration)
+            }) + index(&self.guild_integrations);
+        }
+
+        if resource_types.contains(ResourceType::INVITE) {
+            // Codes are stored as the keys of both maps.
+            size += entries(&self.invites, |invite| {
+                invite.code().len() + invite.estimated_size()
+            }) + entries(&self.guild_invites, |codes| {
+                codes.estimated_size() + codes.iter().map(String::len).sum::<usize>()
+            }) + entries(&self.deleted_invites, |deleted| {
+                deleted
+                    .iter()
+                    .map(|deleted| mem::size_of_val(deleted) + deleted.invite.code().len())
+                    .sum::<usize>()
+            }) + self.invite_synced_guilds.len() * mem::size_of::<Id<GuildMarker>>();
+        }
+
+        if resource_types.contains(ResourceType::MEMBER) {
+            size += entries(&self.members, CachedMember::estimated_size)
+                + entries(&self.guild_member_names, MemberNames::estimated_size)
+                + index(&self.guild_members)
+                + index(&self.role_members);
+        }
+
+        if resource_types.contains(ResourceType::MESSAGE) {
+            size += entries(&self.messages, CachedMessage::estimated_size)
+                + index(&self.channel_messages);
+        }
+
+        if resource_types.contains(ResourceType::PRESENCE) {
+            size += entries(&self.presences, CachedPresence::estimated_size)
+                + index(&self.guild_presences);
+        }
+
+        if resource_types.contains(ResourceType::ROLE) {
+            size += entries(&self.roles, |role| guild_resource(role, self::role))
+                + index(&self.guild_roles);
+        }
+
+        if resource_types.contains(ResourceType::STAGE_INSTANCE) {
+            size += entries(&self.stage_instances, |stage_instance| {
+                guild_resource(stage_instance, self::stage_instance)
+            }) + index(&self.guild_stage_instances);
+        }
+
+        if resource_types.contains(ResourceType::STICKER) {
+            size += entries(&self.stickers, |sticker| {
+                guild_resource(sticker, CachedSticker::estimated_size)
+            }) + index(&self.guild_stickers);
+        }
+
+        if resource_types.contains(ResourceType::THREAD_MEMBER) {
+            size += entries(&self.thread_members, |_| {
+                mem::size_of::<CachedThreadMember>()
+            }) + index(&self.thread_member_threads)
+                + index(&self.thread_member_users);
+        }
+
+        if resource_types.contains(ResourceType::USER) {
+            size += entries(&self.users, user) + index(&self.user_guilds);
+        }
+
+        if resource_types.contains(ResourceType::USER_CURRENT) {
+            size += self
+                .current_user
+                .lock()
+                .expect("current user poisoned")
+                .as_ref()
+                .map_or(0, current_user);
+        }
+
+        if resource_types.contains(ResourceType::VOICE_STATE) {
+            size += entries(&self.voice_states, CachedVoiceState::estimated_size)
+                + index(&self.voice_state_channels)
+                + index(&self.voice_state_guilds);
+        }
+
+        size
+    }
+
+    /// Approximate number of bytes used by resources of some types in a
+    /// guild.
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn guild_memory_usage(
+        &self,
+        guild_id: Id<GuildMarker>,
+        resource_types: ResourceType,
+    ) -> usize {
+        let mut size = 0;
+
+        if resource_types.contains(ResourceType::AUTO_MODERATION_RULE) {
+            let (index, rule_ids) = index_entry(&self.guild_auto_moderation_rules, &guild_id);
+            size +=
+                index + some_entries(&self.auto_moderation_rules, rule_ids, auto_moderation_rule);
+        }
+
+        let (channel_index, channel_ids) = index_entry(&self.guild_channels, &guild_id);
+
+        if resource_types.contains(ResourceType::CHANNEL) {
+            size += channel_index
+                + some_entries(&self.channels, channel_ids.iter().copied(), channel)
+                + some_entries(
+                    &self.channel_children,
+                    channel_ids.iter().copied(),
+                    Collection::estimated_size,
+                );
+        }
+
+        if resource_types.contains(ResourceType::EMOJI) {
+            let (index, emoji_ids) = index_entry(&self.guild_emojis, &guild_id);
+            size += index
+                + some_entries(&self.emojis, emoji_ids, |emoji| {
+                    guild_resource(emoji, CachedEmoji::estimated_size)
+                });
+        }
+
+        if resource_types.contains(ResourceType::GUILD) {
+            size += some_entries(&self.guilds, [guild_id], CachedGuild::estimated_size);
+        }
+
+        if resource_types.contains(ResourceType::INTEGRATION) {
+            let (index, integration_ids) = index_entry(&self.guild_integrations, &guild_id);
+            size += index
+                + some_entries(
+                    &self.integrations,
+                    integration_ids
+                        .into_iter()
+                        .map(|integration_id| (guild_id, integration_id)),
+                    |integration| guild_resource(integration, self::integration),
+                );
+        }
+
+        if resource_types.contains(ResourceType::INVITE) {
+            let (index, codes) = index_entry(&self.guild_invites, &guild_id);
+            size += index
+                + codes.iter().map(String::len).sum::<usize>()
+                + codes
+                    .iter()
+                    .filter_map(|code| self.invites.get(code.as_str()))
+                    .map(|invite| {
+                        mem::size_of::<String>() + invite.code().len() + invite.estimated_size()
+                    })
+                    .sum::<usize>();
+        }
+
+        if resource_types.contains(ResourceType::MEMBER) {
+            let (index, user_ids) = index_entry(&self.guild_members, &guild_id);
+            size += index
+                + some_entries(
+                    &self.members,
+                    user_ids.into_iter().map(|user_id| (guild_id, user_id)),
+                    |member| {
+                        member.estimated_size()
+                            + member.roles.len()
+                                * mem::size_of::<(Id<GuildMarker>, Id<UserMarker>)>()
+                    },
+                )
+                + some_entries(
+                    &self.guild_member_names,
+                    [guild_id],
+                    MemberNames::estimated_size,
+                );
+        }
+
+        if resource_types.contains(ResourceType::MESSAGE) {
+            for channel_id in &channel_ids {
+                let message_ids = self
+                    .channel_messages
+                    .get(channel_id)
+                    .map(|message_ids| {
+                        size += mem::size_of::<Id<ChannelMarker>>() + message_ids.estimated_size();
+
+                        message_ids.iter().copied().collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                size += some_entries(&self.messages, message_ids, CachedMessage::estimated_size);
+            }
+        }
+
+        if resource_types.contains(ResourceType::PRESENCE) {
+            let (index, user_ids) = index_entry(&self.guild_presences, &guild_id);
+            size += index
+                + some_entries(
+                    &self.presences,
+                    user_ids.into_iter().map(|user_id| (guild_id, user_id)),
+                    CachedPresence::estimated_size,
+                );
+        }
+
+        if resource_types.contains(ResourceType::ROLE) {
+            let (index, role_ids) = index_entry(&self.guild_roles, &guild_id);
+            size += index
+                + some_entries(&self.roles, role_ids, |role| {
+                    guild_resource(role, self::role)
+                });
+        }
+
+        if resource_types.contains(ResourceType::STAGE_INSTANCE) {
+            let (index, stage_ids) = index_entry(&self.guild_stage_instances, &guild_id);
+            size += index
+                + some_entries(&self.stage_instances, stage_ids, |stage_instance| {
+                    guild_resource(stage_instance, self::stage_instance)
+                });
+        }
+
+        if resource_types.contains(ResourceType::STICKER) {
+            let (index, sticker_ids) = index_entry(&self.guild_stickers, &guild_id);
+            size += index
+                + some_entries(&self.stickers, sticker_ids, |sticker| {
+                    guild_resource(sticker, CachedSticker::estimated_size)
+                });
+        }
+
+        if resource_types.contains(ResourceType::THREAD_MEMBER) {
+            for channel_id in &channel_ids {
+                let (index, user_ids) = index_entry(&self.thread_member_threads, channel_id);
+                size += index
+                    + some_entries(
+                        &self.thread_members,
+                        user_ids.into_iter().map(|user_id| (*channel_id, user_id)),
+                        |_| mem::size_of::<CachedThreadMember>(),
+                    );
+            }
+        }
+
+        if resource_types.contains(ResourceType::VOICE_STATE) {
+            let (index, user_ids) = index_entry(&self.voice_state_guilds, &guild_id);
+            size += index
+                + some_entries(
+                    &self.voice_states,
+                    user_ids.into_iter().map(|user_id| (guild_id, user_id)),
+                    CachedVoiceState::estimated_size,
+                );
+        }
+
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test, InMemoryCache, ResourceType};
+    use twilight_model::{
+        gateway::payload::incoming::{GuildDelete, MemberAdd, MessageCreate},
+        id::Id,
+    };
+
+    #[test]
+    fn memory_usage() {
+        let cache = InMemoryCache::new();
+        assert_eq!(0, cache.stats().memory_usage(ResourceType::all()));
+
+        let guild_id = Id::new(1);
+        let mut member = test::member(Id::new(2), guild_id);
+        member.nick = Some("nick".to_owned());
+        cache.update(&MemberAdd(member.clone()));
+        let (_, channel_id, channel) = test::guild_channel_text();
+        cache.cache_channel(channel);
+        cache.update(&MessageCreate(test::message(channel_id, Id::new(3))));
+
+        let stats = cache.stats();
+        let members = stats.memory_usage(ResourceType::MEMBER);
+        let messages = stats.memory_usage(ResourceType::MESSAGE);
+        assert!(members > 0);
+        assert!(messages > 0);
+        assert_eq!(
+            members + messages,
+            stats.memory_usage(ResourceType::MEMBER | ResourceType::MESSAGE)
+        );
+        assert_eq!(
+            members,
+            stats.guild_memory_usage(guild_id, ResourceType::MEMBER)
+        );
+        assert_eq!(
+            messages,
+            stats.guild_memory_usage(guild_id, ResourceType::MESSAGE)
+        );
+        assert_eq!(0, stats.guild_memory_usage(Id::new(4), ResourceType::all()));
+
+        // Longer strings take up more memory.
+        member.nick = Some("a much longer nickname".to_owned());
+        cache.update(&MemberAdd(member));
+        assert!(cache.stats().memory_usage(ResourceType::MEMBER) > members);
+
+        cache.update(&GuildDelete {
+            id: guild_id,
+            unavailable: false,
+        });
+        assert_eq!(
+            0,
+            cache
+                .stats()
+                .guild_memory_usage(guild_id, ResourceType::all())
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Accounting of cached messages across channels to enforce the global message
+//! budget and expiry.
+
+use crate::{config::Config, InMemoryCache};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::MutexGuard,
+    time::Instant,
+};
+use twilight_model::id::{
+    marker::{ChannelMarker, MessageMarker},
+    Id,
+};
+
+/// Tracked message.
+#[derive(Debug)]
+struct Entry {
+    channel_id: Id<ChannelMarker>,
+    size: usize,
+    tick: u64,
+    touched_at: Instant,
+}
+
+/// Messages in the order they were last created or updated, along with the
+/// number of evicted messages.
+#[derive(Debug, Default)]
+pub(crate) struct MessageBudget {
+    /// Approximate number of bytes used by tracked messages.
+    bytes: usize,
+    /// Number of messages evicted to stay within the global budget.
+    pub(crate) evicted_budget: u64,
+    /// Number of messages evicted to stay within the size of their channel.
+    pub(crate) evicted_channel: u64,
+    /// Number of messages evicted because they expired.
+    pub(crate) expired: u64,
+    entries: HashMap<Id<MessageMarker>, Entry>,
+    /// Tracked messages, least recently used first.
+    order: BTreeMap<u64, Id<MessageMarker>>,
+    tick: u64,
+}
+
+impl MessageBudget {
+    /// Start tracking a message or mark it as used.
+    fn track(
+        &mut self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+        size: usize,
+        now: Instant,
+    ) {
+        self.untrack(message_id);
+        self.tick += 1;
+        self.bytes += size;
+        self.order.insert(self.tick, message_id);
+        self.entries.insert(
+            message_id,
+            Entry {
+                channel_id,
+                size,
+                tick: self.tick,
+                touched_at: now,
+            },
+        );
+    }
+
+    /// Stop tracking a message.
+    fn untrack(&mut self, message_id: Id<MessageMarker>) -> Option<Entry> {
+        let entry = self.entries.remove(&message_id)?;
+        self.bytes -= entry.size;
+        self.order.remove(&entry.tick);
+
+        Some(entry)
+    }
+
+    /// Stop tracking the least recently used messages that are expired or
+    /// exceed the budget, returning them.
+    fn evict(
+        &mut self,
+        config: &Config,
+        now: Instant,
+    ) -> Vec<(Id<ChannelMarker>, Id<MessageMarker>)> {
+        let mut evicted = Vec::new();
+
+        while let Some((_, message_id)) = self.order.iter().next() {
+            let message_id = *message_id;
+            let entry = &self.entries[&message_id];
+
+            let expired = config
+                .message_cache_ttl()
+                .map_or(false, |ttl| now.duration_since(entry.touched_at) > ttl);
+            let over_budget = config
+                .message_cache_max_messages()
+                .map_or(false, |max| self.entries.len() > max)
+                || config
+                    .message_cache_max_bytes()
+                    .map_or(false, |max| self.bytes > max);
+
+            if expired {
+                self.expired += 1;
+            } else if over_budget {
+                self.evicted_budget += 1;
+            } else {
+                break;
+            }
+
+            let entry = self.untrack(message_id).expect("message is tracked");
+            evicted.push((entry.channel_id, message_id));
+        }
+
+        evicted
+    }
+}
+
+impl InMemoryCache {
+    /// Lock the message budget.
+    pub(crate) fn message_budget(&self) -> MutexGuard<'_, MessageBudget> {
+        self.message_budget.lock().expect("message budget poisoned")
+    }
+
+    /// Track that a message was created or updated, evicting messages that
+    /// expired or exceed the global budget.
+    pub(crate) fn track_message(&self, message_id: Id<MessageMarker>) {
+        if !self.config.tracks_messages() {
+            return;
+        }
+
+        let (channel_id, size) = match self.messages.get(&message_id) {
+            Some(message) => (message.channel_id(), message.estimated_size()),
+            None => return,
+        };
+
+        let now = Instant::now();
+        let evicted = {
+            let mut budget = self.message_budget();
+            budget.track(channel_id, message_id, size, now);
+
+            budget.evict(&self.config, now)
+        };
+
+        self.remove_messages(evicted);
+    }
+
+    /// Stop tracking messages that were removed from the cache.
+    ///
+    /// Whether the messages were evicted to stay within the size of their
+    /// channel is recorded.
+    pub(crate) fn untrack_messages(
+        &self,
+        message_ids: impl IntoIterator<Item = Id<MessageMarker>>,
+        evicted: bool,
+    ) {
+        if !evicted && !self.config.tracks_messages() {
+            return;
+        }
+
+        let mut budget = self.message_budget();
+
+        for message_id in message_ids {
+            if evicted {
+                budget.evicted_channel += 1;
+            }
+
+            if self.config.tracks_messages() {
+                budget.untrack(message_id);
+            }
+        }
+    }
+
+    /// Track all cached messages from scratch, such as after restoring the
+    /// cache, in the order they were sent.
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn track_all_messages(&self) {
+        if !self.config.tracks_messages() {
+            return;
+        }
+
+        let mut message_ids = self
+            .messages
+            .iter()
+            .map(|message| *message.key())
+            .collect::<Vec<_>>();
+        message_ids.sort_unstable();
+
+        for message_id in message_ids {
+            self.track_message(message_id);
+        }
+    }
+
+    /// Remove messages from the cache and their channels.
+    fn remove_messages(&self, messages: Vec<(Id<ChannelMarker>, Id<MessageMarker>)>) {
+        for (channel_id, message_id) in messages {
+            self.messages.remove(&message_id);
+
+            if let Some(mut channel_messages) = self.channel_messages.get_mut(&channel_id) {
+                if let Some(idx) = channel_messages.iter().position(|id| *id == message_id) {
+                    channel_messages.remove(idx);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessageBudget;
+    use crate::{test, InMemoryCache};
+    use static_assertions::assert_impl_all;
+    use std::{fmt::Debug, thread, time::Duration};
+    use twilight_model::{
+        gateway::payload::incoming::{MessageCreate, MessageUpdate},
+        id::Id,
+    };
+
+    assert_impl_all!(MessageBudget: Debug, Default, Send, Sync);
+
+    fn message_create(channel_id: u64, message_id: u64) -> MessageCreate {
+        MessageCreate(test::message(Id::new(channel_id), Id::new(message_id)))
+    }
+
+    #[test]
+    fn max_messages_evicts_least_recently_used() {
+        let cache = InMemoryCache::builder()
+            .message_cache_max_messages(2)
+            .build();
+
+        cache.update(&message_create(1, 10));
+        cache.update(&message_create(2, 20));
+
+        // Editing the first message makes the second the least recently used.
+        cache.update(&MessageUpdate {
+            attachments: None,
+            author: None,
+            channel_id: Id::new(1),
+            content: Some("edited".to_owned()),
+            edited_timestamp: None,
+            embeds: None,
+            guild_id: None,
+            id: Id::new(10),
+            kind: None,
+            mention_everyone: None,
+            mention_roles: None,
+            mentions: None,
+            pinned: None,
+            timestamp: None,
+            tts: None,
+        });
+        cache.update(&message_create(3, 30));
+
+        assert!(cache.message(Id::new(10)).is_some());
+        assert!(cache.message(Id::new(20)).is_none());
+        assert!(cache.message(Id::new(30)).is_some());
+        assert!(cache.channel_messages(Id::new(2)).unwrap().is_empty());
+        assert_eq!(1, cache.stats().budget_evicted_messages());
+    }
+
+    #[test]
+    fn max_bytes() {
+        let cache = InMemoryCache::new();
+        cache.update(&message_create(1, 10));
+        let size = cache.message(Id::new(10)).unwrap().estimated_size();
+
+        let cache = InMemoryCache::builder()
+            .message_cache_max_bytes(size * 2)
+            .build();
+
+        for id in 1..=5 {
+            cache.update(&message_create(id, id * 10));
+        }
+
+        assert_eq!(2, cache.messages.len());
+        assert_eq!(3, cache.stats().budget_evicted_messages());
+    }
+
+    #[test]
+    fn ttl() {
+        let cache = InMemoryCache::builder()
+            .message_cache_ttl(Duration::from_millis(1))
+            .build();
+
+        cache.update(&message_create(1, 10));
+        thread::sleep(Duration::from_millis(10));
+        cache.update(&message_create(2, 20));
+
+        assert!(cache.message(Id::new(10)).is_none());
+        assert!(cache.message(Id::new(20)).is_some());
+        assert_eq!(1, cache.stats().expired_messages());
+    }
+
+    #[test]
+    fn channel_size_override() {
+        let cache = InMemoryCache::builder()
+            .message_cache_size(2)
+            .channel_message_cache_size(Id::new(1), 1)
+            .channel_message_cache_size(Id::new(2), 0)
+            .build();
+
+        cache.update(&message_create(1, 10));
+        cache.update(&message_create(1, 11));
+        cache.update(&message_create(2, 20));
+        cache.update(&message_create(3, 30));
+        cache.update(&message_create(3, 31));
+
+        assert_eq!(Some(1), cache.stats().channel_messages(Id::new(1)));
+        assert!(cache.message(Id::new(20)).is_none());
+        assert_eq!(Some(2), cache.stats().channel_messages(Id::new(3)));
+        assert_eq!(1, cache.stats().channel_evicted_messages());
+    }
+}
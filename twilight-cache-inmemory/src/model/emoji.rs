@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::mem;
 use twilight_model::{
     guild::Emoji,
     id::{
@@ -11,7 +12,7 @@ use twilight_model::{
 ///
 /// [`Emoji`]: twilight_model::guild::Emoji
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedEmoji {
     pub(crate) animated: bool,
     pub(crate) available: bool,
@@ -90,6 +91,14 @@ impl CachedEmoji {
             user_id: user.map(|user| user.id),
         }
     }
+
+    /// Approximate number of bytes used by the emoji, including its name and
+    /// roles.
+    pub(crate) fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.name.len()
+            + self.roles.len() * mem::size_of::<Id<RoleMarker>>()
+    }
 }
 
 impl PartialEq<Emoji> for CachedEmoji {
@@ -108,7 +117,7 @@ impl PartialEq<Emoji> for CachedEmoji {
 #[cfg(test)]
 mod tests {
     use super::CachedEmoji;
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use static_assertions::{assert_fields, assert_impl_all};
     use std::fmt::Debug;
     use twilight_model::{guild::Emoji, id::Id};
@@ -125,6 +134,7 @@ mod tests {
     assert_impl_all!(
         CachedEmoji: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         PartialEq,
         PartialEq<Emoji>,
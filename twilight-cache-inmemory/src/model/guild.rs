@@ -1,10 +1,10 @@
-use std::slice::Iter;
+use std::{mem, slice::Iter};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use twilight_model::{
     guild::{
-        DefaultMessageNotificationLevel, ExplicitContentFilter, GuildFeature, MfaLevel, NSFWLevel,
-        Permissions, PremiumTier, SystemChannelFlags, VerificationLevel,
+        DefaultMessageNotificationLevel, ExplicitContentFilter, Guild, GuildFeature, MfaLevel,
+        NSFWLevel, Permissions, PremiumTier, SystemChannelFlags, VerificationLevel,
     },
     id::{
         marker::{ApplicationMarker, ChannelMarker, GuildMarker, UserMarker},
@@ -16,7 +16,7 @@ use twilight_model::{
 /// Represents a cached [`Guild`].
 ///
 /// [`Guild`]: twilight_model::guild::Guild
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedGuild {
     pub(crate) afk_channel_id: Option<Id<ChannelMarker>>,
     pub(crate) afk_timeout: u64,
@@ -260,6 +260,112 @@ impl CachedGuild {
     pub const fn widget_enabled(&self) -> Option<bool> {
         self.widget_enabled
     }
+
+    /// Construct a cached guild from its [`twilight_model`] form.
+    ///
+    /// Collections of the guild, such as its channels and members, are
+    /// cached separately and are dropped here.
+    pub(crate) fn from_model(guild: Guild) -> Self {
+        let Guild {
+            afk_channel_id,
+            afk_timeout,
+            application_id,
+            approximate_member_count: _,
+            approximate_presence_count: _,
+            banner,
+            channels: _,
+            default_message_notifications,
+            description,
+            discovery_splash,
+            emojis: _,
+            explicit_content_filter,
+            features,
+            icon,
+            id,
+            joined_at,
+            large,
+            max_members,
+            max_presences,
+            max_video_channel_users,
+            member_count,
+            members: _,
+            mfa_level,
+            name,
+            nsfw_level,
+            owner_id,
+            owner,
+            permissions,
+            preferred_locale,
+            premium_progress_bar_enabled,
+            premium_subscription_count,
+            premium_tier,
+            presences: _,
+            roles: _,
+            rules_channel_id,
+            splash,
+            stage_instances: _,
+            stickers: _,
+            system_channel_flags,
+            system_channel_id,
+            threads: _,
+            unavailable,
+            vanity_url_code,
+            verification_level,
+            voice_states: _,
+            widget_channel_id,
+            widget_enabled,
+        } = guild;
+
+        Self {
+            afk_channel_id,
+            afk_timeout,
+            application_id,
+            banner,
+            default_message_notifications,
+            description,
+            discovery_splash,
+            explicit_content_filter,
+            features,
+            icon,
+            id,
+            joined_at,
+            large,
+            max_members,
+            max_presences,
+            max_video_channel_users,
+            member_count,
+            mfa_level,
+            name,
+            nsfw_level,
+            owner_id,
+            owner,
+            permissions,
+            preferred_locale,
+            premium_progress_bar_enabled,
+            premium_subscription_count,
+            premium_tier,
+            rules_channel_id,
+            splash,
+            system_channel_id,
+            system_channel_flags,
+            unavailable,
+            vanity_url_code,
+            verification_level,
+            widget_channel_id,
+            widget_enabled,
+        }
+    }
+
+    /// Approximate number of bytes used by the guild, including the heap
+    /// allocations of its strings and features.
+    pub(crate) fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.description.as_ref().map_or(0, String::len)
+            + self.features.len() * mem::size_of::<GuildFeature>()
+            + self.name.len()
+            + self.preferred_locale.len()
+            + self.vanity_url_code.as_ref().map_or(0, String::len)
+    }
 }
 
 pub struct Features<'a> {
@@ -277,7 +383,7 @@ impl<'a> Iterator for Features<'a> {
 #[cfg(test)]
 mod tests {
     use super::{CachedGuild, Features};
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use static_assertions::{assert_fields, assert_impl_all};
     use std::fmt::Debug;
 
@@ -322,6 +428,7 @@ mod tests {
     assert_impl_all!(
         CachedGuild: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         PartialEq,
         Send,
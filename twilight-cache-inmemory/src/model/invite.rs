@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::mem;
+use twilight_model::{
+    gateway::payload::incoming::InviteCreate,
+    id::{
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    invite::Invite,
+    util::Timestamp,
+};
+
+/// Represents a cached guild [`Invite`].
+///
+/// [`Invite`]: twilight_model::invite::Invite
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CachedInvite {
+    channel_id: Id<ChannelMarker>,
+    code: String,
+    created_at: Option<Timestamp>,
+    guild_id: Id<GuildMarker>,
+    inviter_id: Option<Id<UserMarker>>,
+    max_age: Option<u64>,
+    max_uses: Option<u64>,
+    temporary: bool,
+    pub(crate) uses: u64,
+}
+
+impl CachedInvite {
+    /// ID of the channel the invite is for.
+    pub const fn channel_id(&self) -> Id<ChannelMarker> {
+        self.channel_id
+    }
+
+    /// Unique code of the invite.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// When the invite was created.
+    pub const fn created_at(&self) -> Option<Timestamp> {
+        self.created_at
+    }
+
+    /// ID of the guild the invite is for.
+    pub const fn guild_id(&self) -> Id<GuildMarker> {
+        self.guild_id
+    }
+
+    /// ID of the user who created the invite.
+    pub const fn inviter_id(&self) -> Option<Id<UserMarker>> {
+        self.inviter_id
+    }
+
+    /// Number of seconds the invite is valid for.
+    ///
+    /// A value of 0 means the invite never expires.
+    pub const fn max_age(&self) -> Option<u64> {
+        self.max_age
+    }
+
+    /// Maximum number of times the invite can be used.
+    ///
+    /// A value of 0 means the invite can be used an unlimited number of times.
+    pub const fn max_uses(&self) -> Option<u64> {
+        self.max_uses
+    }
+
+    /// Whether the invite only grants temporary membership.
+    pub const fn temporary(&self) -> bool {
+        self.temporary
+    }
+
+    /// Number of times the invite has been used.
+    pub const fn uses(&self) -> u64 {
+        self.uses
+    }
+
+    /// Construct a cached invite from an invite created event.
+    pub(crate) fn from_create(invite: &InviteCreate) -> Self {
+        Self {
+            channel_id: invite.channel_id,
+            code: invite.code.clone(),
+            created_at: Some(invite.created_at),
+            guild_id: invite.guild_id,
+            inviter_id: invite.inviter.as_ref().map(|inviter| inviter.id),
+            max_age: Some(invite.max_age),
+            max_uses: Some(invite.max_uses),
+            temporary: invite.temporary,
+            uses: invite.uses.into(),
+        }
+    }
+
+    /// Construct a cached invite from its [`twilight_model`] form.
+    ///
+    /// Returns `None` if the invite isn't for a channel.
+    pub(crate) fn from_model(guild_id: Id<GuildMarker>, invite: &Invite) -> Option<Self> {
+        // Reasons for dropping fields:
+        //
+        // - `approximate_member_count`, `approximate_presence_count`: only
+        //   present when requested, and not kept up to date
+        // - `expires_at`: derivable from `created_at` and `max_age`
+        // - `guild`: we have the guild's ID
+        // - `target_type`, `target_user`: not present in invite created events
+        Some(Self {
+            channel_id: invite.channel.as_ref()?.id,
+            code: invite.code.clone(),
+            created_at: invite.created_at,
+            guild_id,
+            inviter_id: invite.inviter.as_ref().map(|inviter| inviter.id),
+            max_age: invite.max_age,
+            max_uses: invite.max_uses,
+            temporary: invite.temporary.unwrap_or_default(),
+            uses: invite.uses.unwrap_or_default(),
+        })
+    }
+
+    /// Approximate number of bytes used by the invite, including its code.
+    pub(crate) fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>() + self.code.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachedInvite;
+    use serde::{Deserialize, Serialize};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(
+        CachedInvite: Clone,
+        Debug,
+        Deserialize<'static>,
+        Eq,
+        PartialEq,
+        Send,
+        Serialize,
+        Sync
+    );
+}
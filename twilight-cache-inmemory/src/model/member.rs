@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::mem;
 use twilight_model::{
     application::interaction::application_command::InteractionMember,
     guild::{Member, PartialMember},
@@ -20,7 +21,7 @@ pub(crate) struct ComputedInteractionMemberFields {
 /// Represents a cached [`Member`].
 ///
 /// [`Member`]: twilight_model::guild::Member
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedMember {
     pub(crate) avatar: Option<ImageHash>,
     pub(crate) communication_disabled_until: Option<Timestamp>,
@@ -199,6 +200,14 @@ impl CachedMember {
             user_id: user.map_or(user_id, |user| user.id),
         }
     }
+
+    /// Approximate number of bytes used by the member, including its nickname
+    /// and roles.
+    pub(crate) fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.nick.as_ref().map_or(0, String::len)
+            + self.roles.len() * mem::size_of::<Id<RoleMarker>>()
+    }
 }
 
 impl PartialEq<Member> for CachedMember {
@@ -1,6 +1,7 @@
 //! Cached message-related models.
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::mem;
 use twilight_model::{
     application::{component::Component, interaction::InteractionType},
     channel::{
@@ -23,7 +24,7 @@ use twilight_model::{
 };
 
 /// Information about the message interaction.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedMessageInteraction {
     id: Id<InteractionMarker>,
     #[serde(rename = "type")]
@@ -79,7 +80,7 @@ impl CachedMessageInteraction {
 /// Represents a cached [`Message`].
 ///
 /// [`Message`]: twilight_model::channel::Message
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedMessage {
     activity: Option<MessageActivity>,
     application: Option<MessageApplication>,
@@ -341,6 +342,56 @@ impl CachedMessage {
             webhook_id,
         }
     }
+
+    /// Approximate number of bytes used by the message, including the heap
+    /// allocations of its larger fields.
+    ///
+    /// This is an estimate used to enforce the message budget and isn't exact.
+    pub(crate) fn estimated_size(&self) -> usize {
+        fn len(value: Option<&str>) -> usize {
+            value.map_or(0, str::len)
+        }
+
+        let attachments = self
+            .attachments
+            .iter()
+            .map(|attachment| {
+                mem::size_of::<Attachment>()
+                    + len(attachment.content_type.as_deref())
+                    + len(attachment.description.as_deref())
+                    + attachment.filename.len()
+                    + attachment.proxy_url.len()
+                    + attachment.url.len()
+            })
+            .sum::<usize>();
+
+        let embeds = self
+            .embeds
+            .iter()
+            .map(|embed| {
+                mem::size_of::<Embed>()
+                    + len(embed.description.as_deref())
+                    + len(embed.title.as_deref())
+                    + len(embed.url.as_deref())
+                    + embed
+                        .fields
+                        .iter()
+                        .map(|field| field.name.len() + field.value.len())
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+
+        mem::size_of::<Self>()
+            + attachments
+            + self.components.len() * mem::size_of::<Component>()
+            + self.content.len()
+            + embeds
+            + self.mention_channels.len() * mem::size_of::<ChannelMention>()
+            + self.mention_roles.len() * mem::size_of::<Id<RoleMarker>>()
+            + self.mentions.len() * mem::size_of::<Id<UserMarker>>()
+            + self.reactions.len() * mem::size_of::<MessageReaction>()
+            + self.sticker_items.len() * mem::size_of::<MessageSticker>()
+    }
 }
 
 impl From<Message> for CachedMessage {
@@ -352,7 +403,7 @@ impl From<Message> for CachedMessage {
 #[cfg(test)]
 mod tests {
     use super::{CachedMessage, CachedMessageInteraction};
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use static_assertions::{assert_fields, assert_impl_all};
     use std::fmt::Debug;
     use twilight_model::channel::message::Message;
@@ -390,6 +441,7 @@ mod tests {
     assert_impl_all!(
         CachedMessage: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         From<Message>,
         PartialEq,
@@ -401,6 +453,7 @@ mod tests {
     assert_impl_all!(
         CachedMessageInteraction: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         PartialEq,
         Send,
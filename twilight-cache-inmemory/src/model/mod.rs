@@ -4,12 +4,15 @@ pub mod message;
 
 mod emoji;
 mod guild;
+mod invite;
 pub(crate) mod member;
 mod presence;
 mod sticker;
+mod thread_member;
 mod voice_state;
 
 pub use self::{
-    emoji::CachedEmoji, guild::CachedGuild, member::CachedMember, message::CachedMessage,
-    presence::CachedPresence, sticker::CachedSticker, voice_state::CachedVoiceState,
+    emoji::CachedEmoji, guild::CachedGuild, invite::CachedInvite, member::CachedMember,
+    message::CachedMessage, presence::CachedPresence, sticker::CachedSticker,
+    thread_member::CachedThreadMember, voice_state::CachedVoiceState,
 };
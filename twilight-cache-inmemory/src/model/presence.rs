@@ -1,6 +1,7 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::mem;
 use twilight_model::{
-    gateway::presence::{Activity, ClientStatus, Presence, Status},
+    gateway::presence::{Activity, ActivityButton, ClientStatus, Presence, Status},
     id::{
         marker::{GuildMarker, UserMarker},
         Id,
@@ -10,7 +11,7 @@ use twilight_model::{
 /// Represents a cached [`Presence`].
 ///
 /// [`Presence`]: twilight_model::gateway::presence::Presence
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedPresence {
     pub(crate) activities: Vec<Activity>,
     pub(crate) client_status: ClientStatus,
@@ -64,6 +65,30 @@ impl CachedPresence {
             user_id: user.id(),
         }
     }
+
+    /// Approximate number of bytes used by the presence, including the heap
+    /// allocations of its activities.
+    pub(crate) fn estimated_size(&self) -> usize {
+        fn len(value: Option<&str>) -> usize {
+            value.map_or(0, str::len)
+        }
+
+        let activities = self
+            .activities
+            .iter()
+            .map(|activity| {
+                mem::size_of::<Activity>()
+                    + activity.buttons.len() * mem::size_of::<ActivityButton>()
+                    + len(activity.details.as_deref())
+                    + len(activity.id.as_deref())
+                    + activity.name.len()
+                    + len(activity.state.as_deref())
+                    + len(activity.url.as_deref())
+            })
+            .sum::<usize>();
+
+        mem::size_of::<Self>() + activities
+    }
 }
 
 impl PartialEq<Presence> for CachedPresence {
@@ -85,7 +110,7 @@ impl From<Presence> for CachedPresence {
 #[cfg(test)]
 mod tests {
     use super::CachedPresence;
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use static_assertions::{assert_fields, assert_impl_all};
     use std::fmt::Debug;
     use twilight_model::gateway::presence::Presence;
@@ -100,6 +125,7 @@ mod tests {
     assert_impl_all!(
         CachedPresence: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         From<Presence>,
         PartialEq,
@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::mem;
 use twilight_model::{
     channel::message::{
         sticker::{StickerFormatType, StickerType},
@@ -13,7 +14,7 @@ use twilight_model::{
 /// Representation of a cached [`Sticker`].
 ///
 /// [`Sticker`]: twilight_model::channel::message::sticker::Sticker
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedSticker {
     /// Whether the sticker is available.
     pub(crate) available: bool,
@@ -125,6 +126,12 @@ impl CachedSticker {
             user_id: user.map(|user| user.id),
         }
     }
+
+    /// Approximate number of bytes used by the sticker, including its name,
+    /// description, and tags.
+    pub(crate) fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>() + self.description.len() + self.name.len() + self.tags.len()
+    }
 }
 
 impl PartialEq<Sticker> for CachedSticker {
@@ -146,7 +153,7 @@ impl PartialEq<Sticker> for CachedSticker {
 #[cfg(test)]
 mod tests {
     use super::CachedSticker;
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use static_assertions::{assert_fields, assert_impl_all};
     use std::fmt::Debug;
     use twilight_model::{
@@ -175,6 +182,7 @@ mod tests {
     assert_impl_all!(
         CachedSticker: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         PartialEq,
         PartialEq<Sticker>,
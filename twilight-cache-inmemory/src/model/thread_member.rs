@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use twilight_model::{
+    channel::thread::ThreadMember,
+    id::{
+        marker::{ChannelMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+/// Represents a cached [`ThreadMember`].
+///
+/// [`ThreadMember`]: twilight_model::channel::thread::ThreadMember
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CachedThreadMember {
+    flags: u64,
+    join_timestamp: Timestamp,
+    thread_id: Id<ChannelMarker>,
+    user_id: Id<UserMarker>,
+}
+
+impl CachedThreadMember {
+    /// Flags of the thread member.
+    ///
+    /// These are currently undocumented.
+    pub const fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    /// When the user joined the thread.
+    pub const fn join_timestamp(&self) -> Timestamp {
+        self.join_timestamp
+    }
+
+    /// ID of the thread.
+    pub const fn thread_id(&self) -> Id<ChannelMarker> {
+        self.thread_id
+    }
+
+    /// ID of the user.
+    pub const fn user_id(&self) -> Id<UserMarker> {
+        self.user_id
+    }
+
+    /// Construct a cached thread member from its [`twilight_model`] form.
+    ///
+    /// The thread and user IDs are provided separately as they are omitted
+    /// in some events.
+    pub(crate) const fn from_model(
+        thread_member: &ThreadMember,
+        thread_id: Id<ChannelMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Self {
+        // Reasons for dropping fields:
+        //
+        // - `member`: cached separately as a guild member
+        // - `presence`: cached separately as a guild presence
+        Self {
+            flags: thread_member.flags,
+            join_timestamp: thread_member.join_timestamp,
+            thread_id,
+            user_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachedThreadMember;
+    use serde::{Deserialize, Serialize};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+    use twilight_model::{channel::thread::ThreadMember, id::Id, util::Timestamp};
+
+    assert_impl_all!(
+        CachedThreadMember: Clone,
+        Debug,
+        Deserialize<'static>,
+        Eq,
+        PartialEq,
+        Send,
+        Serialize,
+        Sync
+    );
+
+    #[test]
+    fn from_model() {
+        let join_timestamp = Timestamp::from_secs(1_632_072_645).expect("non zero");
+        let thread_member = ThreadMember {
+            flags: 1,
+            id: None,
+            join_timestamp,
+            member: None,
+            presence: None,
+            user_id: None,
+        };
+
+        let cached = CachedThreadMember::from_model(&thread_member, Id::new(2), Id::new(3));
+
+        assert_eq!(1, cached.flags());
+        assert_eq!(join_timestamp, cached.join_timestamp());
+        assert_eq!(Id::new(2), cached.thread_id());
+        assert_eq!(Id::new(3), cached.user_id());
+    }
+}
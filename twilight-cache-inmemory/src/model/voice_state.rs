@@ -1,4 +1,5 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::mem;
 use twilight_model::{
     id::{
         marker::{ChannelMarker, GuildMarker, UserMarker},
@@ -12,7 +13,7 @@ use twilight_model::{
 ///
 /// [`VoiceState`]: twilight_model::voice::VoiceState
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CachedVoiceState {
     channel_id: Id<ChannelMarker>,
     deaf: bool,
@@ -140,6 +141,12 @@ impl CachedVoiceState {
             user_id,
         }
     }
+
+    /// Approximate number of bytes used by the voice state, including its
+    /// session ID and token.
+    pub(crate) fn estimated_size(&self) -> usize {
+        mem::size_of::<Self>() + self.session_id.len() + self.token.as_ref().map_or(0, String::len)
+    }
 }
 
 impl PartialEq<VoiceState> for CachedVoiceState {
@@ -164,7 +171,7 @@ impl PartialEq<VoiceState> for CachedVoiceState {
 mod tests {
     use super::CachedVoiceState;
     use crate::test;
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use static_assertions::{assert_fields, assert_impl_all};
     use std::fmt::Debug;
     use twilight_model::{
@@ -193,6 +200,7 @@ mod tests {
     assert_impl_all!(
         CachedVoiceState: Clone,
         Debug,
+        Deserialize<'static>,
         Eq,
         PartialEq,
         PartialEq<VoiceState>,
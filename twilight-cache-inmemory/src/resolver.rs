@@ -0,0 +1,891 @@
+//! Resolve resources from the cache, falling back to the HTTP API.
+
+use crate::{
+    config::ResourceType,
+    model::{CachedGuild, CachedMember, CachedMessage},
+    InMemoryCache,
+};
+use dashmap::DashMap;
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use twilight_http::{
+    error::ErrorType as HttpErrorType, response::Response, Client, Error as HttpError,
+};
+use twilight_model::{
+    channel::{Channel, Message},
+    guild::{Guild, Role},
+    id::{
+        marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
+        Id,
+    },
+    user::User,
+};
+
+#[cfg(feature = "permission-calculator")]
+use twilight_model::guild::Permissions;
+
+/// Default duration that resources not found over HTTP are remembered for.
+const MISSING_TTL: Duration = Duration::from_secs(60);
+
+/// Resolving a resource failed.
+#[derive(Debug)]
+pub struct ResolveError {
+    kind: ResolveErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ResolveError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ResolveErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (ResolveErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+
+    fn deserializing(source: impl Error + Send + Sync + 'static) -> Self {
+        Self::new(ResolveErrorType::Deserializing, source)
+    }
+
+    fn new(kind: ResolveErrorType, source: impl Error + Send + Sync + 'static) -> Self {
+        Self {
+            kind,
+            source: Some(Box::new(source)),
+        }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.kind {
+            ResolveErrorType::Deserializing => {
+                f.write_str("deserializing the response body failed")
+            }
+            ResolveErrorType::Permissions => f.write_str("calculating permissions failed"),
+            ResolveErrorType::Request => f.write_str("sending the request failed"),
+        }
+    }
+}
+
+impl Error for ResolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`ResolveError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ResolveErrorType {
+    /// Response body of a fetched resource couldn't be deserialized.
+    Deserializing,
+    /// Calculating permissions with the resolved resources failed, such as
+    /// because the cache isn't configured to store them.
+    Permissions,
+    /// Request to fetch a resource failed with an error other than the
+    /// resource not being found.
+    Request,
+}
+
+/// Resource that was not found over HTTP.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Missing {
+    Channel(Id<ChannelMarker>),
+    Guild(Id<GuildMarker>),
+    Member(Id<GuildMarker>, Id<UserMarker>),
+    Message(Id<MessageMarker>),
+    Role(Id<RoleMarker>),
+    User(Id<UserMarker>),
+}
+
+/// Resolve resources from the cache, fetching them over HTTP on a miss.
+///
+/// Fetched resources are inserted into the cache if it is configured to store
+/// them, so later lookups are served from the cache. Resources that Discord
+/// responds with a 404 for are remembered as missing for a
+/// [duration][`missing_ttl`] and resolve to `None` without another request.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::sync::Arc;
+/// use twilight_cache_inmemory::{InMemoryCache, InMemoryCacheResolver};
+/// use twilight_http::Client;
+/// use twilight_model::id::Id;
+///
+/// let cache = Arc::new(InMemoryCache::new());
+/// let http = Arc::new(Client::new("token".to_owned()));
+/// let resolver = InMemoryCacheResolver::new(Arc::clone(&cache), http);
+///
+/// if let Some(user) = resolver.user(Id::new(1)).await? {
+///     println!("resolved user {}", user.name);
+/// }
+/// # Ok(()) }
+/// ```
+///
+/// [`missing_ttl`]: Self::missing_ttl
+#[derive(Debug)]
+#[must_use = "has no effect if unused"]
+pub struct InMemoryCacheResolver {
+    cache: Arc<InMemoryCache>,
+    http: Arc<Client>,
+    missing: DashMap<Missing, Instant>,
+    missing_ttl: Duration,
+}
+
+impl InMemoryCacheResolver {
+    /// Create a resolver backed by a cache and an HTTP client.
+    pub fn new(cache: Arc<InMemoryCache>, http: Arc<Client>) -> Self {
+        Self {
+            cache,
+            http,
+            missing: DashMap::new(),
+            missing_ttl: MISSING_TTL,
+        }
+    }
+
+    /// Immutable reference to the underlying cache.
+    pub fn cache_ref(&self) -> &InMemoryCache {
+        &self.cache
+    }
+
+    /// Immutable reference to the underlying HTTP client.
+    pub fn http_ref(&self) -> &Client {
+        &self.http
+    }
+
+    /// Set how long resources that were not found are remembered as missing.
+    ///
+    /// Defaults to 60 seconds.
+    pub const fn missing_ttl(mut self, missing_ttl: Duration) -> Self {
+        self.missing_ttl = missing_ttl;
+
+        self
+    }
+
+    /// Forget which resources were not found, so they're requested again.
+    pub fn clear_missing(&self) {
+        self.missing.clear();
+    }
+
+    /// Resolve a channel by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] error type if the
+    /// fetched channel couldn't be deserialized.
+    ///
+    /// Returns a [`ResolveErrorType::Request`] error type if fetching the
+    /// channel failed.
+    pub async fn channel(
+        &self,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<Option<Channel>, ResolveError> {
+        if let Some(channel) = self.cache.channel(channel_id) {
+            return Ok(Some(channel.clone()));
+        }
+
+        let key = Missing::Channel(channel_id);
+
+        if self.is_missing(key) {
+            return Ok(None);
+        }
+
+        let result = self.http.channel(channel_id).exec().await;
+
+        let channel = if let Some(response) = self.fetch(key, result)? {
+            response
+                .model()
+                .await
+                .map_err(ResolveError::deserializing)?
+        } else {
+            return Ok(None);
+        };
+
+        if self.cache.wants_in(channel.guild_id, ResourceType::CHANNEL) {
+            self.cache.cache_channel(channel.clone());
+        }
+
+        Ok(Some(channel))
+    }
+
+    /// Resolve a guild by ID.
+    ///
+    /// The fetched guild's emojis, roles, and stickers are cached alongside
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] error type if the
+    /// fetched guild couldn't be deserialized.
+    ///
+    /// Returns a [`ResolveErrorType::Request`] error type if fetching the
+    /// guild failed.
+    pub async fn guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<CachedGuild>, ResolveError> {
+        if let Some(guild) = self.cache.guild(guild_id) {
+            return Ok(Some(guild.clone()));
+        }
+
+        let key = Missing::Guild(guild_id);
+
+        if self.is_missing(key) {
+            return Ok(None);
+        }
+
+        let result = self.http.guild(guild_id).exec().await;
+
+        let guild = if let Some(response) = self.fetch(key, result)? {
+            response
+                .model()
+                .await
+                .map_err(ResolveError::deserializing)?
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.cache.cache_fetched_guild(guild)))
+    }
+
+    /// Resolve a member of a guild by their user ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] error type if the
+    /// fetched member couldn't be deserialized.
+    ///
+    /// Returns a [`ResolveErrorType::Request`] error type if fetching the
+    /// member failed.
+    pub async fn member(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Result<Option<CachedMember>, ResolveError> {
+        if let Some(member) = self.cache.member(guild_id, user_id) {
+            return Ok(Some(member.clone()));
+        }
+
+        let key = Missing::Member(guild_id, user_id);
+
+        if self.is_missing(key) {
+            return Ok(None);
+        }
+
+        let result = self.http.guild_member(guild_id, user_id).exec().await;
+
+        let member = if let Some(response) = self.fetch(key, result)? {
+            response
+                .model()
+                .await
+                .map_err(ResolveError::deserializing)?
+        } else {
+            return Ok(None);
+        };
+
+        if self.cache.wants_in(Some(guild_id), ResourceType::MEMBER) {
+            self.cache.cache_member(guild_id, member.clone());
+        }
+
+        Ok(Some(CachedMember::from_model(member)))
+    }
+
+    /// Resolve a message in a channel by ID.
+    ///
+    /// Messages fetched over HTTP don't include their guild ID, so the
+    /// message's channel is resolved to determine it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] error type if the
+    /// fetched message couldn't be deserialized.
+    ///
+    /// Returns a [`ResolveErrorType::Request`] error type if fetching the
+    /// message or its channel failed.
+    pub async fn message(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<Option<CachedMessage>, ResolveError> {
+        if let Some(message) = self.cache.message(message_id) {
+            return Ok(Some(message.clone()));
+        }
+
+        let key = Missing::Message(message_id);
+
+        if self.is_missing(key) {
+            return Ok(None);
+        }
+
+        let result = self.http.message(channel_id, message_id).exec().await;
+
+        let mut message = if let Some(response) = self.fetch(key, result)? {
+            response
+                .model()
+                .await
+                .map_err(ResolveError::deserializing)?
+        } else {
+            return Ok(None);
+        };
+
+        if message.guild_id.is_none() {
+            message.guild_id = self
+                .channel(message.channel_id)
+                .await?
+                .and_then(|channel| channel.guild_id);
+        }
+
+        if self.cache.wants(ResourceType::USER) {
+            self.cache
+                .cache_user(Cow::Borrowed(&message.author), message.guild_id);
+        }
+
+        if self
+            .cache
+            .wants_messages_in(message.guild_id, message.channel_id)
+        {
+            self.cache.cache_fetched_message(message.clone());
+        }
+
+        Ok(Some(CachedMessage::from_model(message)))
+    }
+
+    /// Resolve a role in a guild by ID.
+    ///
+    /// Roles can only be fetched together, so all of the guild's roles are
+    /// cached on a miss.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] error type if the
+    /// fetched roles couldn't be deserialized.
+    ///
+    /// Returns a [`ResolveErrorType::Request`] error type if fetching the
+    /// guild's roles failed.
+    pub async fn role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        role_id: Id<RoleMarker>,
+    ) -> Result<Option<Role>, ResolveError> {
+        if let Some(role) = self.cache.role(role_id) {
+            return Ok(Some(role.resource().clone()));
+        }
+
+        let key = Missing::Role(role_id);
+
+        if self.is_missing(key) {
+            return Ok(None);
+        }
+
+        let result = self.http.roles(guild_id).exec().await;
+
+        let roles = if let Some(response) = self.fetch(key, result)? {
+            response
+                .model()
+                .await
+                .map_err(ResolveError::deserializing)?
+        } else {
+            return Ok(None);
+        };
+
+        let role = roles.iter().find(|role| role.id == role_id).cloned();
+
+        if self.cache.wants_in(Some(guild_id), ResourceType::ROLE) {
+            self.cache.cache_roles(guild_id, roles);
+        }
+
+        if role.is_none() {
+            self.mark_missing(key);
+        }
+
+        Ok(role)
+    }
+
+    /// Resolve a user by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] error type if the
+    /// fetched user couldn't be deserialized.
+    ///
+    /// Returns a [`ResolveErrorType::Request`] error type if fetching the
+    /// user failed.
+    pub async fn user(&self, user_id: Id<UserMarker>) -> Result<Option<User>, ResolveError> {
+        if let Some(user) = self.cache.user(user_id) {
+            return Ok(Some(user.clone()));
+        }
+
+        let key = Missing::User(user_id);
+
+        if self.is_missing(key) {
+            return Ok(None);
+        }
+
+        let result = self.http.user(user_id).exec().await;
+
+        let user = if let Some(response) = self.fetch(key, result)? {
+            response
+                .model()
+                .await
+                .map_err(ResolveError::deserializing)?
+        } else {
+            return Ok(None);
+        };
+
+        if self.cache.wants(ResourceType::USER) {
+            self.cache.cache_user(Cow::Borrowed(&user), None);
+        }
+
+        Ok(Some(user))
+    }
+
+    /// Calculate the permissions of a member in a guild channel, resolving
+    /// the channel, its parent if it's a thread, the guild, the member, and
+    /// their roles first.
+    ///
+    /// Returns `None` if the channel, guild, or member doesn't exist.
+    ///
+    /// Permissions are calculated via [`InMemoryCachePermissions::in_channel`],
+    /// so the cache must be configured to store the [`ResourceType`]s it
+    /// requires.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Permissions`] error type if calculating
+    /// the permissions with the information in the cache failed.
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] or
+    /// [`ResolveErrorType::Request`] error type if fetching a resource failed.
+    ///
+    /// [`InMemoryCachePermissions::in_channel`]: crate::InMemoryCachePermissions::in_channel
+    #[cfg(feature = "permission-calculator")]
+    pub async fn permissions(
+        &self,
+        user_id: Id<UserMarker>,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<Option<Permissions>, ResolveError> {
+        let channel = if let Some(channel) = self.channel(channel_id).await? {
+            channel
+        } else {
+            return Ok(None);
+        };
+
+        if let (true, Some(parent_id)) = (channel.kind.is_thread(), channel.parent_id) {
+            self.channel(parent_id).await?;
+        }
+
+        if let Some(guild_id) = channel.guild_id {
+            if !self.resolve_member_roles(guild_id, user_id).await? {
+                return Ok(None);
+            }
+        }
+
+        self.cache
+            .permissions()
+            .in_channel(user_id, channel_id)
+            .map(Some)
+            .map_err(|source| ResolveError::new(ResolveErrorType::Permissions, source))
+    }
+
+    /// Calculate the guild-level permissions of a member, resolving the
+    /// guild, the member, and their roles first.
+    ///
+    /// Returns `None` if the guild or member doesn't exist.
+    ///
+    /// Permissions are calculated via [`InMemoryCachePermissions::root`], so
+    /// the cache must be configured to store the [`ResourceType`]s it
+    /// requires.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResolveErrorType::Permissions`] error type if calculating
+    /// the permissions with the information in the cache failed.
+    ///
+    /// Returns a [`ResolveErrorType::Deserializing`] or
+    /// [`ResolveErrorType::Request`] error type if fetching a resource failed.
+    ///
+    /// [`InMemoryCachePermissions::root`]: crate::InMemoryCachePermissions::root
+    #[cfg(feature = "permission-calculator")]
+    pub async fn root_permissions(
+        &self,
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<Permissions>, ResolveError> {
+        if !self.resolve_member_roles(guild_id, user_id).await? {
+            return Ok(None);
+        }
+
+        self.cache
+            .permissions()
+            .root(user_id, guild_id)
+            .map(Some)
+            .map_err(|source| ResolveError::new(ResolveErrorType::Permissions, source))
+    }
+
+    /// Resolve the guild, member, and roles needed to calculate a member's
+    /// permissions, returning whether the guild and member exist.
+    #[cfg(feature = "permission-calculator")]
+    async fn resolve_member_roles(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+    ) -> Result<bool, ResolveError> {
+        if self.guild(guild_id).await?.is_none() {
+            return Ok(false);
+        }
+
+        let member = if let Some(member) = self.member(guild_id, user_id).await? {
+            member
+        } else {
+            return Ok(false);
+        };
+
+        // Resolving one role caches all of the guild's roles, so only the
+        // first miss results in a request.
+        self.role(guild_id, guild_id.cast()).await?;
+
+        for role_id in member.roles() {
+            self.role(guild_id, *role_id).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Handle the result of a request for a resource, remembering the
+    /// resource as missing and returning `None` if it was not found.
+    fn fetch<T>(
+        &self,
+        key: Missing,
+        result: Result<Response<T>, HttpError>,
+    ) -> Result<Option<Response<T>>, ResolveError> {
+        match result {
+            Ok(response) => Ok(Some(response)),
+            Err(source) if is_not_found(&source) => {
+                self.mark_missing(key);
+
+                Ok(None)
+            }
+            Err(source) => Err(ResolveError::new(ResolveErrorType::Request, source)),
+        }
+    }
+
+    /// Remember a resource as missing, pruning resources whose TTL has
+    /// elapsed so that the map doesn't grow without bound.
+    fn mark_missing(&self, key: Missing) {
+        self.missing.retain(|_, at| at.elapsed() < self.missing_ttl);
+        self.missing.insert(key, Instant::now());
+    }
+
+    /// Whether a resource was not found within the TTL, removing it if the
+    /// TTL has elapsed.
+    fn is_missing(&self, key: Missing) -> bool {
+        self.missing
+            .remove_if(&key, |_, at| at.elapsed() >= self.missing_ttl)
+            .is_none()
+            && self.missing.contains_key(&key)
+    }
+}
+
+impl InMemoryCache {
+    /// Cache a guild fetched over HTTP, returning its cached form.
+    ///
+    /// Unlike guilds received over the gateway, fetched guilds don't include
+    /// their channels and members, so the guild's existing sets of them are
+    /// left alone.
+    fn cache_fetched_guild(&self, mut guild: Guild) -> CachedGuild {
+        let id = guild.id;
+        let emojis = mem::take(&mut guild.emojis);
+        let roles = mem::take(&mut guild.roles);
+        let stickers = mem::take(&mut guild.stickers);
+
+        if self.wants_in(Some(id), ResourceType::EMOJI) {
+            self.cache_emojis(id, emojis);
+        }
+
+        if self.wants_in(Some(id), ResourceType::ROLE) {
+            self.cache_roles(id, roles);
+        }
+
+        if self.wants_in(Some(id), ResourceType::STICKER) {
+            self.cache_stickers(id, stickers);
+        }
+
+        let guild = CachedGuild::from_model(guild);
+
+        if self.wants_in(Some(id), ResourceType::GUILD) {
+            self.unavailable_guilds.remove(&id);
+            self.guilds.insert(id, guild.clone());
+        }
+
+        guild
+    }
+
+    /// Cache a message fetched over HTTP.
+    ///
+    /// Channels' lists of messages are ordered from newest to oldest and a
+    /// fetched message may be older than the ones already cached, so it's
+    /// inserted in order of its ID and not cached at all if it'd be the first
+    /// to be evicted.
+    fn cache_fetched_message(&self, message: Message) {
+        let message_id = message.id;
+        let message_cache_size = self
+            .config
+            .message_cache_size_in(message.guild_id, message.channel_id);
+
+        let popped_id = {
+            let mut channel_messages = self.channel_messages.entry(message.channel_id).or_default();
+
+            let index = channel_messages
+                .iter()
+                .position(|id| *id < message_id)
+                .unwrap_or(channel_messages.len());
+
+            if index >= message_cache_size {
+                return;
+            }
+
+            channel_messages.insert(index, message_id);
+
+            if channel_messages.len() > message_cache_size {
+                channel_messages.pop_back()
+            } else {
+                None
+            }
+        };
+
+        if let Some(popped_id) = popped_id {
+            self.messages.remove(&popped_id);
+            self.untrack_messages([popped_id], true);
+        }
+
+        self.messages
+            .insert(message_id, CachedMessage::from_model(message));
+        self.track_message(message_id);
+    }
+}
+
+/// Whether an HTTP error is due to the requested resource not being found.
+const fn is_not_found(error: &HttpError) -> bool {
+    matches!(error.kind(), HttpErrorType::Response { status, .. } if status.get() == 404)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InMemoryCacheResolver, ResolveError, ResolveErrorType};
+    use crate::{test, InMemoryCache};
+    use static_assertions::assert_impl_all;
+    use std::{error::Error, fmt::Debug, sync::Arc, time::Duration};
+    use twilight_http::routing::Route;
+    use twilight_mock::http::{MockHttp, MockResponse};
+    use twilight_model::{
+        gateway::payload::incoming::{ChannelCreate, MessageCreate},
+        id::Id,
+    };
+
+    assert_impl_all!(InMemoryCacheResolver: Debug, Send, Sync);
+    assert_impl_all!(ResolveErrorType: Debug, Send, Sync);
+    assert_impl_all!(ResolveError: Error, Send, Sync);
+
+    fn resolver(server: &MockHttp, cache: InMemoryCache) -> InMemoryCacheResolver {
+        let http = server
+            .client_builder()
+            .token("Bot token".to_owned())
+            .build();
+
+        InMemoryCacheResolver::new(Arc::new(cache), Arc::new(http))
+    }
+
+    #[tokio::test]
+    async fn fetch_on_miss() -> Result<(), Box<dyn Error>> {
+        let server = MockHttp::bind().await?;
+        let resolver = resolver(&server, InMemoryCache::new());
+        let (guild_id, channel_id, channel) = test::guild_channel_text();
+        server.mock(
+            &Route::GetChannel {
+                channel_id: channel_id.get(),
+            },
+            MockResponse::json(&channel),
+        );
+
+        assert_eq!(Some(&channel), resolver.channel(channel_id).await?.as_ref());
+        assert_eq!(Some(&channel), resolver.channel(channel_id).await?.as_ref());
+        assert_eq!(1, server.requests().len());
+        assert!(resolver
+            .cache_ref()
+            .guild_channels(guild_id)
+            .unwrap()
+            .contains(&channel_id));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_found() -> Result<(), Box<dyn Error>> {
+        let server = MockHttp::bind().await?;
+        let resolver = resolver(&server, InMemoryCache::new());
+        let user_id = Id::new(1);
+
+        assert!(resolver.user(user_id).await?.is_none());
+        assert!(resolver.user(user_id).await?.is_none());
+        assert_eq!(1, server.requests().len());
+
+        resolver.clear_missing();
+        assert!(resolver.user(user_id).await?.is_none());
+        assert_eq!(2, server.requests().len());
+
+        let resolver = resolver.missing_ttl(Duration::ZERO);
+        assert!(resolver.user(user_id).await?.is_none());
+        assert_eq!(3, server.requests().len());
+
+        Ok(())
+    }
+
+    /// Test that fetched messages are cached in order of their IDs and that
+    /// messages older than a full channel's messages aren't cached.
+    #[tokio::test]
+    async fn message_order() -> Result<(), Box<dyn Error>> {
+        let server = MockHttp::bind().await?;
+        let cache = InMemoryCache::builder().message_cache_size(2).build();
+        let channel_id = Id::new(1);
+        cache.update(&MessageCreate(test::message(channel_id, Id::new(3))));
+        cache.update(&MessageCreate(test::message(channel_id, Id::new(5))));
+        let resolver = resolver(&server, cache);
+
+        for message_id in [1, 4] {
+            server.mock(
+                &Route::GetMessage {
+                    channel_id: channel_id.get(),
+                    message_id,
+                },
+                MockResponse::json(&test::message(channel_id, Id::new(message_id))),
+            );
+        }
+
+        assert!(resolver.message(channel_id, Id::new(4)).await?.is_some());
+        assert_eq!(
+            Vec::from([Id::new(5), Id::new(4)]),
+            Vec::from(
+                resolver
+                    .cache_ref()
+                    .channel_messages(channel_id)
+                    .unwrap()
+                    .clone()
+            )
+        );
+        assert!(resolver.cache_ref().message(Id::new(3)).is_none());
+
+        assert!(resolver.message(channel_id, Id::new(1)).await?.is_some());
+        assert!(resolver.cache_ref().message(Id::new(1)).is_none());
+
+        Ok(())
+    }
+
+    /// Test that fetched messages take their guild ID from their channel.
+    #[tokio::test]
+    async fn message_guild_id() -> Result<(), Box<dyn Error>> {
+        let server = MockHttp::bind().await?;
+        let (guild_id, channel_id, channel) = test::guild_channel_text();
+        let cache = InMemoryCache::new();
+        cache.update(&ChannelCreate(channel));
+        let resolver = resolver(&server, cache);
+        let message = test::message(channel_id, Id::new(3));
+        let author_id = message.author.id;
+        server.mock(
+            &Route::GetMessage {
+                channel_id: channel_id.get(),
+                message_id: 3,
+            },
+            MockResponse::json(&message),
+        );
+
+        let message = resolver.message(channel_id, Id::new(3)).await?.unwrap();
+        assert_eq!(Some(guild_id), message.guild_id());
+        assert_eq!(
+            Some(guild_id),
+            resolver.cache_ref().message(Id::new(3)).unwrap().guild_id()
+        );
+        assert!(resolver
+            .cache_ref()
+            .user_guilds
+            .get(&author_id)
+            .unwrap()
+            .contains(&guild_id));
+
+        Ok(())
+    }
+
+    /// Test that resources whose TTL has elapsed are pruned when another
+    /// resource is remembered as missing.
+    #[tokio::test]
+    async fn missing_pruned() -> Result<(), Box<dyn Error>> {
+        let server = MockHttp::bind().await?;
+        let resolver = resolver(&server, InMemoryCache::new()).missing_ttl(Duration::ZERO);
+
+        assert!(resolver.user(Id::new(1)).await?.is_none());
+        assert!(resolver.user(Id::new(2)).await?.is_none());
+        assert_eq!(1, resolver.missing.len());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "permission-calculator")]
+    #[tokio::test]
+    async fn root_permissions() -> Result<(), Box<dyn Error>> {
+        use twilight_model::guild::Permissions;
+
+        let server = MockHttp::bind().await?;
+        let resolver = resolver(&server, InMemoryCache::new());
+        let guild_id = Id::new(1);
+        let user_id = Id::new(2);
+        let mut guild = test::guild(guild_id, None);
+        let mut everyone = test::role(guild_id.cast());
+        everyone.permissions = Permissions::SEND_MESSAGES;
+        guild.roles.push(everyone);
+        server.mock(
+            &Route::GetGuild {
+                guild_id: guild_id.get(),
+                with_counts: false,
+            },
+            MockResponse::json(&guild),
+        );
+        server.mock(
+            &Route::GetMember {
+                guild_id: guild_id.get(),
+                user_id: user_id.get(),
+            },
+            MockResponse::json(&test::member(user_id, guild_id)),
+        );
+
+        assert_eq!(
+            Some(Permissions::SEND_MESSAGES),
+            resolver.root_permissions(user_id, guild_id).await?
+        );
+        assert!(resolver
+            .root_permissions(Id::new(3), guild_id)
+            .await?
+            .is_none());
+        assert_eq!(3, server.requests().len());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,459 @@
+//! Versioned binary snapshots of the cache.
+//!
+//! Snapshots start with an 8 byte magic followed by the little endian `u32`
+//! version of the format, and then contain the resources and relations of the
+//! cache encoded as CBOR.
+
+use crate::{
+    model::{
+        CachedEmoji, CachedGuild, CachedInvite, CachedMember, CachedMessage, CachedPresence,
+        CachedSticker, CachedThreadMember, CachedVoiceState,
+    },
+    GuildResource, InMemoryCache,
+};
+use dashmap::{DashMap, DashSet};
+use serde::{
+    ser::{SerializeSeq, SerializeStruct},
+    Deserialize, Serialize, Serializer,
+};
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    hash::Hash,
+    io::{Read, Write},
+};
+use twilight_model::{
+    channel::{Channel, StageInstance},
+    guild::{auto_moderation::AutoModerationRule, GuildIntegration, Role},
+    id::{
+        marker::{
+            AutoModerationRuleMarker, ChannelMarker, EmojiMarker, GuildMarker, IntegrationMarker,
+            MessageMarker, RoleMarker, StageMarker, StickerMarker, UserMarker,
+        },
+        Id,
+    },
+    user::{CurrentUser, User},
+};
+
+/// Magic bytes at the start of snapshots.
+const MAGIC: [u8; 8] = *b"TWLCACHE";
+
+/// Version of the snapshot format.
+///
+/// Bump this when changing the stores of the cache.
+const VERSION: u32 = 4;
+
+/// Writing or reading a cache snapshot failed.
+#[derive(Debug)]
+pub struct SnapshotError {
+    kind: SnapshotErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl SnapshotError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &SnapshotErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (SnapshotErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            SnapshotErrorType::Deserializing => f.write_str("reading the snapshot failed"),
+            SnapshotErrorType::Serializing => f.write_str("writing the snapshot failed"),
+            SnapshotErrorType::SnapshotInvalid => f.write_str("data is not a cache snapshot"),
+            SnapshotErrorType::VersionUnsupported { version } => {
+                f.write_str("snapshot version ")?;
+                Display::fmt(version, f)?;
+                f.write_str(" is unsupported, expected version ")?;
+
+                Display::fmt(&VERSION, f)
+            }
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`SnapshotError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotErrorType {
+    /// Reading or decoding the snapshot failed.
+    Deserializing,
+    /// Encoding or writing the snapshot failed.
+    Serializing,
+    /// Data doesn't start with the magic of snapshots.
+    SnapshotInvalid,
+    /// Snapshot was written in an unsupported version of the format, such as
+    /// by a different version of the crate.
+    VersionUnsupported {
+        /// Version of the snapshot.
+        version: u32,
+    },
+}
+
+/// Serialize the entries of a map as a sequence of key-value pairs without
+/// cloning them.
+struct Entries<'a, K, V>(&'a DashMap<K, V>);
+
+impl<K: Eq + Hash + Serialize, V: Serialize> Serialize for Entries<'_, K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+
+        for entry in self.0 {
+            seq.serialize_element(&(entry.key(), entry.value()))?;
+        }
+
+        seq.end()
+    }
+}
+
+/// Serialize the values of a set as a sequence.
+struct Values<'a, T>(&'a DashSet<T>);
+
+impl<T: Eq + Hash + Serialize> Serialize for Values<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+
+        for value in self.0.iter() {
+            seq.serialize_element(value.key())?;
+        }
+
+        seq.end()
+    }
+}
+
+/// Define the owned snapshot read from snapshots and the borrowed snapshot
+/// written from the cache, given the maps of the cache and their entries.
+macro_rules! snapshot {
+    ($($field:ident: ($key:ty, $value:ty),)*) => {
+        /// Snapshot read from a reader.
+        #[derive(Deserialize)]
+        struct Snapshot {
+            current_user: Option<CurrentUser>,
+            unavailable_guilds: Vec<Id<GuildMarker>>,
+            $($field: Vec<($key, $value)>,)*
+        }
+
+        impl Snapshot {
+            /// Replace the contents of the cache with the snapshot.
+            fn restore(self, cache: &InMemoryCache) {
+                cache.clear();
+
+                *cache.current_user.lock().expect("current user poisoned") = self.current_user;
+
+                for guild_id in self.unavailable_guilds {
+                    cache.unavailable_guilds.insert(guild_id);
+                }
+
+                $(
+                    for (key, value) in self.$field {
+                        cache.$field.insert(key, value);
+                    }
+                )*
+
+                cache.track_all_messages();
+                cache.rebuild_indexes();
+            }
+        }
+
+        /// Snapshot borrowing the stores of the cache.
+        struct SnapshotRef<'a>(&'a InMemoryCache);
+
+        impl Serialize for SnapshotRef<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let cache = self.0;
+                let current_user = cache
+                    .current_user
+                    .lock()
+                    .expect("current user poisoned")
+                    .clone();
+
+                let fields = [$(stringify!($field)),*].len() + 2;
+                let mut state = serializer.serialize_struct("Snapshot", fields)?;
+                state.serialize_field("current_user", &current_user)?;
+                state.serialize_field("unavailable_guilds", &Values(&cache.unavailable_guilds))?;
+                $(state.serialize_field(stringify!($field), &Entries(&cache.$field))?;)*
+
+                state.end()
+            }
+        }
+    };
+}
+
+snapshot! {
+    auto_moderation_rules: (Id<AutoModerationRuleMarker>, AutoModerationRule),
+    channels: (Id<ChannelMarker>, Channel),
+    channel_messages: (Id<ChannelMarker>, VecDeque<Id<MessageMarker>>),
+    emojis: (Id<EmojiMarker>, GuildResource<CachedEmoji>),
+    guilds: (Id<GuildMarker>, CachedGuild),
+    guild_auto_moderation_rules: (Id<GuildMarker>, HashSet<Id<AutoModerationRuleMarker>>),
+    guild_channels: (Id<GuildMarker>, HashSet<Id<ChannelMarker>>),
+    guild_emojis: (Id<GuildMarker>, HashSet<Id<EmojiMarker>>),
+    guild_integrations: (Id<GuildMarker>, HashSet<Id<IntegrationMarker>>),
+    guild_invites: (Id<GuildMarker>, HashSet<String>),
+    guild_members: (Id<GuildMarker>, HashSet<Id<UserMarker>>),
+    guild_presences: (Id<GuildMarker>, HashSet<Id<UserMarker>>),
+    guild_roles: (Id<GuildMarker>, HashSet<Id<RoleMarker>>),
+    guild_stage_instances: (Id<GuildMarker>, HashSet<Id<StageMarker>>),
+    guild_stickers: (Id<GuildMarker>, HashSet<Id<StickerMarker>>),
+    integrations: ((Id<GuildMarker>, Id<IntegrationMarker>), GuildResource<GuildIntegration>),
+    invites: (String, CachedInvite),
+    members: ((Id<GuildMarker>, Id<UserMarker>), CachedMember),
+    messages: (Id<MessageMarker>, CachedMessage),
+    presences: ((Id<GuildMarker>, Id<UserMarker>), CachedPresence),
+    roles: (Id<RoleMarker>, GuildResource<Role>),
+    stage_instances: (Id<StageMarker>, GuildResource<StageInstance>),
+    stickers: (Id<StickerMarker>, GuildResource<CachedSticker>),
+    thread_member_threads: (Id<ChannelMarker>, HashSet<Id<UserMarker>>),
+    thread_member_users: (Id<UserMarker>, HashSet<Id<ChannelMarker>>),
+    thread_members: ((Id<ChannelMarker>, Id<UserMarker>), CachedThreadMember),
+    users: (Id<UserMarker>, User),
+    user_guilds: (Id<UserMarker>, BTreeSet<Id<GuildMarker>>),
+    voice_state_channels: (Id<ChannelMarker>, HashSet<(Id<GuildMarker>, Id<UserMarker>)>),
+    voice_state_guilds: (Id<GuildMarker>, HashSet<Id<UserMarker>>),
+    voice_states: ((Id<GuildMarker>, Id<UserMarker>), CachedVoiceState),
+}
+
+impl InMemoryCache {
+    /// Write a snapshot of the cache, including all cached resources and
+    /// their relations, to restore it later via [`restore`].
+    ///
+    /// Resources are serialized while iterating over the cache, so updates
+    /// made concurrently may or may not be included. Take snapshots while no
+    /// events are being processed, such as after shutting down shards.
+    ///
+    /// # Examples
+    ///
+    /// Take a snapshot of the cache after shutting down a cluster, and
+    /// restore the cache and resume the shards' sessions on startup:
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::{collections::HashMap, env, fs::File};
+    /// use twilight_cache_inmemory::InMemoryCache;
+    /// use twilight_gateway::{cluster::Cluster, shard::ResumeSession, Intents};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let cache = InMemoryCache::new();
+    ///
+    /// // On shutdown, after processing all received events:
+    /// # let (cluster, _) = Cluster::new(token.clone(), Intents::GUILDS).await?;
+    /// let sessions = cluster.down_resumable();
+    /// cache.snapshot(File::create("cache.snapshot")?)?;
+    /// // Persist the sessions as well...
+    ///
+    /// // On startup:
+    /// cache.restore(File::open("cache.snapshot")?)?;
+    /// # let sessions: HashMap<u64, ResumeSession> = sessions;
+    /// let (cluster, events) = Cluster::builder(token, Intents::GUILDS)
+    ///     .resume_sessions(sessions)
+    ///     .build()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SnapshotErrorType::Serializing`] error type if encoding or
+    /// writing the snapshot failed.
+    ///
+    /// [`restore`]: Self::restore
+    pub fn snapshot(&self, mut writer: impl Write) -> Result<(), SnapshotError> {
+        let serializing = |source: Box<dyn Error + Send + Sync>| SnapshotError {
+            kind: SnapshotErrorType::Serializing,
+            source: Some(source),
+        };
+
+        writer
+            .write_all(&MAGIC)
+            .and_then(|()| writer.write_all(&VERSION.to_le_bytes()))
+            .map_err(|source| serializing(Box::new(source)))?;
+
+        ciborium::ser::into_writer(&SnapshotRef(self), &mut writer)
+            .map_err(|source| serializing(Box::new(source)))?;
+
+        writer
+            .flush()
+            .map_err(|source| serializing(Box::new(source)))
+    }
+
+    /// Replace the contents of the cache with a snapshot written by
+    /// [`snapshot`].
+    ///
+    /// The snapshot is fully read before the cache is replaced, so the cache
+    /// is left untouched if reading it fails. The configuration of the cache
+    /// is kept, and resources of disabled [`ResourceType`]s are restored if
+    /// they were cached when the snapshot was taken.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SnapshotErrorType::Deserializing`] error type if reading or
+    /// decoding the snapshot failed.
+    ///
+    /// Returns a [`SnapshotErrorType::SnapshotInvalid`] error type if the data
+    /// isn't a snapshot.
+    ///
+    /// Returns a [`SnapshotErrorType::VersionUnsupported`] error type if the
+    /// snapshot was written in an unsupported version of the format.
+    ///
+    /// [`ResourceType`]: crate::ResourceType
+    /// [`snapshot`]: Self::snapshot
+    pub fn restore(&self, mut reader: impl Read) -> Result<(), SnapshotError> {
+        let mut header = [0; 12];
+        reader
+            .read_exact(&mut header)
+            .map_err(|source| SnapshotError {
+                kind: SnapshotErrorType::Deserializing,
+                source: Some(Box::new(source)),
+            })?;
+
+        if header[..8] != MAGIC {
+            return Err(SnapshotError {
+                kind: SnapshotErrorType::SnapshotInvalid,
+                source: None,
+            });
+        }
+
+        let version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+
+        if version != VERSION {
+            return Err(SnapshotError {
+                kind: SnapshotErrorType::VersionUnsupported { version },
+                source: None,
+            });
+        }
+
+        let snapshot =
+            ciborium::de::from_reader::<Snapshot, _>(reader).map_err(|source| SnapshotError {
+                kind: SnapshotErrorType::Deserializing,
+                source: Some(Box::new(source)),
+            })?;
+
+        snapshot.restore(self);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnapshotError, SnapshotErrorType, MAGIC, VERSION};
+    use crate::{test, InMemoryCache};
+    use static_assertions::assert_impl_all;
+    use std::{error::Error, fmt::Debug};
+    use twilight_model::{gateway::payload::incoming::VoiceStateUpdate, id::Id};
+
+    assert_impl_all!(SnapshotErrorType: Debug, Send, Sync);
+    assert_impl_all!(SnapshotError: Error, Send, Sync);
+
+    #[test]
+    fn round_trip() -> Result<(), Box<dyn Error>> {
+        let cache = test::cache_with_message_and_reactions();
+        let guild_id = Id::new(1);
+        cache.cache_roles(guild_id, [test::role(Id::new(3))]);
+        cache.update(&VoiceStateUpdate(test::voice_state(
+            guild_id,
+            Some(Id::new(4)),
+            Id::new(5),
+        )));
+        cache.cache_user(
+            std::borrow::Cow::Owned(test::user(Id::new(6))),
+            Some(guild_id),
+        );
+        cache.unavailable_guilds.insert(Id::new(7));
+        *cache.current_user.lock().unwrap() = Some(test::current_user(8));
+
+        let mut snapshot = Vec::new();
+        cache.snapshot(&mut snapshot)?;
+
+        let restored = InMemoryCache::new();
+        restored.cache_emoji(Id::new(9), test::emoji(Id::new(10), None));
+        restored.cache_user(
+            std::borrow::Cow::Owned(test::user(Id::new(11))),
+            Some(Id::new(12)),
+        );
+        restored.restore(snapshot.as_slice())?;
+
+        // Resources and relations cached before restoring are removed.
+        assert!(restored.emojis.is_empty());
+        assert!(restored.user(Id::new(11)).is_none());
+        assert!(!restored.user_guilds.contains_key(&Id::new(11)));
+
+        let message = cache.messages.iter().next().unwrap().value().clone();
+        assert_eq!(Some(&message), restored.message(message.id()).as_deref());
+        assert_eq!(
+            cache.role(Id::new(3)).as_deref(),
+            restored.role(Id::new(3)).as_deref()
+        );
+        assert_eq!(
+            cache.voice_state(Id::new(5), guild_id).as_deref(),
+            restored.voice_state(Id::new(5), guild_id).as_deref()
+        );
+        assert_eq!(
+            1,
+            restored.voice_channel_states(Id::new(4)).unwrap().count()
+        );
+        assert_eq!(
+            cache.user_guilds.get(&Id::new(6)).as_deref(),
+            restored.user_guilds.get(&Id::new(6)).as_deref()
+        );
+        assert!(restored.unavailable_guilds.contains(&Id::new(7)));
+        assert_eq!(cache.current_user(), restored.current_user());
+        assert_eq!(cache.stats().users(), restored.stats().users());
+        assert_eq!(
+            cache.members_by_name_prefix(guild_id, "member"),
+            restored.members_by_name_prefix(guild_id, "member")
+        );
+        assert!(!restored
+            .members_by_name_prefix(guild_id, "member")
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        let cache = InMemoryCache::new();
+        cache.unavailable_guilds.insert(Id::new(1));
+
+        let error = cache.restore(&b"not a snapshot"[..]).unwrap_err();
+        assert!(matches!(error.kind(), SnapshotErrorType::SnapshotInvalid));
+
+        let mut snapshot = MAGIC.to_vec();
+        snapshot.extend((VERSION + 1).to_le_bytes());
+        let error = cache.restore(snapshot.as_slice()).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            SnapshotErrorType::VersionUnsupported { version } if *version == VERSION + 1
+        ));
+
+        let error = cache.restore(&MAGIC[..4]).unwrap_err();
+        assert!(matches!(error.kind(), SnapshotErrorType::Deserializing));
+
+        // Failed restores leave the cache untouched.
+        assert!(cache.unavailable_guilds.contains(&Id::new(1)));
+    }
+}
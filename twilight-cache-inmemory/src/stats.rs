@@ -3,7 +3,7 @@ use twilight_model::id::{
     Id,
 };
 
-use super::InMemoryCache;
+use super::{InMemoryCache, ResourceType};
 
 /// Retrieve statistics about the number of entities of each resource in the
 /// cache.
@@ -46,11 +46,34 @@ impl<'a> InMemoryCacheStats<'a> {
         self.0
     }
 
+    /// Number of auto moderation rules in the cache.
+    pub fn auto_moderation_rules(&self) -> usize {
+        self.0.auto_moderation_rules.len()
+    }
+
+    /// Number of messages evicted to stay within the global message budget.
+    ///
+    /// Refer to [`InMemoryCacheBuilder::message_cache_max_bytes`] and
+    /// [`InMemoryCacheBuilder::message_cache_max_messages`] for more
+    /// information.
+    ///
+    /// [`InMemoryCacheBuilder::message_cache_max_bytes`]: crate::InMemoryCacheBuilder::message_cache_max_bytes
+    /// [`InMemoryCacheBuilder::message_cache_max_messages`]: crate::InMemoryCacheBuilder::message_cache_max_messages
+    pub fn budget_evicted_messages(&self) -> u64 {
+        self.0.message_budget().evicted_budget
+    }
+
     /// Number of channels in the cache.
     pub fn channels(&self) -> usize {
         self.0.channels.len()
     }
 
+    /// Number of messages evicted to stay within the message cache size of
+    /// their channel.
+    pub fn channel_evicted_messages(&self) -> u64 {
+        self.0.message_budget().evicted_channel
+    }
+
     /// Number of messages in a given channel in the cache.
     ///
     /// Returns `None` if the channel hasn't yet been cached or there are no
@@ -62,6 +85,17 @@ impl<'a> InMemoryCacheStats<'a> {
         Some(channel.len())
     }
 
+    /// Number of members of a given thread in the cache.
+    ///
+    /// Returns `None` if the thread hasn't yet been cached or there are no
+    /// members of the thread. However, the provided number may still be 0
+    /// if some number is returned.
+    pub fn channel_thread_members(&self, channel_id: Id<ChannelMarker>) -> Option<usize> {
+        let thread = self.0.thread_member_threads.get(&channel_id)?;
+
+        Some(thread.len())
+    }
+
     /// Number of voice states in a given channel in the cache.
     ///
     /// Returns `None` if the channel hasn't yet been cached or there are no
@@ -78,11 +112,30 @@ impl<'a> InMemoryCacheStats<'a> {
         self.0.emojis.len()
     }
 
+    /// Number of messages evicted because they expired.
+    ///
+    /// Refer to [`InMemoryCacheBuilder::message_cache_ttl`] for more
+    /// information.
+    ///
+    /// [`InMemoryCacheBuilder::message_cache_ttl`]: crate::InMemoryCacheBuilder::message_cache_ttl
+    pub fn expired_messages(&self) -> u64 {
+        self.0.message_budget().expired
+    }
+
     /// Number of guilds in the cache.
     pub fn guilds(&self) -> usize {
         self.0.guilds.len()
     }
 
+    /// Number of auto moderation rules in a given guild in the cache.
+    ///
+    /// Returns `None` if the guild hasn't yet been cached.
+    pub fn guild_auto_moderation_rules(&self, guild_id: Id<GuildMarker>) -> Option<usize> {
+        let guild = self.0.guild_auto_moderation_rules.get(&guild_id)?;
+
+        Some(guild.len())
+    }
+
     /// Number of channels in a given guild in the cache.
     ///
     /// Returns `None` if the guild hasn't yet been cached.
@@ -101,6 +154,15 @@ impl<'a> InMemoryCacheStats<'a> {
         Some(guild.len())
     }
 
+    /// Number of invites in a given guild in the cache.
+    ///
+    /// Returns `None` if the guild hasn't yet been cached.
+    pub fn guild_invites(&self, guild_id: Id<GuildMarker>) -> Option<usize> {
+        let guild = self.0.guild_invites.get(&guild_id)?;
+
+        Some(guild.len())
+    }
+
     /// Number of members in a given guild in the cache.
     ///
     /// Returns `None` if the guild hasn't yet been cached.
@@ -110,6 +172,21 @@ impl<'a> InMemoryCacheStats<'a> {
         Some(guild.len())
     }
 
+    /// Approximate number of bytes used by resources of the given types in a
+    /// guild.
+    ///
+    /// Users aren't included, as they're shared between guilds. Refer to
+    /// [`memory_usage`] for more information.
+    ///
+    /// [`memory_usage`]: Self::memory_usage
+    pub fn guild_memory_usage(
+        &self,
+        guild_id: Id<GuildMarker>,
+        resource_types: ResourceType,
+    ) -> usize {
+        self.0.guild_memory_usage(guild_id, resource_types)
+    }
+
     /// Number of presences in a given guild in the cache.
     ///
     /// Returns `None` if the guild hasn't yet been cached.
@@ -137,11 +214,51 @@ impl<'a> InMemoryCacheStats<'a> {
         Some(guild.len())
     }
 
+    /// Number of invites in the cache.
+    pub fn invites(&self) -> usize {
+        self.0.invites.len()
+    }
+
     /// Number of members in the cache.
     pub fn members(&self) -> usize {
         self.0.members.len()
     }
 
+    /// Approximate number of bytes used by resources of the given types in the
+    /// cache.
+    ///
+    /// Includes the resources themselves, the heap allocations of their
+    /// strings and lists, and the indexes relating them. Excludes the unused
+    /// capacity of maps and lists and the overhead of the allocator, so actual
+    /// usage is higher. Reactions are included in the size of their messages.
+    ///
+    /// Estimating the size iterates over all of the resources of the types, so
+    /// avoid doing so frequently on large caches.
+    ///
+    /// # Examples
+    ///
+    /// Log the approximate memory used by members and presences:
+    ///
+    /// ```no_run
+    /// use twilight_cache_inmemory::{InMemoryCache, ResourceType};
+    ///
+    /// let cache = InMemoryCache::new();
+    ///
+    /// // later on...
+    /// let stats = cache.stats();
+    /// println!("members: {} bytes", stats.memory_usage(ResourceType::MEMBER));
+    /// println!("presences: {} bytes", stats.memory_usage(ResourceType::PRESENCE));
+    /// println!("total: {} bytes", stats.memory_usage(ResourceType::all()));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current user's lock is poisoned and
+    /// [`ResourceType::USER_CURRENT`] is included.
+    pub fn memory_usage(&self, resource_types: ResourceType) -> usize {
+        self.0.memory_usage(resource_types)
+    }
+
     /// Number of presences in the cache.
     pub fn presences(&self) -> usize {
         self.0.presences.len()
@@ -152,6 +269,11 @@ impl<'a> InMemoryCacheStats<'a> {
         self.0.roles.len()
     }
 
+    /// Number of thread members in the cache.
+    pub fn thread_members(&self) -> usize {
+        self.0.thread_members.len()
+    }
+
     /// Number of unavailable guilds in the cache.
     pub fn unavailable_guilds(&self) -> usize {
         self.0.unavailable_guilds.len()
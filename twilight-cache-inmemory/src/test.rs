@@ -15,7 +15,7 @@ use twilight_model::{
         VerificationLevel,
     },
     id::{
-        marker::{ChannelMarker, EmojiMarker, GuildMarker, RoleMarker, UserMarker},
+        marker::{ChannelMarker, EmojiMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
         Id,
     },
     user::{CurrentUser, User},
@@ -274,6 +274,40 @@ pub fn member(id: Id<UserMarker>, guild_id: Id<GuildMarker>) -> Member {
     }
 }
 
+pub fn message(channel_id: Id<ChannelMarker>, id: Id<MessageMarker>) -> Message {
+    Message {
+        activity: None,
+        application: None,
+        application_id: None,
+        attachments: Vec::new(),
+        author: user(Id::new(1)),
+        channel_id,
+        components: Vec::new(),
+        content: "content".to_owned(),
+        edited_timestamp: None,
+        embeds: Vec::new(),
+        flags: Some(MessageFlags::empty()),
+        guild_id: None,
+        id,
+        interaction: None,
+        kind: MessageType::Regular,
+        member: None,
+        mention_channels: Vec::new(),
+        mention_everyone: false,
+        mention_roles: Vec::new(),
+        mentions: Vec::new(),
+        pinned: false,
+        reactions: Vec::new(),
+        reference: None,
+        sticker_items: Vec::new(),
+        thread: None,
+        referenced_message: None,
+        timestamp: Timestamp::from_secs(1_632_072_645).expect("non zero"),
+        tts: false,
+        webhook_id: None,
+    }
+}
+
 pub fn role(id: Id<RoleMarker>) -> Role {
     Role {
         color: 0,
@@ -0,0 +1,94 @@
+//! Standalone server for coordinating session initializations across the
+//! processes of a bot.
+//!
+//! Configured via environment variables:
+//!
+//! - `QUEUE_ADDRESS`: address to listen on, defaulting to `127.0.0.1:7100`;
+//! - `DISCORD_TOKEN`: token of the bot to retrieve its maximum concurrency and
+//!   session start limit with, if built with HTTP support;
+//! - `MAX_CONCURRENCY`: maximum concurrency of the bot, used if no token is
+//!   provided and defaulting to 1;
+//! - `SESSION_LIMIT`: daily session start limit of the bot, used if no token
+//!   is provided and defaulting to 1000.
+//!
+//! Shards connect to the server via a `RemoteQueue`.
+
+use std::{env, error::Error, str::FromStr, time::Duration};
+use tokio::net::TcpListener;
+use twilight_gateway_queue::{QueueServer, SessionLimit};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7100";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    tracing_subscriber::fmt::init();
+
+    let address = env::var("QUEUE_ADDRESS").unwrap_or_else(|_| DEFAULT_ADDRESS.to_owned());
+    let (max_concurrency, limit) = session_start_limit().await?;
+
+    tracing::info!(
+        "serving queue on {address} with a maximum concurrency of {max_concurrency} and \
+         {}/{} sessions remaining",
+        limit.remaining,
+        limit.total,
+    );
+
+    let listener = TcpListener::bind(&address).await?;
+    QueueServer::new(max_concurrency, limit)
+        .serve(listener)
+        .await?;
+
+    Ok(())
+}
+
+/// Retrieve the bot's maximum concurrency and session start limit.
+async fn session_start_limit() -> Result<(u64, SessionLimit), Box<dyn Error + Send + Sync>> {
+    #[cfg(any(
+        feature = "native",
+        feature = "rustls-native-roots",
+        feature = "rustls-webpki-roots"
+    ))]
+    if let Ok(token) = env::var("DISCORD_TOKEN") {
+        let info = twilight_http::Client::new(token)
+            .gateway()
+            .authed()
+            .exec()
+            .await?
+            .model()
+            .await?;
+
+        let limit = info.session_start_limit;
+
+        return Ok((
+            limit.max_concurrency,
+            SessionLimit {
+                remaining: limit.remaining,
+                reset_after: Duration::from_millis(limit.reset_after),
+                total: limit.total,
+            },
+        ));
+    }
+
+    let max_concurrency = var("MAX_CONCURRENCY", 1)?;
+    let total = var("SESSION_LIMIT", 1000)?;
+
+    Ok((
+        max_concurrency,
+        SessionLimit {
+            remaining: total,
+            reset_after: Duration::from_secs(60 * 60 * 24),
+            total,
+        },
+    ))
+}
+
+/// Parse an environment variable, falling back to a default if it's unset.
+fn var<T: FromStr>(name: &str, default: T) -> Result<T, Box<dyn Error + Send + Sync>>
+where
+    T::Err: Error + Send + Sync + 'static,
+{
+    match env::var(name) {
+        Ok(value) => Ok(value.parse()?),
+        Err(_) => Ok(default),
+    }
+}
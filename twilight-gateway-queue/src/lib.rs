@@ -31,6 +31,9 @@ mod day_limiter;
     feature = "rustls-webpki-roots"
 ))]
 mod large_bot_queue;
+mod local_queue;
+#[cfg(feature = "remote")]
+mod remote;
 
 #[cfg(any(
     feature = "native",
@@ -38,19 +41,22 @@ mod large_bot_queue;
     feature = "rustls-webpki-roots"
 ))]
 pub use large_bot_queue::LargeBotQueue;
+#[cfg(any(
+    feature = "native",
+    feature = "rustls-native-roots",
+    feature = "rustls-webpki-roots"
+))]
+pub use local_queue::{LocalQueueError, LocalQueueErrorType};
+
+pub use local_queue::{LocalQueue, QueuePosition};
+
+#[cfg(feature = "remote")]
+pub use remote::{QueueServer, RemoteQueue, SessionLimit};
 
 use std::{
     fmt::Debug,
     future::{self, Future},
     pin::Pin,
-    time::Duration,
-};
-use tokio::{
-    sync::{
-        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
-        oneshot::{self, Sender},
-    },
-    time::sleep,
 };
 
 /// Queue for shards to request the ability to initialize new sessions with the
@@ -70,81 +76,6 @@ pub trait Queue: Debug + Send + Sync {
     fn request<'a>(&'a self, shard_id: [u64; 2]) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
 }
 
-/// A local, in-process implementation of a [`Queue`] which manages the
-/// connection attempts of one or more shards.
-///
-/// The queue will take incoming requests and then queue them, releasing one of
-/// the requests every 6 seconds. The queue is necessary because there's a
-/// ratelimit on how often shards can initiate sessions.
-///
-/// You usually won't need to handle this yourself, because the `Cluster` will
-/// do that for you when managing multiple shards.
-///
-/// # When not to use this
-///
-/// This queue implementation is "local", meaning it's intended to be used if
-/// you manage shards only in this process. If you run shards in multiple
-/// different processes (do you utilize microservices a lot?), then you **must
-/// not** use this implementation. Shards across multiple processes may
-/// create new sessions at the same time, which is bad.
-///
-/// It should also not be used for very large sharding, for that the
-/// [`LargeBotQueue`] can be used.
-///
-/// If you can't use this, look into an alternative implementation of the
-/// [`Queue`], such as the [`gateway-queue`] broker.
-///
-/// [`gateway-queue`]: https://github.com/twilight-rs/gateway-queue
-#[derive(Clone, Debug)]
-pub struct LocalQueue(UnboundedSender<Sender<()>>);
-
-impl Default for LocalQueue {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl LocalQueue {
-    /// Creates a new local queue.
-    pub fn new() -> Self {
-        let (tx, rx) = unbounded_channel();
-
-        tokio::spawn(waiter(rx));
-
-        Self(tx)
-    }
-}
-
-async fn waiter(mut rx: UnboundedReceiver<Sender<()>>) {
-    const DUR: Duration = Duration::from_secs(6);
-    while let Some(req) = rx.recv().await {
-        if let Err(source) = req.send(()) {
-            tracing::warn!("skipping, send failed: {source:?}");
-        }
-        sleep(DUR).await;
-    }
-}
-
-impl Queue for LocalQueue {
-    /// Request to be able to identify with the gateway. This will place this
-    /// request behind all other requests, and the returned future will resolve
-    /// once the request has been completed.
-    fn request(&'_ self, [id, total]: [u64; 2]) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async move {
-            let (tx, rx) = oneshot::channel();
-
-            if let Err(source) = self.0.send(tx) {
-                tracing::warn!("skipping, send failed: {source:?}");
-                return;
-            }
-
-            tracing::info!("shard {id}/{total} waiting for allowance");
-
-            let _ = rx.await;
-        })
-    }
-}
-
 /// An implementation of [`Queue`] that instantly allows requests.
 ///
 /// Useful when running behind a proxy gateway. Running without a
@@ -160,11 +91,10 @@ impl Queue for NoOpQueue {
 
 #[cfg(test)]
 mod tests {
-    use super::{LocalQueue, NoOpQueue, Queue};
+    use super::{NoOpQueue, Queue};
     use static_assertions::{assert_impl_all, assert_obj_safe};
     use std::fmt::Debug;
 
-    assert_impl_all!(LocalQueue: Clone, Debug, Queue, Send, Sync);
     assert_impl_all!(NoOpQueue: Debug, Queue, Send, Sync);
     assert_impl_all!(dyn Queue: Debug, Send, Sync);
     assert_obj_safe!(Queue);
@@ -0,0 +1,469 @@
+use super::Queue;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    sync::{
+        oneshot::{self, Sender},
+        Notify,
+    },
+    time::{sleep, Instant},
+};
+
+#[cfg(any(
+    feature = "native",
+    feature = "rustls-native-roots",
+    feature = "rustls-webpki-roots"
+))]
+pub use self::http::{LocalQueueError, LocalQueueErrorType};
+
+/// Period within which each bucket may initialize one session.
+const WINDOW: Duration = Duration::from_secs(5);
+
+/// Position of a shard waiting in a [`LocalQueue`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QueuePosition {
+    bucket: u64,
+    position: usize,
+    wait: Duration,
+}
+
+impl QueuePosition {
+    /// ID of the bucket the shard is queued in.
+    ///
+    /// This is the shard's ID modulo the queue's maximum concurrency.
+    pub const fn bucket(&self) -> u64 {
+        self.bucket
+    }
+
+    /// Number of shards queued ahead of the shard in its bucket.
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Estimated time until the shard is allowed to identify.
+    pub const fn wait(&self) -> Duration {
+        self.wait
+    }
+}
+
+/// Shard waiting for allowance in a lane.
+struct Waiter {
+    shard_id: u64,
+    tx: Sender<()>,
+}
+
+#[derive(Default)]
+struct LaneState {
+    /// Whether the queue has been dropped and the lane should stop.
+    closed: bool,
+    queue: VecDeque<Waiter>,
+    /// When a shard was last allowed to identify.
+    released_at: Option<Instant>,
+}
+
+/// Queue of a single bucket, releasing one shard every 5 seconds.
+#[derive(Default)]
+struct Lane {
+    notify: Notify,
+    state: Mutex<LaneState>,
+}
+
+impl Lane {
+    /// Queue a shard, returning its position.
+    fn push(&self, bucket: u64, shard_id: u64, tx: Sender<()>) -> QueuePosition {
+        let mut state = self.state.lock().expect("lane poisoned");
+        state.queue.push_back(Waiter { shard_id, tx });
+        let position = Self::position(&state, bucket, shard_id);
+        drop(state);
+
+        self.notify.notify_one();
+
+        position.expect("shard was just queued")
+    }
+
+    fn position(state: &LaneState, bucket: u64, shard_id: u64) -> Option<QueuePosition> {
+        let position = state
+            .queue
+            .iter()
+            .filter(|waiter| !waiter.tx.is_closed())
+            .position(|waiter| waiter.shard_id == shard_id)?;
+
+        let cooldown = state.released_at.map_or(Duration::ZERO, |released_at| {
+            (released_at + WINDOW).saturating_duration_since(Instant::now())
+        });
+
+        #[allow(clippy::cast_possible_truncation)]
+        let wait = cooldown + WINDOW * (position as u32);
+
+        Some(QueuePosition {
+            bucket,
+            position,
+            wait,
+        })
+    }
+
+    /// Release queued shards until the lane is closed.
+    async fn run(self: Arc<Self>) {
+        loop {
+            let waiter = loop {
+                {
+                    let mut state = self.state.lock().expect("lane poisoned");
+
+                    if state.closed {
+                        return;
+                    }
+
+                    if let Some(waiter) = state.queue.pop_front() {
+                        break waiter;
+                    }
+                }
+
+                self.notify.notified().await;
+            };
+
+            if let Err(source) = waiter.tx.send(()) {
+                tracing::warn!("skipping, send failed: {source:?}");
+
+                continue;
+            }
+
+            self.state.lock().expect("lane poisoned").released_at = Some(Instant::now());
+
+            sleep(WINDOW).await;
+        }
+    }
+}
+
+struct Inner {
+    lanes: Vec<Arc<Lane>>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        for lane in &self.lanes {
+            lane.state.lock().expect("lane poisoned").closed = true;
+            lane.notify.notify_one();
+        }
+    }
+}
+
+/// A local, in-process implementation of a [`Queue`] which manages the
+/// connection attempts of one or more shards.
+///
+/// Shards are assigned a bucket with an ID of `shard_id % max_concurrency`,
+/// where the maximum concurrency is the number of shards Discord allows to
+/// identify at once. Each bucket releases one of its queued requests every 5
+/// seconds, so up to `max_concurrency` shards may identify in parallel. The
+/// queue is necessary because there's a ratelimit on how often shards can
+/// initiate sessions.
+///
+/// The maximum concurrency is available as the `max_concurrency` of the
+/// session start limit returned by the [Get Gateway Bot] endpoint, and may be
+/// retrieved by creating the queue via `LocalQueue::from_client` if the
+/// `twilight-http` feature is enabled.
+///
+/// You usually won't need to handle this yourself, because the `Cluster` will
+/// do that for you when managing multiple shards.
+///
+/// # When not to use this
+///
+/// This queue implementation is "local", meaning it's intended to be used if
+/// you manage shards only in this process. If you run shards in multiple
+/// different processes (do you utilize microservices a lot?), then you **must
+/// not** use this implementation. Shards across multiple processes may
+/// create new sessions at the same time, which is bad.
+///
+/// If you can't use this, look into an alternative implementation of the
+/// [`Queue`], such as the `RemoteQueue` enabled by the `remote` feature or
+/// the [`gateway-queue`] broker.
+///
+/// [`gateway-queue`]: https://github.com/twilight-rs/gateway-queue
+/// [Get Gateway Bot]: https://discord.com/developers/docs/topics/gateway#get-gateway-bot
+#[derive(Clone)]
+pub struct LocalQueue(Arc<Inner>);
+
+impl Default for LocalQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalQueue {
+    /// Creates a new local queue with a maximum concurrency of 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn new() -> Self {
+        Self::with_max_concurrency(1)
+    }
+
+    /// Creates a new local queue with a maximum concurrency.
+    ///
+    /// A `max_concurrency` of 0 is treated as 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn with_max_concurrency(max_concurrency: u64) -> Self {
+        let lanes = (0..max_concurrency.max(1))
+            .map(|_| {
+                let lane = Arc::new(Lane::default());
+
+                tokio::spawn(Arc::clone(&lane).run());
+
+                lane
+            })
+            .collect();
+
+        Self(Arc::new(Inner { lanes }))
+    }
+
+    /// Maximum number of shards that may identify concurrently.
+    pub fn max_concurrency(&self) -> u64 {
+        self.0.lanes.len() as u64
+    }
+
+    /// Position of a shard currently waiting in the queue.
+    ///
+    /// Returns [`None`] if the shard isn't waiting for allowance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock of the shard's bucket is poisoned.
+    pub fn position(&self, shard_id: u64) -> Option<QueuePosition> {
+        let bucket = shard_id % self.max_concurrency();
+        let state = self.lane(bucket).state.lock().expect("lane poisoned");
+
+        Lane::position(&state, bucket, shard_id)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn lane(&self, bucket: u64) -> &Lane {
+        &self.0.lanes[bucket as usize]
+    }
+}
+
+impl Debug for LocalQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("LocalQueue")
+            .field("max_concurrency", &self.max_concurrency())
+            .finish()
+    }
+}
+
+impl Queue for LocalQueue {
+    /// Request to be able to identify with the gateway. This will place this
+    /// request behind all other requests of the shard's bucket, and the
+    /// returned future will resolve once the request has been completed.
+    fn request(&'_ self, [id, total]: [u64; 2]) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            let (tx, rx) = oneshot::channel();
+            let bucket = id % self.max_concurrency();
+            let queued_at = Instant::now();
+
+            let position = self.lane(bucket).push(bucket, id, tx);
+
+            tracing::info!(
+                "shard {id}/{total} waiting for allowance in bucket {bucket} at position {} \
+                 (estimated wait: {:.2?})",
+                position.position(),
+                position.wait(),
+            );
+
+            if rx.await.is_ok() {
+                tracing::debug!(
+                    "shard {id}/{total} allowed to identify after {:.2?}",
+                    queued_at.elapsed(),
+                );
+            }
+        })
+    }
+}
+
+#[cfg(any(
+    feature = "native",
+    feature = "rustls-native-roots",
+    feature = "rustls-webpki-roots"
+))]
+mod http {
+    use super::LocalQueue;
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+    };
+    use twilight_http::Client;
+
+    /// Creating a local queue failed.
+    #[derive(Debug)]
+    pub struct LocalQueueError {
+        kind: LocalQueueErrorType,
+        source: Option<Box<dyn Error + Send + Sync>>,
+    }
+
+    impl LocalQueueError {
+        /// Immutable reference to the type of error that occurred.
+        #[must_use = "retrieving the type has no effect if left unused"]
+        pub const fn kind(&self) -> &LocalQueueErrorType {
+            &self.kind
+        }
+
+        /// Consume the error, returning the source error if there is any.
+        #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+        pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+            self.source
+        }
+
+        /// Consume the error, returning the owned error type and the source
+        /// error.
+        #[must_use = "consuming the error into its parts has no effect if left unused"]
+        pub fn into_parts(self) -> (LocalQueueErrorType, Option<Box<dyn Error + Send + Sync>>) {
+            (self.kind, self.source)
+        }
+    }
+
+    impl Display for LocalQueueError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match &self.kind {
+                LocalQueueErrorType::RetrievingMaxConcurrency => {
+                    f.write_str("retrieving the bot's maximum concurrency failed")
+                }
+            }
+        }
+    }
+
+    impl Error for LocalQueueError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source
+                .as_ref()
+                .map(|source| &**source as &(dyn Error + 'static))
+        }
+    }
+
+    /// Type of [`LocalQueueError`] that occurred.
+    #[derive(Debug)]
+    #[non_exhaustive]
+    pub enum LocalQueueErrorType {
+        /// Retrieving the bot's maximum concurrency via the HTTP API failed.
+        RetrievingMaxConcurrency,
+    }
+
+    impl LocalQueue {
+        /// Creates a new local queue, retrieving the bot's maximum concurrency
+        /// via the HTTP API.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`LocalQueueErrorType::RetrievingMaxConcurrency`] error
+        /// type if the request to retrieve the bot's gateway information
+        /// failed.
+        ///
+        /// # Panics
+        ///
+        /// Panics if called outside of a Tokio runtime.
+        pub async fn from_client(http: &Client) -> Result<Self, LocalQueueError> {
+            let info = http
+                .gateway()
+                .authed()
+                .exec()
+                .await
+                .map_err(|source| LocalQueueError {
+                    kind: LocalQueueErrorType::RetrievingMaxConcurrency,
+                    source: Some(Box::new(source)),
+                })?
+                .model()
+                .await
+                .map_err(|source| LocalQueueError {
+                    kind: LocalQueueErrorType::RetrievingMaxConcurrency,
+                    source: Some(Box::new(source)),
+                })?;
+
+            Ok(Self::with_max_concurrency(
+                info.session_start_limit.max_concurrency,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalQueue, Queue, QueuePosition};
+    use static_assertions::assert_impl_all;
+    use std::{fmt::Debug, time::Duration};
+    use tokio::{
+        task::{self, JoinHandle},
+        time,
+    };
+
+    assert_impl_all!(LocalQueue: Clone, Debug, Default, Queue, Send, Sync);
+    assert_impl_all!(QueuePosition: Clone, Copy, Debug, Eq, PartialEq, Send, Sync);
+
+    fn spawn_request(queue: &LocalQueue, shard_id: [u64; 2]) -> JoinHandle<()> {
+        let queue = queue.clone();
+
+        tokio::spawn(async move { queue.request(shard_id).await })
+    }
+
+    async fn settle() {
+        for _ in 0..16 {
+            task::yield_now().await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn buckets() {
+        let queue = LocalQueue::with_max_concurrency(2);
+
+        let first = spawn_request(&queue, [0, 6]);
+        let second = spawn_request(&queue, [1, 6]);
+        let third = spawn_request(&queue, [2, 6]);
+        let fourth = spawn_request(&queue, [4, 6]);
+        settle().await;
+
+        assert!(first.is_finished());
+        assert!(second.is_finished());
+        assert!(!third.is_finished());
+
+        time::advance(Duration::from_secs(5)).await;
+        settle().await;
+        assert!(third.is_finished());
+        assert!(!fourth.is_finished());
+
+        time::advance(Duration::from_secs(5)).await;
+        settle().await;
+        assert!(fourth.is_finished());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn position() {
+        let queue = LocalQueue::with_max_concurrency(2);
+        assert_eq!(2, queue.max_concurrency());
+
+        let _first = spawn_request(&queue, [0, 6]);
+        let _second = spawn_request(&queue, [2, 6]);
+        let _third = spawn_request(&queue, [4, 6]);
+        settle().await;
+
+        assert!(queue.position(0).is_none());
+        assert!(queue.position(1).is_none());
+
+        let second = queue.position(2).unwrap();
+        assert_eq!(0, second.bucket());
+        assert_eq!(0, second.position());
+        assert_eq!(Duration::from_secs(5), second.wait());
+
+        let third = queue.position(4).unwrap();
+        assert_eq!(0, third.bucket());
+        assert_eq!(1, third.position());
+        assert_eq!(Duration::from_secs(10), third.wait());
+
+        time::advance(Duration::from_secs(2)).await;
+        assert_eq!(Duration::from_secs(3), queue.position(2).unwrap().wait());
+    }
+}
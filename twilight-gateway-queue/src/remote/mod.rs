@@ -0,0 +1,270 @@
+//! Queue shared by shards across multiple processes or machines.
+//!
+//! A [`QueueServer`] schedules session initializations for every shard of a
+//! bot, while each process hands its shards a [`RemoteQueue`] pointing to it.
+//! The `twilight-gateway-queue-server` binary, enabled via the `server`
+//! feature, runs a standalone server.
+//!
+//! # Protocol
+//!
+//! Clients and servers communicate over TCP using newline-delimited text
+//! messages. To request to initialize a session a client sends
+//! `IDENTIFY <shard id> <shard total>`, to which the server responds with
+//! `READY` once the shard may identify, or with `ERROR <reason>` if the request
+//! was malformed. Multiple requests may be sent over one connection; they are
+//! answered in order.
+
+mod server;
+
+pub use self::server::{QueueServer, SessionLimit};
+
+use super::Queue;
+use std::{
+    future::Future,
+    io::{Error as IoError, ErrorKind as IoErrorKind},
+    pin::Pin,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    time::sleep,
+};
+
+/// Prefix of a request to initialize a session.
+const IDENTIFY: &str = "IDENTIFY";
+
+/// Prefix of a response to a malformed request.
+const ERROR: &str = "ERROR";
+
+/// Response to a request allowing the shard to initialize a session.
+const READY: &str = "READY";
+
+/// Duration to wait before first retrying a request that failed due to the
+/// server being unreachable.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum duration to wait before retrying a request, reached by doubling
+/// [`RETRY_DELAY`] after every failed attempt.
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(60);
+
+/// Implementation of a [`Queue`] requesting allowance from a [`QueueServer`]
+/// over the network.
+///
+/// All shards of a bot, across all of its processes, must use queues pointing
+/// to the same server.
+///
+/// If the server can't be reached the request is retried until it succeeds,
+/// as initializing a session without allowance risks being ratelimited. The
+/// delay between attempts starts at 1 second and doubles after every failed
+/// attempt, up to 1 minute.
+///
+/// If the server rejects the request with an `ERROR` response retrying won't
+/// help, so the error is logged and the shard is allowed to identify.
+///
+/// # Examples
+///
+/// Use a queue server running on another machine for a shard:
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use twilight_gateway_queue::RemoteQueue;
+///
+/// let queue = Arc::new(RemoteQueue::new("10.0.0.2:7100"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RemoteQueue {
+    address: String,
+}
+
+impl RemoteQueue {
+    /// Create a new remote queue connecting to the server at an address.
+    ///
+    /// The address may be anything that can be resolved into a socket
+    /// address, such as `"127.0.0.1:7100"` or `"queue.internal:7100"`.
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+        }
+    }
+
+    /// Address of the server.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Send a single request to the server and wait for its response.
+    async fn try_request(&self, [id, total]: [u64; 2]) -> Result<(), RequestError> {
+        let mut stream = TcpStream::connect(self.address.as_str()).await?;
+        let (read, mut write) = stream.split();
+
+        write
+            .write_all(format!("{IDENTIFY} {id} {total}\n").as_bytes())
+            .await?;
+
+        let mut line = String::new();
+        BufReader::new(read).read_line(&mut line).await?;
+
+        match line.trim_end() {
+            READY => Ok(()),
+            "" => Err(RequestError::Io(IoError::new(
+                IoErrorKind::UnexpectedEof,
+                "server closed the connection",
+            ))),
+            other => match other.strip_prefix(ERROR) {
+                Some(reason) if reason.is_empty() || reason.starts_with(' ') => {
+                    Err(RequestError::Rejected(reason.trim_start().to_owned()))
+                }
+                _ => Err(RequestError::Io(IoError::new(
+                    IoErrorKind::InvalidData,
+                    format!("unexpected response: {other:?}"),
+                ))),
+            },
+        }
+    }
+}
+
+/// Reason a single request to the server failed.
+#[derive(Debug)]
+enum RequestError {
+    /// Sending the request or receiving its response failed.
+    Io(IoError),
+    /// Server rejected the request with a reason.
+    Rejected(String),
+}
+
+impl From<IoError> for RequestError {
+    fn from(source: IoError) -> Self {
+        Self::Io(source)
+    }
+}
+
+impl Queue for RemoteQueue {
+    /// Request to be able to identify with the gateway. The returned future
+    /// will resolve once the server allows the shard to identify.
+    fn request(&'_ self, shard_id: [u64; 2]) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            let [id, total] = shard_id;
+
+            tracing::info!("shard {id}/{total} waiting for allowance");
+
+            let mut delay = RETRY_DELAY;
+
+            loop {
+                match self.try_request(shard_id).await {
+                    Ok(()) => return,
+                    Err(RequestError::Io(source)) => {
+                        tracing::warn!(
+                            "requesting allowance for shard {id}/{total} failed, retrying in \
+                             {delay:?}: {source}"
+                        );
+
+                        sleep(delay).await;
+                        delay = (delay * 2).min(RETRY_DELAY_MAX);
+                    }
+                    Err(RequestError::Rejected(reason)) => {
+                        tracing::error!(
+                            "server rejected request for allowance for shard {id}/{total}, \
+                             not retrying: {reason}"
+                        );
+
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Parse a request line sent by a client into the shard ID it's for.
+fn parse_request(line: &str) -> Option<[u64; 2]> {
+    let mut parts = line.split_ascii_whitespace();
+
+    if parts.next()? != IDENTIFY {
+        return None;
+    }
+
+    let id = parts.next()?.parse().ok()?;
+    let total = parts.next()?.parse().ok()?;
+
+    if parts.next().is_some() || id >= total {
+        return None;
+    }
+
+    Some([id, total])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_request, QueueServer, RemoteQueue, SessionLimit};
+    use crate::Queue;
+    use static_assertions::assert_impl_all;
+    use std::{fmt::Debug, time::Duration};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    assert_impl_all!(RemoteQueue: Clone, Debug, Queue, Send, Sync);
+
+    #[test]
+    fn request_parsing() {
+        assert_eq!(Some([0, 1]), parse_request("IDENTIFY 0 1"));
+        assert_eq!(Some([7, 16]), parse_request("IDENTIFY 7 16\r"));
+        assert!(parse_request("IDENTIFY 1 1").is_none());
+        assert!(parse_request("IDENTIFY 0").is_none());
+        assert!(parse_request("IDENTIFY 0 1 2").is_none());
+        assert!(parse_request("IDENTIFY a 1").is_none());
+        assert!(parse_request("READY").is_none());
+        assert!(parse_request("").is_none());
+    }
+
+    #[tokio::test]
+    async fn remote_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = QueueServer::new(
+            1,
+            SessionLimit {
+                remaining: 1000,
+                reset_after: Duration::from_secs(60 * 60 * 24),
+                total: 1000,
+            },
+        );
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let queue = RemoteQueue::new(address.to_string());
+        tokio::time::timeout(Duration::from_secs(1), queue.request([0, 1]))
+            .await
+            .expect("shard not allowed to identify");
+    }
+
+    #[tokio::test]
+    async fn rejected_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+            write.write_all(b"ERROR malformed request\n").await.unwrap();
+
+            listener
+        });
+
+        let queue = RemoteQueue::new(address.to_string());
+        tokio::time::timeout(Duration::from_secs(1), queue.request([0, 1]))
+            .await
+            .expect("rejected request was retried");
+
+        let listener = server.await.unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), listener.accept())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use super::{parse_request, ERROR, READY};
+use crate::{LocalQueue, Queue};
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    future::Future,
+    io::Error as IoError,
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+    time::{self, Instant},
+};
+
+/// Period after which the session start limit resets once exhausted.
+const DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Bot's limit of session initializations within a period of time.
+///
+/// Retrieved via the [Get Gateway Bot] endpoint as its `session_start_limit`.
+///
+/// [Get Gateway Bot]: https://discord.com/developers/docs/topics/gateway#get-gateway-bot
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SessionLimit {
+    /// Number of remaining sessions that may be started before the reset.
+    pub remaining: u64,
+    /// Time until the number of remaining sessions resets to the total.
+    pub reset_after: Duration,
+    /// Total number of sessions that may be started within the period.
+    pub total: u64,
+}
+
+/// Tracker of the bot's session start limit.
+///
+/// Unlike the HTTP-backed day limiter used by the `LargeBotQueue`, this is
+/// fed an initial limit once and then tracks resets locally.
+#[derive(Debug)]
+struct SessionLimiter {
+    remaining: u64,
+    reset_at: Instant,
+    total: u64,
+}
+
+impl SessionLimiter {
+    fn new(limit: SessionLimit) -> Self {
+        Self {
+            remaining: limit.remaining.min(limit.total),
+            reset_at: Instant::now() + limit.reset_after,
+            total: limit.total,
+        }
+    }
+
+    /// Wait until a session may be started and consume it.
+    async fn acquire(&mut self) {
+        if self.remaining == 0 {
+            let wait = self.reset_at.saturating_duration_since(Instant::now());
+
+            tracing::warn!("session start limit exhausted, waiting {wait:.2?} for reset");
+
+            time::sleep_until(self.reset_at).await;
+        }
+
+        let now = Instant::now();
+
+        if now >= self.reset_at {
+            self.remaining = self.total;
+            self.reset_at = now + DAY;
+
+            tracing::info!("session start limit reset to {}", self.total);
+        }
+
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+}
+
+struct Inner {
+    limiter: Mutex<SessionLimiter>,
+    queue: LocalQueue,
+}
+
+/// Server scheduling session initializations for the shards of a bot across
+/// all of its processes.
+///
+/// Shards are scheduled by a [`LocalQueue`]: each shard is assigned a bucket
+/// with an ID of `shard_id % max_concurrency`, and each bucket allows one shard
+/// to identify every 5 seconds. Additionally,
+/// the bot's daily session start limit is tracked, delaying shards once it has
+/// been exhausted until it resets.
+///
+/// Shards in other processes connect to the server via a [`RemoteQueue`]. The
+/// server also implements [`Queue`] itself, so shards in the same process may
+/// use it directly.
+///
+/// [`RemoteQueue`]: super::RemoteQueue
+///
+/// # Examples
+///
+/// Serve a queue for a bot with a maximum concurrency of 16:
+///
+/// ```no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+/// use tokio::net::TcpListener;
+/// use twilight_gateway_queue::{QueueServer, SessionLimit};
+///
+/// let server = QueueServer::new(
+///     16,
+///     SessionLimit {
+///         remaining: 2000,
+///         reset_after: Duration::from_secs(60 * 60 * 24),
+///         total: 2000,
+///     },
+/// );
+///
+/// let listener = TcpListener::bind("0.0.0.0:7100").await?;
+/// server.serve(listener).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct QueueServer {
+    inner: Arc<Inner>,
+}
+
+impl QueueServer {
+    /// Create a new queue server for a bot.
+    ///
+    /// A `max_concurrency` of 0 is treated as 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn new(max_concurrency: u64, limit: SessionLimit) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                limiter: Mutex::new(SessionLimiter::new(limit)),
+                queue: LocalQueue::with_max_concurrency(max_concurrency),
+            }),
+        }
+    }
+
+    /// Maximum number of shards that may identify concurrently.
+    pub fn max_concurrency(&self) -> u64 {
+        self.inner.queue.max_concurrency()
+    }
+
+    /// Accept connections from [`RemoteQueue`]s on a listener and serve their
+    /// requests.
+    ///
+    /// Connections are served concurrently in their own tasks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if accepting a connection failed, such as due to the
+    /// listener being closed.
+    ///
+    /// [`RemoteQueue`]: super::RemoteQueue
+    pub async fn serve(&self, listener: TcpListener) -> Result<(), IoError> {
+        loop {
+            let (stream, address) = listener.accept().await?;
+
+            tracing::debug!("accepted connection from {address}");
+
+            let server = self.clone();
+
+            tokio::spawn(async move {
+                if let Err(source) = server.handle(stream).await {
+                    tracing::debug!("connection from {address} failed: {source}");
+                }
+            });
+        }
+    }
+
+    /// Answer the requests sent over a connection until it is closed.
+    async fn handle(&self, mut stream: TcpStream) -> Result<(), IoError> {
+        let (read, mut write) = stream.split();
+        let mut lines = BufReader::new(read).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            let response = if let Some(shard_id) = parse_request(&line) {
+                self.request(shard_id).await;
+
+                format!("{READY}\n")
+            } else {
+                tracing::debug!("received malformed request: {line:?}");
+
+                format!("{ERROR} malformed request\n")
+            };
+
+            write.write_all(response.as_bytes()).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for QueueServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("QueueServer")
+            .field("max_concurrency", &self.max_concurrency())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Queue for QueueServer {
+    /// Request to be able to identify with the gateway. This will place this
+    /// request behind all other requests of the shard's bucket, and the
+    /// returned future will resolve once the request has been completed.
+    fn request(&'_ self, [id, total]: [u64; 2]) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            self.inner.limiter.lock().await.acquire().await;
+
+            self.inner.queue.request([id, total]).await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueueServer, SessionLimit};
+    use crate::Queue;
+    use static_assertions::assert_impl_all;
+    use std::{fmt::Debug, time::Duration};
+    use tokio::{
+        task::{self, JoinHandle},
+        time,
+    };
+
+    assert_impl_all!(QueueServer: Clone, Debug, Queue, Send, Sync);
+
+    const DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+    fn spawn_request(server: &QueueServer, shard_id: [u64; 2]) -> JoinHandle<()> {
+        let server = server.clone();
+
+        tokio::spawn(async move { server.request(shard_id).await })
+    }
+
+    async fn settle() {
+        for _ in 0..16 {
+            task::yield_now().await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn buckets() {
+        let server = QueueServer::new(
+            2,
+            SessionLimit {
+                remaining: 1000,
+                reset_after: DAY,
+                total: 1000,
+            },
+        );
+
+        server.request([0, 4]).await;
+        server.request([1, 4]).await;
+
+        let third = spawn_request(&server, [2, 4]);
+        settle().await;
+        assert!(!third.is_finished());
+
+        time::advance(Duration::from_secs(5)).await;
+        settle().await;
+        assert!(third.is_finished());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn session_limit() {
+        let server = QueueServer::new(
+            4,
+            SessionLimit {
+                remaining: 1,
+                reset_after: Duration::from_secs(60),
+                total: 1000,
+            },
+        );
+
+        server.request([0, 2]).await;
+
+        let second = spawn_request(&server, [1, 2]);
+        settle().await;
+        assert!(!second.is_finished());
+
+        time::advance(Duration::from_secs(60)).await;
+        settle().await;
+        assert!(second.is_finished());
+    }
+}
@@ -1,6 +1,6 @@
 use super::{Cluster, ClusterStartError, ClusterStartErrorType, Config, Events, ShardScheme};
 use crate::{
-    shard::{ResumeSession, ShardBuilder},
+    shard::{BackpressurePolicy, ResumeSession, ShardBuilder},
     EventTypeFlags,
 };
 use std::{
@@ -113,7 +113,7 @@ impl ClusterBuilder {
             shard_scheme: self.shard_scheme.expect("always set"),
         };
 
-        Ok(Cluster::new_with_config(config, &shard_config))
+        Ok(Cluster::new_with_config(config, shard_config))
     }
 
     /// Retrieves the recommended shard count as a [`ShardScheme::Range`].
@@ -141,6 +141,21 @@ impl ClusterBuilder {
         })
     }
 
+    /// Bound the stream of events of each shard, applying a policy once a
+    /// shard's stream reaches the capacity.
+    ///
+    /// Refer to [`ShardBuilder::bounded_events`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is 0.
+    #[track_caller]
+    pub fn bounded_events(mut self, capacity: usize, policy: BackpressurePolicy) -> Self {
+        self.shard = self.shard.bounded_events(capacity, policy);
+
+        self
+    }
+
     /// Set the event types to process.
     ///
     /// This is an optimization technique; all events not included in the
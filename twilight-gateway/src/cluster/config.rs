@@ -21,8 +21,10 @@ pub struct Config {
 impl Config {
     /// Return an immutable reference to the shard scheme used to start shards.
     ///
-    /// Refer to [`ClusterBuilder::shard_scheme`] for the default value.
+    /// Refer to [`ClusterBuilder::shard_scheme`] for the default value, and to
+    /// [`Cluster::shard_scheme`] for the scheme of a resharded cluster.
     ///
+    /// [`Cluster::shard_scheme`]: super::Cluster::shard_scheme
     /// [`ClusterBuilder::shard_scheme`]: super::ClusterBuilder::shard_scheme
     pub const fn shard_scheme(&self) -> &ShardScheme {
         &self.shard_scheme
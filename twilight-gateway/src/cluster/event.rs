@@ -12,12 +12,14 @@
 //! [`EventType`]: twilight_model::gateway::event::EventType
 //! [`ClusterBuilder::event_types`]: crate::cluster::ClusterBuilder::event_types
 
-use crate::shard::Events as ShardEvents;
+use crate::{shard::Events as ShardEvents, EventTypeFlags};
 use futures_util::stream::{SelectAll, Stream};
 use std::{
+    collections::VecDeque,
     pin::Pin,
     task::{Context, Poll},
 };
+use tokio::sync::mpsc::UnboundedReceiver;
 use twilight_model::gateway::event::Event;
 
 /// Stream of events from a [`Cluster`].
@@ -37,12 +39,17 @@ use twilight_model::gateway::event::Event;
 #[derive(Debug)]
 pub struct Events {
     stream: SelectAll<ShardEventsWithId>,
+    updates: UnboundedReceiver<Update>,
 }
 
 impl Events {
-    /// Create a new stream of shards' events.
-    pub(super) const fn new(stream: SelectAll<ShardEventsWithId>) -> Self {
-        Self { stream }
+    /// Create a new stream of shards' events, whose set of shards is updated
+    /// by the cluster via a channel.
+    pub(crate) const fn new(
+        stream: SelectAll<ShardEventsWithId>,
+        updates: UnboundedReceiver<Update>,
+    ) -> Self {
+        Self { stream, updates }
     }
 }
 
@@ -50,23 +57,66 @@ impl Stream for Events {
     type Item = (u64, Event);
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        while let Poll::Ready(Some(update)) = self.updates.poll_recv(cx) {
+            match update {
+                Update::Insert(stream) => self.stream.push(stream),
+            }
+        }
+
         Pin::new(&mut self.stream).poll_next(cx)
     }
 }
 
+/// Update to the set of shards whose events are received by [`Events`].
+///
+/// Streams are never removed, since a shard's stream ends on its own once the
+/// shard has been shut down and its queued events have been received.
+#[derive(Debug)]
+pub enum Update {
+    /// Insert a shard's stream.
+    Insert(ShardEventsWithId),
+}
+
 /// Poll a shard's [`Events`] stream, mapping the result to the shard's ID.
 ///
 /// [`Events`]: crate::shard::Events
+///
+/// Events not of the provided event types are skipped, which allows the
+/// cluster to receive more events from a shard than the user configured.
 #[derive(Debug)]
 pub struct ShardEventsWithId {
+    /// Events already received from the shard, yielded before the events of
+    /// the shard's stream.
+    buffered: VecDeque<Event>,
+    event_types: EventTypeFlags,
     id: u64,
     stream: ShardEvents,
 }
 
 impl ShardEventsWithId {
     /// Create a new stream with shard's ID and event stream.
-    pub(super) const fn new(id: u64, stream: ShardEvents) -> Self {
-        Self { id, stream }
+    pub(crate) fn new(id: u64, stream: ShardEvents, event_types: EventTypeFlags) -> Self {
+        Self {
+            buffered: VecDeque::new(),
+            event_types,
+            id,
+            stream,
+        }
+    }
+
+    /// Set events already received from the shard, to be yielded first.
+    pub(super) fn set_buffered(&mut self, buffered: VecDeque<Event>) {
+        self.buffered = buffered;
+    }
+
+    /// ID of the shard.
+    pub(super) const fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Set the event types to pass through.
+    pub(super) fn set_event_types(&mut self, event_types: EventTypeFlags) {
+        self.event_types = event_types;
     }
 }
 
@@ -74,10 +124,25 @@ impl Stream for ShardEventsWithId {
     type Item = (u64, Event);
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match Pin::new(&mut self.stream).poll_next(cx) {
-            Poll::Ready(Some(event)) => Poll::Ready(Some((self.id, event))),
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
+        loop {
+            let next = if let Some(event) = self.buffered.pop_front() {
+                Poll::Ready(Some(event))
+            } else {
+                Pin::new(&mut self.stream).poll_next(cx)
+            };
+
+            match next {
+                Poll::Ready(Some(event)) => {
+                    if self
+                        .event_types
+                        .contains(EventTypeFlags::from(event.kind()))
+                    {
+                        return Poll::Ready(Some((self.id, event)));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
         }
     }
 }
@@ -1,18 +1,34 @@
-use super::{ClusterBuilder, Config, Events};
+use super::{
+    event::{ShardEventsWithId, Update},
+    reshard::{
+        self, ClusterReshardError, ClusterReshardErrorType, GuildTracker, NewShards, Reshard,
+        ReshardProgress, WarmingShard,
+    },
+    store::ShardStore,
+    ClusterBuilder, Config, Events, ShardScheme,
+};
 use crate::{
-    cluster::event::ShardEventsWithId,
     shard::{
-        raw_message::Message, Command, Config as ShardConfig, Information, ResumeSession, Shard,
+        raw_message::Message, Command, Config as ShardConfig, Events as ShardEvents, Information,
+        MemberChunks, ResumeSession, Shard,
     },
-    Intents,
+    EventTypeFlags, Intents,
+};
+use futures_util::{
+    future,
+    stream::{FuturesUnordered, SelectAll, StreamExt},
 };
-use futures_util::{future, stream::SelectAll};
 use std::{
-    collections::{hash_map::Values, HashMap},
+    collections::{HashMap, VecDeque},
     error::Error,
     fmt::{Display, Formatter, Result as FmtResult},
     iter::FusedIterator,
+    sync::{PoisonError, RwLock},
+    task::Poll,
+    vec::IntoIter,
 };
+use tokio::sync::mpsc::{self, UnboundedSender};
+use twilight_model::gateway::payload::outgoing::RequestGuildMembers;
 
 /// Sending a command to a shard failed.
 #[derive(Debug)]
@@ -146,6 +162,106 @@ pub enum ClusterSendErrorType {
     },
 }
 
+/// Managing the shards of a running cluster failed.
+#[derive(Debug)]
+pub struct ClusterShardError {
+    kind: ClusterShardErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ClusterShardError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ClusterShardErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (ClusterShardErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for ClusterShardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            ClusterShardErrorType::ShardExists { id } => {
+                f.write_str("shard ")?;
+                Display::fmt(id, f)?;
+
+                f.write_str(" already exists")
+            }
+            ClusterShardErrorType::ShardNonexistent { id } => {
+                f.write_str("shard ")?;
+                Display::fmt(id, f)?;
+
+                f.write_str(" does not exist")
+            }
+            ClusterShardErrorType::Starting { id } => {
+                f.write_str("starting shard ")?;
+                Display::fmt(id, f)?;
+
+                f.write_str(" failed")
+            }
+            ClusterShardErrorType::TotalMismatch { expected, provided } => {
+                f.write_str("provided total number of shards (")?;
+                Display::fmt(provided, f)?;
+                f.write_str(") does not match the cluster's total (")?;
+                Display::fmt(expected, f)?;
+
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+impl Error for ClusterShardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`ClusterShardError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ClusterShardErrorType {
+    /// Shard with the provided ID is already managed by the cluster.
+    ShardExists {
+        /// Provided shard ID.
+        id: u64,
+    },
+    /// Provided shard ID does not exist.
+    ShardNonexistent {
+        /// Provided shard ID.
+        id: u64,
+    },
+    /// Starting the shard failed.
+    ///
+    /// The shard remains part of the cluster and may be restarted via
+    /// [`Cluster::restart`].
+    Starting {
+        /// ID of the shard.
+        id: u64,
+    },
+    /// Total number of shards of the provided scheme does not match the
+    /// cluster's total number of shards.
+    TotalMismatch {
+        /// Cluster's total number of shards.
+        expected: u64,
+        /// Total number of shards of the provided scheme.
+        provided: u64,
+    },
+}
+
 /// Starting a cluster failed.
 #[derive(Debug)]
 pub struct ClusterStartError {
@@ -210,7 +326,11 @@ pub enum ClusterStartErrorType {
 /// # Using a cluster in multiple tasks
 ///
 /// To use a cluster instance in multiple tasks, consider wrapping it in an
-/// [`std::sync::Arc`] or [`std::rc::Rc`].
+/// [`std::sync::Arc`] or [`std::rc::Rc`]. Shards can be added, removed,
+/// restarted, and resharded through a shared reference.
+///
+/// Shards that are removed or replaced are shut down, but kept until the
+/// cluster is dropped, so that references to them remain valid.
 ///
 /// # Examples
 ///
@@ -218,7 +338,13 @@ pub enum ClusterStartErrorType {
 #[derive(Debug)]
 pub struct Cluster {
     config: Config,
-    shards: HashMap<u64, Shard>,
+    shard_config: ShardConfig,
+    /// Scheme of the managed shards, which is replaced when resharding.
+    shard_scheme: RwLock<ShardScheme>,
+    /// Managed shards, which are replaced when resharding.
+    shards: ShardStore,
+    /// Sender of updates to the set of shards of the [`Events`] stream.
+    updates: UnboundedSender<Update>,
 }
 
 impl Cluster {
@@ -269,13 +395,10 @@ impl Cluster {
         Self::builder(token, intents).build().await
     }
 
-    pub(super) fn new_with_config(
-        mut config: Config,
-        shard_config: &ShardConfig,
-    ) -> (Self, Events) {
+    pub(super) fn new_with_config(mut config: Config, shard_config: ShardConfig) -> (Self, Events) {
         #[derive(Default)]
         struct ShardFold {
-            shards: HashMap<u64, Shard>,
+            shards: Vec<(u64, Shard)>,
             streams: Vec<ShardEventsWithId>,
         }
 
@@ -287,35 +410,58 @@ impl Cluster {
             metrics::gauge!("Cluster-Shard-Count", total as f64);
         }
 
+        let event_types = shard_config.event_types();
         let ShardFold { shards, streams } =
             config
                 .shard_scheme()
                 .iter()
                 .fold(ShardFold::default(), |mut fold, idx| {
-                    let mut shard_config = shard_config.clone();
-                    shard_config.shard = [idx, total];
-
-                    if let Some(data) = config.resume_sessions.remove(&idx) {
-                        shard_config.session_id = Some(data.session_id.into_boxed_str());
-                        shard_config.sequence = Some(data.sequence);
-                    }
-
-                    if let Some(shard_presence) = &config.shard_presence {
-                        shard_config.presence = shard_presence(idx)
-                    }
+                    let resume = config.resume_sessions.remove(&idx);
+                    let (shard, stream) =
+                        Self::create_shard(&config, shard_config.clone(), [idx, total], resume);
 
-                    let (shard, stream) = Shard::new_with_config(shard_config);
-
-                    fold.shards.insert(idx, shard);
-                    fold.streams.push(ShardEventsWithId::new(idx, stream));
+                    fold.shards.push((idx, shard));
+                    fold.streams
+                        .push(ShardEventsWithId::new(idx, stream, event_types));
 
                     fold
                 });
 
         #[allow(clippy::from_iter_instead_of_collect)]
         let select_all = SelectAll::from_iter(streams);
+        let (updates, updates_rx) = mpsc::unbounded_channel();
+
+        (
+            Self {
+                shard_scheme: RwLock::new(config.shard_scheme.clone()),
+                config,
+                shard_config,
+                shards: ShardStore::new(shards),
+                updates,
+            },
+            Events::new(select_all, updates_rx),
+        )
+    }
+
+    /// Create a shard, configured with the cluster's shard presence.
+    fn create_shard(
+        config: &Config,
+        mut shard_config: ShardConfig,
+        shard: [u64; 2],
+        resume: Option<ResumeSession>,
+    ) -> (Shard, ShardEvents) {
+        shard_config.shard = shard;
 
-        (Self { config, shards }, Events::new(select_all))
+        if let Some(data) = resume {
+            shard_config.session_id = Some(data.session_id.into_boxed_str());
+            shard_config.sequence = Some(data.sequence);
+        }
+
+        if let Some(shard_presence) = &config.shard_presence {
+            shard_config.presence = shard_presence(shard[0])
+        }
+
+        Shard::new_with_config(shard_config)
     }
 
     /// Create a builder to configure and construct a cluster.
@@ -363,6 +509,20 @@ impl Cluster {
         &self.config
     }
 
+    /// Return the scheme of the shards the cluster manages.
+    ///
+    /// This is the [`Config::shard_scheme`] until the cluster has been
+    /// [resharded].
+    ///
+    /// [`Config::shard_scheme`]: super::Config::shard_scheme
+    /// [resharded]: Self::reshard
+    pub fn shard_scheme(&self) -> ShardScheme {
+        self.shard_scheme
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
     /// Bring up the cluster, starting all of the shards that it was configured
     /// to manage.
     ///
@@ -388,12 +548,110 @@ impl Cluster {
     /// # Ok(()) }
     /// ```
     pub async fn up(&self) {
-        future::join_all(self.shards.values().map(Shard::start)).await;
+        let shards = self.shards.managed();
+
+        future::join_all(shards.iter().map(|(_, shard)| shard.start())).await;
+    }
+
+    /// Add and start shards from a scheme, such as a new range of shards.
+    ///
+    /// The events of the added shards are received through the cluster's
+    /// [`Events`] stream.
+    ///
+    /// The shards are all added before being started. Note that the cluster's
+    /// [`Config::shard_scheme`] isn't updated, since it can't represent an
+    /// arbitrary set of shards.
+    ///
+    /// # Examples
+    ///
+    /// Add shards 10 through 19 to a cluster of 20 shards:
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::env;
+    /// use twilight_gateway::{cluster::{Cluster, ShardScheme}, Intents};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let (cluster, _events) = Cluster::builder(token, Intents::GUILDS)
+    ///     .shard_scheme(ShardScheme::try_from((0..10, 20))?)
+    ///     .build()
+    ///     .await?;
+    /// cluster.up().await;
+    ///
+    /// // some time later..
+    /// cluster.add_shards(ShardScheme::try_from((10..20, 20))?).await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClusterShardErrorType::ShardExists`] error type if a shard
+    /// of the scheme is already managed by the cluster. No shards are added in
+    /// this case.
+    ///
+    /// Returns a [`ClusterShardErrorType::Starting`] error type if starting a
+    /// shard failed. The other shards are still started.
+    ///
+    /// Returns a [`ClusterShardErrorType::TotalMismatch`] error type if the
+    /// scheme's total number of shards does not match the cluster's total.
+    ///
+    /// [`Config::shard_scheme`]: super::Config::shard_scheme
+    pub async fn add_shards(&self, scheme: ShardScheme) -> Result<(), ClusterShardError> {
+        let expected = self.shard_scheme().total();
+
+        if scheme.total() != expected {
+            return Err(ClusterShardError {
+                kind: ClusterShardErrorType::TotalMismatch {
+                    expected,
+                    provided: scheme.total(),
+                },
+                source: None,
+            });
+        }
+
+        let ids = scheme.iter().collect::<Vec<_>>();
+        let (shards, streams): (Vec<_>, Vec<_>) = ids
+            .iter()
+            .map(|id| {
+                let (shard, stream) = Self::create_shard(
+                    &self.config,
+                    self.shard_config.clone(),
+                    [*id, expected],
+                    None,
+                );
+                let stream = ShardEventsWithId::new(*id, stream, self.shard_config.event_types());
+
+                ((*id, shard), stream)
+            })
+            .unzip();
+
+        self.shards
+            .insert_new(shards)
+            .map_err(|id| ClusterShardError {
+                kind: ClusterShardErrorType::ShardExists { id },
+                source: None,
+            })?;
+
+        for stream in streams {
+            // The events stream may have been dropped, in which case there's
+            // no need to receive the shard's events.
+            let _res = self.updates.send(Update::Insert(stream));
+        }
+
+        #[cfg(feature = "metrics")]
+        #[allow(clippy::cast_precision_loss)]
+        {
+            metrics::gauge!("Cluster-Shard-Count", self.shards.len() as f64);
+        }
+
+        let results = future::join_all(ids.iter().map(|id| self.start_shard(*id))).await;
+
+        results.into_iter().collect()
     }
 
     /// Bring down the cluster, stopping all of the shards that it's managing.
     pub fn down(&self) {
-        for shard in self.shards.values() {
+        for (_, shard) in self.shards.managed() {
             shard.shutdown();
         }
     }
@@ -409,21 +667,368 @@ impl Cluster {
     /// events already.
     pub fn down_resumable(&self) -> HashMap<u64, ResumeSession> {
         self.shards
-            .values()
-            .map(Shard::shutdown_resumable)
+            .managed()
+            .into_iter()
+            .map(|(_, shard)| shard.shutdown_resumable())
             .filter_map(|(id, session)| session.map(|s| (id, s)))
             .collect()
     }
 
+    /// Remove a shard from the cluster, shutting it down in a resumable way.
+    ///
+    /// Events the shard already queued are still received through the
+    /// cluster's [`Events`] stream. Returns the information needed to resume
+    /// the shard's session, if it can be resumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClusterShardErrorType::ShardNonexistent`] error type if the
+    /// provided shard ID does not exist in the cluster.
+    pub fn remove_shard(&self, id: u64) -> Result<Option<ResumeSession>, ClusterShardError> {
+        let shard = self.shards.remove(id).ok_or(ClusterShardError {
+            kind: ClusterShardErrorType::ShardNonexistent { id },
+            source: None,
+        })?;
+
+        #[cfg(feature = "metrics")]
+        #[allow(clippy::cast_precision_loss)]
+        {
+            metrics::gauge!("Cluster-Shard-Count", self.shards.len() as f64);
+        }
+
+        Ok(shard.shutdown_resumable().1)
+    }
+
+    /// Restart a shard, such as one with a zombied connection or a high
+    /// latency.
+    ///
+    /// The shard is shut down in a resumable way and replaced by a new shard
+    /// resuming its session, if possible. The new shard's events are received
+    /// through the cluster's [`Events`] stream.
+    ///
+    /// # Examples
+    ///
+    /// Restart shards with an average latency over a second:
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::{env, time::Duration};
+    /// use twilight_gateway::{Cluster, Intents};
+    ///
+    /// let (cluster, _events) = Cluster::new(env::var("DISCORD_TOKEN")?, Intents::GUILDS).await?;
+    /// cluster.up().await;
+    ///
+    /// // some time later..
+    /// let slow = cluster
+    ///     .info()
+    ///     .into_iter()
+    ///     .filter(|(_, info)| {
+    ///         info.latency()
+    ///             .average()
+    ///             .map_or(false, |latency| latency > Duration::from_secs(1))
+    ///     })
+    ///     .map(|(shard_id, _)| shard_id)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// for shard_id in slow {
+    ///     cluster.restart(shard_id).await?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClusterShardErrorType::ShardNonexistent`] error type if the
+    /// provided shard ID does not exist in the cluster.
+    ///
+    /// Returns a [`ClusterShardErrorType::Starting`] error type if starting the
+    /// new shard failed.
+    pub async fn restart(&self, id: u64) -> Result<(), ClusterShardError> {
+        let shard = self.shard(id).ok_or(ClusterShardError {
+            kind: ClusterShardErrorType::ShardNonexistent { id },
+            source: None,
+        })?;
+
+        let total = shard.config().shard()[1];
+        let (_, resume) = shard.shutdown_resumable();
+
+        tracing::debug!(resumable = resume.is_some(), "restarting shard {id}");
+
+        self.insert_shard([id, total], resume);
+
+        self.start_shard(id).await
+    }
+
+    /// Create a shard and insert it into the cluster, replacing an existing
+    /// shard with the same ID.
+    ///
+    /// The existing shard must already be shut down.
+    fn insert_shard(&self, shard: [u64; 2], resume: Option<ResumeSession>) {
+        let (new_shard, stream) =
+            Self::create_shard(&self.config, self.shard_config.clone(), shard, resume);
+        let stream = ShardEventsWithId::new(shard[0], stream, self.shard_config.event_types());
+
+        self.shards.insert(shard[0], new_shard);
+
+        // The events stream may have been dropped, in which case there's no
+        // need to receive the shard's events.
+        let _res = self.updates.send(Update::Insert(stream));
+    }
+
+    /// Start a shard of the cluster.
+    async fn start_shard(&self, id: u64) -> Result<(), ClusterShardError> {
+        let shard = self.shard(id).ok_or(ClusterShardError {
+            kind: ClusterShardErrorType::ShardNonexistent { id },
+            source: None,
+        })?;
+
+        shard.start().await.map_err(|source| ClusterShardError {
+            kind: ClusterShardErrorType::Starting { id },
+            source: Some(Box::new(source)),
+        })
+    }
+
+    /// Reshard the cluster without downtime, switching to a new set of shards.
+    ///
+    /// The shards of the new scheme are started in the background while the
+    /// current shards keep running. Once every new shard has received all of
+    /// its guilds the cluster's [`Events`] stream switches to the new shards,
+    /// and the current shards are shut down.
+    ///
+    /// Events a new shard receives before it has all of its guilds aren't
+    /// delivered, since the current shards still deliver events for the same
+    /// guilds. Events it receives afterwards are buffered and delivered after
+    /// the switch, as are the events the current shards queued by then. No
+    /// events are lost, but events received around the switch may be
+    /// delivered by both a current and a new shard.
+    ///
+    /// Resharding is driven by polling the returned stream, which yields the
+    /// progress of the resharding. Refer to [`Reshard`] for more information.
+    ///
+    /// Discord recommends resharding when the number of shards recommended by
+    /// the [Get Gateway Bot] endpoint increases.
+    ///
+    /// # Examples
+    ///
+    /// Reshard the cluster to the number of shards Discord recommends:
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use futures::StreamExt;
+    /// use std::env;
+    /// use twilight_gateway::{cluster::{Cluster, ShardScheme}, Intents};
+    /// use twilight_http::Client;
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let (cluster, _events) = Cluster::new(token.clone(), Intents::GUILDS).await?;
+    /// cluster.up().await;
+    ///
+    /// // some time later..
+    /// let client = Client::new(token);
+    /// let info = client.gateway().authed().exec().await?.model().await?;
+    ///
+    /// if info.shards > cluster.shard_scheme().total() {
+    ///     let scheme = ShardScheme::try_from((0..info.shards, info.shards))?;
+    ///     let mut reshard = cluster.reshard(scheme);
+    ///
+    ///     while let Some(progress) = reshard.next().await {
+    ///         println!("resharding: {:?}", progress?);
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    ///
+    /// # Errors
+    ///
+    /// Yields a [`ClusterReshardErrorType::SchemeEmpty`] error type if the
+    /// scheme has no shards.
+    ///
+    /// Yields a [`ClusterReshardErrorType::ShardStopped`] error type if a new
+    /// shard stopped before it received all of its guilds.
+    ///
+    /// Yields a [`ClusterReshardErrorType::Starting`] error type if starting a
+    /// new shard failed.
+    ///
+    /// [Get Gateway Bot]: https://discord.com/developers/docs/topics/gateway#get-gateway-bot
+    pub fn reshard(&self, scheme: ShardScheme) -> Reshard<'_> {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let future = Box::pin(async move {
+            if let Err(source) = self.reshard_inner(scheme, &tx).await {
+                let _res = tx.send(Err(source));
+            }
+        });
+
+        Reshard::new(future, rx)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn reshard_inner(
+        &self,
+        scheme: ShardScheme,
+        progress: &UnboundedSender<Result<ReshardProgress, ClusterReshardError>>,
+    ) -> Result<(), ClusterReshardError> {
+        // Switching to no shards would shut down the current shards without
+        // replacing them.
+        if scheme.iter().next().is_none() {
+            return Err(ClusterReshardError {
+                kind: ClusterReshardErrorType::SchemeEmpty,
+                source: None,
+            });
+        }
+
+        let total = scheme.total();
+        let event_types = self.shard_config.event_types();
+
+        // Guild events are required to know when the new shards are ready, but
+        // aren't delivered if the user doesn't want them.
+        let mut shard_config = self.shard_config.clone();
+        shard_config.event_types |=
+            EventTypeFlags::READY | EventTypeFlags::GUILD_CREATE | EventTypeFlags::GUILD_DELETE;
+        let wait_for_guilds = shard_config.intents().contains(Intents::GUILDS);
+
+        let mut buffers = HashMap::<u64, VecDeque<_>>::new();
+        let mut shards = HashMap::new();
+        let mut streams = SelectAll::new();
+        let mut trackers = HashMap::new();
+
+        for id in scheme.iter() {
+            let (shard, stream) =
+                Self::create_shard(&self.config, shard_config.clone(), [id, total], None);
+
+            shards.insert(id, shard);
+            streams.push(WarmingShard::new(ShardEventsWithId::new(
+                id,
+                stream,
+                EventTypeFlags::all(),
+            )));
+            trackers.insert(id, GuildTracker::new(wait_for_guilds));
+        }
+
+        let shards = NewShards(shards);
+        let mut remaining = shards.0.len() as u64;
+
+        tracing::info!("resharding to {remaining} of {total} shards");
+
+        let _res = progress.send(Ok(ReshardProgress::Started {
+            shards: remaining,
+            total,
+        }));
+
+        {
+            let mut starting = shards
+                .0
+                .iter()
+                .map(|(id, shard)| async move { (*id, shard.start().await) })
+                .collect::<FuturesUnordered<_>>();
+
+            while remaining > 0 {
+                let next = future::poll_fn(|cx| {
+                    if let Poll::Ready(Some(started)) = starting.poll_next_unpin(cx) {
+                        return Poll::Ready(Ok(started));
+                    }
+
+                    streams.poll_next_unpin(cx).map(Err)
+                })
+                .await;
+
+                match next {
+                    Ok((id, Ok(()))) => {
+                        let _res = progress.send(Ok(ReshardProgress::ShardStarted { id }));
+                    }
+                    Ok((id, Err(source))) => return Err(reshard::starting(id, source)),
+                    Err(Some((id, Some(event)))) => {
+                        let tracker = trackers.get_mut(&id).expect("tracker exists");
+                        let was_complete = tracker.is_complete();
+
+                        match (was_complete, tracker.update(&event)) {
+                            (false, true) => {
+                                remaining -= 1;
+
+                                tracing::debug!("new shard {id} ready, {remaining} remaining");
+
+                                let _res = progress
+                                    .send(Ok(ReshardProgress::ShardReady { id, remaining }));
+                            }
+                            // The current shards may not have delivered the
+                            // events for the same guilds by the time of the
+                            // switch.
+                            (true, true) => {
+                                if event_types.contains(EventTypeFlags::from(event.kind())) {
+                                    buffers.entry(id).or_default().push_back(event);
+                                }
+                            }
+                            // The shard started a new session, so its guilds
+                            // are sent again.
+                            (true, false) => remaining += 1,
+                            (false, false) => {}
+                        }
+                    }
+                    Err(Some((id, None))) => {
+                        return Err(ClusterReshardError {
+                            kind: ClusterReshardErrorType::ShardStopped { id },
+                            source: None,
+                        });
+                    }
+                    Err(None) => unreachable!("new shards' streams end individually"),
+                }
+            }
+        }
+
+        for mut stream in streams.into_iter().filter_map(WarmingShard::into_inner) {
+            if let Some(buffered) = buffers.remove(&stream.id()) {
+                stream.set_buffered(buffered);
+            }
+
+            stream.set_event_types(event_types);
+
+            // The events stream may have been dropped, in which case there's
+            // nothing to switch.
+            let _res = self.updates.send(Update::Insert(stream));
+        }
+
+        let previous = self.shards.replace(shards.take());
+
+        // The streams of the previous shards end once their queued events have
+        // been received.
+        for shard in previous {
+            shard.shutdown();
+        }
+
+        *self
+            .shard_scheme
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = scheme;
+
+        #[cfg(feature = "metrics")]
+        #[allow(clippy::cast_precision_loss)]
+        {
+            metrics::gauge!("Cluster-Shard-Count", total as f64);
+        }
+
+        tracing::info!("resharded to {total} shards");
+
+        let _res = progress.send(Ok(ReshardProgress::Completed { total }));
+
+        Ok(())
+    }
+
     /// Return a Shard by its ID.
     pub fn shard(&self, id: u64) -> Option<&Shard> {
-        self.shards.get(&id)
+        self.shards.get(id)
     }
 
-    /// Return an iterator of all the shards.
+    /// Return an iterator of all the shards managed at the time of calling.
     pub fn shards(&self) -> Shards<'_> {
         Shards {
-            iter: self.shards.values(),
+            iter: self
+                .shards
+                .managed()
+                .into_iter()
+                .map(|(_, shard)| shard)
+                .collect::<Vec<_>>()
+                .into_iter(),
         }
     }
 
@@ -455,8 +1060,9 @@ impl Cluster {
     /// ```
     pub fn info(&self) -> HashMap<u64, Information> {
         self.shards
-            .iter()
-            .filter_map(|(id, shard)| shard.info().ok().map(|info| (*id, info)))
+            .managed()
+            .into_iter()
+            .filter_map(|(id, shard)| shard.info().ok().map(|info| (id, info)))
             .collect()
     }
 
@@ -527,6 +1133,38 @@ impl Cluster {
             })
     }
 
+    /// Request members of a guild over the specified shard, returning a
+    /// stream of the requested member chunks.
+    ///
+    /// The shard must be the one the guild is on. Refer to
+    /// [`Shard::request_guild_members`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClusterCommandErrorType::Sending`] error type if the shard
+    /// exists, but sending it failed.
+    ///
+    /// Returns a [`ClusterCommandErrorType::ShardNonexistent`] error type if
+    /// the provided shard ID does not exist in the cluster.
+    pub async fn request_guild_members(
+        &self,
+        id: u64,
+        request: RequestGuildMembers,
+    ) -> Result<MemberChunks, ClusterCommandError> {
+        let shard = self.shard(id).ok_or(ClusterCommandError {
+            kind: ClusterCommandErrorType::ShardNonexistent { id },
+            source: None,
+        })?;
+
+        shard
+            .request_guild_members(request)
+            .await
+            .map_err(|source| ClusterCommandError {
+                kind: ClusterCommandErrorType::Sending,
+                source: Some(Box::new(source)),
+            })
+    }
+
     /// Send a raw websocket message.
     ///
     /// # Examples
@@ -580,7 +1218,7 @@ impl Cluster {
 ///
 /// This is returned by [`Cluster::shards`].
 pub struct Shards<'a> {
-    iter: Values<'a, u64, Shard>,
+    iter: IntoIter<&'a Shard>,
 }
 
 impl ExactSizeIterator for Shards<'_> {
@@ -603,8 +1241,14 @@ impl<'a> Iterator for Shards<'a> {
 mod tests {
     use super::{
         Cluster, ClusterCommandError, ClusterCommandErrorType, ClusterSendError,
-        ClusterSendErrorType, ClusterStartError, ClusterStartErrorType,
+        ClusterSendErrorType, ClusterShardError, ClusterShardErrorType, ClusterStartError,
+        ClusterStartErrorType,
     };
+    use crate::{
+        cluster::{ClusterReshardErrorType, ShardScheme},
+        Intents,
+    };
+    use futures_util::StreamExt;
     use static_assertions::{assert_fields, assert_impl_all};
     use std::{error::Error, fmt::Debug};
 
@@ -614,7 +1258,79 @@ mod tests {
     assert_impl_all!(ClusterSendErrorType: Debug, Send, Sync);
     assert_fields!(ClusterSendErrorType::ShardNonexistent: id);
     assert_impl_all!(ClusterSendError: Error, Send, Sync);
+    assert_impl_all!(ClusterShardErrorType: Debug, Send, Sync);
+    assert_fields!(ClusterShardErrorType::ShardExists: id);
+    assert_fields!(ClusterShardErrorType::ShardNonexistent: id);
+    assert_fields!(ClusterShardErrorType::Starting: id);
+    assert_fields!(ClusterShardErrorType::TotalMismatch: expected, provided);
+    assert_impl_all!(ClusterShardError: Error, Send, Sync);
     assert_impl_all!(ClusterStartErrorType: Debug, Send, Sync);
     assert_impl_all!(ClusterStartError: Error, Send, Sync);
     assert_impl_all!(Cluster: Debug, Send, Sync);
+
+    #[tokio::test]
+    async fn manage_shards() -> Result<(), Box<dyn Error>> {
+        let scheme = ShardScheme::try_from((0..2, 4))?;
+        let (cluster, _events) = Cluster::builder("token".to_owned(), Intents::empty())
+            .shard_scheme(scheme)
+            .build()
+            .await?;
+
+        let error = cluster
+            .add_shards(ShardScheme::try_from((2..4, 5))?)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ClusterShardErrorType::TotalMismatch {
+                expected: 4,
+                provided: 5
+            }
+        ));
+
+        let error = cluster
+            .add_shards(ShardScheme::try_from((1..3, 4))?)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ClusterShardErrorType::ShardExists { id: 1 }
+        ));
+        assert_eq!(2, cluster.shards().len());
+
+        assert!(cluster.remove_shard(1)?.is_none());
+        assert!(matches!(
+            cluster.remove_shard(1).unwrap_err().kind(),
+            ClusterShardErrorType::ShardNonexistent { id: 1 }
+        ));
+        assert!(cluster.shard(1).is_none());
+        assert_eq!(1, cluster.shards().len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reshard_empty_scheme() -> Result<(), Box<dyn Error>> {
+        let scheme = ShardScheme::try_from((0..2, 2))?;
+        let (cluster, _events) = Cluster::builder("token".to_owned(), Intents::empty())
+            .shard_scheme(scheme.clone())
+            .build()
+            .await?;
+
+        let empty = ShardScheme::Range {
+            from: 1,
+            to: 0,
+            total: 2,
+        };
+        let mut reshard = cluster.reshard(empty);
+        let error = reshard.next().await.unwrap().unwrap_err();
+        assert!(matches!(error.kind(), ClusterReshardErrorType::SchemeEmpty));
+        assert!(reshard.next().await.is_none());
+        drop(reshard);
+
+        assert_eq!(2, cluster.shards().len());
+        assert_eq!(scheme, cluster.shard_scheme());
+
+        Ok(())
+    }
 }
@@ -87,16 +87,19 @@ pub mod scheme;
 
 mod builder;
 mod config;
-mod event;
+pub(crate) mod event;
 mod r#impl;
+mod reshard;
+mod store;
 
 pub use self::{
     builder::ClusterBuilder,
     config::Config,
     event::Events,
     r#impl::{
-        Cluster, ClusterCommandError, ClusterCommandErrorType, ClusterStartError,
-        ClusterStartErrorType, Shards,
+        Cluster, ClusterCommandError, ClusterCommandErrorType, ClusterShardError,
+        ClusterShardErrorType, ClusterStartError, ClusterStartErrorType, Shards,
     },
+    reshard::{ClusterReshardError, ClusterReshardErrorType, Reshard, ReshardProgress},
     scheme::{ShardScheme, ShardSchemeRangeError, ShardSchemeRangeErrorType},
 };
@@ -0,0 +1,403 @@
+//! Resharding a running [`Cluster`] to a new set of shards.
+//!
+//! [`Cluster`]: super::Cluster
+
+use super::event::ShardEventsWithId;
+use crate::shard::{Shard, ShardStartError};
+use futures_util::stream::{Stream, StreamExt};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::UnboundedReceiver;
+use twilight_model::{
+    gateway::event::Event,
+    id::{marker::GuildMarker, Id},
+};
+
+/// Resharding a cluster failed.
+///
+/// The cluster continues to run with its previous set of shards.
+#[derive(Debug)]
+pub struct ClusterReshardError {
+    pub(super) kind: ClusterReshardErrorType,
+    pub(super) source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ClusterReshardError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ClusterReshardErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(
+        self,
+    ) -> (
+        ClusterReshardErrorType,
+        Option<Box<dyn Error + Send + Sync>>,
+    ) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for ClusterReshardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            ClusterReshardErrorType::SchemeEmpty => f.write_str("shard scheme has no shards"),
+            ClusterReshardErrorType::ShardStopped { id } => {
+                f.write_str("new shard ")?;
+                Display::fmt(id, f)?;
+
+                f.write_str(" stopped before receiving all of its guilds")
+            }
+            ClusterReshardErrorType::Starting { id } => {
+                f.write_str("starting new shard ")?;
+                Display::fmt(id, f)?;
+
+                f.write_str(" failed")
+            }
+        }
+    }
+}
+
+impl Error for ClusterReshardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`ClusterReshardError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ClusterReshardErrorType {
+    /// Shard scheme has no shards, such as a range whose start is after its
+    /// end.
+    SchemeEmpty,
+    /// New shard stopped before it received all of its guilds, such as due to
+    /// a fatal close code.
+    ShardStopped {
+        /// ID of the shard.
+        id: u64,
+    },
+    /// Starting a new shard failed.
+    Starting {
+        /// ID of the shard.
+        id: u64,
+    },
+}
+
+/// Progress of resharding a cluster.
+///
+/// Returned by the [`Reshard`] stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReshardProgress {
+    /// Events are delivered from the new shards and the previous shards have
+    /// been shut down.
+    Completed {
+        /// Total number of shards the bot now uses.
+        total: u64,
+    },
+    /// New shard has received all of its guilds.
+    ShardReady {
+        /// ID of the shard.
+        id: u64,
+        /// Number of new shards that have yet to receive all of their guilds.
+        remaining: u64,
+    },
+    /// New shard has been started.
+    ShardStarted {
+        /// ID of the shard.
+        id: u64,
+    },
+    /// New shards have been created and are being started.
+    Started {
+        /// Number of new shards managed by the cluster.
+        shards: u64,
+        /// Total number of shards the bot uses.
+        total: u64,
+    },
+}
+
+/// Stream driving the resharding of a cluster, yielding its progress.
+///
+/// The resharding only progresses while the stream is polled. The stream ends
+/// after yielding [`ReshardProgress::Completed`] or an error. Dropping the
+/// stream before then cancels the resharding, shutting down the new shards.
+///
+/// Created via [`Cluster::reshard`].
+///
+/// [`Cluster::reshard`]: super::Cluster::reshard
+#[must_use = "streams do nothing unless polled"]
+pub struct Reshard<'a> {
+    future: Option<Pin<Box<dyn Future<Output = ()> + Send + 'a>>>,
+    progress: UnboundedReceiver<Result<ReshardProgress, ClusterReshardError>>,
+}
+
+impl<'a> Reshard<'a> {
+    pub(super) fn new(
+        future: Pin<Box<dyn Future<Output = ()> + Send + 'a>>,
+        progress: UnboundedReceiver<Result<ReshardProgress, ClusterReshardError>>,
+    ) -> Self {
+        Self {
+            future: Some(future),
+            progress,
+        }
+    }
+}
+
+impl Debug for Reshard<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Reshard")
+            .field("future", &"<future>")
+            .field("progress", &self.progress)
+            .finish()
+    }
+}
+
+impl Stream for Reshard<'_> {
+    type Item = Result<ReshardProgress, ClusterReshardError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(future) = self.future.as_mut() {
+            if future.as_mut().poll(cx).is_ready() {
+                self.future = None;
+            }
+        }
+
+        // The sender is owned by the future, so the channel closes once it has
+        // completed.
+        self.progress.poll_recv(cx)
+    }
+}
+
+/// New shards that are shut down when dropped, unless taken.
+#[derive(Debug)]
+pub(super) struct NewShards(pub HashMap<u64, Shard>);
+
+impl NewShards {
+    /// Take the shards, no longer shutting them down when dropped.
+    pub fn take(mut self) -> HashMap<u64, Shard> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Drop for NewShards {
+    fn drop(&mut self) {
+        for shard in self.0.values() {
+            shard.shutdown();
+        }
+    }
+}
+
+/// Tracker of which guilds a new shard has yet to receive.
+#[derive(Debug, Default)]
+pub(super) struct GuildTracker {
+    /// Guilds that have yet to be received, if the shard is ready.
+    pending: Option<HashSet<Id<GuildMarker>>>,
+    /// Whether to wait for guilds to be received.
+    ///
+    /// Guilds are only sent with the `GUILDS` intent.
+    wait_for_guilds: bool,
+}
+
+impl GuildTracker {
+    pub const fn new(wait_for_guilds: bool) -> Self {
+        Self {
+            pending: None,
+            wait_for_guilds,
+        }
+    }
+
+    /// Update the tracker with an event of the shard, returning whether it
+    /// received all of its guilds.
+    pub fn update(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Ready(ready) => {
+                let guilds = if self.wait_for_guilds {
+                    ready.guilds.iter().map(|guild| guild.id).collect()
+                } else {
+                    HashSet::new()
+                };
+
+                self.pending = Some(guilds);
+            }
+            Event::GuildCreate(guild) => {
+                if let Some(pending) = self.pending.as_mut() {
+                    pending.remove(&guild.id);
+                }
+            }
+            Event::GuildDelete(guild) => {
+                if let Some(pending) = self.pending.as_mut() {
+                    pending.remove(&guild.id);
+                }
+            }
+            _ => {}
+        }
+
+        self.is_complete()
+    }
+
+    /// Whether the shard received all of its guilds.
+    pub fn is_complete(&self) -> bool {
+        self.pending.as_ref().map_or(false, HashSet::is_empty)
+    }
+}
+
+/// Stream of a new shard's events that yields an item once the shard's stream
+/// ended, allowing the stream to be taken back afterwards.
+#[derive(Debug)]
+pub(super) struct WarmingShard {
+    id: u64,
+    stream: Option<ShardEventsWithId>,
+}
+
+impl WarmingShard {
+    pub const fn new(stream: ShardEventsWithId) -> Self {
+        Self {
+            id: stream.id(),
+            stream: Some(stream),
+        }
+    }
+
+    /// Take back the stream, if it hasn't ended.
+    pub fn into_inner(self) -> Option<ShardEventsWithId> {
+        self.stream
+    }
+}
+
+impl Stream for WarmingShard {
+    /// ID of the shard and its event, or `None` once it ended.
+    type Item = (u64, Option<Event>);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let id = self.id;
+
+        let stream = if let Some(stream) = self.stream.as_mut() {
+            stream
+        } else {
+            return Poll::Ready(None);
+        };
+
+        match stream.poll_next_unpin(cx) {
+            Poll::Ready(Some((_, event))) => Poll::Ready(Some((id, Some(event)))),
+            Poll::Ready(None) => {
+                self.stream = None;
+
+                Poll::Ready(Some((id, None)))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Map a shard start error to a resharding error.
+pub(super) fn starting(id: u64, source: ShardStartError) -> ClusterReshardError {
+    ClusterReshardError {
+        kind: ClusterReshardErrorType::Starting { id },
+        source: Some(Box::new(source)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClusterReshardError, ClusterReshardErrorType, GuildTracker, Reshard, ReshardProgress,
+    };
+    use futures_util::stream::Stream;
+    use static_assertions::{assert_fields, assert_impl_all};
+    use std::{error::Error, fmt::Debug};
+    use twilight_model::{
+        gateway::{
+            event::Event,
+            payload::incoming::{GuildDelete, Ready},
+        },
+        guild::UnavailableGuild,
+        id::Id,
+        oauth::{ApplicationFlags, PartialApplication},
+        user::CurrentUser,
+    };
+
+    assert_impl_all!(ClusterReshardErrorType: Debug, Send, Sync);
+    assert_fields!(ClusterReshardErrorType::ShardStopped: id);
+    assert_fields!(ClusterReshardErrorType::Starting: id);
+    assert_impl_all!(ClusterReshardError: Error, Send, Sync);
+    assert_impl_all!(ReshardProgress: Clone, Debug, Eq, PartialEq, Send, Sync);
+    assert_impl_all!(Reshard<'_>: Debug, Send, Stream);
+
+    fn ready(guilds: &[u64]) -> Event {
+        Event::Ready(Box::new(Ready {
+            application: PartialApplication {
+                flags: ApplicationFlags::empty(),
+                id: Id::new(1),
+            },
+            guilds: guilds
+                .iter()
+                .map(|id| UnavailableGuild {
+                    id: Id::new(*id),
+                    unavailable: true,
+                })
+                .collect(),
+            session_id: "session".to_owned(),
+            shard: Some([0, 2]),
+            user: CurrentUser {
+                accent_color: None,
+                avatar: None,
+                banner: None,
+                bot: true,
+                discriminator: 1,
+                email: None,
+                flags: None,
+                id: Id::new(2),
+                locale: None,
+                mfa_enabled: false,
+                name: "bot".to_owned(),
+                premium_type: None,
+                public_flags: None,
+                verified: None,
+            },
+            version: 10,
+        }))
+    }
+
+    fn guild_delete(id: u64) -> Event {
+        Event::GuildDelete(GuildDelete {
+            id: Id::new(id),
+            unavailable: true,
+        })
+    }
+
+    #[test]
+    fn guild_tracker() {
+        let mut tracker = GuildTracker::new(true);
+        assert!(!tracker.update(&Event::GatewayHeartbeatAck));
+        assert!(!tracker.update(&ready(&[1, 2])));
+        assert!(!tracker.update(&guild_delete(1)));
+        assert!(tracker.update(&guild_delete(2)));
+
+        // A new session resets the pending guilds.
+        assert!(!tracker.update(&ready(&[3])));
+    }
+
+    #[test]
+    fn guild_tracker_without_guilds() {
+        let mut tracker = GuildTracker::new(false);
+        assert!(!tracker.is_complete());
+        assert!(tracker.update(&ready(&[1, 2])));
+    }
+}
@@ -0,0 +1,224 @@
+//! Storage of a cluster's shards that may be changed through a shared
+//! reference.
+
+use crate::shard::Shard;
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+use tokio::sync::OnceCell;
+
+/// Number of segments, which is enough to index every shard that can be
+/// stored.
+const SEGMENTS: usize = usize::BITS as usize;
+
+/// Storage of a cluster's shards.
+///
+/// Shards are stored in segments that are never moved or freed while the
+/// store is alive, so references to shards stay valid after they're replaced
+/// or removed. Replaced and removed shards are expected to be shut down, and
+/// are kept until the store is dropped.
+#[derive(Debug)]
+pub(super) struct ShardStore {
+    /// Index of the managed shards.
+    ///
+    /// The lock is never held across an await point. Poisoning is ignored,
+    /// since the index is never left in an inconsistent state.
+    index: RwLock<Index>,
+    /// Segments of stored shards, the Nth of which holds 2^N shards.
+    segments: Vec<OnceCell<Vec<OnceCell<Shard>>>>,
+}
+
+/// Index of a store's managed shards.
+#[derive(Debug, Default)]
+struct Index {
+    /// Position of each managed shard by its ID.
+    ids: HashMap<u64, usize>,
+    /// Number of shards stored, including those no longer managed.
+    len: usize,
+}
+
+impl ShardStore {
+    /// Create a new store managing the provided shards.
+    pub fn new(shards: impl IntoIterator<Item = (u64, Shard)>) -> Self {
+        let store = Self {
+            index: RwLock::default(),
+            segments: (0..SEGMENTS).map(|_| OnceCell::new()).collect(),
+        };
+
+        store.replace(shards);
+
+        store
+    }
+
+    /// Managed shard with an ID.
+    pub fn get(&self, id: u64) -> Option<&Shard> {
+        let position = *self.read().ids.get(&id)?;
+
+        self.at(position)
+    }
+
+    /// Insert a shard, replacing and returning the managed shard with the same
+    /// ID.
+    pub fn insert(&self, id: u64, shard: Shard) -> Option<&Shard> {
+        let mut index = self.write();
+        let position = self.push(&mut index, shard);
+
+        index
+            .ids
+            .insert(id, position)
+            .and_then(|previous| self.at(previous))
+    }
+
+    /// Insert shards if none of their IDs are managed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first ID that's already managed, in which case no shards
+    /// are inserted.
+    pub fn insert_new(&self, shards: Vec<(u64, Shard)>) -> Result<(), u64> {
+        let mut index = self.write();
+
+        if let Some((id, _)) = shards.iter().find(|(id, _)| index.ids.contains_key(id)) {
+            return Err(*id);
+        }
+
+        for (id, shard) in shards {
+            let position = self.push(&mut index, shard);
+            index.ids.insert(id, position);
+        }
+
+        Ok(())
+    }
+
+    /// Number of managed shards.
+    #[cfg(feature = "metrics")]
+    pub fn len(&self) -> usize {
+        self.read().ids.len()
+    }
+
+    /// Managed shards with their IDs.
+    pub fn managed(&self) -> Vec<(u64, &Shard)> {
+        let positions = self
+            .read()
+            .ids
+            .iter()
+            .map(|(id, position)| (*id, *position))
+            .collect::<Vec<_>>();
+
+        positions
+            .into_iter()
+            .filter_map(|(id, position)| self.at(position).map(|shard| (id, shard)))
+            .collect()
+    }
+
+    /// Stop managing the shard with an ID, returning it.
+    pub fn remove(&self, id: u64) -> Option<&Shard> {
+        let position = self.write().ids.remove(&id)?;
+
+        self.at(position)
+    }
+
+    /// Replace all managed shards, returning the previously managed shards.
+    pub fn replace(&self, shards: impl IntoIterator<Item = (u64, Shard)>) -> Vec<&Shard> {
+        let mut index = self.write();
+        let ids = shards
+            .into_iter()
+            .map(|(id, shard)| (id, self.push(&mut index, shard)))
+            .collect();
+        let previous = std::mem::replace(&mut index.ids, ids);
+
+        previous
+            .into_values()
+            .filter_map(|position| self.at(position))
+            .collect()
+    }
+
+    /// Stored shard at a position.
+    fn at(&self, position: usize) -> Option<&Shard> {
+        let (segment, offset) = locate(position);
+
+        self.segments.get(segment)?.get()?.get(offset)?.get()
+    }
+
+    /// Store a shard, returning its position.
+    fn push(&self, index: &mut Index, shard: Shard) -> usize {
+        let position = index.len;
+        let (segment, offset) = locate(position);
+
+        if let Some(cells) = self.segments.get(segment) {
+            if !cells.initialized() {
+                let _res = cells.set((0..1 << segment).map(|_| OnceCell::new()).collect());
+            }
+
+            // Positions are only stored once, while holding the write lock.
+            if let Some(cell) = cells.get().and_then(|cells| cells.get(offset)) {
+                let _res = cell.set(shard);
+            }
+        }
+
+        index.len += 1;
+
+        position
+    }
+
+    /// Acquire read access to the index.
+    fn read(&self) -> RwLockReadGuard<'_, Index> {
+        self.index.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Acquire write access to the index.
+    fn write(&self) -> RwLockWriteGuard<'_, Index> {
+        self.index.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Segment and offset within it of a position.
+const fn locate(position: usize) -> (usize, usize) {
+    let number = position + 1;
+    let segment = (usize::BITS - 1 - number.leading_zeros()) as usize;
+
+    (segment, number - (1 << segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{locate, ShardStore};
+    use crate::{shard::Shard, Intents};
+
+    fn shard(id: u64) -> (u64, Shard) {
+        let (shard, _events) = Shard::builder("token".to_owned(), Intents::empty())
+            .shard(id, 4)
+            .expect("valid shard ID")
+            .build();
+
+        (id, shard)
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!((0, 0), locate(0));
+        assert_eq!((1, 0), locate(1));
+        assert_eq!((1, 1), locate(2));
+        assert_eq!((2, 0), locate(3));
+        assert_eq!((2, 3), locate(6));
+        assert_eq!((3, 0), locate(7));
+    }
+
+    #[tokio::test]
+    async fn references_outlive_changes() {
+        let store = ShardStore::new([shard(0), shard(1)]);
+        let first = store.get(0).unwrap();
+
+        assert!(store.insert(0, shard(0).1).is_some());
+        assert!(store.remove(1).is_some());
+        assert_eq!(Err(0), store.insert_new(Vec::from([shard(2), shard(0)])));
+        assert!(store.get(2).is_none());
+        assert!(store.insert_new(Vec::from([shard(2), shard(3)])).is_ok());
+        assert_eq!(3, store.replace([shard(1)]).len());
+
+        assert_eq!(0, first.config().shard()[0]);
+        assert_eq!(1, store.managed().len());
+        assert_eq!(1, store.managed()[0].0);
+    }
+}
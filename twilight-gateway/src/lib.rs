@@ -20,6 +20,7 @@
 )]
 
 pub mod cluster;
+pub mod recording;
 pub mod shard;
 
 mod event;
@@ -0,0 +1,871 @@
+//! Recording dispatch payloads received by shards and replaying them.
+//!
+//! A [`Recorder`] writes the raw dispatch payloads of shards to a compact
+//! binary file, along with the ID of the shard that received them and when
+//! they were received. A [`Replay`] reads the recorded payloads back, either
+//! as an iterator of [`Record`]s to deserialize into [`Event`]s, such as to
+//! update a cache with, or as a [shard's] or [cluster's] event stream, which
+//! may be [paced] to replay events with the timing they were received with.
+//!
+//! This allows reproducing bugs and benchmarking event handlers with captured
+//! production traffic.
+//!
+//! Raw payloads are only emitted by shards subscribed to
+//! [`EventTypeFlags::SHARD_PAYLOAD`], which must be opted in to.
+//!
+//! # Examples
+//!
+//! Record the dispatch payloads received by a cluster:
+//!
+//! ```no_run
+//! # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use futures::StreamExt;
+//! use std::{env, fs::File, io::BufWriter};
+//! use twilight_gateway::{recording::Recorder, Cluster, EventTypeFlags, Intents};
+//!
+//! let token = env::var("DISCORD_TOKEN")?;
+//! let (cluster, mut events) = Cluster::builder(token, Intents::GUILDS)
+//!     .event_types(EventTypeFlags::SHARD_PAYLOAD)
+//!     .build()
+//!     .await?;
+//! cluster.up().await;
+//!
+//! let mut recorder = Recorder::new(BufWriter::new(File::create("events.rec")?))?;
+//!
+//! while let Some((shard_id, event)) = events.next().await {
+//!     recorder.record(shard_id, &event)?;
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! Replay the recorded events:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use std::{fs::File, io::BufReader};
+//! use twilight_gateway::recording::Replay;
+//!
+//! let replay = Replay::new(BufReader::new(File::open("events.rec")?))?;
+//!
+//! for record in replay {
+//!     let record = record?;
+//!     let event = record.event()?;
+//!
+//!     println!("shard {} received {:?}", record.shard_id(), event.kind());
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! [cluster's]: crate::cluster::Events
+//! [paced]: Replay::paced
+//! [shard's]: crate::shard::Events
+
+use crate::{
+    cluster::{
+        self,
+        event::{ShardEventsWithId, Update},
+    },
+    shard::{
+        self,
+        channel::{self, EventSender},
+        json, BackpressurePolicy,
+    },
+    EventTypeFlags,
+};
+use futures_util::stream::SelectAll;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error as IoError, ErrorKind, Read, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    runtime::Handle,
+    sync::mpsc,
+    time::{self, Instant},
+};
+use twilight_model::gateway::{
+    event::{Event, GatewayEvent, GatewayEventDeserializer},
+    OpCode,
+};
+
+/// Bytes at the start of a recording, identifying the format and its version.
+const MAGIC: &[u8; 8] = b"TWLGREC\x01";
+
+/// Length of the header of a record: its shard ID, timestamp and length.
+const HEADER_LEN: usize = 20;
+
+/// Maximum length of a recorded payload, in bytes.
+///
+/// This is far larger than any payload sent by Discord, and protects against
+/// allocating memory for the length of a record of a corrupted recording.
+pub const MAX_PAYLOAD_LEN: usize = 1 << 27;
+
+/// Number of replayed events queued for each shard before reading the
+/// recording is paused until they're received.
+const QUEUED_EVENTS: usize = 64;
+
+/// Replaying a recording failed.
+#[derive(Debug)]
+pub struct ReplayError {
+    kind: ReplayErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ReplayError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ReplayErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (ReplayErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            ReplayErrorType::Deserializing => f.write_str("recorded payload is not a valid event"),
+            ReplayErrorType::Format => f.write_str("input is not a valid recording"),
+            ReplayErrorType::Reading => f.write_str("reading the recording failed"),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`ReplayError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReplayErrorType {
+    /// Recorded payload could not be deserialized into an event.
+    ///
+    /// This may be the case for payloads of events unknown to this version
+    /// of Twilight.
+    Deserializing,
+    /// Input is not a recording of a supported version, was truncated, or
+    /// has a record longer than [`MAX_PAYLOAD_LEN`].
+    Format,
+    /// Reading from the input failed.
+    Reading,
+}
+
+/// Recorded dispatch payload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    bytes: Vec<u8>,
+    shard_id: u64,
+    timestamp: u64,
+}
+
+impl Record {
+    /// Create a record of a payload received by a shard.
+    ///
+    /// The timestamp is the number of milliseconds since the Unix epoch.
+    pub const fn new(shard_id: u64, timestamp: u64, bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            shard_id,
+            timestamp,
+        }
+    }
+
+    /// Raw JSON bytes of the payload.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Deserialize the payload into an event.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReplayErrorType::Deserializing`] error type if the payload
+    /// could not be deserialized.
+    pub fn event(&self) -> Result<Event, ReplayError> {
+        let deserializing = |source: Option<Box<dyn Error + Send + Sync>>| ReplayError {
+            kind: ReplayErrorType::Deserializing,
+            source,
+        };
+
+        let text = std::str::from_utf8(&self.bytes)
+            .map_err(|source| deserializing(Some(Box::new(source))))?;
+        let (op, sequence, event_type) = GatewayEventDeserializer::from_json(text)
+            .ok_or_else(|| deserializing(None))?
+            .into_parts();
+        let event_type = event_type.map(ToOwned::to_owned);
+
+        let mut bytes = self.bytes.clone();
+        let event = json::parse_gateway_event(op, sequence, event_type.as_deref(), &mut bytes)
+            .map_err(|source| deserializing(Some(Box::new(source))))?;
+
+        match event {
+            GatewayEvent::Dispatch(_, event) => Ok(Event::from(*event)),
+            _ => Err(deserializing(None)),
+        }
+    }
+
+    /// ID of the shard that received the payload.
+    pub const fn shard_id(&self) -> u64 {
+        self.shard_id
+    }
+
+    /// When the payload was received, in milliseconds since the Unix epoch.
+    pub const fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+/// Writer of dispatch payloads received by shards to a recording.
+///
+/// Writes are not buffered, so wrap writers such as files in a
+/// [`BufWriter`].
+///
+/// Refer to the [module-level] documentation for more information.
+///
+/// [`BufWriter`]: std::io::BufWriter
+/// [module-level]: self
+#[derive(Debug)]
+pub struct Recorder<W> {
+    writer: W,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Create a recorder, writing the start of the recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing failed.
+    pub fn new(mut writer: W) -> Result<Self, IoError> {
+        writer.write_all(MAGIC)?;
+
+        Ok(Self { writer })
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if flushing failed.
+    pub fn flush(&mut self) -> Result<(), IoError> {
+        self.writer.flush()
+    }
+
+    /// Consume the recorder, returning the underlying writer.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Record an event received by a shard, returning whether it was recorded.
+    ///
+    /// Only [`Event::ShardPayload`]s of dispatch payloads are recorded; other
+    /// events are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is longer than [`MAX_PAYLOAD_LEN`] or
+    /// writing failed.
+    pub fn record(&mut self, shard_id: u64, event: &Event) -> Result<bool, IoError> {
+        let bytes = if let Event::ShardPayload(payload) = event {
+            &payload.bytes
+        } else {
+            return Ok(false);
+        };
+
+        let is_dispatch = std::str::from_utf8(bytes)
+            .ok()
+            .and_then(GatewayEventDeserializer::from_json)
+            .map_or(false, |deserializer| {
+                deserializer.op() == OpCode::Event as u8
+            });
+
+        if !is_dispatch {
+            return Ok(false);
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| {
+                u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+            });
+
+        self.write_payload(shard_id, timestamp, bytes)?;
+
+        Ok(true)
+    }
+
+    /// Write a record to the recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is longer than [`MAX_PAYLOAD_LEN`] or
+    /// writing failed.
+    pub fn write(&mut self, record: &Record) -> Result<(), IoError> {
+        self.write_payload(record.shard_id, record.timestamp, &record.bytes)
+    }
+
+    fn write_payload(
+        &mut self,
+        shard_id: u64,
+        timestamp: u64,
+        bytes: &[u8],
+    ) -> Result<(), IoError> {
+        if bytes.len() > MAX_PAYLOAD_LEN {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "payload is too long to be recorded",
+            ));
+        }
+
+        // Maximum payload length fits in a u32.
+        #[allow(clippy::cast_possible_truncation)]
+        let len = bytes.len() as u32;
+
+        let mut header = [0; HEADER_LEN];
+        header[..8].copy_from_slice(&shard_id.to_le_bytes());
+        header[8..16].copy_from_slice(&timestamp.to_le_bytes());
+        header[16..].copy_from_slice(&len.to_le_bytes());
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(bytes)
+    }
+}
+
+/// Reader of a recording, iterating over its records in the order they were
+/// recorded.
+///
+/// The iterator ends after the last record or the first error.
+///
+/// Refer to the [module-level] documentation for more information.
+///
+/// [module-level]: self
+#[derive(Debug)]
+pub struct Replay<R> {
+    finished: bool,
+    paced: bool,
+    reader: R,
+}
+
+impl<R: Read> Replay<R> {
+    /// Create a replay of a recording, reading the start of the recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReplayErrorType::Format`] error type if the input is not
+    /// a recording.
+    ///
+    /// Returns a [`ReplayErrorType::Reading`] error type if reading failed.
+    pub fn new(mut reader: R) -> Result<Self, ReplayError> {
+        let mut magic = [0; MAGIC.len()];
+
+        if fill(&mut reader, &mut magic)? != MAGIC.len() || &magic != MAGIC {
+            return Err(ReplayError {
+                kind: ReplayErrorType::Format,
+                source: None,
+            });
+        }
+
+        Ok(Self {
+            finished: false,
+            paced: false,
+            reader,
+        })
+    }
+
+    /// Set whether event streams replay events with the timing they were
+    /// recorded with, instead of as fast as they're received.
+    ///
+    /// The first event is replayed immediately, and each following event
+    /// after the time between it and the first event was recorded.
+    ///
+    /// Defaults to false.
+    #[allow(clippy::missing_const_for_fn)]
+    #[must_use = "has no effect if the replay is not used"]
+    pub fn paced(mut self, paced: bool) -> Self {
+        self.paced = paced;
+
+        self
+    }
+
+    /// Read the next record, if there is one.
+    fn read_record(&mut self) -> Result<Option<Record>, ReplayError> {
+        let mut header = [0; HEADER_LEN];
+
+        match fill(&mut self.reader, &mut header)? {
+            0 => return Ok(None),
+            HEADER_LEN => {}
+            _ => return Err(truncated()),
+        }
+
+        let mut shard_id = [0; 8];
+        shard_id.copy_from_slice(&header[..8]);
+        let mut timestamp = [0; 8];
+        timestamp.copy_from_slice(&header[8..16]);
+        let mut len = [0; 4];
+        len.copy_from_slice(&header[16..]);
+
+        let len = u32::from_le_bytes(len) as usize;
+
+        if len > MAX_PAYLOAD_LEN {
+            return Err(ReplayError {
+                kind: ReplayErrorType::Format,
+                source: None,
+            });
+        }
+
+        // The buffer grows as the payload is read, so that a truncated
+        // recording doesn't allocate the full length.
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut bytes)
+            .map_err(|source| ReplayError {
+                kind: ReplayErrorType::Reading,
+                source: Some(Box::new(source)),
+            })?;
+
+        if bytes.len() != len {
+            return Err(truncated());
+        }
+
+        Ok(Some(Record {
+            bytes,
+            shard_id: u64::from_le_bytes(shard_id),
+            timestamp: u64::from_le_bytes(timestamp),
+        }))
+    }
+}
+
+impl<R: Read + Send + 'static> Replay<R> {
+    /// Replay the recording as a cluster's stream of events.
+    ///
+    /// The events of each shard are yielded in the order they were recorded.
+    /// Payloads of events unknown to this version of Twilight are skipped.
+    ///
+    /// The recording is read in a blocking task as events are received, and
+    /// reading stops once the stream is dropped. The stream ends early if
+    /// reading a later record fails, which is logged.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReplayErrorType::Format`] error type if the first record
+    /// was truncated.
+    ///
+    /// Returns a [`ReplayErrorType::Reading`] error type if reading the first
+    /// record failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn into_cluster_events(mut self) -> Result<cluster::Events, ReplayError> {
+        // The stream ends once it has no shards, so the first record's shard
+        // is added before the stream is returned. Its stream stays open until
+        // the recording has been read, during which other shards are added.
+        let first = self.next().transpose()?;
+        let mut senders = HashMap::new();
+        let mut stream = SelectAll::new();
+
+        if let Some(record) = &first {
+            let (tx, events) = replayed_shard(record.shard_id);
+            senders.insert(record.shard_id, tx);
+            stream.push(events);
+        }
+
+        let (updates_tx, updates) = mpsc::unbounded_channel();
+        let mut feeder = Feeder::new(self.paced);
+
+        tokio::task::spawn_blocking(move || {
+            for record in first.into_iter().map(Ok).chain(self) {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(source) => {
+                        tracing::warn!("failed to read replayed record: {source}");
+
+                        break;
+                    }
+                };
+
+                if updates_tx.is_closed() {
+                    break;
+                }
+
+                let tx = senders.entry(record.shard_id).or_insert_with(|| {
+                    let (tx, events) = replayed_shard(record.shard_id);
+                    let _res = updates_tx.send(Update::Insert(events));
+
+                    tx
+                });
+
+                feeder.send(tx, &record);
+            }
+        });
+
+        Ok(cluster::Events::new(stream, updates))
+    }
+
+    /// Replay the recording as a shard's stream of events.
+    ///
+    /// Events of all recorded shards are yielded in the order they were
+    /// recorded. Payloads of events unknown to this version of Twilight are
+    /// skipped.
+    ///
+    /// The recording is read in a blocking task as events are received, and
+    /// reading stops once the stream is dropped. The stream ends early if
+    /// reading a record fails, which is logged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn into_events(self) -> shard::Events {
+        let (tx, rx) = replay_channel(0);
+        let mut feeder = Feeder::new(self.paced);
+
+        tokio::task::spawn_blocking(move || {
+            for record in self {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(source) => {
+                        tracing::warn!("failed to read replayed record: {source}");
+
+                        break;
+                    }
+                };
+
+                if tx.is_closed() {
+                    break;
+                }
+
+                feeder.send(&tx, &record);
+            }
+        });
+
+        shard::Events::new(EventTypeFlags::all(), rx)
+    }
+}
+
+impl<R: Read> Iterator for Replay<R> {
+    type Item = Result<Record, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.read_record().transpose();
+        self.finished = !matches!(result, Some(Ok(_)));
+
+        result
+    }
+}
+
+/// Sender of replayed events to streams, optionally pacing them with the
+/// timing they were recorded with.
+struct Feeder {
+    /// Runtime to wait for the streams on.
+    handle: Handle,
+    paced: bool,
+    /// When the first event was replayed and its record's timestamp.
+    start: Option<(Instant, u64)>,
+}
+
+impl Feeder {
+    /// Create a new sender of replayed events in the current runtime.
+    fn new(paced: bool) -> Self {
+        Self {
+            handle: Handle::current(),
+            paced,
+            start: None,
+        }
+    }
+
+    /// Send the event of a record once it's due and the stream has room for
+    /// it, skipping it if it is unknown.
+    fn send(&mut self, tx: &EventSender, record: &Record) {
+        let event = if let Some(event) = replayed_event(record) {
+            event
+        } else {
+            return;
+        };
+
+        if self.paced {
+            let (start, first) = *self
+                .start
+                .get_or_insert_with(|| (Instant::now(), record.timestamp));
+            let offset = Duration::from_millis(record.timestamp.saturating_sub(first));
+
+            self.handle.block_on(time::sleep_until(start + offset));
+        }
+
+        self.handle.block_on(tx.wait_for_capacity());
+        tx.send(event);
+    }
+}
+
+/// Create a channel of a shard's replayed events.
+fn replay_channel(shard_id: u64) -> (EventSender, channel::EventReceiver) {
+    channel::channel(shard_id, Some((QUEUED_EVENTS, BackpressurePolicy::Block)))
+}
+
+/// Create a shard's stream of replayed events for a cluster.
+fn replayed_shard(shard_id: u64) -> (EventSender, ShardEventsWithId) {
+    let (tx, rx) = replay_channel(shard_id);
+    let events = shard::Events::new(EventTypeFlags::all(), rx);
+
+    (
+        tx,
+        ShardEventsWithId::new(shard_id, events, EventTypeFlags::all()),
+    )
+}
+
+/// Read into the buffer until it is full or the input ended, returning the
+/// number of bytes read.
+fn fill(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, ReplayError> {
+    let mut read = 0;
+
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(source) if source.kind() == ErrorKind::Interrupted => {}
+            Err(source) => {
+                return Err(ReplayError {
+                    kind: ReplayErrorType::Reading,
+                    source: Some(Box::new(source)),
+                })
+            }
+        }
+    }
+
+    Ok(read)
+}
+
+/// Deserialize the event of a record to be replayed, skipping it if it is
+/// unknown.
+fn replayed_event(record: &Record) -> Option<Event> {
+    match record.event() {
+        Ok(event) => Some(event),
+        Err(source) => {
+            tracing::debug!(
+                shard_id = record.shard_id,
+                "skipping recorded payload: {source}"
+            );
+
+            None
+        }
+    }
+}
+
+const fn truncated() -> ReplayError {
+    ReplayError {
+        kind: ReplayErrorType::Format,
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Record, Recorder, Replay, ReplayError, ReplayErrorType, HEADER_LEN, MAGIC, MAX_PAYLOAD_LEN,
+    };
+    use futures_util::StreamExt;
+    use static_assertions::assert_impl_all;
+    use std::{
+        error::Error,
+        fmt::Debug,
+        io::{Cursor, Error as IoError, ErrorKind, Read},
+        time::{Duration, Instant},
+    };
+    use twilight_model::{
+        gateway::event::{shard::Payload, Event},
+        id::Id,
+    };
+
+    assert_impl_all!(ReplayErrorType: Debug, Send, Sync);
+    assert_impl_all!(ReplayError: Error, Send, Sync);
+    assert_impl_all!(Record: Clone, Debug, Eq, PartialEq, Send, Sync);
+    assert_impl_all!(Recorder<Vec<u8>>: Debug, Send, Sync);
+    assert_impl_all!(Replay<&[u8]>: Debug, Iterator, Send, Sync);
+
+    fn payload(json: &str) -> Event {
+        Event::ShardPayload(Payload {
+            bytes: json.as_bytes().to_vec(),
+        })
+    }
+
+    fn guild_delete(id: u64) -> Event {
+        payload(&format!(
+            r#"{{"op":0,"s":1,"t":"GUILD_DELETE","d":{{"id":"{id}","unavailable":true}}}}"#
+        ))
+    }
+
+    fn recording() -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut recorder = Recorder::new(Vec::new())?;
+        assert!(recorder.record(0, &guild_delete(1))?);
+        assert!(!recorder.record(0, &payload(r#"{"op":11,"d":null}"#))?);
+        assert!(!recorder.record(0, &Event::GatewayHeartbeatAck)?);
+        assert!(recorder.record(1, &payload(r#"{"op":0,"s":2,"t":"UNKNOWN","d":{}}"#))?);
+        assert!(recorder.record(1, &guild_delete(2))?);
+
+        Ok(recorder.into_inner())
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), Box<dyn Error>> {
+        let bytes = recording()?;
+        let records = Replay::new(bytes.as_slice())?.collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(3, records.len());
+        assert_eq!(0, records[0].shard_id());
+        assert_eq!(1, records[2].shard_id());
+        assert!(records[0].timestamp() > 0);
+        assert!(matches!(
+            records[0].event()?,
+            Event::GuildDelete(guild) if guild.id == Id::new(1)
+        ));
+        assert!(matches!(
+            records[1].event().unwrap_err().kind(),
+            ReplayErrorType::Deserializing
+        ));
+
+        let mut rewritten = Recorder::new(Vec::new())?;
+        for record in &records {
+            rewritten.write(record)?;
+        }
+        assert_eq!(bytes, rewritten.into_inner());
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() -> Result<(), Box<dyn Error>> {
+        assert!(matches!(
+            Replay::new(b"not a recording".as_slice())
+                .unwrap_err()
+                .kind(),
+            ReplayErrorType::Format
+        ));
+        assert!(Replay::new(MAGIC.as_slice())?.next().is_none());
+
+        let bytes = recording()?;
+        let mut replay = Replay::new(&bytes[..bytes.len() - 1])?;
+        assert!(replay.next().transpose()?.is_some());
+        assert!(replay.next().transpose()?.is_some());
+        assert!(matches!(
+            replay.next().transpose().unwrap_err().kind(),
+            ReplayErrorType::Format
+        ));
+        assert!(replay.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn too_long() -> Result<(), Box<dyn Error>> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[0; HEADER_LEN - 4]);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut replay = Replay::new(bytes.as_slice())?;
+        assert!(matches!(
+            replay.next().transpose().unwrap_err().kind(),
+            ReplayErrorType::Format
+        ));
+
+        let record = Record::new(0, 0, vec![0; MAX_PAYLOAD_LEN + 1]);
+        let error = Recorder::new(Vec::new())?.write(&record).unwrap_err();
+        assert_eq!(ErrorKind::InvalidInput, error.kind());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn events() -> Result<(), Box<dyn Error>> {
+        let bytes = recording()?;
+
+        let events = Replay::new(Cursor::new(bytes.clone()))?
+            .into_events()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(2, events.len());
+
+        let mut events = Replay::new(Cursor::new(bytes))?
+            .into_cluster_events()?
+            .map(|(shard_id, _)| shard_id)
+            .collect::<Vec<_>>()
+            .await;
+        events.sort_unstable();
+        assert_eq!(vec![0, 1], events);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn events_read_lazily() -> Result<(), Box<dyn Error>> {
+        let bytes = recording()?;
+        let first = Replay::new(bytes.as_slice())?.next().unwrap()?;
+        let len = MAGIC.len() + HEADER_LEN + first.bytes().len();
+
+        // Reading fails once the first record has been read, which ends the
+        // stream after its event instead of failing to create it.
+        let reader = Failing(Cursor::new(bytes[..len].to_vec()));
+        let events = Replay::new(reader)?.into_events().collect::<Vec<_>>().await;
+        assert_eq!(1, events.len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn events_paced() -> Result<(), Box<dyn Error>> {
+        let bytes = recording()?;
+        let records = Replay::new(bytes.as_slice())?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut recorder = Recorder::new(Vec::new())?;
+        for (record, timestamp) in records.iter().zip([1000, 1000, 1200]) {
+            recorder.write(&Record::new(
+                record.shard_id(),
+                timestamp,
+                record.bytes().to_vec(),
+            ))?;
+        }
+
+        let start = Instant::now();
+        let events = Replay::new(Cursor::new(recorder.into_inner()))?
+            .paced(true)
+            .into_events()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(2, events.len());
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        Ok(())
+    }
+
+    /// Reader failing once its inner reader has been exhausted.
+    struct Failing(Cursor<Vec<u8>>);
+
+    impl Read for Failing {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+            match self.0.read(buf)? {
+                0 => Err(IoError::new(ErrorKind::Other, "failed")),
+                read => Ok(read),
+            }
+        }
+    }
+}
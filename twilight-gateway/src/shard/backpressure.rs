@@ -0,0 +1,56 @@
+//! Policies for bounding a shard's stream of events.
+
+use crate::EventTypeFlags;
+
+/// Action a shard takes when its bounded event stream is full.
+///
+/// Event streams are unbounded by default, so a slow consumer of a shard's
+/// [`Events`] lets memory grow without bound. Once bounded via
+/// [`ShardBuilder::bounded_events`] the shard applies this policy when the
+/// number of queued events reaches the capacity.
+///
+/// [`Events`]: super::Events
+/// [`ShardBuilder::bounded_events`]: super::ShardBuilder::bounded_events
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BackpressurePolicy {
+    /// Stop reading from the websocket until the consumer catches up.
+    ///
+    /// Only reading is stalled: heartbeats and other outgoing messages are
+    /// still sent while the shard is blocked, so the connection stays open.
+    /// Messages sent by the gateway in the meantime, including heartbeat
+    /// acknowledgements, are read once the consumer catches up.
+    Block,
+    /// Drop the oldest queued event with one of the provided, low priority,
+    /// event types, such as [`EventTypeFlags::TYPING_START`] and
+    /// [`EventTypeFlags::PRESENCE_UPDATE`].
+    ///
+    /// If no queued event is of a low priority type then the shard blocks as
+    /// with [`Block`].
+    ///
+    /// [`Block`]: Self::Block
+    DropOldest(EventTypeFlags),
+    /// Disconnect from the gateway until the consumer catches up, and then
+    /// resume the session.
+    ///
+    /// The gateway replays missed events upon resuming, so no events are lost
+    /// as long as the session is still resumable.
+    Reconnect,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackpressurePolicy;
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(
+        BackpressurePolicy: Clone,
+        Copy,
+        Debug,
+        Eq,
+        PartialEq,
+        Send,
+        Sync
+    );
+}
@@ -1,4 +1,4 @@
-use super::{Config, Events, Shard};
+use super::{BackpressurePolicy, Config, Events, Shard};
 use crate::EventTypeFlags;
 use std::{
     borrow::Cow,
@@ -98,6 +98,7 @@ pub enum ShardIdErrorType {
 #[derive(Debug)]
 #[must_use = "has no effect if not built"]
 pub struct ShardBuilder {
+    bounded_events: Option<(usize, BackpressurePolicy)>,
     event_types: EventTypeFlags,
     pub(crate) gateway_url: Option<String>,
     identify_properties: Option<IdentifyProperties>,
@@ -120,6 +121,7 @@ impl ShardBuilder {
         }
 
         Self {
+            bounded_events: None,
             event_types: EventTypeFlags::default(),
             gateway_url: None,
             identify_properties: None,
@@ -135,6 +137,7 @@ impl ShardBuilder {
 
     pub(crate) fn into_config(self) -> Config {
         Config {
+            bounded_events: self.bounded_events,
             event_types: self.event_types,
             gateway_url: match self.gateway_url {
                 Some(s) => Cow::Owned(s),
@@ -164,6 +167,49 @@ impl ShardBuilder {
         Shard::new_with_config(self.into_config())
     }
 
+    /// Bound the number of events queued in the shard's [`Events`] stream.
+    ///
+    /// Once `capacity` events are queued and not yet received the policy is
+    /// applied. Refer to [`BackpressurePolicy`] for the available policies.
+    ///
+    /// The number of queued events is available via [`Shard::queued_events`],
+    /// and is additionally reported as a gauge if the `metrics` feature is
+    /// enabled.
+    ///
+    /// The default is for the stream to be unbounded.
+    ///
+    /// # Examples
+    ///
+    /// Drop typing and presence update events if the consumer falls more than
+    /// 10000 events behind:
+    ///
+    /// ```no_run
+    /// use std::env;
+    /// use twilight_gateway::{shard::BackpressurePolicy, EventTypeFlags, Intents, Shard};
+    ///
+    /// let token = env::var("DISCORD_TOKEN")?;
+    /// let low_priority = EventTypeFlags::PRESENCE_UPDATE | EventTypeFlags::TYPING_START;
+    ///
+    /// let shard = Shard::builder(token, Intents::GUILDS)
+    ///     .bounded_events(10_000, BackpressurePolicy::DropOldest(low_priority))
+    ///     .build();
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided capacity is 0.
+    ///
+    /// [`Shard::queued_events`]: super::Shard::queued_events
+    #[track_caller]
+    pub fn bounded_events(mut self, capacity: usize, policy: BackpressurePolicy) -> Self {
+        assert!(capacity > 0, "event capacity must be greater than 0");
+
+        self.bounded_events = Some((capacity, policy));
+
+        self
+    }
+
     /// Set the event types to process.
     ///
     /// This is an optimization technique; all events not included in the
@@ -0,0 +1,370 @@
+//! Channel of events from a shard's processor to its [`Events`] stream.
+//!
+//! Unlike Tokio's channels this allows dropping queued events and inspecting
+//! the number of queued events, which are needed to apply a
+//! [`BackpressurePolicy`].
+//!
+//! [`Events`]: super::Events
+
+use super::BackpressurePolicy;
+use crate::EventTypeFlags;
+use futures_util::task::AtomicWaker;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::Notify;
+use twilight_model::gateway::event::Event;
+
+/// Create a new channel, bounded if a capacity and policy are provided.
+pub fn channel(
+    shard_id: u64,
+    bound: Option<(usize, BackpressurePolicy)>,
+) -> (EventSender, EventReceiver) {
+    let shared = Arc::new(Shared {
+        bound,
+        receiver: AtomicWaker::new(),
+        shard_id,
+        space: Notify::new(),
+        state: Mutex::new(State {
+            events: VecDeque::new(),
+            receiver_alive: true,
+            senders: 1,
+        }),
+    });
+
+    (EventSender(Arc::clone(&shared)), EventReceiver(shared))
+}
+
+struct State {
+    events: VecDeque<Event>,
+    receiver_alive: bool,
+    senders: usize,
+}
+
+struct Shared {
+    bound: Option<(usize, BackpressurePolicy)>,
+    /// Waker of the receiver waiting for an event.
+    receiver: AtomicWaker,
+    shard_id: u64,
+    /// Notified when an event has been received from a bounded channel.
+    space: Notify,
+    state: Mutex<State>,
+}
+
+impl Shared {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("event queue poisoned")
+    }
+
+    /// Record the depth of the queue.
+    #[cfg(feature = "metrics")]
+    #[allow(clippy::cast_precision_loss)]
+    fn depth_metrics(&self, depth: usize) {
+        metrics::gauge!(format!("Event-Queue-Depth-{}", self.shard_id), depth as f64);
+    }
+}
+
+/// Handle to inspect the queue of a channel without keeping it open.
+#[derive(Clone)]
+pub struct EventQueue(Arc<Shared>);
+
+impl EventQueue {
+    /// Number of events queued in the channel.
+    pub fn len(&self) -> usize {
+        self.0.state().events.len()
+    }
+}
+
+impl Debug for EventQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("EventQueue")
+            .field("bound", &self.0.bound)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Sending half of a channel.
+///
+/// The channel is closed once all senders have been dropped.
+pub struct EventSender(Arc<Shared>);
+
+impl EventSender {
+    /// Create a handle to inspect the queue of the channel.
+    pub fn queue(&self) -> EventQueue {
+        EventQueue(Arc::clone(&self.0))
+    }
+
+    /// Queue an event, applying the [`BackpressurePolicy::DropOldest`] policy
+    /// if the channel is over capacity.
+    ///
+    /// The event is discarded if the receiver has been dropped.
+    pub fn send(&self, event: Event) {
+        let mut state = self.0.state();
+
+        if !state.receiver_alive {
+            return;
+        }
+
+        state.events.push_back(event);
+
+        if let Some((capacity, BackpressurePolicy::DropOldest(event_types))) = self.0.bound {
+            if state.events.len() > capacity {
+                let maybe_index = state
+                    .events
+                    .iter()
+                    .position(|event| event_types.contains(EventTypeFlags::from(event.kind())));
+
+                if let Some(event) = maybe_index.and_then(|index| state.events.remove(index)) {
+                    tracing::debug!(
+                        shard_id = self.0.shard_id,
+                        "event queue full, dropped {:?} event",
+                        event.kind(),
+                    );
+
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!(format!("Event-Queue-Dropped-{}", self.0.shard_id), 1);
+                }
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        self.0.depth_metrics(state.events.len());
+
+        drop(state);
+
+        self.0.receiver.wake();
+    }
+
+    /// Whether the receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        !self.0.state().receiver_alive
+    }
+
+    /// Whether the channel is bounded and has reached its capacity.
+    ///
+    /// Returns false if the receiver has been dropped.
+    pub fn is_full(&self) -> bool {
+        self.0.bound.map_or(false, |(capacity, _)| {
+            let state = self.0.state();
+
+            state.receiver_alive && state.events.len() >= capacity
+        })
+    }
+
+    /// Wait until the channel has room for another event or the receiver has
+    /// been dropped.
+    pub async fn wait_for_capacity(&self) {
+        loop {
+            // Created before checking so that notifications sent in between
+            // aren't missed.
+            let notified = self.0.space.notified();
+
+            if !self.is_full() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+impl Clone for EventSender {
+    fn clone(&self) -> Self {
+        self.0.state().senders += 1;
+
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl Debug for EventSender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("EventSender")
+            .field("bound", &self.0.bound)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for EventSender {
+    fn drop(&mut self) {
+        let mut state = self.0.state();
+        state.senders -= 1;
+        let closed = state.senders == 0;
+        drop(state);
+
+        if closed {
+            self.0.receiver.wake();
+        }
+    }
+}
+
+/// Receiving half of a channel.
+pub struct EventReceiver(Arc<Shared>);
+
+impl EventReceiver {
+    /// Poll to receive the next event, returning [`None`] once the channel is
+    /// closed and all queued events have been received.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        self.0.receiver.register(cx.waker());
+
+        let mut state = self.0.state();
+
+        if let Some(event) = state.events.pop_front() {
+            let has_space = self
+                .0
+                .bound
+                .map_or(false, |(capacity, _)| state.events.len() < capacity);
+
+            #[cfg(feature = "metrics")]
+            self.0.depth_metrics(state.events.len());
+
+            drop(state);
+
+            if has_space {
+                self.0.space.notify_waiters();
+            }
+
+            return Poll::Ready(Some(event));
+        }
+
+        if state.senders == 0 {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Debug for EventReceiver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("EventReceiver")
+            .field("bound", &self.0.bound)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        let mut state = self.0.state();
+        state.receiver_alive = false;
+        state.events.clear();
+        drop(state);
+
+        // Unblock a sender waiting for capacity.
+        self.0.space.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{channel, EventQueue, EventReceiver, EventSender};
+    use crate::{shard::BackpressurePolicy, EventTypeFlags};
+    use futures::{future::FutureExt, task::noop_waker_ref};
+    use static_assertions::assert_impl_all;
+    use std::{
+        fmt::Debug,
+        task::{Context, Poll},
+    };
+    use twilight_model::gateway::event::{Event, EventType};
+
+    assert_impl_all!(EventQueue: Clone, Debug, Send, Sync);
+    assert_impl_all!(EventReceiver: Debug, Send, Sync);
+    assert_impl_all!(EventSender: Clone, Debug, Send, Sync);
+
+    fn try_recv(rx: &mut EventReceiver) -> Poll<Option<Event>> {
+        rx.poll_recv(&mut Context::from_waker(noop_waker_ref()))
+    }
+
+    #[test]
+    fn unbounded() {
+        let (tx, mut rx) = channel(0, None);
+        let queue = tx.queue();
+
+        for _ in 0..100 {
+            tx.send(Event::GatewayReconnect);
+        }
+
+        assert!(!tx.is_full());
+        assert_eq!(100, queue.len());
+        assert!(matches!(
+            try_recv(&mut rx),
+            Poll::Ready(Some(Event::GatewayReconnect))
+        ));
+        assert_eq!(99, queue.len());
+    }
+
+    #[test]
+    fn closes_with_senders() {
+        let (tx, mut rx) = channel(0, None);
+        let tx2 = tx.clone();
+
+        tx.send(Event::GatewayReconnect);
+        drop(tx);
+        assert!(matches!(try_recv(&mut rx), Poll::Ready(Some(_))));
+        assert!(try_recv(&mut rx).is_pending());
+
+        drop(tx2);
+        assert!(matches!(try_recv(&mut rx), Poll::Ready(None)));
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let (tx, mut rx) = channel(
+            0,
+            Some((
+                2,
+                BackpressurePolicy::DropOldest(EventTypeFlags::GATEWAY_RECONNECT),
+            )),
+        );
+
+        tx.send(Event::GatewayHeartbeatAck);
+        tx.send(Event::GatewayReconnect);
+        assert!(tx.is_full());
+
+        // The reconnect event is dropped in favor of the new event.
+        tx.send(Event::GatewayHeartbeat(1));
+        assert_eq!(2, tx.queue().len());
+
+        // No event is of a droppable type, so the queue grows past capacity.
+        tx.send(Event::GatewayHeartbeat(2));
+        assert_eq!(3, tx.queue().len());
+
+        let kinds = [
+            EventType::GatewayHeartbeatAck,
+            EventType::GatewayHeartbeat,
+            EventType::GatewayHeartbeat,
+        ];
+
+        for kind in kinds {
+            assert_eq!(
+                Poll::Ready(kind),
+                try_recv(&mut rx).map(|event| event.unwrap().kind())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn wait_for_capacity() {
+        let (tx, mut rx) = channel(0, Some((1, BackpressurePolicy::Block)));
+        tx.send(Event::GatewayReconnect);
+        assert!(tx.is_full());
+
+        let mut wait = Box::pin(tx.wait_for_capacity());
+        assert!((&mut wait).now_or_never().is_none());
+
+        assert!(try_recv(&mut rx).is_ready());
+        assert!(wait.now_or_never().is_some());
+
+        // Dropping the receiver unblocks the sender.
+        tx.send(Event::GatewayReconnect);
+        let wait = tx.wait_for_capacity();
+        assert!(!tx.is_closed());
+        drop(rx);
+        assert!(wait.now_or_never().is_some());
+        assert!(!tx.is_full());
+        assert!(tx.is_closed());
+    }
+}
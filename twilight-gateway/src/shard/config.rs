@@ -1,3 +1,4 @@
+use super::BackpressurePolicy;
 use crate::EventTypeFlags;
 use std::{borrow::Cow, sync::Arc};
 use twilight_gateway_queue::Queue;
@@ -21,7 +22,8 @@ use super::tls::TlsContainer;
 /// [`Shard::builder`]: super::Shard::builder
 #[derive(Clone, Debug)]
 pub struct Config {
-    pub(super) event_types: EventTypeFlags,
+    pub(super) bounded_events: Option<(usize, BackpressurePolicy)>,
+    pub(crate) event_types: EventTypeFlags,
     pub(super) gateway_url: Cow<'static, str>,
     pub(super) identify_properties: Option<IdentifyProperties>,
     pub(super) intents: Intents,
@@ -42,6 +44,12 @@ pub struct Config {
 }
 
 impl Config {
+    /// Capacity of the shard's event stream and the policy applied once it's
+    /// full, if it's bounded.
+    pub const fn bounded_events(&self) -> Option<(usize, BackpressurePolicy)> {
+        self.bounded_events
+    }
+
     /// Copy of the event type flags.
     pub const fn event_types(&self) -> EventTypeFlags {
         self.event_types
@@ -1,10 +1,14 @@
-use super::json;
+use super::{
+    channel::{self, EventReceiver, EventSender},
+    json,
+    member_chunks::Collectors,
+    BackpressurePolicy,
+};
 use crate::{Event, EventTypeFlags};
 use std::{
     error::Error,
     fmt::{Debug, Display, Formatter, Result as FmtResult},
 };
-use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use twilight_model::gateway::event::shard::Payload;
 
 #[derive(Debug)]
@@ -63,16 +67,51 @@ pub enum EmitJsonErrorType {
 /// operations.
 #[derive(Clone, Debug)]
 pub struct Emitter {
+    collectors: Collectors,
     event_types: EventTypeFlags,
-    tx: UnboundedSender<Event>,
+    tx: EventSender,
 }
 
 impl Emitter {
     /// Create a new emitter for events and bytes.
-    pub fn new(event_types: EventTypeFlags) -> (Self, UnboundedReceiver<Event>) {
-        let (tx, rx) = mpsc::unbounded_channel();
+    ///
+    /// The channel of events is bounded if a capacity and policy are
+    /// provided.
+    pub fn new(
+        event_types: EventTypeFlags,
+        shard_id: u64,
+        bound: Option<(usize, BackpressurePolicy)>,
+    ) -> (Self, EventReceiver) {
+        let (tx, rx) = channel::channel(shard_id, bound);
+
+        (
+            Self {
+                collectors: Collectors::default(),
+                event_types,
+                tx,
+            },
+            rx,
+        )
+    }
+
+    /// Collectors of member chunks, which receive chunks before the listener.
+    pub fn collectors(&self) -> Collectors {
+        self.collectors.clone()
+    }
+
+    /// Whether the channel of events is bounded and has reached its capacity.
+    pub fn is_full(&self) -> bool {
+        self.tx.is_full()
+    }
 
-        (Self { event_types, tx }, rx)
+    /// Create a handle to inspect the queue of events.
+    pub fn queue(&self) -> channel::EventQueue {
+        self.tx.queue()
+    }
+
+    /// Wait until the channel of events has room for another event.
+    pub async fn wait_for_capacity(&self) {
+        self.tx.wait_for_capacity().await;
     }
 
     /// Whether the configured event types include an individual event type.
@@ -135,7 +174,9 @@ impl Emitter {
             }
         })?;
 
-        if self.wants(flag) {
+        let collecting = flag == EventTypeFlags::MEMBER_CHUNK && self.collectors.is_collecting();
+
+        if self.wants(flag) || collecting {
             let gateway_event =
                 json::parse_gateway_event(op, seq, event_type, json).map_err(|source| {
                     EmitJsonError {
@@ -143,14 +184,25 @@ impl Emitter {
                         source: Some(Box::new(source)),
                     }
                 })?;
-            self.send(Event::from(gateway_event));
+
+            let event = match Event::from(gateway_event) {
+                Event::MemberChunk(chunk) if collecting => self
+                    .collectors
+                    .collect(chunk, self.wants(flag))
+                    .map(Event::MemberChunk),
+                event => Some(event),
+            };
+
+            if let Some(event) = event.filter(|_| self.wants(flag)) {
+                self.send(event);
+            }
         }
 
         Ok(())
     }
 
     fn send(&self, event: Event) {
-        let _res = self.tx.send(event);
+        self.tx.send(event);
     }
 }
 
@@ -158,24 +210,33 @@ impl Emitter {
 mod tests {
     use super::Emitter;
     use crate::{Event, EventTypeFlags};
+    use futures::{future::poll_fn, FutureExt};
+    use std::task::Poll;
+
+    fn try_recv(rx: &mut super::EventReceiver) -> bool {
+        matches!(
+            poll_fn(|cx| Poll::Ready(rx.poll_recv(cx))).now_or_never(),
+            Some(Poll::Ready(Some(_)))
+        )
+    }
 
     #[test]
     fn bytes_send() {
-        let (emitter, mut rx) = Emitter::new(EventTypeFlags::SHARD_PAYLOAD);
+        let (emitter, mut rx) = Emitter::new(EventTypeFlags::SHARD_PAYLOAD, 0, None);
         emitter.bytes(&[1]);
 
-        assert!(rx.try_recv().is_ok());
-        assert!(rx.try_recv().is_err());
+        assert!(try_recv(&mut rx));
+        assert!(!try_recv(&mut rx));
     }
 
     #[test]
     fn event_sends_to_rx() {
-        let (emitter, mut rx) = Emitter::new(EventTypeFlags::default());
+        let (emitter, mut rx) = Emitter::new(EventTypeFlags::default(), 0, None);
         emitter.event(Event::GatewayReconnect);
 
-        assert!(rx.try_recv().is_ok());
+        assert!(try_recv(&mut rx));
 
         // now check that the event didn't send the event twice
-        assert!(rx.try_recv().is_err());
+        assert!(!try_recv(&mut rx));
     }
 }
@@ -12,13 +12,13 @@
 //! [`EventType`]: ::twilight_model::gateway::event::EventType
 //! [`ShardBuilder::event_types`]: crate::shard::ShardBuilder::event_types
 
+use super::channel::EventReceiver;
 use crate::EventTypeFlags;
 use futures_util::stream::Stream;
 use std::{
     pin::Pin,
     task::{Context, Poll},
 };
-use tokio::sync::mpsc::UnboundedReceiver;
 use twilight_model::gateway::event::Event;
 
 /// A stream of events from a [`Shard`].
@@ -29,21 +29,25 @@ use twilight_model::gateway::event::Event;
 ///
 /// This implements [`futures::stream::Stream`].
 ///
+/// The stream is unbounded unless configured otherwise via
+/// [`ShardBuilder::bounded_events`].
+///
 /// # Examples
 ///
 /// Refer to [`Shard`] for an example of how to use this.
 ///
 /// [`Events::event_types`]: Self::event_types
+/// [`ShardBuilder::bounded_events`]: super::ShardBuilder::bounded_events
 /// [`Shard`]: super::Shard
 /// [`futures::stream::Stream`]: https://docs.rs/futures/*/futures/stream/trait.Stream.html
 #[derive(Debug)]
 pub struct Events {
     event_types: EventTypeFlags,
-    rx: UnboundedReceiver<Event>,
+    rx: EventReceiver,
 }
 
 impl Events {
-    pub(super) const fn new(event_types: EventTypeFlags, rx: UnboundedReceiver<Event>) -> Self {
+    pub(crate) const fn new(event_types: EventTypeFlags, rx: EventReceiver) -> Self {
         Self { event_types, rx }
     }
 
@@ -1,10 +1,12 @@
 use super::{
     builder::ShardBuilder,
+    channel::EventQueue,
     command::Command,
     config::Config,
     emitter::Emitter,
     event::Events,
     json,
+    member_chunks::{Collectors, MemberChunks},
     processor::{ConnectingErrorType, Latency, Session, ShardProcessor},
     raw_message::Message,
     stage::Stage,
@@ -16,7 +18,7 @@ use std::{
     borrow::Cow,
     error::Error,
     fmt::{Display, Formatter, Result as FmtResult},
-    sync::{atomic::Ordering, Arc, Mutex},
+    sync::{atomic::Ordering, Arc, Mutex, PoisonError},
 };
 use tokio::{
     sync::{watch::Receiver as WatchReceiver, OnceCell},
@@ -26,6 +28,7 @@ use tokio::{
 use tokio_tungstenite::tungstenite::protocol::{
     frame::coding::CloseCode, CloseFrame as TungsteniteCloseFrame,
 };
+use twilight_model::gateway::payload::outgoing::RequestGuildMembers;
 
 /// Sending a command failed.
 #[derive(Debug)]
@@ -395,8 +398,10 @@ pub struct ResumeSession {
 /// [docs:shards]: https://discord.com/developers/docs/topics/gateway#sharding
 #[derive(Debug)]
 pub struct Shard {
+    collectors: Collectors,
     config: Arc<Config>,
     emitter: Mutex<Option<Emitter>>,
+    events: EventQueue,
     processor_handle: OnceCell<JoinHandle<()>>,
     session: OnceCell<WatchReceiver<Arc<Session>>>,
 }
@@ -440,10 +445,12 @@ impl Shard {
         let config = Arc::new(config);
         let event_types = config.event_types();
 
-        let (emitter, rx) = Emitter::new(event_types);
+        let (emitter, rx) = Emitter::new(event_types, config.shard()[0], config.bounded_events());
 
         let this = Self {
+            collectors: emitter.collectors(),
             config,
+            events: emitter.queue(),
             emitter: Mutex::new(Some(emitter)),
             processor_handle: OnceCell::new(),
             session: OnceCell::new(),
@@ -464,6 +471,16 @@ impl Shard {
         &self.config
     }
 
+    /// Number of events queued in the shard's [`Events`] stream that have not
+    /// yet been received.
+    ///
+    /// Useful for monitoring whether the consumer keeps up with the shard,
+    /// especially when bounding the stream via
+    /// [`ShardBuilder::bounded_events`].
+    pub fn queued_events(&self) -> usize {
+        self.events.len()
+    }
+
     /// Start the shard, connecting it to the gateway and starting the process
     /// of receiving and processing events.
     ///
@@ -620,6 +637,70 @@ impl Shard {
             .map_err(CommandError::from_send)
     }
 
+    /// Request members of a guild, returning a stream of the requested
+    /// [`MemberChunk`]s.
+    ///
+    /// A nonce is generated for the request if it has none. Chunks with the
+    /// nonce are routed to the returned stream, and are additionally emitted
+    /// to the shard's [`Events`] stream if it subscribed to
+    /// [`EventTypeFlags::MEMBER_CHUNK`]. Use [`MemberChunks::collect_members`]
+    /// to wait for all chunks and combine them.
+    ///
+    /// # Examples
+    ///
+    /// Request all members of a guild and print how many there are:
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::{env, time::Duration};
+    /// use twilight_gateway::{shard::Shard, Intents};
+    /// use twilight_model::{gateway::payload::outgoing::RequestGuildMembers, id::Id};
+    ///
+    /// let intents = Intents::GUILDS | Intents::GUILD_MEMBERS;
+    /// let token = env::var("DISCORD_TOKEN")?;
+    ///
+    /// let (shard, _events) = Shard::new(token, intents);
+    /// shard.start().await?;
+    ///
+    /// let request = RequestGuildMembers::builder(Id::new(1)).query("", None);
+    /// let members = shard
+    ///     .request_guild_members(request)
+    ///     .await?
+    ///     .timeout(Duration::from_secs(30))
+    ///     .collect_members()
+    ///     .await?;
+    ///
+    /// println!("guild has {} members", members.members.len());
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`command`].
+    ///
+    /// [`EventTypeFlags::MEMBER_CHUNK`]: crate::EventTypeFlags::MEMBER_CHUNK
+    /// [`MemberChunk`]: twilight_model::gateway::payload::incoming::MemberChunk
+    /// [`command`]: Self::command
+    pub async fn request_guild_members(
+        &self,
+        mut request: RequestGuildMembers,
+    ) -> Result<MemberChunks, CommandError> {
+        let nonce = request
+            .d
+            .nonce
+            .get_or_insert_with(|| self.collectors.nonce())
+            .clone();
+
+        // Register before sending so that no chunks are missed.
+        let chunks = self
+            .collectors
+            .register(nonce, MemberChunks::DEFAULT_TIMEOUT);
+        self.command(&request).await?;
+
+        Ok(chunks)
+    }
+
     /// Send a raw websocket message.
     ///
     /// # Examples
@@ -716,6 +797,9 @@ impl Shard {
             processor_handle.abort();
         }
 
+        self.drop_emitter();
+        self.collectors.clear();
+
         if let Ok(session) = self.session() {
             // Since we're shutting down now, we don't care if it sends or not.
             let _res = session.close(Some(TungsteniteCloseFrame {
@@ -739,6 +823,9 @@ impl Shard {
             processor_handle.abort();
         }
 
+        self.drop_emitter();
+        self.collectors.clear();
+
         let shard_id = self.config().shard()[0];
 
         let session = if let Ok(session) = self.session() {
@@ -765,6 +852,17 @@ impl Shard {
         (shard_id, data)
     }
 
+    /// Drop the emitter if the shard was never started, ending its stream of
+    /// events.
+    ///
+    /// A started shard's emitter is owned by its processor instead.
+    fn drop_emitter(&self) {
+        self.emitter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+
     /// Return a handle to the current session.
     ///
     /// # Errors
@@ -0,0 +1,444 @@
+//! Collect the member chunks of guild member requests.
+//!
+//! Member chunks are routed to collectors by the nonce of their request before
+//! they are emitted, so collecting them doesn't require subscribing to
+//! [`EventTypeFlags::MEMBER_CHUNK`].
+//!
+//! [`EventTypeFlags::MEMBER_CHUNK`]: crate::EventTypeFlags::MEMBER_CHUNK
+
+use futures_util::stream::{Stream, StreamExt};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    time::{self, Instant, Sleep},
+};
+use twilight_model::{
+    gateway::{payload::incoming::MemberChunk, presence::Presence},
+    guild::Member,
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Collecting the member chunks of a request failed.
+#[derive(Debug)]
+pub struct MemberChunksError {
+    kind: MemberChunksErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl MemberChunksError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &MemberChunksErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (MemberChunksErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for MemberChunksError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            MemberChunksErrorType::ShardStopped => {
+                f.write_str("shard stopped before all member chunks were received")
+            }
+            MemberChunksErrorType::TimedOut { received } => {
+                f.write_str("timed out waiting for a member chunk after receiving ")?;
+                Display::fmt(received, f)?;
+
+                f.write_str(" chunks")
+            }
+        }
+    }
+}
+
+impl Error for MemberChunksError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`MemberChunksError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MemberChunksErrorType {
+    /// Shard stopped before all chunks were received, or another request
+    /// with the same nonce was made.
+    ShardStopped,
+    /// Next chunk wasn't received within the timeout.
+    TimedOut {
+        /// Number of chunks received before timing out.
+        received: u32,
+    },
+}
+
+/// Members, presences, and missing users of a guild member request, collected
+/// from all of its chunks.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct GuildMembers {
+    /// ID of the guild the members are in.
+    pub guild_id: Id<GuildMarker>,
+    /// Members of all chunks.
+    pub members: Vec<Member>,
+    /// Nonce of the request.
+    pub nonce: String,
+    /// IDs of requested users that aren't members of the guild.
+    pub not_found: Vec<Id<UserMarker>>,
+    /// Presences of the members, if requested.
+    pub presences: Vec<Presence>,
+}
+
+/// Senders of registered requests along with their registration ID, keyed by
+/// nonce.
+type Senders = HashMap<String, (u64, UnboundedSender<MemberChunk>)>;
+
+/// Collectors of the member chunks of a shard, keyed by request nonce.
+#[derive(Clone, Debug, Default)]
+pub struct Collectors {
+    /// Counter of the nonces and registrations of requests.
+    counter: Arc<AtomicU64>,
+    senders: Arc<Mutex<Senders>>,
+}
+
+impl Collectors {
+    /// Route a member chunk to the collector of its nonce.
+    ///
+    /// The chunk is returned if it has no collector, or if `keep` is true, in
+    /// which case it is cloned.
+    pub fn collect(&self, chunk: MemberChunk, keep: bool) -> Option<MemberChunk> {
+        let mut senders = self.senders.lock().expect("collectors poisoned");
+
+        let nonce = match chunk.nonce.as_deref() {
+            Some(nonce) if senders.contains_key(nonce) => nonce.to_owned(),
+            _ => return Some(chunk),
+        };
+
+        let last = chunk.chunk_index + 1 >= chunk.chunk_count;
+        let (collected, kept) = if keep {
+            (chunk.clone(), Some(chunk))
+        } else {
+            (chunk, None)
+        };
+
+        if let Some((_, tx)) = senders.get(&nonce) {
+            let _res = tx.send(collected);
+        }
+
+        if last {
+            senders.remove(&nonce);
+        }
+
+        kept
+    }
+
+    /// Stop collecting the chunks of all requests, ending their streams with
+    /// a [`MemberChunksErrorType::ShardStopped`] error.
+    pub fn clear(&self) {
+        self.senders.lock().expect("collectors poisoned").clear();
+    }
+
+    /// Whether any requests are being collected.
+    pub fn is_collecting(&self) -> bool {
+        !self.senders.lock().expect("collectors poisoned").is_empty()
+    }
+
+    /// Generate a nonce unique to the shard.
+    pub fn nonce(&self) -> String {
+        let id = self.counter.fetch_add(1, Ordering::Relaxed);
+
+        format!("twilight-{id}")
+    }
+
+    /// Start collecting the chunks of a request.
+    pub fn register(&self, nonce: String, timeout: Duration) -> MemberChunks {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let id = self.counter.fetch_add(1, Ordering::Relaxed);
+        self.senders
+            .lock()
+            .expect("collectors poisoned")
+            .insert(nonce.clone(), (id, tx));
+
+        MemberChunks {
+            collectors: self.clone(),
+            finished: false,
+            id,
+            nonce,
+            received: 0,
+            rx,
+            sleep: Box::pin(time::sleep(timeout)),
+            timeout,
+        }
+    }
+
+    /// Stop collecting the chunks of a request.
+    fn unregister(&self, nonce: &str, id: u64) {
+        let mut senders = self.senders.lock().expect("collectors poisoned");
+
+        // Another request may have reused the nonce.
+        if senders
+            .get(nonce)
+            .map_or(false, |(registered, _)| *registered == id)
+        {
+            senders.remove(nonce);
+        }
+    }
+}
+
+/// Stream of the member chunks of a guild member request.
+///
+/// Created via [`Shard::request_guild_members`]. The stream ends after the
+/// last chunk is received, or after returning an error if the shard stops or
+/// a chunk isn't received within the [timeout].
+///
+/// Use [`collect_members`] to wait for all chunks and combine them.
+///
+/// [`Shard::request_guild_members`]: super::Shard::request_guild_members
+/// [`collect_members`]: Self::collect_members
+/// [timeout]: Self::timeout
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct MemberChunks {
+    collectors: Collectors,
+    finished: bool,
+    /// Registration ID of the request.
+    id: u64,
+    nonce: String,
+    received: u32,
+    rx: UnboundedReceiver<MemberChunk>,
+    sleep: Pin<Box<Sleep>>,
+    timeout: Duration,
+}
+
+impl MemberChunks {
+    /// Default duration to wait for each chunk.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Wait for all chunks, combining their members, presences, and missing
+    /// users.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MemberChunksErrorType::ShardStopped`] error type if the
+    /// shard stopped before all chunks were received.
+    ///
+    /// Returns a [`MemberChunksErrorType::TimedOut`] error type if a chunk
+    /// wasn't received within the [timeout].
+    ///
+    /// [timeout]: Self::timeout
+    pub async fn collect_members(mut self) -> Result<GuildMembers, MemberChunksError> {
+        let mut collected = None;
+
+        while let Some(chunk) = self.next().await {
+            collected = Some(merge(collected, chunk?, &self.nonce));
+        }
+
+        collected.ok_or(MemberChunksError {
+            kind: MemberChunksErrorType::ShardStopped,
+            source: None,
+        })
+    }
+
+    /// Nonce of the request.
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// Set the duration to wait for each chunk.
+    ///
+    /// Defaults to [`DEFAULT_TIMEOUT`].
+    ///
+    /// [`DEFAULT_TIMEOUT`]: Self::DEFAULT_TIMEOUT
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self.sleep.as_mut().reset(Instant::now() + timeout);
+
+        self
+    }
+}
+
+impl Drop for MemberChunks {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.collectors.unregister(&self.nonce, self.id);
+        }
+    }
+}
+
+impl Stream for MemberChunks {
+    type Item = Result<MemberChunk, MemberChunksError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+
+        match self.rx.poll_recv(cx) {
+            Poll::Ready(Some(chunk)) => {
+                self.received += 1;
+                self.finished = chunk.chunk_index + 1 >= chunk.chunk_count;
+
+                let deadline = Instant::now() + self.timeout;
+                self.sleep.as_mut().reset(deadline);
+
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+            Poll::Ready(None) => {
+                self.finished = true;
+
+                return Poll::Ready(Some(Err(MemberChunksError {
+                    kind: MemberChunksErrorType::ShardStopped,
+                    source: None,
+                })));
+            }
+            Poll::Pending => {}
+        }
+
+        if self.sleep.as_mut().poll(cx).is_ready() {
+            self.finished = true;
+            self.collectors.unregister(&self.nonce, self.id);
+
+            return Poll::Ready(Some(Err(MemberChunksError {
+                kind: MemberChunksErrorType::TimedOut {
+                    received: self.received,
+                },
+                source: None,
+            })));
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Merge a chunk into the members collected so far.
+fn merge(collected: Option<GuildMembers>, chunk: MemberChunk, nonce: &str) -> GuildMembers {
+    let mut collected = collected.unwrap_or_else(|| GuildMembers {
+        guild_id: chunk.guild_id,
+        members: Vec::new(),
+        nonce: nonce.to_owned(),
+        not_found: Vec::new(),
+        presences: Vec::new(),
+    });
+
+    collected.members.extend(chunk.members);
+    collected.not_found.extend(chunk.not_found);
+    collected.presences.extend(chunk.presences);
+
+    collected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collectors, GuildMembers, MemberChunks, MemberChunksError, MemberChunksErrorType};
+    use futures_util::{stream::Stream, StreamExt};
+    use static_assertions::assert_impl_all;
+    use std::{error::Error, fmt::Debug, time::Duration};
+    use twilight_model::{gateway::payload::incoming::MemberChunk, id::Id};
+
+    assert_impl_all!(GuildMembers: Clone, Debug, PartialEq, Send, Sync);
+    assert_impl_all!(MemberChunks: Debug, Send, Stream, Sync);
+    assert_impl_all!(MemberChunksError: Error, Send, Sync);
+    assert_impl_all!(MemberChunksErrorType: Debug, Send, Sync);
+
+    fn chunk(nonce: &str, chunk_index: u32, chunk_count: u32, not_found: u64) -> MemberChunk {
+        MemberChunk {
+            chunk_count,
+            chunk_index,
+            guild_id: Id::new(1),
+            members: Vec::new(),
+            nonce: Some(nonce.to_owned()),
+            not_found: Vec::from([Id::new(not_found)]),
+            presences: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn collect_members() {
+        let collectors = Collectors::default();
+        let nonce = collectors.nonce();
+        let chunks = collectors.register(nonce.clone(), MemberChunks::DEFAULT_TIMEOUT);
+        assert!(collectors.is_collecting());
+
+        assert!(collectors.collect(chunk("other", 0, 1, 2), false).is_some());
+        assert!(collectors.collect(chunk(&nonce, 0, 2, 3), false).is_none());
+        assert!(collectors.collect(chunk(&nonce, 1, 2, 4), true).is_some());
+        assert!(!collectors.is_collecting());
+
+        let members = chunks.collect_members().await.unwrap();
+        assert_eq!(nonce, members.nonce);
+        assert_eq!(Vec::from([Id::new(3), Id::new(4)]), members.not_found);
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let collectors = Collectors::default();
+        let mut chunks = collectors
+            .register(collectors.nonce(), MemberChunks::DEFAULT_TIMEOUT)
+            .timeout(Duration::from_millis(10));
+        assert!(collectors
+            .collect(chunk(chunks.nonce(), 0, 2, 2), false)
+            .is_none());
+
+        assert!(chunks.next().await.unwrap().is_ok());
+        let error = chunks.next().await.unwrap().unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            MemberChunksErrorType::TimedOut { received: 1 }
+        ));
+        assert!(chunks.next().await.is_none());
+        assert!(!collectors.is_collecting());
+    }
+
+    #[tokio::test]
+    async fn clear() {
+        let collectors = Collectors::default();
+        let chunks = collectors.register(collectors.nonce(), MemberChunks::DEFAULT_TIMEOUT);
+        collectors.clear();
+        assert!(!collectors.is_collecting());
+
+        let error = chunks.collect_members().await.unwrap_err();
+        assert!(matches!(error.kind(), MemberChunksErrorType::ShardStopped));
+    }
+
+    #[test]
+    fn nonces_unique() {
+        let collectors = Collectors::default();
+
+        assert_ne!(collectors.nonce(), collectors.nonce());
+    }
+
+    #[tokio::test]
+    async fn drop_unregisters() {
+        let collectors = Collectors::default();
+        let chunks = collectors.register(collectors.nonce(), MemberChunks::DEFAULT_TIMEOUT);
+        drop(chunks);
+
+        assert!(!collectors.is_collecting());
+    }
+}
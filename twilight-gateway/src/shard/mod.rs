@@ -55,13 +55,16 @@
 pub mod raw_message;
 pub mod stage;
 
+mod backpressure;
 mod builder;
+pub(crate) mod channel;
 mod command;
 mod config;
 mod emitter;
 mod event;
 mod r#impl;
-mod json;
+pub(crate) mod json;
+mod member_chunks;
 mod processor;
 #[cfg(any(
     feature = "native",
@@ -71,10 +74,12 @@ mod processor;
 pub(crate) mod tls;
 
 pub use self::{
+    backpressure::BackpressurePolicy,
     builder::{ShardBuilder, ShardIdError, ShardIdErrorType},
     command::Command,
     config::Config,
     event::Events,
+    member_chunks::{GuildMembers, MemberChunks, MemberChunksError, MemberChunksErrorType},
     processor::heartbeat::Latency,
     r#impl::{
         CommandError, CommandErrorType, Information, ResumeSession, SendError, SendErrorType,
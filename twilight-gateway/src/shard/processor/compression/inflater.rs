@@ -96,7 +96,12 @@ impl Inflater {
             let saved_percentage =
                 self.decompress.total_in() as f64 / self.decompress.total_out() as f64;
             let saved_percentage_readable = saved_percentage * 100.0;
-            let saved_kib = (self.decompress.total_out() - self.decompress.total_in()) / 1_024;
+            // Small payloads may be larger when compressed.
+            let saved_kib = self
+                .decompress
+                .total_out()
+                .saturating_sub(self.decompress.total_in())
+                / 1_024;
 
             tracing::trace!(
                 saved_kib = saved_kib,
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::VecDeque,
     sync::{
-        atomic::{AtomicU32, AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
         Arc, Mutex,
     },
     time::{Duration, Instant},
@@ -71,6 +71,9 @@ impl Latency {
 
 #[derive(Debug)]
 pub struct Heartbeats {
+    /// Whether reading payloads is blocked on the shard's bounded event
+    /// stream, in which case acknowledgements can't be received.
+    blocked: AtomicBool,
     received: Mutex<Option<Instant>>,
     recent: Mutex<VecDeque<u64>>,
     sent: Mutex<Option<Instant>>,
@@ -99,6 +102,14 @@ impl Heartbeats {
         }
     }
 
+    pub fn is_blocked(&self) -> bool {
+        self.blocked.load(Ordering::Relaxed)
+    }
+
+    pub fn set_blocked(&self, blocked: bool) {
+        self.blocked.store(blocked, Ordering::Relaxed);
+    }
+
     pub fn last_acked(&self) -> bool {
         self.received().is_some()
     }
@@ -164,6 +175,7 @@ impl Heartbeats {
 impl Default for Heartbeats {
     fn default() -> Self {
         Self {
+            blocked: AtomicBool::new(false),
             received: Mutex::new(None),
             recent: Mutex::new(VecDeque::with_capacity(5)),
             sent: Mutex::new(None),
@@ -220,7 +232,10 @@ impl Heartbeater {
             // - if so, then mark that we didn't get one this time
             // - if not, then end the heartbeater because something is off
             // (connecting closed?)
-            if self.heartbeats.last_acked() {
+            //
+            // Acknowledgements aren't read while the socket is blocked, so
+            // they aren't expected until it's unblocked.
+            if self.heartbeats.last_acked() || self.heartbeats.is_blocked() {
                 last = true;
             } else if last {
                 last = false;
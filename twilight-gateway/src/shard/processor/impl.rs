@@ -2,11 +2,12 @@ use super::{
     super::{
         emitter::{EmitJsonErrorType, Emitter},
         json::{self, GatewayEventParsingError, GatewayEventParsingErrorType},
-        Config, ShardStream, Stage,
+        BackpressurePolicy, Config, ShardStream, Stage,
     },
     compression::{self, Compression},
+    heartbeat::Heartbeats,
     session::{Session, SessionSendError, SessionSendErrorType},
-    socket_forwarder::SocketForwarder,
+    socket_forwarder::{ForwarderReceiver, SocketForwarder},
 };
 use crate::{EventTypeFlags, API_VERSION};
 use serde::{Deserialize, Serialize};
@@ -19,9 +20,8 @@ use std::{
     sync::{atomic::Ordering, Arc},
     time::Duration,
 };
-use tokio::sync::{
-    mpsc::UnboundedReceiver,
-    watch::{channel as watch_channel, Receiver as WatchReceiver, Sender as WatchSender},
+use tokio::sync::watch::{
+    channel as watch_channel, Receiver as WatchReceiver, Sender as WatchSender,
 };
 use tokio_tungstenite::tungstenite::{
     protocol::{frame::coding::CloseCode, CloseFrame, WebSocketConfig},
@@ -305,7 +305,7 @@ struct ReadyMinimal {
 pub struct ShardProcessor {
     pub config: Arc<Config>,
     pub emitter: Emitter,
-    pub rx: UnboundedReceiver<Message>,
+    pub rx: ForwarderReceiver,
     pub session: Arc<Session>,
     compression: Compression,
     url: Box<str>,
@@ -359,10 +359,15 @@ impl ShardProcessor {
             config.tls.as_ref(),
         )
         .await?;
-        let (forwarder, rx, tx) = SocketForwarder::new(stream);
+        let heartbeats = Arc::new(Heartbeats::default());
+        let (forwarder, rx, tx) = SocketForwarder::new(
+            stream,
+            config.bounded_events().map(|(capacity, _)| capacity),
+            Arc::clone(&heartbeats),
+        );
         tokio::spawn(forwarder.run());
 
-        let session = Arc::new(Session::new(tx, config.ratelimit_payloads));
+        let session = Arc::new(Session::new(tx, heartbeats, config.ratelimit_payloads));
 
         if resumable {
             session.set_id(config.session_id.clone().unwrap());
@@ -395,12 +400,17 @@ impl ShardProcessor {
 
     pub async fn run(mut self) {
         loop {
+            self.backpressure().await;
+
             if let Err(source) = self.next_payload().await {
                 tracing::warn!("{source}");
 
                 self.emit_disconnected(None, None).await;
 
                 if source.fatal() {
+                    // Chunks of pending member requests won't be received.
+                    self.emitter.collectors().clear();
+
                     break;
                 }
 
@@ -440,6 +450,58 @@ impl ShardProcessor {
         }
     }
 
+    /// Apply the configured [`BackpressurePolicy`] if the stream of events is
+    /// full.
+    ///
+    /// Only [`BackpressurePolicy::DropOldest`] drops events, which it does as
+    /// they're queued; this waits for the consumer to catch up before reading
+    /// the next payload. [`BackpressurePolicy::Reconnect`] closes the
+    /// connection while waiting, which the socket forwarder sends even while
+    /// it's blocked on a full channel. Otherwise the heartbeats are marked as
+    /// blocked while waiting, as acknowledgements aren't read in the meantime.
+    async fn backpressure(&mut self) {
+        let policy = if let Some((_, policy)) = self.config.bounded_events() {
+            policy
+        } else {
+            return;
+        };
+
+        if !self.emitter.is_full() {
+            return;
+        }
+
+        tracing::debug!(
+            shard_id = self.config.shard()[0],
+            queued = self.emitter.queue().len(),
+            "event stream full, applying {policy:?} policy",
+        );
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!(format!("Event-Queue-Full-{}", self.config.shard()[0]), 1);
+
+        if policy == BackpressurePolicy::Reconnect {
+            self.session.stop_heartbeater();
+
+            let frame = CloseFrame {
+                code: CloseCode::Restart,
+                reason: Cow::Borrowed("Event stream full"),
+            };
+
+            if let Err(source) = self.session.close(Some(frame.clone())) {
+                tracing::warn!("failed to close connection: {source:?}");
+            }
+
+            self.emit_disconnected(Some(frame.code.into()), Some(frame.reason.to_string()))
+                .await;
+            self.emitter.wait_for_capacity().await;
+            self.resume().await;
+        } else {
+            self.session.heartbeats.set_blocked(true);
+            self.emitter.wait_for_capacity().await;
+            self.session.heartbeats.set_blocked(false);
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn process(&mut self) -> Result<(), ProcessError> {
         let (op, seq, event_type) = {
@@ -1108,12 +1170,14 @@ impl ShardProcessor {
     /// Set the session details and create and run a new socket forwarder for a
     /// new websocket connection.
     fn set_session(&mut self, stream: ShardStream, stage: Stage) {
-        let (forwarder, rx, tx) = SocketForwarder::new(stream);
+        let capacity = self.config.bounded_events().map(|(capacity, _)| capacity);
+        let heartbeats = Arc::new(Heartbeats::default());
+        let (forwarder, rx, tx) = SocketForwarder::new(stream, capacity, Arc::clone(&heartbeats));
 
         tokio::spawn(forwarder.run());
 
         self.rx = rx;
-        self.session = Arc::new(Session::new(tx, self.config.ratelimit_payloads));
+        self.session = Arc::new(Session::new(tx, heartbeats, self.config.ratelimit_payloads));
 
         if let Err(source) = self.wtx.send(Arc::clone(&self.session)) {
             tracing::error!("failed to broadcast new session: {source:?}");
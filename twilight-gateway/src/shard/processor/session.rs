@@ -76,10 +76,14 @@ pub struct Session {
 }
 
 impl Session {
-    pub fn new(tx: UnboundedSender<TungsteniteMessage>, ratelimit_payloads: bool) -> Self {
+    pub fn new(
+        tx: UnboundedSender<TungsteniteMessage>,
+        heartbeats: Arc<Heartbeats>,
+        ratelimit_payloads: bool,
+    ) -> Self {
         let session = Self {
             heartbeater_handle: MutexSync::new(None),
-            heartbeats: Arc::new(Heartbeats::default()),
+            heartbeats,
             heartbeat_interval: AtomicU64::new(0),
             id: MutexSync::new(None),
             seq: Arc::new(AtomicU64::new(0)),
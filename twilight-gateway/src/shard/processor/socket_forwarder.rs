@@ -1,33 +1,80 @@
-use super::super::ShardStream;
+use super::{super::ShardStream, heartbeat::Heartbeats};
 use futures_util::{
     future::{self, Either},
     sink::SinkExt,
     stream::StreamExt,
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tokio::{
-    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    sync::mpsc::{self, error::TrySendError, Receiver, Sender, UnboundedReceiver, UnboundedSender},
     time::timeout,
 };
 use tokio_tungstenite::tungstenite::Message;
 
+/// Receiver of messages read from the socket by a [`SocketForwarder`].
+#[derive(Debug)]
+pub enum ForwarderReceiver {
+    Bounded(Receiver<Message>),
+    Unbounded(UnboundedReceiver<Message>),
+}
+
+impl ForwarderReceiver {
+    /// Receive the next message, returning `None` once the forwarder ended.
+    pub async fn recv(&mut self) -> Option<Message> {
+        match self {
+            Self::Bounded(rx) => rx.recv().await,
+            Self::Unbounded(rx) => rx.recv().await,
+        }
+    }
+}
+
+/// Sender of messages read from the socket.
+///
+/// When bounded, sending waits for the processor to make room, which stops the
+/// forwarder from reading from the socket.
+enum ForwarderSender {
+    Bounded(Sender<Message>),
+    Unbounded(UnboundedSender<Message>),
+}
+
 pub struct SocketForwarder {
+    /// Heartbeats of the session, marked as blocked while waiting for the
+    /// processor to make room.
+    heartbeats: Arc<Heartbeats>,
     rx: UnboundedReceiver<Message>,
     pub stream: ShardStream,
-    tx: UnboundedSender<Message>,
+    tx: ForwarderSender,
 }
 
 impl SocketForwarder {
     const TIMEOUT: Duration = Duration::from_secs(90);
 
+    /// Create a new forwarder for a socket.
+    ///
+    /// Messages read from the socket are buffered up to the capacity, if
+    /// provided.
     pub fn new(
         stream: ShardStream,
-    ) -> (Self, UnboundedReceiver<Message>, UnboundedSender<Message>) {
-        let (to_user, from_forwarder) = mpsc::unbounded_channel();
+        capacity: Option<usize>,
+        heartbeats: Arc<Heartbeats>,
+    ) -> (Self, ForwarderReceiver, UnboundedSender<Message>) {
+        let (to_user, from_forwarder) = if let Some(capacity) = capacity {
+            let (tx, rx) = mpsc::channel(capacity);
+
+            (ForwarderSender::Bounded(tx), ForwarderReceiver::Bounded(rx))
+        } else {
+            let (tx, rx) = mpsc::unbounded_channel();
+
+            (
+                ForwarderSender::Unbounded(tx),
+                ForwarderReceiver::Unbounded(rx),
+            )
+        };
         let (to_forwarder, from_user) = mpsc::unbounded_channel();
 
         (
             Self {
+                heartbeats,
                 rx: from_user,
                 stream,
                 tx: to_user,
@@ -41,14 +88,25 @@ impl SocketForwarder {
         tracing::debug!("starting driving loop");
 
         loop {
-            tokio::pin! {
-                let rx = self.rx.recv();
-                let tx = self.stream.next();
-            }
+            // Scoped so that the futures are dropped before forwarding a
+            // message, which may wait on `rx` itself.
+            let next = {
+                tokio::pin! {
+                    let rx = self.rx.recv();
+                    let tx = self.stream.next();
+                }
 
-            match timeout(Self::TIMEOUT, future::select(rx, tx)).await {
+                timeout(Self::TIMEOUT, future::select(rx, tx))
+                    .await
+                    .map(|either| match either {
+                        Either::Left((maybe_msg, _)) => Either::Left(maybe_msg),
+                        Either::Right((try_msg, _)) => Either::Right(try_msg),
+                    })
+            };
+
+            match next {
                 // `rx` future finished first.
-                Ok(Either::Left((maybe_msg, _))) => {
+                Ok(Either::Left(maybe_msg)) => {
                     if let Some(msg) = maybe_msg {
                         tracing::trace!("sending message: {msg}");
 
@@ -66,9 +124,9 @@ impl SocketForwarder {
                     }
                 }
                 // `tx` future finished first.
-                Ok(Either::Right((try_msg, _))) => match try_msg {
+                Ok(Either::Right(try_msg)) => match try_msg {
                     Some(Ok(msg)) => {
-                        if self.tx.send(msg).is_err() {
+                        if !self.forward(msg).await {
                             break;
                         }
                     }
@@ -94,4 +152,149 @@ impl SocketForwarder {
 
         tracing::debug!("Leaving loop");
     }
+
+    /// Forward a message read from the socket to the processor, returning
+    /// whether the forwarder should keep running.
+    ///
+    /// Messages from the processor are still sent while waiting for it to make
+    /// room, so that heartbeats keep being sent and the processor can close
+    /// the connection while it's not reading.
+    async fn forward(&mut self, msg: Message) -> bool {
+        let tx = match &self.tx {
+            ForwarderSender::Bounded(tx) => tx.clone(),
+            ForwarderSender::Unbounded(tx) => return tx.send(msg).is_ok(),
+        };
+
+        match tx.try_reserve() {
+            Ok(permit) => {
+                permit.send(msg);
+
+                return true;
+            }
+            Err(TrySendError::Closed(())) => return false,
+            Err(TrySendError::Full(())) => {}
+        }
+
+        tracing::debug!("blocked on forwarding a message");
+        self.heartbeats.set_blocked(true);
+        let forwarded = self.forward_blocked(&tx, msg).await;
+        self.heartbeats.set_blocked(false);
+
+        forwarded
+    }
+
+    /// Wait for the processor to make room for a message, sending messages
+    /// from the processor in the meantime.
+    async fn forward_blocked(&mut self, tx: &Sender<Message>, msg: Message) -> bool {
+        let Self { rx, stream, .. } = self;
+
+        let reserve = tx.reserve();
+        tokio::pin!(reserve);
+
+        loop {
+            let recv = rx.recv();
+            tokio::pin!(recv);
+
+            match future::select(reserve.as_mut(), recv).await {
+                Either::Left((Ok(permit), _)) => {
+                    permit.send(msg);
+
+                    return true;
+                }
+                Either::Left((Err(_), _)) => return false,
+                Either::Right((Some(outgoing), _)) => {
+                    let close = outgoing.is_close();
+
+                    tracing::trace!("sending message while blocked: {outgoing}");
+
+                    if let Err(source) = stream.send(outgoing).await {
+                        tracing::warn!("sending failed: {source}");
+
+                        return false;
+                    }
+
+                    if close {
+                        tracing::debug!("closed socket while blocked");
+
+                        return false;
+                    }
+                }
+                Either::Right((None, _)) => {
+                    tracing::debug!("rx stream ended, closing socket");
+
+                    let _res = stream.close(None).await;
+
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::heartbeat::Heartbeats, SocketForwarder};
+    use serde_json::json;
+    use std::{error::Error, sync::Arc, time::Duration};
+    use tokio::time;
+    use tokio_tungstenite::tungstenite::{
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    };
+    use twilight_mock::gateway::{ClientPayload, MockGateway};
+
+    #[tokio::test]
+    async fn close_while_blocked() -> Result<(), Box<dyn Error>> {
+        let mut gateway = MockGateway::bind().await?;
+        let (stream, _) = tokio_tungstenite::connect_async(gateway.url()).await?;
+        let mut connection = gateway.accept().await.unwrap();
+
+        // The Hello payload fills the channel, so the forwarder blocks on
+        // forwarding the dispatch.
+        let (forwarder, _rx, tx) =
+            SocketForwarder::new(stream, Some(1), Arc::new(Heartbeats::default()));
+        let forwarder = tokio::spawn(forwarder.run());
+        connection.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "1" }));
+        time::sleep(Duration::from_millis(100)).await;
+
+        tx.send(Message::Close(Some(CloseFrame {
+            code: CloseCode::Restart,
+            reason: "".into(),
+        })))?;
+
+        let payload = time::timeout(Duration::from_secs(10), connection.recv()).await?;
+        assert_eq!(Some(ClientPayload::Close(Some(1012))), payload);
+        time::timeout(Duration::from_secs(10), forwarder).await??;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn heartbeat_while_blocked() -> Result<(), Box<dyn Error>> {
+        let mut gateway = MockGateway::bind().await?;
+        let (stream, _) = tokio_tungstenite::connect_async(gateway.url()).await?;
+        let mut connection = gateway.accept().await.unwrap();
+
+        let heartbeats = Arc::new(Heartbeats::default());
+        let (forwarder, mut rx, tx) =
+            SocketForwarder::new(stream, Some(1), Arc::clone(&heartbeats));
+        tokio::spawn(forwarder.run());
+        connection.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "1" }));
+        time::sleep(Duration::from_millis(100)).await;
+        assert!(heartbeats.is_blocked());
+
+        tx.send(Message::Text(json!({ "op": 1, "d": 1 }).to_string()))?;
+        let payload = time::timeout(Duration::from_secs(10), connection.recv()).await?;
+        assert_eq!(Some(ClientPayload::Heartbeat(Some(1))), payload);
+
+        // The Hello payload, dispatch and heartbeat acknowledgement.
+        for _ in 0..3 {
+            assert!(rx.recv().await.is_some());
+        }
+
+        time::sleep(Duration::from_millis(100)).await;
+        assert!(!heartbeats.is_blocked());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,437 @@
+use futures::stream::StreamExt;
+use serde_json::json;
+use std::{
+    collections::HashSet, error::Error, future::Future, pin::Pin, sync::Arc, time::Duration,
+};
+use tokio::time;
+use twilight_gateway::{
+    cluster::{Cluster, ShardScheme},
+    queue::Queue,
+    shard::{BackpressurePolicy, Events, MemberChunksErrorType, Shard},
+    Event, EventTypeFlags, Intents,
+};
+use twilight_mock::gateway::{ClientPayload, MockGateway};
+use twilight_model::{
+    gateway::{event::EventType, payload::outgoing::RequestGuildMembers},
+    id::Id,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Queue letting shards identify immediately.
+#[derive(Debug)]
+struct NoopQueue;
+
+impl Queue for NoopQueue {
+    fn request(&'_ self, _: [u64; 2]) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+}
+
+fn shard(gateway: &MockGateway) -> (Shard, Events) {
+    Shard::builder("token".to_owned(), Intents::GUILDS)
+        .event_types(EventTypeFlags::all() - EventTypeFlags::SHARD_PAYLOAD)
+        .gateway_url(gateway.url())
+        .queue(Arc::new(NoopQueue))
+        .build()
+}
+
+/// Wait for an event of a type, skipping other events.
+async fn wait_for(events: &mut Events, kind: EventType) -> Event {
+    time::timeout(TIMEOUT, async {
+        loop {
+            let event = events.next().await.expect("shard running");
+
+            if event.kind() == kind {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("event received in time")
+}
+
+#[tokio::test]
+async fn test_identify_dispatch_resume() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (shard, mut events) = shard(&gateway);
+    shard.start().await?;
+
+    let mut connection = gateway.accept().await.unwrap();
+    let identify = connection.recv_identify().await.unwrap();
+    assert_eq!("Bot token", identify.d.token);
+    assert_eq!(Some([0, 1]), identify.d.shard);
+    assert_eq!(
+        cfg!(any(feature = "zlib-simd", feature = "zlib-stock")),
+        connection.compressed()
+    );
+
+    connection.ready("session", &[Id::new(1)]);
+    wait_for(&mut events, EventType::ShardConnected).await;
+
+    connection.dispatch(
+        "CHANNEL_PINS_UPDATE",
+        &json!({ "channel_id": "2", "guild_id": "1" }),
+    );
+    let event = wait_for(&mut events, EventType::ChannelPinsUpdate).await;
+    assert!(matches!(event, Event::ChannelPinsUpdate(update) if update.channel_id == Id::new(2)));
+
+    connection.reconnect();
+
+    let mut connection = gateway.accept().await.unwrap();
+    let resume = connection.recv_resume().await.unwrap();
+    assert_eq!("session", resume.d.session_id);
+    assert_eq!(2, resume.d.seq);
+
+    connection.set_sequence(resume.d.seq);
+    connection.resumed();
+    wait_for(&mut events, EventType::ShardConnected).await;
+    assert_eq!(Some("session"), shard.info()?.session_id());
+
+    shard.shutdown();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_session_reidentifies() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (shard, mut events) = shard(&gateway);
+    shard.start().await?;
+
+    let mut connection = gateway.accept().await.unwrap();
+    connection.recv_identify().await.unwrap();
+    connection.invalid_session(false);
+    wait_for(&mut events, EventType::GatewayInvalidateSession).await;
+
+    let mut connection = gateway.accept().await.unwrap();
+    assert!(connection.recv_identify().await.is_some());
+    connection.ready("new session", &[]);
+    wait_for(&mut events, EventType::Ready).await;
+
+    shard.shutdown();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_heartbeat_request() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (shard, mut events) = shard(&gateway);
+    shard.start().await?;
+
+    let mut connection = gateway.accept().await.unwrap();
+    connection.recv_identify().await.unwrap();
+    connection.ready("session", &[]);
+    connection.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "2" }));
+    wait_for(&mut events, EventType::ChannelPinsUpdate).await;
+
+    // The shard may have sent heartbeats before receiving the event.
+    connection.heartbeat();
+    time::timeout(TIMEOUT, async {
+        while connection.recv().await.unwrap() != ClientPayload::Heartbeat(Some(2)) {}
+    })
+    .await?;
+
+    shard.shutdown();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_guild_members() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (shard, mut events) = shard(&gateway);
+    shard.start().await?;
+
+    let mut connection = gateway.accept().await.unwrap();
+    connection.recv_identify().await.unwrap();
+    connection.ready("session", &[]);
+    wait_for(&mut events, EventType::Ready).await;
+
+    let request =
+        RequestGuildMembers::builder(Id::new(1)).user_ids(Vec::from([Id::new(2), Id::new(3)]))?;
+    let chunks = shard.request_guild_members(request).await?;
+    let nonce = chunks.nonce().to_owned();
+
+    let payload = time::timeout(TIMEOUT, async {
+        loop {
+            if let ClientPayload::Other(payload) = connection.recv().await.unwrap() {
+                return payload;
+            }
+        }
+    })
+    .await?;
+    assert_eq!(8, payload["op"]);
+    assert_eq!(nonce, payload["d"]["nonce"]);
+
+    for (chunk_index, user_id) in [(0, "2"), (1, "3")] {
+        connection.dispatch(
+            "GUILD_MEMBERS_CHUNK",
+            &json!({
+                "chunk_count": 2,
+                "chunk_index": chunk_index,
+                "guild_id": "1",
+                "members": [],
+                "nonce": nonce,
+                "not_found": [user_id],
+            }),
+        );
+    }
+
+    let members = time::timeout(TIMEOUT, chunks.collect_members()).await??;
+    assert_eq!(Id::new(1), members.guild_id);
+    assert_eq!(Vec::from([Id::new(2), Id::new(3)]), members.not_found);
+
+    // Chunks are still emitted to subscribed streams.
+    wait_for(&mut events, EventType::MemberChunk).await;
+
+    shard.shutdown();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_guild_members_shard_stopped() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+
+    // Shut down the shard, then close the connection with a fatal close code.
+    for shutdown in [true, false] {
+        let (shard, mut events) = shard(&gateway);
+        shard.start().await?;
+
+        let mut connection = gateway.accept().await.unwrap();
+        connection.recv_identify().await.unwrap();
+        connection.ready("session", &[]);
+        wait_for(&mut events, EventType::Ready).await;
+
+        let request = RequestGuildMembers::builder(Id::new(1)).query("", None);
+        let chunks = shard.request_guild_members(request).await?;
+        connection.dispatch(
+            "GUILD_MEMBERS_CHUNK",
+            &json!({
+                "chunk_count": 2,
+                "chunk_index": 0,
+                "guild_id": "1",
+                "members": [],
+                "nonce": chunks.nonce(),
+            }),
+        );
+        wait_for(&mut events, EventType::MemberChunk).await;
+
+        if shutdown {
+            shard.shutdown();
+        } else {
+            connection.close(4004, "Authentication failed.");
+            wait_for(&mut events, EventType::ShardDisconnected).await;
+        }
+
+        let error = time::timeout(TIMEOUT, chunks.timeout(TIMEOUT / 2).collect_members())
+            .await?
+            .unwrap_err();
+        assert!(matches!(error.kind(), MemberChunksErrorType::ShardStopped));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fatal_close_code() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (shard, mut events) = shard(&gateway);
+    shard.start().await?;
+
+    let mut connection = gateway.accept().await.unwrap();
+    connection.recv_identify().await.unwrap();
+    connection.close(4004, "Authentication failed.");
+
+    // The shard stops, ending its stream of events.
+    time::timeout(TIMEOUT, async { while events.next().await.is_some() {} }).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_backpressure_reconnect_closes_connection() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (shard, mut events) = Shard::builder("token".to_owned(), Intents::GUILDS)
+        .bounded_events(2, BackpressurePolicy::Reconnect)
+        .event_types(EventTypeFlags::CHANNEL_PINS_UPDATE)
+        .gateway_url(gateway.url())
+        .queue(Arc::new(NoopQueue))
+        .build();
+    shard.start().await?;
+
+    let mut connection = gateway.accept().await.unwrap();
+    connection.recv_identify().await.unwrap();
+    connection.ready("session", &[]);
+
+    // Fill the stream of events and the socket forwarder's buffer while the
+    // consumer is stalled.
+    for _ in 0..8 {
+        connection.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "2" }));
+    }
+
+    time::timeout(TIMEOUT, async {
+        loop {
+            match connection.recv().await {
+                Some(ClientPayload::Close(_)) => break,
+                Some(_) => {}
+                None => panic!("connection ended without a close frame"),
+            }
+        }
+    })
+    .await?;
+
+    // The session is resumed once the consumer catches up.
+    wait_for(&mut events, EventType::ChannelPinsUpdate).await;
+    wait_for(&mut events, EventType::ChannelPinsUpdate).await;
+
+    let mut connection = time::timeout(TIMEOUT, gateway.accept()).await?.unwrap();
+    let resume = connection.recv_resume().await.unwrap();
+    assert_eq!("session", resume.d.session_id);
+
+    shard.shutdown();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reshard_loses_no_events() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (cluster, mut events) = Cluster::builder("token".to_owned(), Intents::GUILD_MESSAGES)
+        .event_types(EventTypeFlags::CHANNEL_PINS_UPDATE)
+        .gateway_url(gateway.url())
+        .queue(Arc::new(NoopQueue))
+        .shard_scheme(ShardScheme::try_from((0..1, 1))?)
+        .build()
+        .await?;
+    cluster.up().await;
+
+    let mut previous = gateway.accept().await.unwrap();
+    previous.recv_identify().await.unwrap();
+    previous.ready("previous", &[]);
+
+    // Queued by the previous shard and not yet received at the switch.
+    previous.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "1" }));
+    let shard = cluster.shard(0).unwrap();
+    time::timeout(TIMEOUT, async {
+        while shard.queued_events() == 0 {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await?;
+
+    let mut reshard = cluster.reshard(ShardScheme::try_from((0..2, 2))?);
+    let drive = async {
+        while let Some(progress) = reshard.next().await {
+            progress?;
+        }
+
+        Ok::<_, Box<dyn Error>>(())
+    };
+    let script = async {
+        let mut connections = Vec::new();
+
+        for _ in 0..2 {
+            let mut connection = gateway.accept().await.unwrap();
+            let identify = connection.recv_identify().await.unwrap();
+            connections.push((identify.d.shard.unwrap()[0], connection));
+        }
+
+        connections.sort_by_key(|(id, _)| *id);
+        let mut connections = connections.into_iter().map(|(_, connection)| connection);
+        let first = connections.next().unwrap();
+        let second = connections.next().unwrap();
+
+        // Received by the first new shard after it's ready, while the cluster
+        // waits for the second new shard.
+        first.ready("first", &[]);
+        first.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "2" }));
+        time::sleep(Duration::from_millis(100)).await;
+        second.ready("second", &[]);
+
+        (first, second)
+    };
+    let (result, (_first, second)) =
+        time::timeout(TIMEOUT, async { tokio::join!(drive, script) }).await?;
+    result?;
+    assert_eq!(2, cluster.shards().len());
+    assert_eq!(2, cluster.shard_scheme().total());
+
+    second.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "3" }));
+
+    let mut channel_ids = HashSet::new();
+    time::timeout(TIMEOUT, async {
+        while channel_ids.len() < 3 {
+            if let (_, Event::ChannelPinsUpdate(update)) = events.next().await.unwrap() {
+                channel_ids.insert(update.channel_id.get());
+            }
+        }
+    })
+    .await?;
+
+    assert_eq!(HashSet::from([1, 2, 3]), channel_ids);
+
+    cluster.down();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_restart_shared_cluster() -> Result<(), Box<dyn Error>> {
+    let mut gateway = MockGateway::bind().await?;
+    let (cluster, mut events) = Cluster::builder("token".to_owned(), Intents::GUILD_MESSAGES)
+        .event_types(EventTypeFlags::CHANNEL_PINS_UPDATE)
+        .gateway_url(gateway.url())
+        .queue(Arc::new(NoopQueue))
+        .shard_scheme(ShardScheme::try_from((0..1, 1))?)
+        .build()
+        .await?;
+    let cluster = Arc::new(cluster);
+    cluster.up().await;
+
+    // Events are consumed in another task while the shard is restarted.
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some((shard_id, event)) = events.next().await {
+            if let Event::ChannelPinsUpdate(update) = event {
+                let _res = tx.send((shard_id, update.channel_id.get()));
+            }
+        }
+    });
+
+    let mut connection = gateway.accept().await.unwrap();
+    connection.recv_identify().await.unwrap();
+    connection.ready("session", &[]);
+    connection.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "1" }));
+    assert_eq!(Some((0, 1)), time::timeout(TIMEOUT, rx.recv()).await?);
+
+    let restart = tokio::spawn({
+        let cluster = Arc::clone(&cluster);
+
+        async move { cluster.restart(0).await }
+    });
+
+    // A resumed shard's first connection is closed when it reconnects to
+    // resume the session.
+    let (connection, resume) = loop {
+        let mut connection = time::timeout(TIMEOUT, gateway.accept()).await?.unwrap();
+
+        if let Some(resume) = connection.recv_resume().await {
+            break (connection, resume);
+        }
+    };
+    assert_eq!("session", resume.d.session_id);
+    connection.set_sequence(resume.d.seq);
+    connection.resumed();
+    time::timeout(TIMEOUT, restart).await???;
+
+    connection.dispatch("CHANNEL_PINS_UPDATE", &json!({ "channel_id": "2" }));
+    assert_eq!(Some((0, 2)), time::timeout(TIMEOUT, rx.recv()).await?);
+    assert_eq!(1, cluster.shards().len());
+
+    cluster.down();
+
+    Ok(())
+}
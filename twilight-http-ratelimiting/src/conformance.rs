@@ -0,0 +1,544 @@
+//! Conformance test suite for [`Ratelimiter`] implementations.
+//!
+//! Implementors of custom ratelimiters, such as those backed by a database,
+//! can use this module to verify that their implementation behaves like the
+//! [`InMemoryRatelimiter`]. The suite drives a ratelimiter through scripted
+//! scenarios of [`RatelimitHeaders`] and checks that:
+//!
+//! - tickets for a [`Path`] are granted in the order they were requested;
+//! - a bucket with no remaining tickets waits for its reset before granting
+//!   the next ticket;
+//! - a global ratelimit blocks every path until it has passed;
+//! - headers sent via [`TicketSender::headers`] update the bucket returned by
+//!   [`Ratelimiter::bucket`];
+//! - exhausted buckets don't affect other paths;
+//! - tickets whose [`TicketSender`] is dropped don't stall the queue.
+//!
+//! Ratelimiters supporting [`Priority`] can additionally be checked with
+//! [`priority_order`], which is not part of [`run`] because supporting
+//! priorities is optional.
+//!
+//! Time is controlled through Tokio's paused clock, so tests must be run on a
+//! current-thread runtime with time paused. This requires Tokio's
+//! `test-util` feature, which is enabled by this crate's `conformance`
+//! feature.
+//!
+//! Ratelimiters must use [`tokio::time`] for measuring and sleeping for the
+//! clock to have an effect on them.
+//!
+//! # Examples
+//!
+//! Run the full suite against the [`InMemoryRatelimiter`]:
+//!
+//! ```
+//! use twilight_http_ratelimiting::{conformance, InMemoryRatelimiter};
+//!
+//! # #[tokio::main(flavor = "current_thread", start_paused = true)]
+//! # async fn main() {
+//! conformance::run(InMemoryRatelimiter::new).await;
+//! # }
+//! ```
+//!
+//! Script a custom scenario with a [`Harness`]:
+//!
+//! ```
+//! use std::time::Duration;
+//! use twilight_http_ratelimiting::{
+//!     conformance::{self, Harness},
+//!     InMemoryRatelimiter, Path,
+//! };
+//!
+//! # #[tokio::main(flavor = "current_thread", start_paused = true)]
+//! # async fn main() {
+//! let mut harness = Harness::new(InMemoryRatelimiter::new());
+//!
+//! let first = harness.ticket(Path::ChannelsId(1)).await;
+//! let second = harness.ticket(Path::ChannelsId(1)).await;
+//! harness.assert_granted(first);
+//!
+//! let headers = conformance::present(1, 0, Duration::from_secs(1));
+//! harness.respond(first, Some(headers)).await;
+//! harness.assert_pending(second);
+//!
+//! harness.advance(Duration::from_secs(1)).await;
+//! harness.assert_granted(second);
+//! # }
+//! ```
+//!
+//! [`InMemoryRatelimiter`]: crate::InMemoryRatelimiter
+//! [`TicketSender::headers`]: crate::ticket::TicketSender::headers
+
+use crate::{
+    headers::RatelimitHeaders,
+    request::{Path, Priority},
+    ticket::{TicketReceiver, TicketSender},
+    Ratelimiter,
+};
+use futures_util::FutureExt;
+use std::{mem, time::Duration};
+
+/// Number of times to yield to the runtime when letting a ratelimiter
+/// process its pending work.
+const SETTLE_YIELDS: usize = 64;
+
+/// Create bucketed ratelimit headers.
+///
+/// # Panics
+///
+/// Panics if the headers could not be parsed, which indicates a bug.
+#[must_use]
+pub fn present(limit: u64, remaining: u64, reset_after: Duration) -> RatelimitHeaders {
+    let limit = limit.to_string();
+    let remaining = remaining.to_string();
+    let reset_after = format!("{:.3}", reset_after.as_secs_f64());
+
+    let headers = [
+        ("x-ratelimit-bucket", "conformance".as_bytes()),
+        ("x-ratelimit-limit", limit.as_bytes()),
+        ("x-ratelimit-remaining", remaining.as_bytes()),
+        ("x-ratelimit-reset", "0.000".as_bytes()),
+        ("x-ratelimit-reset-after", reset_after.as_bytes()),
+    ];
+
+    RatelimitHeaders::from_pairs(headers.into_iter()).expect("present headers are valid")
+}
+
+/// Create global ratelimit headers.
+///
+/// Discord specifies the retry duration in whole seconds, so any sub-second
+/// precision of `retry_after` is discarded.
+///
+/// # Panics
+///
+/// Panics if the headers could not be parsed, which indicates a bug.
+#[must_use]
+pub fn global(retry_after: Duration) -> RatelimitHeaders {
+    let retry_after = retry_after.as_secs().to_string();
+
+    let headers = [
+        ("retry-after", retry_after.as_bytes()),
+        ("x-ratelimit-global", "true".as_bytes()),
+        ("x-ratelimit-scope", "global".as_bytes()),
+    ];
+
+    RatelimitHeaders::from_pairs(headers.into_iter()).expect("global headers are valid")
+}
+
+/// Identifier of a ticket requested through a [`Harness`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TicketId(usize);
+
+/// State of a ticket requested through a [`Harness`].
+#[derive(Debug)]
+enum TicketState {
+    /// Ticket has been requested but not yet granted.
+    Pending(TicketReceiver),
+    /// Ticket has been granted and the request may be performed.
+    Granted(TicketSender),
+    /// Ticket has been completed, dropped, or closed by the ratelimiter.
+    Finished,
+}
+
+/// Scriptable driver for a [`Ratelimiter`].
+///
+/// The harness keeps track of requested tickets and lets scenarios check
+/// which have been granted, respond to them with headers, and advance the
+/// clock.
+#[derive(Debug)]
+pub struct Harness<T> {
+    /// Ratelimiter under test.
+    ratelimiter: T,
+    /// Requested tickets, indexed by [`TicketId`].
+    tickets: Vec<TicketState>,
+}
+
+impl<T: Ratelimiter> Harness<T> {
+    /// Create a new harness around a ratelimiter.
+    pub const fn new(ratelimiter: T) -> Self {
+        Self {
+            ratelimiter,
+            tickets: Vec::new(),
+        }
+    }
+
+    /// Immutable reference to the ratelimiter under test.
+    pub const fn ratelimiter(&self) -> &T {
+        &self.ratelimiter
+    }
+
+    /// Request a ticket for a path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ratelimiter returns an error.
+    pub async fn ticket(&mut self, path: Path) -> TicketId {
+        self.ticket_with_priority(path, Priority::default()).await
+    }
+
+    /// Request a ticket for a path with a [`Priority`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ratelimiter returns an error.
+    pub async fn ticket_with_priority(&mut self, path: Path, priority: Priority) -> TicketId {
+        let rx = self
+            .ratelimiter
+            .ticket_with_priority(path, priority)
+            .await
+            .expect("ratelimiter failed to provide a ticket");
+        self.tickets.push(TicketState::Pending(rx));
+        self.settle().await;
+
+        TicketId(self.tickets.len() - 1)
+    }
+
+    /// Let the ratelimiter process its pending work without advancing the
+    /// clock.
+    pub async fn settle(&self) {
+        for _ in 0..SETTLE_YIELDS {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Advance the paused clock and let the ratelimiter process the work
+    /// that has become ready.
+    pub async fn advance(&self, duration: Duration) {
+        tokio::time::advance(duration).await;
+        self.settle().await;
+    }
+
+    /// Whether a ticket has been granted.
+    ///
+    /// Tickets that have already been responded to are not considered to be
+    /// granted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ticket ID is unknown.
+    pub fn is_granted(&mut self, id: TicketId) -> bool {
+        let state = self.tickets.get_mut(id.0).expect("unknown ticket");
+
+        if let TicketState::Pending(rx) = state {
+            match rx.now_or_never() {
+                Some(Ok(tx)) => *state = TicketState::Granted(tx),
+                Some(Err(_)) => *state = TicketState::Finished,
+                None => {}
+            }
+        }
+
+        matches!(state, TicketState::Granted(_))
+    }
+
+    /// Assert that a ticket has been granted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ticket has not been granted.
+    #[track_caller]
+    pub fn assert_granted(&mut self, id: TicketId) {
+        assert!(self.is_granted(id), "ticket {} should be granted", id.0);
+    }
+
+    /// Assert that a ticket has not been granted yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ticket has been granted or closed.
+    #[track_caller]
+    pub fn assert_pending(&mut self, id: TicketId) {
+        let granted = self.is_granted(id);
+
+        assert!(
+            !granted && matches!(self.tickets[id.0], TicketState::Pending(_)),
+            "ticket {} should be pending",
+            id.0,
+        );
+    }
+
+    /// Send the response's headers for a granted ticket to the ratelimiter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ticket has not been granted.
+    pub async fn respond(&mut self, id: TicketId, headers: Option<RatelimitHeaders>) {
+        let tx = self.take_granted(id);
+        // The ratelimiter may have timed out waiting for the headers, which
+        // is not the concern of the harness.
+        let _sent = tx.headers(headers);
+
+        self.settle().await;
+    }
+
+    /// Drop a granted ticket without sending any headers, as if the request
+    /// was canceled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ticket has not been granted.
+    pub async fn drop_ticket(&mut self, id: TicketId) {
+        drop(self.take_granted(id));
+
+        self.settle().await;
+    }
+
+    /// Take the sender of a granted ticket, marking it as finished.
+    #[track_caller]
+    fn take_granted(&mut self, id: TicketId) -> TicketSender {
+        assert!(self.is_granted(id), "ticket {} should be granted", id.0);
+
+        match mem::replace(&mut self.tickets[id.0], TicketState::Finished) {
+            TicketState::Granted(tx) => tx,
+            _ => unreachable!("ticket is granted"),
+        }
+    }
+}
+
+/// Run every conformance check against ratelimiters created by a factory.
+///
+/// A new ratelimiter is created for each check so that state doesn't leak
+/// between them.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn run<T: Ratelimiter>(mut factory: impl FnMut() -> T) {
+    ticket_order(factory()).await;
+    bucket_reset(factory()).await;
+    global_lock(factory()).await;
+    bucket_headers(factory()).await;
+    independent_paths(factory()).await;
+    dropped_ticket(factory()).await;
+}
+
+/// Check that tickets for a path are granted in the order they were
+/// requested, one at a time.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn ticket_order<T: Ratelimiter>(ratelimiter: T) {
+    const TICKETS: usize = 5;
+
+    let mut harness = Harness::new(ratelimiter);
+    let mut ids = Vec::with_capacity(TICKETS);
+
+    for _ in 0..TICKETS {
+        ids.push(harness.ticket(Path::ChannelsId(1)).await);
+    }
+
+    for (idx, id) in ids.iter().enumerate() {
+        harness.assert_granted(*id);
+
+        for later in &ids[idx + 1..] {
+            harness.assert_pending(*later);
+        }
+
+        harness.respond(*id, Some(RatelimitHeaders::None)).await;
+    }
+}
+
+/// Check that a bucket with no remaining tickets waits until it resets.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn bucket_reset<T: Ratelimiter>(ratelimiter: T) {
+    let reset_after = Duration::from_secs(2);
+    let mut harness = Harness::new(ratelimiter);
+
+    let first = harness.ticket(Path::ChannelsId(1)).await;
+    harness.assert_granted(first);
+    harness
+        .respond(first, Some(present(2, 1, reset_after)))
+        .await;
+
+    let second = harness.ticket(Path::ChannelsId(1)).await;
+    harness.assert_granted(second);
+    harness
+        .respond(second, Some(present(2, 0, reset_after)))
+        .await;
+
+    let third = harness.ticket(Path::ChannelsId(1)).await;
+    harness.assert_pending(third);
+
+    harness.advance(reset_after / 2).await;
+    harness.assert_pending(third);
+
+    harness.advance(reset_after / 2).await;
+    harness.assert_granted(third);
+}
+
+/// Check that a global ratelimit blocks all paths until it has passed.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn global_lock<T: Ratelimiter>(ratelimiter: T) {
+    let retry_after = Duration::from_secs(1);
+    let mut harness = Harness::new(ratelimiter);
+
+    let first = harness.ticket(Path::ChannelsId(1)).await;
+    harness.assert_granted(first);
+    harness.respond(first, Some(global(retry_after))).await;
+
+    assert!(
+        harness
+            .ratelimiter()
+            .is_globally_locked()
+            .await
+            .expect("ratelimiter failed to provide global status"),
+        "ratelimiter should be globally locked",
+    );
+
+    let other = harness.ticket(Path::ChannelsId(2)).await;
+    harness.assert_pending(other);
+
+    harness.advance(retry_after).await;
+    harness.assert_granted(other);
+
+    assert!(
+        !harness
+            .ratelimiter()
+            .is_globally_locked()
+            .await
+            .expect("ratelimiter failed to provide global status"),
+        "ratelimiter should no longer be globally locked",
+    );
+}
+
+/// Check that headers sent for a ticket update the path's bucket.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn bucket_headers<T: Ratelimiter>(ratelimiter: T) {
+    let path = Path::ChannelsId(1);
+    let mut harness = Harness::new(ratelimiter);
+
+    let first = harness.ticket(path.clone()).await;
+    harness.assert_granted(first);
+
+    assert!(
+        harness
+            .ratelimiter()
+            .has(&path)
+            .await
+            .expect("ratelimiter failed to check for bucket"),
+        "ratelimiter should have a bucket for a requested path",
+    );
+
+    harness
+        .respond(first, Some(present(5, 4, Duration::from_secs(3))))
+        .await;
+
+    let bucket = harness
+        .ratelimiter()
+        .bucket(&path)
+        .await
+        .expect("ratelimiter failed to provide bucket")
+        .expect("ratelimiter should have a bucket for a requested path");
+
+    assert_eq!(5, bucket.limit(), "bucket limit should be updated");
+    assert_eq!(4, bucket.remaining(), "bucket remaining should be updated");
+    assert_eq!(
+        Duration::from_secs(3),
+        bucket.reset_after(),
+        "bucket reset after should be updated",
+    );
+}
+
+/// Check that an exhausted bucket doesn't block requests to other paths.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn independent_paths<T: Ratelimiter>(ratelimiter: T) {
+    let mut harness = Harness::new(ratelimiter);
+
+    let first = harness.ticket(Path::ChannelsId(1)).await;
+    harness.assert_granted(first);
+    harness
+        .respond(first, Some(present(1, 0, Duration::from_secs(5))))
+        .await;
+
+    let exhausted = harness.ticket(Path::ChannelsId(1)).await;
+    let other = harness.ticket(Path::ChannelsId(2)).await;
+    harness.assert_pending(exhausted);
+    harness.assert_granted(other);
+}
+
+/// Check that dropping a granted ticket without sending headers doesn't stall
+/// the path's queue.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn dropped_ticket<T: Ratelimiter>(ratelimiter: T) {
+    let mut harness = Harness::new(ratelimiter);
+
+    let first = harness.ticket(Path::ChannelsId(1)).await;
+    let second = harness.ticket(Path::ChannelsId(1)).await;
+    harness.assert_granted(first);
+    harness.assert_pending(second);
+
+    harness.drop_ticket(first).await;
+    harness.assert_granted(second);
+}
+
+/// Check that tickets are granted by priority, and in the order they were
+/// requested within a priority.
+///
+/// # Panics
+///
+/// Panics if the ratelimiter does not conform.
+pub async fn priority_order<T: Ratelimiter>(ratelimiter: T) {
+    let path = Path::ChannelsId(1);
+    let mut harness = Harness::new(ratelimiter);
+
+    let in_flight = harness.ticket(path.clone()).await;
+    harness.assert_granted(in_flight);
+
+    let low = harness
+        .ticket_with_priority(path.clone(), Priority::Low)
+        .await;
+    let normal = harness
+        .ticket_with_priority(path.clone(), Priority::Normal)
+        .await;
+    let first_high = harness
+        .ticket_with_priority(path.clone(), Priority::High)
+        .await;
+    let second_high = harness.ticket_with_priority(path, Priority::High).await;
+
+    harness
+        .respond(in_flight, Some(RatelimitHeaders::None))
+        .await;
+
+    let expected = [first_high, second_high, normal, low];
+
+    for (idx, id) in expected.iter().enumerate() {
+        harness.assert_granted(*id);
+
+        for later in &expected[idx + 1..] {
+            harness.assert_pending(*later);
+        }
+
+        harness.respond(*id, Some(RatelimitHeaders::None)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Harness, TicketId};
+    use crate::InMemoryRatelimiter;
+    use static_assertions::assert_impl_all;
+    use std::{fmt::Debug, hash::Hash};
+
+    assert_impl_all!(Harness<InMemoryRatelimiter>: Debug, Send, Sync);
+    assert_impl_all!(TicketId: Clone, Copy, Debug, Eq, Hash, PartialEq, Send, Sync);
+
+    #[tokio::test(start_paused = true)]
+    async fn in_memory() {
+        super::run(InMemoryRatelimiter::new).await;
+        super::priority_order(InMemoryRatelimiter::new()).await;
+    }
+}
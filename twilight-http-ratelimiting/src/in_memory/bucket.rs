@@ -1,24 +1,25 @@
 //! [`Bucket`] management used by the [`super::InMemoryRatelimiter`] internally.
-//! Each bucket has an associated [`BucketQueue`] to queue an API request, which is
-//! consumed by the [`BucketQueueTask`] that manages the ratelimit for the bucket
-//! and respects the global ratelimit.
+//! Each bucket has an associated [`BucketQueue`] to queue an API request by
+//! [`Priority`], which is consumed by the [`BucketQueueTask`] that manages the
+//! ratelimit for the bucket and respects the global ratelimit.
 
 use super::GlobalLockPair;
-use crate::{headers::RatelimitHeaders, request::Path, ticket::TicketNotifier};
+use crate::{
+    headers::RatelimitHeaders,
+    request::{Path, Priority},
+    ticket::TicketNotifier,
+};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc, Mutex,
     },
-    time::{Duration, Instant},
+    time::Duration,
 };
 use tokio::{
-    sync::{
-        mpsc::{self, UnboundedReceiver, UnboundedSender},
-        Mutex as AsyncMutex,
-    },
-    time::{sleep, timeout},
+    sync::Notify,
+    time::{sleep, timeout, Instant},
 };
 
 /// Time remaining until a bucket will reset.
@@ -147,36 +148,50 @@ impl Bucket {
 }
 
 /// Queue of ratelimit requests for a bucket.
-#[derive(Debug)]
+///
+/// Requests are received by [`Priority`], and in order of insertion within a
+/// priority.
+#[derive(Debug, Default)]
 pub struct BucketQueue {
-    /// Receiver for the ratelimit requests.
-    rx: AsyncMutex<UnboundedReceiver<TicketNotifier>>,
-    /// Sender for the ratelimit requests.
-    tx: UnboundedSender<TicketNotifier>,
+    /// Notifier for new ratelimit requests.
+    notify: Notify,
+    /// Ratelimit requests, indexed by [`Priority::index`].
+    queues: Mutex<[VecDeque<TicketNotifier>; Priority::COUNT]>,
 }
 
 impl BucketQueue {
     /// Add a new ratelimit request to the queue.
-    pub fn push(&self, tx: TicketNotifier) {
-        let _sent = self.tx.send(tx);
+    pub fn push(&self, tx: TicketNotifier, priority: Priority) {
+        self.queues.lock().expect("queue poisoned")[priority.index()].push_back(tx);
+        self.notify.notify_one();
     }
 
-    /// Receive the first incoming ratelimit request.
+    /// Receive the first incoming ratelimit request of the highest priority.
     pub async fn pop(&self, timeout_duration: Duration) -> Option<TicketNotifier> {
-        let mut rx = self.rx.lock().await;
+        timeout(timeout_duration, async {
+            loop {
+                let notified = self.notify.notified();
 
-        timeout(timeout_duration, rx.recv()).await.ok().flatten()
-    }
-}
+                if let Some(tx) = self.try_pop() {
+                    return tx;
+                }
 
-impl Default for BucketQueue {
-    fn default() -> Self {
-        let (tx, rx) = mpsc::unbounded_channel();
+                notified.await;
+            }
+        })
+        .await
+        .ok()
+    }
 
-        Self {
-            rx: AsyncMutex::new(rx),
-            tx,
-        }
+    /// Take the first queued ratelimit request of the highest priority, if
+    /// any.
+    fn try_pop(&self) -> Option<TicketNotifier> {
+        self.queues
+            .lock()
+            .expect("queue poisoned")
+            .iter_mut()
+            .rev()
+            .find_map(VecDeque::pop_front)
     }
 }
 
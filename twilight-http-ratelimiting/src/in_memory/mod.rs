@@ -8,7 +8,8 @@ use super::{
     Bucket as InfoBucket, Ratelimiter,
 };
 use crate::{
-    request::Path, GetBucketFuture, GetTicketFuture, HasBucketFuture, IsGloballyLockedFuture,
+    request::{Path, Priority},
+    GetBucketFuture, GetTicketFuture, HasBucketFuture, IsGloballyLockedFuture,
 };
 use futures_util::future;
 use std::{
@@ -19,7 +20,7 @@ use std::{
     },
     time::Duration,
 };
-use tokio::sync::Mutex as AsyncMutex;
+use tokio::{sync::Mutex as AsyncMutex, time::Instant};
 
 /// Global lock. We use a pair to avoid actually locking the mutex every check.
 /// This allows futures to only wait on the global lock when a global ratelimit
@@ -48,6 +49,9 @@ impl GlobalLockPair {
 /// Default ratelimiter implementation used in twilight that
 /// stores ratelimit information in an in-memory mapping.
 ///
+/// Requests waiting on the same bucket are granted tickets by [`Priority`],
+/// and in order of retrieval within a priority.
+///
 /// This will meet most users' needs for simple ratelimiting,
 /// but for multi-processed bots, consider either implementing
 /// your own [`Ratelimiter`] that uses a shared storage backend
@@ -72,17 +76,18 @@ impl InMemoryRatelimiter {
         Self::default()
     }
 
-    /// Enqueue the [`TicketNotifier`] to the [`Path`]'s [`Bucket`].
+    /// Enqueue the [`TicketNotifier`] to the [`Path`]'s [`Bucket`] with a
+    /// [`Priority`].
     ///
     /// Returns the new [`Bucket`] if none existed.
-    fn entry(&self, path: Path, tx: TicketNotifier) -> Option<Arc<Bucket>> {
+    fn entry(&self, path: Path, tx: TicketNotifier, priority: Priority) -> Option<Arc<Bucket>> {
         let mut buckets = self.buckets.lock().expect("buckets poisoned");
 
         match buckets.entry(path.clone()) {
             Entry::Occupied(bucket) => {
                 tracing::debug!("got existing bucket: {path:?}");
 
-                bucket.get().queue.push(tx);
+                bucket.get().queue.push(tx, priority);
 
                 tracing::debug!("added request into bucket queue: {path:?}");
 
@@ -92,7 +97,7 @@ impl InMemoryRatelimiter {
                 tracing::debug!("making new bucket for path: {path:?}");
 
                 let bucket = Bucket::new(path);
-                bucket.queue.push(tx);
+                bucket.queue.push(tx, priority);
 
                 let bucket = Arc::new(bucket);
                 entry.insert(Arc::clone(&bucket));
@@ -118,7 +123,7 @@ impl Ratelimiter for InMemoryRatelimiter {
                         limit: bucket.limit(),
                         remaining: bucket.remaining(),
                         reset_after: Duration::from_millis(bucket.reset_after()),
-                        started_at: *started_at,
+                        started_at: started_at.map(Instant::into_std),
                     })))
                 },
             )
@@ -139,11 +144,15 @@ impl Ratelimiter for InMemoryRatelimiter {
     }
 
     fn ticket(&self, path: Path) -> GetTicketFuture {
+        self.ticket_with_priority(path, Priority::default())
+    }
+
+    fn ticket_with_priority(&self, path: Path, priority: Priority) -> GetTicketFuture {
         tracing::debug!("getting bucket for path: {path:?}");
 
         let (tx, rx) = ticket::channel();
 
-        if let Some(bucket) = self.entry(path.clone(), tx) {
+        if let Some(bucket) = self.entry(path.clone(), tx, priority) {
             tokio::spawn(
                 BucketQueueTask::new(
                     bucket,
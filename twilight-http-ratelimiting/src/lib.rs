@@ -17,8 +17,11 @@
     clippy::unnecessary_wraps,
     clippy::used_underscore_binding
 )]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
 
+#[cfg(any(test, feature = "conformance"))]
+pub mod conformance;
 pub mod headers;
 pub mod in_memory;
 pub mod request;
@@ -27,7 +30,7 @@ pub mod ticket;
 pub use self::{
     headers::RatelimitHeaders,
     in_memory::InMemoryRatelimiter,
-    request::{Method, Path},
+    request::{Method, Path, Priority},
 };
 
 use self::ticket::{TicketReceiver, TicketSender};
@@ -144,6 +147,20 @@ pub trait Ratelimiter: Debug + Send + Sync {
     /// available. Tickets are ready in order of retrieval.
     fn ticket(&self, path: Path) -> GetTicketFuture;
 
+    /// Retrieve a ticket with a [`Priority`] to know when to send a request.
+    ///
+    /// Tickets of a higher priority should be ready before tickets of a lower
+    /// priority that are waiting on the same bucket, while tickets of the
+    /// same priority are ready in order of retrieval.
+    ///
+    /// The default implementation ignores the priority and calls
+    /// [`Self::ticket`].
+    fn ticket_with_priority(&self, path: Path, priority: Priority) -> GetTicketFuture {
+        let _ = priority;
+
+        self.ticket(path)
+    }
+
     /// Retrieve a ticket to send a request.
     /// Other than [`Self::ticket`], this method will return
     /// a [`TicketSender`].
@@ -158,4 +175,22 @@ pub trait Ratelimiter: Debug + Send + Sync {
             }
         }))
     }
+
+    /// Retrieve a ticket with a [`Priority`] to send a request.
+    /// Other than [`Self::ticket_with_priority`], this method will return
+    /// a [`TicketSender`].
+    ///
+    /// This is identical to calling [`Self::ticket_with_priority`] and then
+    /// awaiting the [`TicketReceiver`].
+    fn wait_for_ticket_with_priority(&self, path: Path, priority: Priority) -> WaitForTicketFuture {
+        Box::pin(
+            self.ticket_with_priority(path, priority)
+                .then(|maybe_rx| async move {
+                    match maybe_rx {
+                        Ok(rx) => rx.await.map_err(From::from),
+                        Err(e) => Err(e),
+                    }
+                }),
+        )
+    }
 }
@@ -4,7 +4,8 @@
 //! relevant for ratelimiting.
 //!
 //! The [`super::Ratelimiter`] uses [`Path`]s and [`Method`]s to store
-//! and associate buckets with routes.
+//! and associate buckets with routes, and [`Priority`]s to order requests
+//! waiting on the same bucket.
 
 use http::Method as HttpMethod;
 use std::{
@@ -43,6 +44,46 @@ impl Method {
     }
 }
 
+/// Priority of a request waiting for a ratelimit ticket.
+///
+/// Ratelimiters supporting priorities grant tickets for higher priority
+/// requests before those of lower priority requests waiting on the same
+/// bucket. Requests of the same priority are granted in the order they were
+/// queued.
+///
+/// Defaults to [`Normal`].
+///
+/// [`Normal`]: Self::Normal
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Priority {
+    /// Background work that may be delayed, such as bulk role updates.
+    Low,
+    /// Default priority of requests.
+    Normal,
+    /// Time-sensitive work, such as responding to interactions.
+    High,
+}
+
+impl Priority {
+    /// Number of priority levels.
+    pub(crate) const COUNT: usize = 3;
+
+    /// Index of the priority level, from lowest to highest.
+    pub(crate) const fn index(self) -> usize {
+        match self {
+            Self::Low => 0,
+            Self::Normal => 1,
+            Self::High => 2,
+        }
+    }
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
 /// Error returned when a [`Path`] could not be parsed from a string.
 #[derive(Debug)]
 pub struct PathParseError {
@@ -459,7 +500,7 @@ impl TryFrom<(Method, &str)> for Path {
 
 #[cfg(test)]
 mod tests {
-    use super::{Path, PathParseError, PathParseErrorType};
+    use super::{Path, PathParseError, PathParseErrorType, Priority};
     use crate::request::Method;
     use http::Method as HttpMethod;
     use static_assertions::{assert_fields, assert_impl_all};
@@ -469,6 +510,30 @@ mod tests {
     assert_impl_all!(PathParseErrorType: Debug, Send, Sync);
     assert_impl_all!(PathParseError: Error, Send, Sync);
     assert_impl_all!(Path: Clone, Debug, Eq, Hash, PartialEq, Send, Sync);
+    assert_impl_all!(
+        Priority: Clone,
+        Copy,
+        Debug,
+        Default,
+        Eq,
+        Hash,
+        Ord,
+        PartialEq,
+        PartialOrd,
+        Send,
+        Sync
+    );
+
+    #[test]
+    fn priority_order() {
+        assert_eq!(Priority::Normal, Priority::default());
+        assert!(Priority::Low < Priority::Normal);
+        assert!(Priority::Normal < Priority::High);
+        assert_eq!(
+            [0, 1, 2],
+            [Priority::Low, Priority::Normal, Priority::High].map(Priority::index)
+        );
+    }
 
     #[test]
     fn prefix_unimportant() -> Result<(), Box<dyn Error>> {
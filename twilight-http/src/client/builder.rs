@@ -1,4 +1,7 @@
-use crate::{client::connector, Client};
+use crate::{
+    client::{coalesce::Coalescer, connector},
+    Client,
+};
 use hyper::header::HeaderMap;
 use std::{
     sync::{atomic::AtomicBool, Arc},
@@ -11,6 +14,7 @@ use twilight_model::channel::message::allowed_mentions::AllowedMentions;
 /// A builder for [`Client`].
 #[must_use = "has no effect if not built into a Client"]
 pub struct ClientBuilder {
+    coalesce_get_requests: bool,
     pub(crate) default_allowed_mentions: Option<AllowedMentions>,
     pub(crate) proxy: Option<Box<str>>,
     pub(crate) ratelimiter: Option<Box<dyn Ratelimiter>>,
@@ -39,7 +43,10 @@ impl ClientBuilder {
             None
         };
 
+        let coalescer = self.coalesce_get_requests.then(Coalescer::default);
+
         Client {
+            coalescer,
             http,
             default_headers: self.default_headers,
             proxy: self.proxy,
@@ -52,6 +59,29 @@ impl ClientBuilder {
         }
     }
 
+    /// Whether to coalesce concurrent identical `GET` requests.
+    ///
+    /// When enabled, a `GET` request made while an identical one is in flight
+    /// shares its response instead of acquiring another ratelimit ticket and
+    /// sending another request. Requests are identical if they are to the same
+    /// route, including its query string. Requests with custom headers, such
+    /// as an audit log reason, are never coalesced.
+    ///
+    /// The shared request is driven on the Tokio runtime the first request is
+    /// made on, so dropping its [`ResponseFuture`] doesn't cancel it for the
+    /// others. Consequently, [`ResponseFuture::set_pre_flight`] has no effect
+    /// on coalesced requests.
+    ///
+    /// The default is false.
+    ///
+    /// [`ResponseFuture`]: crate::response::ResponseFuture
+    /// [`ResponseFuture::set_pre_flight`]: crate::response::ResponseFuture::set_pre_flight
+    pub const fn coalesce_get_requests(mut self, coalesce: bool) -> Self {
+        self.coalesce_get_requests = coalesce;
+
+        self
+    }
+
     /// Set the default allowed mentions setting to use on all messages sent through the HTTP
     /// client.
     pub fn default_allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
@@ -150,6 +180,7 @@ impl ClientBuilder {
 impl Default for ClientBuilder {
     fn default() -> Self {
         Self {
+            coalesce_get_requests: false,
             default_allowed_mentions: None,
             default_headers: None,
             proxy: None,
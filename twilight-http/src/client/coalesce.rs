@@ -0,0 +1,317 @@
+//! Single-flight coalescing of identical `GET` requests.
+//!
+//! When enabled via [`ClientBuilder::coalesce_get_requests`], the first `GET`
+//! request to a route becomes the leader: it is driven to completion by a
+//! spawned task, which buffers the response body. Identical requests issued
+//! while the leader is in flight subscribe to its result instead of acquiring
+//! their own ratelimit ticket and hitting the API.
+//!
+//! [`ClientBuilder::coalesce_get_requests`]: super::ClientBuilder::coalesce_get_requests
+
+use crate::{
+    error::{Error, ErrorType},
+    response::future,
+};
+use hyper::{
+    body::{self, Bytes},
+    client::ResponseFuture as HyperResponseFuture,
+    header::HeaderMap,
+    Body, Response as HyperResponse, StatusCode as HyperStatusCode,
+};
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{runtime::Handle, sync::watch, time};
+use twilight_http_ratelimiting::WaitForTicketFuture;
+
+/// Future resolving to the response shared by a coalesced request.
+pub(crate) type CoalescedFuture =
+    Pin<Box<dyn Future<Output = Result<Arc<CoalescedResponse>, Error>> + Send + Sync + 'static>>;
+
+/// Identity of a request that may be coalesced.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct CoalesceKey {
+    /// Full path of the route, including its query string.
+    pub path: String,
+    /// Whether the request is sent with the client's authorization token.
+    pub use_authorization_token: bool,
+}
+
+/// Fully buffered response shared between coalesced requests.
+#[derive(Debug)]
+pub(crate) struct CoalescedResponse {
+    pub body: Bytes,
+    pub headers: HeaderMap,
+    pub status: HyperStatusCode,
+}
+
+impl CoalescedResponse {
+    /// Create an owned response for one of the subscribers.
+    pub fn to_response(&self) -> HyperResponse<Body> {
+        let mut response = HyperResponse::new(Body::from(self.body.clone()));
+        *response.headers_mut() = self.headers.clone();
+        *response.status_mut() = self.status;
+
+        response
+    }
+}
+
+/// Result of a coalesced request as observed by its subscribers.
+#[derive(Clone)]
+pub(crate) enum Outcome {
+    Failed {
+        kind: FailureKind,
+        source: Arc<dyn StdError + Send + Sync>,
+    },
+    Response(Arc<CoalescedResponse>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FailureKind {
+    ChunkingResponse,
+    RatelimiterTicket,
+    RequestError,
+    RequestTimedOut,
+}
+
+impl FailureKind {
+    const fn error_type(self) -> ErrorType {
+        match self {
+            Self::ChunkingResponse => ErrorType::ChunkingResponse,
+            Self::RatelimiterTicket => ErrorType::RatelimiterTicket,
+            Self::RequestError => ErrorType::RequestError,
+            Self::RequestTimedOut => ErrorType::RequestTimedOut,
+        }
+    }
+}
+
+/// Tracker of the coalesced requests currently in flight.
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    in_flight: Arc<Mutex<HashMap<CoalesceKey, watch::Receiver<Option<Outcome>>>>>,
+}
+
+impl Coalescer {
+    /// Subscribe to the in-flight request identified by the key, starting it
+    /// if there is none.
+    ///
+    /// `start` is only called when a new request needs to be sent, in which
+    /// case the returned future is driven to completion on the runtime.
+    pub fn subscribe<F, Fut>(&self, handle: &Handle, key: CoalesceKey, start: F) -> CoalescedFuture
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Outcome> + Send + 'static,
+    {
+        let mut in_flight = self.in_flight.lock().expect("in-flight requests poisoned");
+
+        if let Some(rx) = in_flight.get(&key) {
+            return Box::pin(wait(rx.clone()));
+        }
+
+        let (tx, rx) = watch::channel(None);
+        in_flight.insert(key.clone(), rx.clone());
+        drop(in_flight);
+
+        let future = start();
+        let map = Arc::clone(&self.in_flight);
+
+        handle.spawn(async move {
+            let outcome = future.await;
+
+            // Remove the entry before publishing the outcome so requests made
+            // afterwards are sent anew instead of receiving stale data.
+            map.lock()
+                .expect("in-flight requests poisoned")
+                .remove(&key);
+
+            let _res = tx.send(Some(outcome));
+        });
+
+        Box::pin(wait(rx))
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.in_flight
+            .lock()
+            .expect("in-flight requests poisoned")
+            .len()
+    }
+}
+
+impl Debug for Coalescer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Coalescer").finish_non_exhaustive()
+    }
+}
+
+/// Send the leading request of a coalesced group and buffer its response.
+pub(crate) async fn lead(
+    response_future: HyperResponseFuture,
+    timeout: Duration,
+    wait_for_sender: Option<WaitForTicketFuture>,
+) -> Outcome {
+    let tx = match wait_for_sender {
+        Some(wait_for_sender) => match wait_for_sender.await {
+            Ok(tx) => Some(tx),
+            Err(source) => {
+                return Outcome::Failed {
+                    kind: FailureKind::RatelimiterTicket,
+                    source: Arc::from(source),
+                }
+            }
+        },
+        None => None,
+    };
+
+    let response = match time::timeout(timeout, response_future).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(source)) => {
+            return Outcome::Failed {
+                kind: FailureKind::RequestError,
+                source: Arc::new(source),
+            }
+        }
+        Err(source) => {
+            return Outcome::Failed {
+                kind: FailureKind::RequestTimedOut,
+                source: Arc::new(source),
+            }
+        }
+    };
+
+    if let Some(tx) = tx {
+        future::send_ratelimit_headers(tx, &response);
+    }
+
+    let (parts, body) = response.into_parts();
+
+    match body::to_bytes(body).await {
+        Ok(body) => Outcome::Response(Arc::new(CoalescedResponse {
+            body,
+            headers: parts.headers,
+            status: parts.status,
+        })),
+        Err(source) => Outcome::Failed {
+            kind: FailureKind::ChunkingResponse,
+            source: Arc::new(source),
+        },
+    }
+}
+
+/// Wait for the outcome of a coalesced request to be published.
+async fn wait(mut rx: watch::Receiver<Option<Outcome>>) -> Result<Arc<CoalescedResponse>, Error> {
+    loop {
+        let outcome = rx.borrow().clone();
+
+        match outcome {
+            Some(Outcome::Response(response)) => return Ok(response),
+            Some(Outcome::Failed { kind, source }) => {
+                return Err(Error {
+                    kind: kind.error_type(),
+                    source: Some(Box::new(source)),
+                })
+            }
+            None => {}
+        }
+
+        // The task driving the request went away without publishing an
+        // outcome, such as when the runtime is shutting down.
+        if rx.changed().await.is_err() {
+            return Err(Error {
+                kind: ErrorType::RequestCanceled,
+                source: None,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoalesceKey, CoalescedResponse, Coalescer, Outcome};
+    use hyper::{body::Bytes, header::HeaderMap, StatusCode};
+    use static_assertions::assert_impl_all;
+    use std::{
+        fmt::Debug,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+    use tokio::{runtime::Handle, sync::oneshot};
+
+    assert_impl_all!(Coalescer: Debug, Default, Send, Sync);
+
+    fn key(path: &str) -> CoalesceKey {
+        CoalesceKey {
+            path: path.to_owned(),
+            use_authorization_token: true,
+        }
+    }
+
+    fn response(body: &'static [u8]) -> Outcome {
+        Outcome::Response(Arc::new(CoalescedResponse {
+            body: Bytes::from_static(body),
+            headers: HeaderMap::new(),
+            status: StatusCode::OK,
+        }))
+    }
+
+    #[tokio::test]
+    async fn shares_in_flight_request() {
+        let coalescer = Coalescer::default();
+        let handle = Handle::current();
+        let started = AtomicUsize::new(0);
+        let (tx, rx) = oneshot::channel();
+
+        let first = coalescer.subscribe(&handle, key("guilds/1"), || {
+            started.fetch_add(1, Ordering::Relaxed);
+
+            async move { rx.await.expect("sender dropped") }
+        });
+
+        let second = coalescer.subscribe(&handle, key("guilds/1"), || {
+            started.fetch_add(1, Ordering::Relaxed);
+
+            async { response(b"unused") }
+        });
+
+        let other = coalescer.subscribe(&handle, key("guilds/2"), || {
+            started.fetch_add(1, Ordering::Relaxed);
+
+            async { response(b"other") }
+        });
+
+        assert_eq!(2, started.load(Ordering::Relaxed));
+
+        tx.send(response(b"guild")).ok().expect("receiver dropped");
+
+        let first = first.await.expect("first response");
+        let second = second.await.expect("second response");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(b"guild".as_slice(), first.body);
+        assert_eq!(b"other".as_slice(), other.await.expect("other").body);
+
+        assert_eq!(0, coalescer.len());
+    }
+
+    #[tokio::test]
+    async fn completed_request_is_not_reused() {
+        let coalescer = Coalescer::default();
+        let handle = Handle::current();
+
+        let first = coalescer.subscribe(&handle, key("users/1"), || async { response(b"first") });
+
+        assert_eq!(b"first".as_slice(), first.await.expect("first").body);
+
+        let second = coalescer.subscribe(&handle, key("users/1"), || async { response(b"second") });
+
+        assert_eq!(b"second".as_slice(), second.await.expect("second").body);
+    }
+}
@@ -1,4 +1,5 @@
 mod builder;
+pub(crate) mod coalesce;
 mod connector;
 mod interaction;
 
@@ -6,9 +7,13 @@ pub use self::{builder::ClientBuilder, interaction::InteractionClient};
 
 #[allow(deprecated)]
 use crate::{
-    client::connector::Connector,
+    client::{
+        coalesce::{CoalesceKey, Coalescer},
+        connector::Connector,
+    },
     error::{Error, ErrorType},
     request::{
+        attachment::StreamingAttachment,
         channel::{
             invite::{CreateInvite, DeleteInvite, GetChannelInvites, GetInvite},
             message::{
@@ -91,7 +96,7 @@ use std::{
     },
     time::Duration,
 };
-use tokio::time;
+use tokio::{runtime::Handle, time};
 use twilight_http_ratelimiting::Ratelimiter;
 use twilight_model::{
     channel::{message::allowed_mentions::AllowedMentions, ChannelType},
@@ -191,6 +196,10 @@ const TWILIGHT_USER_AGENT: &str = concat!(
 /// [here]: https://discord.com/developers/applications
 #[derive(Debug)]
 pub struct Client {
+    /// Tracker of in-flight `GET` requests, if coalescing them is enabled.
+    ///
+    /// Configured via [`ClientBuilder::coalesce_get_requests`].
+    coalescer: Option<Coalescer>,
     pub(crate) default_allowed_mentions: Option<AllowedMentions>,
     default_headers: Option<HeaderMap>,
     http: HyperClient<Connector>,
@@ -2352,6 +2361,36 @@ impl Client {
         CreateGuildSticker::new(self, guild_id, name, description, tags, file)
     }
 
+    /// Creates a sticker in a guild from a file whose content is streamed when
+    /// the request is sent, and returns the created sticker.
+    ///
+    /// This is useful for uploading large files, such as Lottie animations,
+    /// without holding them in memory. Refer to [`create_guild_sticker`] for
+    /// creating a sticker from a file held in memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`DescriptionInvalid`] if the length is invalid.
+    ///
+    /// Returns an error of type [`NameInvalid`] if the length is invalid.
+    ///
+    /// Returns an error of type [`TagsInvalid`] if the length is invalid.
+    ///
+    /// [`DescriptionInvalid`]: twilight_validate::sticker::StickerValidationErrorType::DescriptionInvalid
+    /// [`NameInvalid`]: twilight_validate::sticker::StickerValidationErrorType::NameInvalid
+    /// [`TagsInvalid`]: twilight_validate::sticker::StickerValidationErrorType::TagsInvalid
+    /// [`create_guild_sticker`]: Self::create_guild_sticker
+    pub fn create_guild_sticker_streaming<'a>(
+        &'a self,
+        guild_id: Id<GuildMarker>,
+        name: &'a str,
+        description: &'a str,
+        tags: &'a str,
+        file: &'a StreamingAttachment,
+    ) -> Result<CreateGuildSticker<'a>, StickerValidationError> {
+        CreateGuildSticker::new_streaming(self, guild_id, name, description, tags, file)
+    }
+
     /// Updates a sticker in a guild, and returns the updated sticker.
     ///
     /// # Examples
@@ -2429,6 +2468,7 @@ impl Client {
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn try_request<T>(&self, request: Request) -> Result<ResponseFuture<T>, Error> {
         if let Some(token_invalidated) = self.token_invalidated.as_ref() {
             if token_invalidated.load(Ordering::Relaxed) {
@@ -2445,10 +2485,35 @@ impl Client {
             headers: req_headers,
             method,
             path,
+            priority,
             ratelimit_path,
+            streaming_form,
             use_authorization_token,
         } = request;
 
+        // Only plain `GET` requests are idempotent and safe to share. Requests
+        // with custom headers may receive different responses, and coalesced
+        // requests need a runtime to be driven on.
+        let coalesce = self
+            .coalescer
+            .as_ref()
+            .filter(|_| {
+                method == Method::Get
+                    && body.is_none()
+                    && form.is_none()
+                    && streaming_form.is_none()
+                    && req_headers.is_none()
+            })
+            .and_then(|coalescer| Some((coalescer, Handle::try_current().ok()?)))
+            .map(|(coalescer, handle)| {
+                let key = CoalesceKey {
+                    path: path.clone(),
+                    use_authorization_token,
+                };
+
+                (coalescer, handle, key)
+            });
+
         let protocol = if self.use_http { "http" } else { "https" };
         let host = self.proxy.as_deref().unwrap_or("discord.com");
 
@@ -2475,7 +2540,12 @@ impl Client {
         }
 
         if let Some(headers) = builder.headers_mut() {
-            if let Some(form) = &form {
+            if let Some(form) = &streaming_form {
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(form.len()));
+                if let Ok(content_type) = HeaderValue::try_from(form.content_type()) {
+                    headers.insert(CONTENT_TYPE, content_type);
+                }
+            } else if let Some(form) = &form {
                 headers.insert(CONTENT_LENGTH, HeaderValue::from(form.len()));
                 if let Ok(content_type) = HeaderValue::try_from(form.content_type()) {
                     headers.insert(CONTENT_TYPE, content_type);
@@ -2510,7 +2580,9 @@ impl Client {
             }
         }
 
-        let try_req = if let Some(form) = form {
+        let try_req = if let Some(form) = streaming_form {
+            builder.body(form.into_body())
+        } else if let Some(form) = form {
             builder.body(Body::from(form.build()))
         } else if let Some(bytes) = body {
             builder.body(Body::from(bytes))
@@ -2530,8 +2602,23 @@ impl Client {
             .then(|| self.token_invalidated.clone())
             .flatten();
 
+        if let Some((coalescer, handle, key)) = coalesce {
+            let timeout = self.timeout;
+            let ratelimiter = self.ratelimiter.as_deref();
+
+            let future = coalescer.subscribe(&handle, key, move || {
+                let tx_future = ratelimiter.map(|ratelimiter| {
+                    ratelimiter.wait_for_ticket_with_priority(ratelimit_path, priority)
+                });
+
+                self::coalesce::lead(inner, timeout, tx_future)
+            });
+
+            return Ok(ResponseFuture::coalesced(future, invalid_token));
+        }
+
         Ok(if let Some(ratelimiter) = &self.ratelimiter {
-            let tx_future = ratelimiter.wait_for_ticket(ratelimit_path);
+            let tx_future = ratelimiter.wait_for_ticket_with_priority(ratelimit_path, priority);
 
             ResponseFuture::ratelimit(invalid_token, inner, self.timeout, tx_future)
         } else {
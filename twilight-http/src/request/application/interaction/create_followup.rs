@@ -2,8 +2,8 @@ use crate::{
     client::Client,
     error::Error as HttpError,
     request::{
-        attachment::{AttachmentManager, PartialAttachment},
-        Nullable, Request, TryIntoRequest,
+        attachment::{AttachmentManager, PartialAttachment, StreamingAttachment},
+        Nullable, Priority, Request, TryIntoRequest,
     },
     response::ResponseFuture,
     routing::Route,
@@ -80,6 +80,7 @@ pub struct CreateFollowup<'a> {
     attachment_manager: AttachmentManager<'a>,
     fields: CreateFollowupFields<'a>,
     http: &'a Client,
+    priority: Priority,
     token: &'a str,
 }
 
@@ -103,6 +104,7 @@ impl<'a> CreateFollowup<'a> {
                 flags: None,
             },
             http,
+            priority: Priority::Normal,
             token,
         }
     }
@@ -238,6 +240,46 @@ impl<'a> CreateFollowup<'a> {
         self
     }
 
+    /// Set the priority of the request when waiting for a ratelimit ticket.
+    ///
+    /// Defaults to [`Priority::Normal`].
+    pub const fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Attach multiple files to the message whose content is streamed when
+    /// the request is sent.
+    ///
+    /// This is useful for uploading large files without holding them in
+    /// memory. Streamed attachments are sent in addition to those set via
+    /// [`attachments`].
+    ///
+    /// Calling this method will clear any previous calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`AttachmentFilename`] if any filename is
+    /// invalid.
+    ///
+    /// [`AttachmentFilename`]: twilight_validate::message::MessageValidationErrorType::AttachmentFilename
+    /// [`attachments`]: Self::attachments
+    pub fn streaming_attachments(
+        mut self,
+        attachments: &'a [StreamingAttachment],
+    ) -> Result<Self, MessageValidationError> {
+        attachments
+            .iter()
+            .try_for_each(|attachment| validate_attachment_filename(&attachment.filename))?;
+
+        self.attachment_manager = self
+            .attachment_manager
+            .set_streams(attachments.iter().collect());
+
+        Ok(self)
+    }
+
     /// Specify true if the message is TTS.
     pub const fn tts(mut self, tts: bool) -> Self {
         self.fields.tts = Some(tts);
@@ -282,29 +324,33 @@ impl TryIntoRequest for CreateFollowup<'_> {
         // body.
         if !self.attachment_manager.is_empty() {
             let form = if let Some(payload_json) = self.fields.payload_json {
-                self.attachment_manager.build_form(payload_json)
+                self.attachment_manager.build_streaming_form(payload_json)
             } else {
                 self.fields.attachments = Some(self.attachment_manager.get_partial_attachments());
 
                 let fields = crate::json::to_vec(&self.fields).map_err(HttpError::json)?;
 
-                self.attachment_manager.build_form(fields.as_ref())
+                self.attachment_manager
+                    .build_streaming_form(fields.as_ref())
             };
 
-            request = request.form(form);
+            request = request.streaming_form(form);
         } else if let Some(payload_json) = self.fields.payload_json {
             request = request.body(payload_json.to_vec());
         } else {
             request = request.json(&self.fields)?;
         }
 
-        Ok(request.build())
+        Ok(request.priority(self.priority).build())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{client::Client, request::TryIntoRequest};
+    use crate::{
+        client::Client,
+        request::{Priority, TryIntoRequest},
+    };
     use std::error::Error;
     use twilight_http_ratelimiting::Path;
     use twilight_model::id::Id;
@@ -322,6 +368,7 @@ mod tests {
             .try_into_request()?;
 
         assert!(!req.use_authorization_token());
+        assert_eq!(Priority::Normal, req.priority());
         assert_eq!(
             &Path::WebhooksIdToken(application_id.get(), token),
             req.ratelimit_path()
@@ -329,4 +376,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn create_followup_message_priority() -> Result<(), Box<dyn Error>> {
+        let client = Client::new(String::new());
+        let req = client
+            .interaction(Id::new(1))
+            .create_followup("foo")
+            .content("test")?
+            .priority(Priority::High)
+            .try_into_request()?;
+
+        assert_eq!(Priority::High, req.priority());
+
+        Ok(())
+    }
 }
@@ -1,14 +1,21 @@
 use crate::{
     client::Client,
-    error::Error as HttpError,
-    request::{attachment::AttachmentManager, Request, TryIntoRequest},
+    error::{Error as HttpError, ErrorType},
+    request::{
+        attachment::{AttachmentManager, PartialAttachment, StreamingAttachment},
+        Priority, Request, TryIntoRequest,
+    },
     response::{marker::EmptyBody, ResponseFuture},
     routing::Route,
 };
+use serde_json::{Map, Value};
 use twilight_model::{
     http::interaction::InteractionResponse,
     id::{marker::InteractionMarker, Id},
 };
+use twilight_validate::message::{
+    attachment_filename as validate_attachment_filename, MessageValidationError,
+};
 
 /// Respond to an interaction, by its ID and token.
 ///
@@ -19,6 +26,8 @@ pub struct CreateResponse<'a> {
     interaction_token: &'a str,
     response: &'a InteractionResponse,
     http: &'a Client,
+    priority: Priority,
+    streams: &'a [StreamingAttachment],
 }
 
 impl<'a> CreateResponse<'a> {
@@ -33,9 +42,48 @@ impl<'a> CreateResponse<'a> {
             interaction_token,
             response,
             http,
+            priority: Priority::Normal,
+            streams: &[],
         }
     }
 
+    /// Set the priority of the request when waiting for a ratelimit ticket.
+    ///
+    /// Defaults to [`Priority::Normal`].
+    pub const fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Attach multiple files to the response whose content is streamed when
+    /// the request is sent.
+    ///
+    /// This is useful for uploading large files without holding them in
+    /// memory. Streamed attachments are sent in addition to the attachments
+    /// of the response's data.
+    ///
+    /// Calling this method will clear any previous calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`AttachmentFilename`] if any filename is
+    /// invalid.
+    ///
+    /// [`AttachmentFilename`]: twilight_validate::message::MessageValidationErrorType::AttachmentFilename
+    pub fn streaming_attachments(
+        mut self,
+        attachments: &'a [StreamingAttachment],
+    ) -> Result<Self, MessageValidationError> {
+        attachments
+            .iter()
+            .try_for_each(|attachment| validate_attachment_filename(&attachment.filename))?;
+
+        self.streams = attachments;
+
+        Ok(self)
+    }
+
     /// Execute the request, returning a future resolving to a [`Response`].
     ///
     /// [`Response`]: crate::response::Response
@@ -62,30 +110,72 @@ impl TryIntoRequest for CreateResponse<'_> {
 
         // Determine whether we need to use a multipart/form-data body or a JSON
         // body.
-        if let Some(attachments) = self
+        let attachments = self
             .response
             .data
             .as_ref()
-            .and_then(|data| data.attachments.as_ref())
-        {
-            let fields = crate::json::to_vec(&self.response).map_err(HttpError::json)?;
-
-            let form = AttachmentManager::new()
-                .set_files(attachments.iter().collect())
-                .build_form(&fields);
-
-            request = request.form(form);
+            .and_then(|data| data.attachments.as_ref());
+
+        if attachments.is_some() || !self.streams.is_empty() {
+            let manager = AttachmentManager::new()
+                .set_files(attachments.map(|a| a.iter().collect()).unwrap_or_default())
+                .set_streams(self.streams.iter().collect());
+
+            let fields = if self.streams.is_empty() {
+                crate::json::to_vec(&self.response).map_err(HttpError::json)?
+            } else {
+                streamed_fields(self.response, &manager.get_partial_attachments()).map_err(
+                    |source| HttpError {
+                        kind: ErrorType::Json,
+                        source: Some(Box::new(source)),
+                    },
+                )?
+            };
+
+            request = request.streaming_form(manager.build_streaming_form(&fields));
         } else {
             request = request.json(&self.response)?;
         }
 
-        Ok(request.build())
+        Ok(request.priority(self.priority).build())
     }
 }
 
+/// Serialize a response, listing the partial attachments in its data.
+///
+/// The response's data is created if there is none.
+fn streamed_fields(
+    response: &InteractionResponse,
+    attachments: &[PartialAttachment<'_>],
+) -> Result<Vec<u8>, serde_json::Error> {
+    let mut value = serde_json::to_value(response)?;
+
+    if let Some(response) = value.as_object_mut() {
+        let data = response
+            .entry("data")
+            .or_insert_with(|| Value::Object(Map::new()));
+
+        if data.is_null() {
+            *data = Value::Object(Map::new());
+        }
+
+        if let Some(data) = data.as_object_mut() {
+            data.insert("attachments".to_owned(), serde_json::to_value(attachments)?);
+        }
+    }
+
+    serde_json::to_vec(&value)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{client::Client, request::TryIntoRequest};
+    use crate::{
+        client::Client,
+        request::{
+            attachment::{AttachmentManager, StreamingAttachment},
+            TryIntoRequest,
+        },
+    };
     use std::error::Error;
     use twilight_http_ratelimiting::Path;
     use twilight_model::{
@@ -119,4 +209,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn streaming_attachments() -> Result<(), Box<dyn Error>> {
+        let client = Client::new(String::new());
+
+        let response = InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: None,
+        };
+        let attachments = [StreamingAttachment::from_reader(
+            "video.mp4".to_owned(),
+            &b"video"[..],
+            5,
+            1,
+        )];
+
+        let req = client
+            .interaction(Id::new(1))
+            .create_response(Id::new(2), "foo", &response)
+            .streaming_attachments(&attachments)?
+            .try_into_request()?;
+
+        assert!(req.form().is_none() && req.streaming_form.is_some());
+
+        let fields = super::streamed_fields(
+            &response,
+            &AttachmentManager::new()
+                .set_streams(attachments.iter().collect())
+                .get_partial_attachments(),
+        )?;
+
+        assert_eq!(
+            serde_json::json!({
+                "type": 4,
+                "data": {
+                    "attachments": [{ "filename": "video.mp4", "id": 1 }],
+                },
+            }),
+            serde_json::from_slice::<serde_json::Value>(&fields)?,
+        );
+
+        Ok(())
+    }
 }
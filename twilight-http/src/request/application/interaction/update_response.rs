@@ -339,7 +339,7 @@ impl TryIntoRequest for UpdateResponse<'_> {
         // body.
         if !self.attachment_manager.is_empty() {
             let form = if let Some(payload_json) = self.fields.payload_json {
-                self.attachment_manager.build_form(payload_json)
+                self.attachment_manager.build_streaming_form(payload_json)
             } else {
                 self.fields.attachments = Some(Nullable(Some(
                     self.attachment_manager.get_partial_attachments(),
@@ -347,10 +347,11 @@ impl TryIntoRequest for UpdateResponse<'_> {
 
                 let fields = crate::json::to_vec(&self.fields).map_err(HttpError::json)?;
 
-                self.attachment_manager.build_form(fields.as_ref())
+                self.attachment_manager
+                    .build_streaming_form(fields.as_ref())
             };
 
-            request = request.form(form);
+            request = request.streaming_form(form);
         } else if let Some(payload_json) = self.fields.payload_json {
             request = request.body(payload_json.to_vec());
         } else {
@@ -1,13 +1,204 @@
-use crate::request::Form;
+use crate::request::{Form, StreamingForm};
 use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    io::{Error as IoError, ErrorKind as IoErrorKind},
+    sync::{Arc, Mutex},
+};
+use tokio::io::AsyncRead;
+
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
 use twilight_model::{
     http::attachment::Attachment,
     id::{marker::AttachmentMarker, Id},
 };
 
+/// Reader providing the content of a [`StreamingAttachment`].
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Attachment whose content is streamed when the request is sent.
+///
+/// Unlike an [`Attachment`], the content of the file isn't held in memory.
+/// Instead it is read from an [`AsyncRead`]er, or a path if the `fs` feature
+/// is enabled, while the request body is being sent, which is useful for
+/// uploading large files such as videos.
+///
+/// The length of the content must be known ahead of time, as it is used for
+/// the request's `Content-Length`.
+///
+/// # Examples
+///
+/// Upload a video from a reader:
+///
+/// ```no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use twilight_http::{request::attachment::StreamingAttachment, Client};
+/// use twilight_model::id::Id;
+///
+/// let client = Client::new("my token".to_owned());
+/// let video: &'static [u8] = &[];
+///
+/// let attachment =
+///     StreamingAttachment::from_reader("video.mp4".to_owned(), video, video.len() as u64, 1);
+///
+/// client
+///     .create_message(Id::new(1))
+///     .streaming_attachments(&[attachment])?
+///     .exec()
+///     .await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct StreamingAttachment {
+    /// Description of the attachment, useful for screen readers and users
+    /// requiring alt text.
+    pub description: Option<String>,
+    /// Name of the file.
+    pub filename: String,
+    /// Unique ID of the attachment in the message.
+    pub id: u64,
+    /// Length of the content in bytes.
+    length: u64,
+    /// Source of the content.
+    source: AttachmentSource,
+}
+
+impl StreamingAttachment {
+    /// Create an attachment from a filename and a path to a file.
+    ///
+    /// The file is opened when the request is sent, so it may be sent
+    /// multiple times.
+    ///
+    /// # Examples
+    ///
+    /// Upload a video from a file:
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use twilight_http::{request::attachment::StreamingAttachment, Client};
+    /// use twilight_model::id::Id;
+    ///
+    /// let client = Client::new("my token".to_owned());
+    ///
+    /// let attachment =
+    ///     StreamingAttachment::from_path("video.mp4".to_owned(), "/tmp/video.mp4".into(), 1)
+    ///         .await?;
+    ///
+    /// client
+    ///     .create_message(Id::new(1))
+    ///     .streaming_attachments(&[attachment])?
+    ///     .exec()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`IoError`] if the metadata of the file could not be
+    /// read.
+    #[cfg(feature = "fs")]
+    pub async fn from_path(filename: String, path: PathBuf, id: u64) -> Result<Self, IoError> {
+        let length = tokio::fs::metadata(&path).await?.len();
+
+        Ok(Self {
+            description: None,
+            filename,
+            id,
+            length,
+            source: AttachmentSource::Path(path),
+        })
+    }
+
+    /// Create an attachment from a filename and a reader of a known length.
+    ///
+    /// The reader is consumed when the request is sent, so the request may
+    /// only be sent once. Sending the request fails if the reader provides
+    /// less than `length` bytes; any bytes after `length` are not read.
+    pub fn from_reader(
+        filename: String,
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        length: u64,
+        id: u64,
+    ) -> Self {
+        Self {
+            description: None,
+            filename,
+            id,
+            length,
+            source: AttachmentSource::Reader(Arc::new(Mutex::new(Some(Box::new(reader))))),
+        }
+    }
+
+    /// Set the description of the attachment.
+    ///
+    /// Attachment descriptions are useful for those requiring screen readers
+    /// and are displayed as alt text.
+    pub fn description(&mut self, description: String) {
+        self.description = Some(description);
+    }
+
+    /// Length of the content in bytes.
+    pub const fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Source of the content.
+    pub(crate) const fn source(&self) -> &AttachmentSource {
+        &self.source
+    }
+}
+
+/// Source of a [`StreamingAttachment`]'s content.
+#[derive(Clone)]
+pub(crate) enum AttachmentSource {
+    /// File at a path, opened when the content is read.
+    #[cfg(feature = "fs")]
+    Path(PathBuf),
+    /// Reader that may only be consumed once.
+    Reader(Arc<Mutex<Option<BoxedReader>>>),
+}
+
+impl AttachmentSource {
+    /// Open the source for reading.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`IoError`] if the file could not be opened or if the reader
+    /// has already been consumed.
+    #[cfg_attr(not(feature = "fs"), allow(clippy::unused_async))]
+    pub async fn open(self) -> Result<BoxedReader, IoError> {
+        match self {
+            #[cfg(feature = "fs")]
+            Self::Path(path) => Ok(Box::new(tokio::fs::File::open(path).await?)),
+            Self::Reader(reader) => reader
+                .lock()
+                .expect("attachment reader poisoned")
+                .take()
+                .ok_or_else(|| {
+                    IoError::new(
+                        IoErrorKind::Other,
+                        "attachment reader has already been consumed",
+                    )
+                }),
+        }
+    }
+}
+
+impl Debug for AttachmentSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            #[cfg(feature = "fs")]
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Reader(_) => f.write_str("Reader"),
+        }
+    }
+}
+
 pub struct AttachmentManager<'a> {
     files: Vec<&'a Attachment>,
     ids: Vec<Id<AttachmentMarker>>,
+    streams: Vec<&'a StreamingAttachment>,
 }
 
 impl<'a> AttachmentManager<'a> {
@@ -15,6 +206,7 @@ impl<'a> AttachmentManager<'a> {
         Self {
             files: Vec::new(),
             ids: Vec::new(),
+            streams: Vec::new(),
         }
     }
 
@@ -22,10 +214,7 @@ impl<'a> AttachmentManager<'a> {
         let mut form = Form::new().json_part(b"payload_json", fields);
 
         for file in &self.files {
-            let mut name = Vec::with_capacity(7 + num_digits(file.id));
-            name.extend(b"files[");
-            push_digits(file.id, &mut name);
-            name.extend(b"]");
+            let name = file_part_name(file.id);
 
             form = form.file_part(name.as_ref(), file.filename.as_bytes(), file.file.as_ref());
         }
@@ -33,6 +222,25 @@ impl<'a> AttachmentManager<'a> {
         form
     }
 
+    /// Build a form whose streamed attachments are streamed from their
+    /// sources when it's sent.
+    pub(crate) fn build_streaming_form(&self, fields: &'a [u8]) -> StreamingForm {
+        let mut form = StreamingForm::from(self.build_form(fields));
+
+        for stream in &self.streams {
+            let name = file_part_name(stream.id);
+
+            form = form.stream_file_part(
+                name.as_ref(),
+                stream.filename.as_bytes(),
+                stream.source().clone(),
+                stream.length(),
+            );
+        }
+
+        form
+    }
+
     pub fn get_partial_attachments(&self) -> Vec<PartialAttachment<'a>> {
         self.files
             .iter()
@@ -41,6 +249,11 @@ impl<'a> AttachmentManager<'a> {
                 filename: Some(attachment.filename.as_ref()),
                 id: attachment.id,
             })
+            .chain(self.streams.iter().map(|attachment| PartialAttachment {
+                description: attachment.description.as_deref(),
+                filename: Some(attachment.filename.as_ref()),
+                id: attachment.id,
+            }))
             .chain(self.ids.iter().map(|id| PartialAttachment {
                 description: None,
                 filename: None,
@@ -50,7 +263,7 @@ impl<'a> AttachmentManager<'a> {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.files.is_empty() && self.ids.is_empty()
+        self.files.is_empty() && self.ids.is_empty() && self.streams.is_empty()
     }
 
     #[must_use = "has no effect if not built into a Form"]
@@ -66,6 +279,13 @@ impl<'a> AttachmentManager<'a> {
 
         self
     }
+
+    #[must_use = "has no effect if not built into a Form"]
+    pub(crate) fn set_streams(mut self, streams: Vec<&'a StreamingAttachment>) -> Self {
+        self.streams = streams;
+
+        self
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -77,6 +297,16 @@ pub struct PartialAttachment<'a> {
     pub id: u64,
 }
 
+/// Name of the form part of an attachment with the given ID.
+fn file_part_name(id: u64) -> Vec<u8> {
+    let mut name = Vec::with_capacity(7 + num_digits(id));
+    name.extend(b"files[");
+    push_digits(id, &mut name);
+    name.extend(b"]");
+
+    name
+}
+
 /// Count the number of digits in a given number.
 const fn num_digits(index: u64) -> usize {
     let mut index = index;
@@ -1,4 +1,4 @@
-use super::{Form, Method};
+use super::{Form, Method, Priority, StreamingForm};
 use crate::{
     error::Error,
     routing::{Path, Route},
@@ -70,7 +70,9 @@ impl RequestBuilder {
             headers: None,
             method,
             path: path_and_query,
+            priority: Priority::Normal,
             ratelimit_path,
+            streaming_form: None,
             use_authorization_token: true,
         })
     }
@@ -93,10 +95,27 @@ impl RequestBuilder {
     #[allow(clippy::missing_const_for_fn)]
     pub fn form(mut self, form: Form) -> Self {
         self.0.form = Some(form);
+        self.0.streaming_form = None;
 
         self
     }
 
+    /// Set a multipart form whose parts may be streamed when the request is
+    /// sent.
+    ///
+    /// Forms without streamed parts are set as the multipart form.
+    pub(crate) fn streaming_form(mut self, form: StreamingForm) -> Self {
+        match form.into_form() {
+            Ok(form) => self.form(form),
+            Err(form) => {
+                self.0.form = None;
+                self.0.streaming_form = Some(form);
+
+                self
+            }
+        }
+    }
+
     /// Set the headers to add.
     pub fn headers(mut self, iter: impl Iterator<Item = (HeaderName, HeaderValue)>) -> Self {
         self.0.headers.replace(iter.collect());
@@ -104,6 +123,40 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the priority of the request when waiting for a ratelimit ticket.
+    ///
+    /// Higher priority requests are sent before lower priority requests
+    /// waiting on the same ratelimit bucket. Defaults to
+    /// [`Priority::Normal`].
+    ///
+    /// # Examples
+    ///
+    /// Prioritize creating a followup message to an interaction over other
+    /// queued requests:
+    ///
+    /// ```
+    /// use twilight_http::{
+    ///     request::{Priority, Request},
+    ///     routing::Route,
+    /// };
+    ///
+    /// let request = Request::builder(&Route::ExecuteWebhook {
+    ///     thread_id: None,
+    ///     token: "token",
+    ///     wait: None,
+    ///     webhook_id: 1,
+    /// })
+    /// .priority(Priority::High)
+    /// .build();
+    ///
+    /// assert_eq!(Priority::High, request.priority());
+    /// ```
+    pub const fn priority(mut self, priority: Priority) -> Self {
+        self.0.priority = priority;
+
+        self
+    }
+
     /// Set the body, to be serialized as JSON.
     ///
     /// # Errors
@@ -136,7 +189,9 @@ pub struct Request {
     pub(crate) headers: Option<HeaderMap<HeaderValue>>,
     pub(crate) method: Method,
     pub(crate) path: String,
+    pub(crate) priority: Priority,
     pub(crate) ratelimit_path: Path,
+    pub(crate) streaming_form: Option<StreamingForm>,
     pub(crate) use_authorization_token: bool,
 }
 
@@ -191,7 +246,9 @@ impl Request {
             headers: None,
             method: route.method(),
             path: route.to_string(),
+            priority: Priority::Normal,
             ratelimit_path: route.to_path(),
+            streaming_form: None,
             use_authorization_token: true,
         }
     }
@@ -221,6 +278,21 @@ impl Request {
         &self.path
     }
 
+    /// Priority of the request when waiting for a ratelimit ticket.
+    pub const fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Set the priority of the request when waiting for a ratelimit ticket.
+    ///
+    /// This is useful for adjusting the priority of requests created by
+    /// [`TryIntoRequest`].
+    ///
+    /// [`TryIntoRequest`]: super::TryIntoRequest
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
     /// Path used for ratelimiting.
     pub const fn ratelimit_path(&self) -> &Path {
         &self.ratelimit_path
@@ -2,8 +2,8 @@ use crate::{
     client::Client,
     error::Error as HttpError,
     request::{
-        attachment::{AttachmentManager, PartialAttachment},
-        Nullable, Request, TryIntoRequest,
+        attachment::{AttachmentManager, PartialAttachment, StreamingAttachment},
+        Nullable, Priority, Request, TryIntoRequest,
     },
     response::ResponseFuture,
     routing::Route,
@@ -88,6 +88,7 @@ pub struct CreateMessage<'a> {
     channel_id: Id<ChannelMarker>,
     fields: CreateMessageFields<'a>,
     http: &'a Client,
+    priority: Priority,
 }
 
 impl<'a> CreateMessage<'a> {
@@ -109,6 +110,7 @@ impl<'a> CreateMessage<'a> {
                 tts: None,
             },
             http,
+            priority: Priority::Normal,
         }
     }
 
@@ -274,6 +276,15 @@ impl<'a> CreateMessage<'a> {
         self
     }
 
+    /// Set the priority of the request when waiting for a ratelimit ticket.
+    ///
+    /// Defaults to [`Priority::Normal`].
+    pub const fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
     /// Specify the ID of another message to create a reply to.
     pub const fn reply(mut self, other: Id<MessageMarker>) -> Self {
         let channel_id = self.channel_id;
@@ -318,6 +329,37 @@ impl<'a> CreateMessage<'a> {
         Ok(self)
     }
 
+    /// Attach multiple files to the message whose content is streamed when
+    /// the request is sent.
+    ///
+    /// This is useful for uploading large files without holding them in
+    /// memory. Streamed attachments are sent in addition to those set via
+    /// [`attachments`].
+    ///
+    /// Calling this method will clear any previous calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`AttachmentFilename`] if any filename is
+    /// invalid.
+    ///
+    /// [`AttachmentFilename`]: twilight_validate::message::MessageValidationErrorType::AttachmentFilename
+    /// [`attachments`]: Self::attachments
+    pub fn streaming_attachments(
+        mut self,
+        attachments: &'a [StreamingAttachment],
+    ) -> Result<Self, MessageValidationError> {
+        attachments
+            .iter()
+            .try_for_each(|attachment| validate_attachment_filename(&attachment.filename))?;
+
+        self.attachment_manager = self
+            .attachment_manager
+            .set_streams(attachments.iter().collect());
+
+        Ok(self)
+    }
+
     /// Specify true if the message is TTS.
     pub const fn tts(mut self, tts: bool) -> Self {
         self.fields.tts = Some(tts);
@@ -355,22 +397,23 @@ impl TryIntoRequest for CreateMessage<'_> {
         // body.
         if !self.attachment_manager.is_empty() {
             let form = if let Some(payload_json) = self.fields.payload_json {
-                self.attachment_manager.build_form(payload_json)
+                self.attachment_manager.build_streaming_form(payload_json)
             } else {
                 self.fields.attachments = Some(self.attachment_manager.get_partial_attachments());
 
                 let fields = crate::json::to_vec(&self.fields).map_err(HttpError::json)?;
 
-                self.attachment_manager.build_form(fields.as_ref())
+                self.attachment_manager
+                    .build_streaming_form(fields.as_ref())
             };
 
-            request = request.form(form);
+            request = request.streaming_form(form);
         } else if let Some(payload_json) = self.fields.payload_json {
             request = request.body(payload_json.to_vec());
         } else {
             request = request.json(&self.fields)?;
         }
 
-        Ok(request.build())
+        Ok(request.priority(self.priority).build())
     }
 }
@@ -2,7 +2,7 @@ use crate::{
     client::Client,
     error::Error as HttpError,
     request::{
-        attachment::{AttachmentManager, PartialAttachment},
+        attachment::{AttachmentManager, PartialAttachment, StreamingAttachment},
         channel::webhook::ExecuteWebhookAndWait,
         Nullable, Request, TryIntoRequest,
     },
@@ -306,6 +306,37 @@ impl<'a> ExecuteWebhook<'a> {
         self
     }
 
+    /// Attach multiple files to the message whose content is streamed when
+    /// the request is sent.
+    ///
+    /// This is useful for uploading large files without holding them in
+    /// memory. Streamed attachments are sent in addition to those set via
+    /// [`attachments`].
+    ///
+    /// Calling this method will clear any previous calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`AttachmentFilename`] if any filename is
+    /// invalid.
+    ///
+    /// [`AttachmentFilename`]: twilight_validate::message::MessageValidationErrorType::AttachmentFilename
+    /// [`attachments`]: Self::attachments
+    pub fn streaming_attachments(
+        mut self,
+        attachments: &'a [StreamingAttachment],
+    ) -> Result<Self, MessageValidationError> {
+        attachments
+            .iter()
+            .try_for_each(|attachment| validate_attachment_filename(&attachment.filename))?;
+
+        self.attachment_manager = self
+            .attachment_manager
+            .set_streams(attachments.iter().collect());
+
+        Ok(self)
+    }
+
     /// Execute in a thread belonging to the channel instead of the channel itself.
     pub fn thread_id(mut self, thread_id: Id<ChannelMarker>) -> Self {
         self.thread_id.replace(thread_id);
@@ -397,16 +428,17 @@ impl TryIntoRequest for ExecuteWebhook<'_> {
         // body.
         if !self.attachment_manager.is_empty() {
             let form = if let Some(payload_json) = self.fields.payload_json {
-                self.attachment_manager.build_form(payload_json)
+                self.attachment_manager.build_streaming_form(payload_json)
             } else {
                 self.fields.attachments = Some(self.attachment_manager.get_partial_attachments());
 
                 let fields = crate::json::to_vec(&self.fields).map_err(HttpError::json)?;
 
-                self.attachment_manager.build_form(fields.as_ref())
+                self.attachment_manager
+                    .build_streaming_form(fields.as_ref())
             };
 
-            request = request.form(form);
+            request = request.streaming_form(form);
         } else if let Some(payload_json) = self.fields.payload_json {
             request = request.body(payload_json.to_vec());
         } else {
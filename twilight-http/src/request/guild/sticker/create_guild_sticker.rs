@@ -1,7 +1,9 @@
 use crate::{
     client::Client,
     error::Error,
-    request::{multipart::Form, AuditLogReason, Request, TryIntoRequest},
+    request::{
+        attachment::StreamingAttachment, AuditLogReason, Request, StreamingForm, TryIntoRequest,
+    },
     response::ResponseFuture,
     routing::Route,
 };
@@ -19,11 +21,19 @@ use twilight_validate::{
 
 struct CreateGuildStickerFields<'a> {
     description: &'a str,
-    file: &'a [u8],
+    file: StickerFile<'a>,
     name: &'a str,
     tags: &'a str,
 }
 
+/// File of the sticker to create.
+enum StickerFile<'a> {
+    /// Content of the file, held in memory.
+    Buffered(&'a [u8]),
+    /// File whose content is streamed when the request is sent.
+    Streamed(&'a StreamingAttachment),
+}
+
 /// Creates a sticker in a guild, and returns the created sticker.
 ///
 /// # Examples
@@ -68,6 +78,42 @@ impl<'a> CreateGuildSticker<'a> {
         description: &'a str,
         tags: &'a str,
         file: &'a [u8],
+    ) -> Result<Self, StickerValidationError> {
+        Self::with_file(
+            http,
+            guild_id,
+            name,
+            description,
+            tags,
+            StickerFile::Buffered(file),
+        )
+    }
+
+    pub(crate) fn new_streaming(
+        http: &'a Client,
+        guild_id: Id<GuildMarker>,
+        name: &'a str,
+        description: &'a str,
+        tags: &'a str,
+        file: &'a StreamingAttachment,
+    ) -> Result<Self, StickerValidationError> {
+        Self::with_file(
+            http,
+            guild_id,
+            name,
+            description,
+            tags,
+            StickerFile::Streamed(file),
+        )
+    }
+
+    fn with_file(
+        http: &'a Client,
+        guild_id: Id<GuildMarker>,
+        name: &'a str,
+        description: &'a str,
+        tags: &'a str,
+        file: StickerFile<'a>,
     ) -> Result<Self, StickerValidationError> {
         validate_description(description)?;
 
@@ -117,13 +163,23 @@ impl TryIntoRequest for CreateGuildSticker<'_> {
             guild_id: self.guild_id.get(),
         });
 
-        let form = Form::new()
-            .part(b"description", self.fields.description.as_bytes())
-            .part(b"file", self.fields.file)
+        let form = StreamingForm::new().part(b"description", self.fields.description.as_bytes());
+
+        let form = match self.fields.file {
+            StickerFile::Buffered(file) => form.part(b"file", file),
+            StickerFile::Streamed(file) => form.stream_file_part(
+                b"file",
+                file.filename.as_bytes(),
+                file.source().clone(),
+                file.length(),
+            ),
+        };
+
+        let form = form
             .part(b"name", self.fields.name.as_bytes())
             .part(b"tags", self.fields.tags.as_bytes());
 
-        request = request.form(form);
+        request = request.streaming_form(form);
 
         Ok(request.build())
     }
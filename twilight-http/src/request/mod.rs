@@ -26,7 +26,9 @@ pub use self::{
     multipart::Form,
     try_into_request::TryIntoRequest,
 };
-pub use twilight_http_ratelimiting::request::Method;
+pub use twilight_http_ratelimiting::request::{Method, Priority};
+
+pub(crate) use self::multipart::StreamingForm;
 
 use crate::error::{Error, ErrorType};
 use hyper::header::{HeaderName, HeaderValue};
@@ -1,4 +1,16 @@
+use super::attachment::AttachmentSource;
+use futures_util::{future, stream};
+use hyper::{body::Bytes, Body};
 use rand::{distributions::Alphanumeric, Rng};
+use std::{
+    collections::VecDeque,
+    io::{Error as IoError, ErrorKind as IoErrorKind},
+    pin::Pin,
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Maximum number of bytes read from a streamed part at a time.
+const CHUNK_SIZE: u64 = 64 * 1024;
 
 #[derive(Clone, Debug)]
 #[must_use = "has no effect if not built into a Form"]
@@ -59,6 +71,20 @@ impl Form {
     }
 
     pub fn file_part(mut self, name: &[u8], filename: &[u8], value: &[u8]) -> Self {
+        self.file_part_headers(name, filename);
+
+        // Write the value itself, a newline, and finally the boundary.
+        self.buffer.extend(value);
+        self.buffer.extend(Self::NEWLINE);
+        self.buffer.extend(Self::BOUNDARY_TERMINATOR);
+        self.buffer.extend(self.boundary);
+
+        self
+    }
+
+    /// Write a file part's headers and the newline between them and the
+    /// value.
+    fn file_part_headers(&mut self, name: &[u8], filename: &[u8]) {
         // Write the Content-Disposition header.
         self.buffer.extend(Self::NEWLINE);
         self.buffer.extend(Self::CONTENT_DISPOSITION_1);
@@ -68,15 +94,8 @@ impl Form {
         self.buffer.extend(Self::CONTENT_DISPOSITION_3);
         self.buffer.extend(Self::NEWLINE);
 
-        // Write a newline between the headers and the value, the value
-        // itself, a newline, and finally the boundary.
+        // Write a newline between the headers and the value.
         self.buffer.extend(Self::NEWLINE);
-        self.buffer.extend(value);
-        self.buffer.extend(Self::NEWLINE);
-        self.buffer.extend(Self::BOUNDARY_TERMINATOR);
-        self.buffer.extend(self.boundary);
-
-        self
     }
 
     /// Preview the built buffer's length without consuming the form.
@@ -125,6 +144,207 @@ impl Default for Form {
     }
 }
 
+/// [`Form`] whose parts may be streamed from [`AttachmentSource`]s when it's
+/// sent.
+///
+/// Streamed forms can't be built into a buffer and are only sent by the
+/// client.
+#[derive(Clone, Debug)]
+#[must_use = "has no effect if not sent by the client"]
+pub(crate) struct StreamingForm {
+    form: Form,
+    /// Parts whose values are streamed, in order of their position in the
+    /// form's buffer.
+    streams: Vec<StreamedValue>,
+}
+
+/// Value of a part streamed from an [`AttachmentSource`] when the form's body
+/// is sent.
+#[derive(Clone, Debug)]
+struct StreamedValue {
+    /// Length of the value in bytes.
+    length: u64,
+    /// Position in the buffer the value is to be inserted at.
+    position: usize,
+    /// Source of the value.
+    source: AttachmentSource,
+}
+
+impl StreamingForm {
+    pub fn new() -> Self {
+        Self::from(Form::new())
+    }
+
+    /// Get the form's appropriate content type for requests.
+    pub fn content_type(&self) -> Vec<u8> {
+        self.form.content_type()
+    }
+
+    pub fn part(mut self, name: &[u8], value: &[u8]) -> Self {
+        self.form = self.form.part(name, value);
+
+        self
+    }
+
+    /// Write a part whose value is streamed from a source when the form is
+    /// sent.
+    pub fn stream_file_part(
+        mut self,
+        name: &[u8],
+        filename: &[u8],
+        source: AttachmentSource,
+        length: u64,
+    ) -> Self {
+        self.form.file_part_headers(name, filename);
+
+        // Mark where the value is to be streamed, then write a newline and
+        // finally the boundary.
+        self.streams.push(StreamedValue {
+            length,
+            position: self.form.buffer.len(),
+            source,
+        });
+        self.form.buffer.extend(Form::NEWLINE);
+        self.form.buffer.extend(Form::BOUNDARY_TERMINATOR);
+        self.form.buffer.extend(self.form.boundary);
+
+        self
+    }
+
+    /// Length of the form's body, including the length of streamed parts.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        let streamed = self
+            .streams
+            .iter()
+            .map(|value| usize::try_from(value.length).unwrap_or(usize::MAX))
+            .fold(0, usize::saturating_add);
+
+        self.form.len().saturating_add(streamed)
+    }
+
+    /// Consume the form, returning the in-memory form if none of its parts
+    /// are streamed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the form back if any of its parts are streamed.
+    pub fn into_form(self) -> Result<Form, Self> {
+        if self.streams.is_empty() {
+            Ok(self.form)
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Consume the form, returning a body that streams its contents.
+    pub fn into_body(self) -> Body {
+        let mut buffer = self.form.buffer;
+        buffer.extend(Form::BOUNDARY_TERMINATOR);
+        let buffer = Bytes::from(buffer);
+
+        let mut segments = VecDeque::with_capacity(self.streams.len() * 2 + 1);
+        let mut start = 0;
+
+        for value in self.streams {
+            segments.push_back(Segment::Buffered(buffer.slice(start..value.position)));
+            segments.push_back(Segment::Streamed(value.source, value.length));
+            start = value.position;
+        }
+
+        segments.push_back(Segment::Buffered(buffer.slice(start..)));
+
+        let state = FormStream {
+            current: None,
+            segments,
+        };
+
+        Body::wrap_stream(stream::unfold(state, |mut state| async move {
+            match state.next_chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), state)),
+                Ok(None) => None,
+                Err(source) => {
+                    // Don't continue sending the form after an error.
+                    state.current = None;
+                    state.segments.clear();
+
+                    Some((Err(source), state))
+                }
+            }
+        }))
+    }
+}
+
+impl From<Form> for StreamingForm {
+    fn from(form: Form) -> Self {
+        Self {
+            form,
+            streams: Vec::new(),
+        }
+    }
+}
+
+/// Segment of a form's body.
+enum Segment {
+    /// Bytes of the form's buffer.
+    Buffered(Bytes),
+    /// Value of a part to be streamed, with its length.
+    Streamed(AttachmentSource, u64),
+}
+
+/// State of a form's body being streamed.
+struct FormStream {
+    /// Reader of the streamed value currently being sent, with the number of
+    /// bytes remaining to be read.
+    current: Option<(Box<dyn AsyncRead + Send + Unpin>, u64)>,
+    /// Segments remaining to be sent.
+    segments: VecDeque<Segment>,
+}
+
+impl FormStream {
+    /// Produce the next chunk of the form's body, returning `None` once the
+    /// body is complete.
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, IoError> {
+        loop {
+            if let Some((reader, remaining)) = &mut self.current {
+                if *remaining == 0 {
+                    self.current = None;
+
+                    continue;
+                }
+
+                let capacity = usize::try_from((*remaining).min(CHUNK_SIZE))
+                    .expect("chunk size fits in usize");
+                let mut chunk = vec![0; capacity];
+                let mut buf = ReadBuf::new(&mut chunk);
+                future::poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buf)).await?;
+                let read = buf.filled().len();
+
+                if read == 0 {
+                    return Err(IoError::new(
+                        IoErrorKind::UnexpectedEof,
+                        "attachment ended before its declared length",
+                    ));
+                }
+
+                chunk.truncate(read);
+                *remaining -= read as u64;
+
+                return Ok(Some(Bytes::from(chunk)));
+            }
+
+            match self.segments.pop_front() {
+                Some(Segment::Buffered(bytes)) if bytes.is_empty() => {}
+                Some(Segment::Buffered(bytes)) => return Ok(Some(bytes)),
+                Some(Segment::Streamed(source, length)) => {
+                    self.current = Some((source.open().await?, length));
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
 /// Generate a random boundary that is 15 characters long.
 pub fn random_boundary() -> [u8; 15] {
     let mut boundary = [0; 15];
@@ -140,6 +360,7 @@ pub fn random_boundary() -> [u8; 15] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::request::attachment::{AttachmentManager, StreamingAttachment};
     use std::str;
 
     #[test]
@@ -168,4 +389,62 @@ mod tests {
         assert_eq!(expected.as_bytes(), buffer);
         assert_eq!(buffer_len, buffer.len());
     }
+
+    #[tokio::test]
+    async fn streamed_form() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let attachment = StreamingAttachment::from_reader(
+            "filename.mp4".to_owned(),
+            &b"streamed_value"[..],
+            14,
+            1,
+        );
+        let form = AttachmentManager::new()
+            .set_streams(vec![&attachment])
+            .build_streaming_form(b"json_value");
+
+        let boundary = str::from_utf8(&form.form.boundary)?.to_owned();
+        let expected = format!(
+            "--{boundary}\r\n\
+        Content-Disposition: form-data; name=\"payload_json\"\r\n\
+        Content-Type: application/json\r\n\
+        \r\n\
+        json_value\r\n\
+        --{boundary}\r\n\
+        Content-Disposition: form-data; name=\"files[1]\"; filename=\"filename.mp4\"\r\n\
+        \r\n\
+        streamed_value\r\n\
+        --{boundary}--",
+        );
+
+        assert_eq!(expected.len(), form.len());
+
+        let body = hyper::body::to_bytes(form.into_body()).await?;
+        assert_eq!(expected.as_bytes(), body);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn streamed_form_too_short() {
+        let attachment =
+            StreamingAttachment::from_reader("filename.mp4".to_owned(), &b"short"[..], 14, 1);
+        let form = AttachmentManager::new()
+            .set_streams(vec![&attachment])
+            .build_streaming_form(b"json_value");
+
+        assert!(hyper::body::to_bytes(form.into_body()).await.is_err());
+    }
+
+    #[test]
+    fn streaming_form_into_form() {
+        let form = AttachmentManager::new().build_streaming_form(b"json_value");
+        assert!(form.into_form().is_ok());
+
+        let attachment =
+            StreamingAttachment::from_reader("filename.mp4".to_owned(), &b"value"[..], 5, 1);
+        let form = AttachmentManager::new()
+            .set_streams(vec![&attachment])
+            .build_streaming_form(b"json_value");
+        assert!(form.into_form().is_err());
+    }
 }
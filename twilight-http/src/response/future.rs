@@ -1,9 +1,13 @@
 use super::{Response, StatusCode};
 use crate::{
     api_error::ApiError,
+    client::coalesce::CoalescedFuture,
     error::{Error, ErrorType},
 };
-use hyper::{client::ResponseFuture as HyperResponseFuture, StatusCode as HyperStatusCode};
+use hyper::{
+    client::ResponseFuture as HyperResponseFuture, Body, Response as HyperResponse,
+    StatusCode as HyperStatusCode,
+};
 use std::{
     future::Future,
     marker::PhantomData,
@@ -62,6 +66,27 @@ impl Chunking {
     }
 }
 
+struct Coalesced {
+    future: CoalescedFuture,
+    guild_id: Option<Id<GuildMarker>>,
+    invalid_token: Option<Arc<AtomicBool>>,
+}
+
+impl Coalesced {
+    fn poll<T>(mut self, cx: &mut Context<'_>) -> InnerPoll<T> {
+        match Pin::new(&mut self.future).poll(cx) {
+            Poll::Ready(Ok(shared)) => process(
+                shared.to_response(),
+                self.guild_id,
+                self.invalid_token,
+                None,
+            ),
+            Poll::Ready(Err(source)) => InnerPoll::Ready(Err(source)),
+            Poll::Pending => InnerPoll::Pending(ResponseFutureStage::Coalesced(self)),
+        }
+    }
+}
+
 struct Failed {
     source: Error,
 }
@@ -98,78 +123,94 @@ impl InFlight {
             Poll::Pending => return InnerPoll::Pending(ResponseFutureStage::InFlight(self)),
         };
 
-        // If the API sent back an Unauthorized response, then the client's
-        // configured token is permanently invalid and future requests must be
-        // ignored to avoid API bans.
-        if resp.status() == HyperStatusCode::UNAUTHORIZED {
-            if let Some(invalid_token) = self.invalid_token {
-                invalid_token.store(true, Ordering::Relaxed);
-            }
+        process(resp, self.guild_id, self.invalid_token, self.tx)
+    }
+}
+
+/// Process a received response into either the success [`Response`] or the
+/// error resulting from it.
+fn process<T>(
+    resp: HyperResponse<Body>,
+    guild_id: Option<Id<GuildMarker>>,
+    invalid_token: Option<Arc<AtomicBool>>,
+    tx: Option<TicketSender>,
+) -> InnerPoll<T> {
+    // If the API sent back an Unauthorized response, then the client's
+    // configured token is permanently invalid and future requests must be
+    // ignored to avoid API bans.
+    if resp.status() == HyperStatusCode::UNAUTHORIZED {
+        if let Some(invalid_token) = invalid_token {
+            invalid_token.store(true, Ordering::Relaxed);
         }
+    }
 
-        if let Some(tx) = self.tx {
-            let headers = resp
-                .headers()
-                .iter()
-                .map(|(key, value)| (key.as_str(), value.as_bytes()));
+    if let Some(tx) = tx {
+        send_ratelimit_headers(tx, &resp);
+    }
 
-            match RatelimitHeaders::from_pairs(headers) {
-                Ok(v) => {
-                    let _res = tx.headers(Some(v));
-                }
-                Err(source) => {
-                    tracing::warn!("header parsing failed: {source:?}; {resp:?}");
+    let status = resp.status();
 
-                    let _res = tx.headers(None);
-                }
-            }
+    if status.is_success() {
+        #[cfg(feature = "decompression")]
+        let mut resp = resp;
+        // Inaccurate since end-users can only access the decompressed body.
+        #[cfg(feature = "decompression")]
+        resp.headers_mut().remove(hyper::header::CONTENT_LENGTH);
+
+        let mut response = Response::new(resp);
+
+        if let Some(guild_id) = guild_id {
+            response.set_guild_id(guild_id);
         }
 
-        let status = resp.status();
+        return InnerPoll::Ready(Ok(response));
+    }
 
-        if status.is_success() {
-            #[cfg(feature = "decompression")]
-            let mut resp = resp;
-            // Inaccurate since end-users can only access the decompressed body.
-            #[cfg(feature = "decompression")]
-            resp.headers_mut().remove(hyper::header::CONTENT_LENGTH);
+    match status {
+        HyperStatusCode::TOO_MANY_REQUESTS => {
+            tracing::warn!("429 response: {resp:?}");
+        }
+        HyperStatusCode::SERVICE_UNAVAILABLE => {
+            return InnerPoll::Ready(Err(Error {
+                kind: ErrorType::ServiceUnavailable { response: resp },
+                source: None,
+            }));
+        }
+        _ => {}
+    }
 
-            let mut response = Response::new(resp);
+    let fut = async {
+        Response::<()>::new(resp)
+            .bytes()
+            .await
+            .map_err(|source| Error {
+                kind: ErrorType::ChunkingResponse,
+                source: Some(Box::new(source)),
+            })
+    };
+
+    InnerPoll::Advance(ResponseFutureStage::Chunking(Chunking {
+        future: Box::pin(fut),
+        status,
+    }))
+}
 
-            if let Some(guild_id) = self.guild_id {
-                response.set_guild_id(guild_id);
-            }
+/// Send the ratelimit headers of a response to the ratelimiter.
+pub(crate) fn send_ratelimit_headers(tx: TicketSender, resp: &HyperResponse<Body>) {
+    let headers = resp
+        .headers()
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_bytes()));
 
-            return InnerPoll::Ready(Ok(response));
+    match RatelimitHeaders::from_pairs(headers) {
+        Ok(v) => {
+            let _res = tx.headers(Some(v));
         }
+        Err(source) => {
+            tracing::warn!("header parsing failed: {source:?}; {resp:?}");
 
-        match status {
-            HyperStatusCode::TOO_MANY_REQUESTS => {
-                tracing::warn!("429 response: {resp:?}");
-            }
-            HyperStatusCode::SERVICE_UNAVAILABLE => {
-                return InnerPoll::Ready(Err(Error {
-                    kind: ErrorType::ServiceUnavailable { response: resp },
-                    source: None,
-                }));
-            }
-            _ => {}
+            let _res = tx.headers(None);
         }
-
-        let fut = async {
-            Response::<()>::new(resp)
-                .bytes()
-                .await
-                .map_err(|source| Error {
-                    kind: ErrorType::ChunkingResponse,
-                    source: Some(Box::new(source)),
-                })
-        };
-
-        InnerPoll::Advance(ResponseFutureStage::Chunking(Chunking {
-            future: Box::pin(fut),
-            status,
-        }))
     }
 }
 
@@ -215,6 +256,7 @@ impl RatelimitQueue {
 
 enum ResponseFutureStage {
     Chunking(Chunking),
+    Coalesced(Coalesced),
     Completed,
     Failed(Failed),
     InFlight(InFlight),
@@ -349,6 +391,20 @@ impl<T> ResponseFuture<T> {
         }
     }
 
+    pub(crate) const fn coalesced(
+        future: CoalescedFuture,
+        invalid_token: Option<Arc<AtomicBool>>,
+    ) -> Self {
+        Self {
+            phantom: PhantomData,
+            stage: ResponseFutureStage::Coalesced(Coalesced {
+                future,
+                guild_id: None,
+                invalid_token,
+            }),
+        }
+    }
+
     pub(crate) const fn error(source: Error) -> Self {
         Self {
             phantom: PhantomData,
@@ -380,6 +436,9 @@ impl<T> ResponseFuture<T> {
     /// Necessary for [`MemberBody`] and [`MemberListBody`] deserialization.
     pub(crate) fn set_guild_id(&mut self, guild_id: Id<GuildMarker>) {
         match &mut self.stage {
+            ResponseFutureStage::Coalesced(stage) => {
+                stage.guild_id.replace(guild_id);
+            }
             ResponseFutureStage::InFlight(stage) => {
                 stage.guild_id.replace(guild_id);
             }
@@ -400,6 +459,7 @@ impl<T: Unpin> Future for ResponseFuture<T> {
 
             let result = match stage {
                 ResponseFutureStage::Chunking(chunking) => chunking.poll(cx),
+                ResponseFutureStage::Coalesced(coalesced) => coalesced.poll(cx),
                 ResponseFutureStage::Completed => panic!("future already completed"),
                 ResponseFutureStage::Failed(failed) => failed.poll(cx),
                 ResponseFutureStage::InFlight(in_flight) => in_flight.poll(cx),
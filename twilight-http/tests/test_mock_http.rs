@@ -0,0 +1,129 @@
+use serde_json::json;
+use std::time::{Duration, Instant};
+use twilight_http::{
+    error::ErrorType,
+    request::{channel::reaction::RequestReactionType, Method},
+    routing::Route,
+    Client,
+};
+use twilight_mock::http::{MockHttp, MockResponse};
+use twilight_model::id::Id;
+
+fn client(server: &MockHttp) -> Client {
+    server
+        .client_builder()
+        .token("Bot token".to_owned())
+        .build()
+}
+
+fn status(source: &twilight_http::Error) -> Option<u16> {
+    match source.kind() {
+        ErrorType::Response { status, .. } => Some(status.get()),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn records_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockHttp::bind().await?;
+    let route = Route::CreateMessage { channel_id: 1 };
+    server.mock(&route, MockResponse::json(&json!({ "id": "2" })));
+
+    let client = client(&server);
+    let response = client
+        .create_message(Id::new(1))
+        .content("hello")?
+        .exec()
+        .await?;
+    assert_eq!(200, response.status().get());
+
+    let requests = server.requests();
+    assert_eq!(1, requests.len());
+
+    let request = &requests[0];
+    assert!(request.matches(&route));
+    assert_eq!(Method::Post, request.method());
+    assert_eq!("channels/1/messages", request.path());
+    assert_eq!(Some("Bot token"), request.header("authorization"));
+    assert_eq!(
+        Some("hello"),
+        request.json::<serde_json::Value>()?["content"].as_str()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn simulated_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockHttp::bind().await?;
+    let emoji = RequestReactionType::Unicode { name: "\u{1f44d}" };
+    let route = Route::CreateReaction {
+        channel_id: 1,
+        emoji: &emoji,
+        message_id: 2,
+    };
+    let error = json!({ "code": 0, "message": "500: Internal Server Error" });
+    server.mock_times(&route, MockResponse::ratelimited(0.1, false), 1);
+    server.mock_times(&route, MockResponse::status(500).json_body(&error), 1);
+    server.mock(&route, MockResponse::no_content());
+
+    let client = client(&server);
+    let request = || {
+        client
+            .create_reaction(Id::new(1), Id::new(2), &emoji)
+            .exec()
+    };
+
+    let source = request().await.unwrap_err();
+    assert_eq!(Some(429), status(&source));
+
+    let source = request().await.unwrap_err();
+    assert_eq!(Some(500), status(&source));
+
+    assert_eq!(204, request().await?.status().get());
+    assert_eq!(3, server.requests().len());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unmatched_route() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockHttp::bind().await?;
+
+    let source = client(&server).current_user().exec().await.unwrap_err();
+    assert_eq!(Some(404), status(&source));
+    assert!(server.requests()[0].matches(&Route::GetCurrentUser));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ratelimit_headers() -> Result<(), Box<dyn std::error::Error>> {
+    const RESET_AFTER: f64 = 0.5;
+
+    let server = MockHttp::bind().await?;
+    let route = Route::CreateMessage { channel_id: 1 };
+    server.mock(
+        &route,
+        MockResponse::json(&json!({ "id": "2" })).ratelimit("bucket", 1, 0, RESET_AFTER),
+    );
+
+    let client = client(&server);
+    client
+        .create_message(Id::new(1))
+        .content("first")?
+        .exec()
+        .await?;
+
+    // The bucket is exhausted, so the ratelimiter waits for it to reset.
+    let start = Instant::now();
+    client
+        .create_message(Id::new(1))
+        .content("second")?
+        .exec()
+        .await?;
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    assert_eq!(2, server.requests().len());
+
+    Ok(())
+}
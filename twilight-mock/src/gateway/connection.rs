@@ -0,0 +1,483 @@
+//! Connection of a client to the mock gateway.
+
+use flate2::{Compress, Compression, FlushCompress};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::{
+    net::TcpStream,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        handshake::server::{Request, Response},
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Error as WsError, Message,
+    },
+    WebSocketStream,
+};
+use twilight_model::{
+    gateway::{
+        payload::{
+            incoming::Ready,
+            outgoing::{Identify, Resume},
+        },
+        OpCode,
+    },
+    guild::UnavailableGuild,
+    id::{
+        marker::{ApplicationMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    oauth::{ApplicationFlags, PartialApplication},
+    user::CurrentUser,
+};
+
+/// ID of the application and user of the mock bot.
+const BOT_ID: u64 = 1;
+
+/// Payload received from a client.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ClientPayload {
+    /// Client closed the connection, with a close code if provided.
+    Close(Option<u16>),
+    /// Heartbeat with the client's last received sequence, if any.
+    Heartbeat(Option<u64>),
+    /// Request to start a new session.
+    Identify(Identify),
+    /// Any other payload, such as a presence update.
+    Other(Value),
+    /// Request to resume a session.
+    Resume(Resume),
+}
+
+impl ClientPayload {
+    /// Parse a payload from a client.
+    fn parse(json: &str) -> Option<Self> {
+        let value = serde_json::from_str::<Value>(json).ok()?;
+        let op = value.get("op").and_then(Value::as_u64)?;
+
+        Some(if op == OpCode::Heartbeat as u64 {
+            Self::Heartbeat(value.get("d").and_then(Value::as_u64))
+        } else if op == OpCode::Identify as u64 {
+            Self::Identify(serde_json::from_value(value).ok()?)
+        } else if op == OpCode::Resume as u64 {
+            Self::Resume(serde_json::from_value(value).ok()?)
+        } else {
+            Self::Other(value)
+        })
+    }
+}
+
+/// Message to send to the client.
+#[derive(Debug)]
+enum Outgoing {
+    Close(CloseFrame<'static>),
+    Json(String),
+}
+
+/// Connection of a client, such as a shard, to the mock gateway.
+///
+/// Payloads are sent without waiting for them to be written. Sending on a
+/// closed connection does nothing. The connection is closed when dropped.
+#[derive(Debug)]
+pub struct Connection {
+    compressed: bool,
+    outgoing: UnboundedSender<Outgoing>,
+    payloads: UnboundedReceiver<ClientPayload>,
+    sequence: AtomicU64,
+    uri: String,
+}
+
+impl Connection {
+    /// Perform the websocket handshake, send a Hello payload, and start
+    /// reading from and writing to the connection in a new task.
+    #[allow(clippy::result_large_err)]
+    pub(super) async fn accept(
+        stream: TcpStream,
+        heartbeat_interval: u64,
+        ack_heartbeats: bool,
+    ) -> Result<Self, WsError> {
+        let mut uri = String::new();
+
+        let mut websocket =
+            tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+                uri = request.uri().to_string();
+
+                Ok(response)
+            })
+            .await?;
+
+        let compressed = uri.contains("compress=zlib-stream");
+        let mut writer = Writer {
+            compressor: compressed.then(|| Compress::new(Compression::default(), true)),
+        };
+
+        let hello = json!({
+            "d": { "heartbeat_interval": heartbeat_interval },
+            "op": OpCode::Hello as u8,
+        });
+        writer.send(&mut websocket, hello.to_string()).await?;
+
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+        let (payloads_tx, payloads_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(run(
+            websocket,
+            writer,
+            ack_heartbeats,
+            outgoing_rx,
+            payloads_tx,
+        ));
+
+        Ok(Self {
+            compressed,
+            outgoing: outgoing_tx,
+            payloads: payloads_rx,
+            sequence: AtomicU64::new(0),
+            uri,
+        })
+    }
+
+    /// Whether the client requested `zlib-stream` compression, in which case
+    /// payloads sent to it are compressed.
+    pub const fn compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// URI the client requested, including its query.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Receive the next payload from the client.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<ClientPayload> {
+        self.payloads.recv().await
+    }
+
+    /// Wait for the client to identify, skipping its heartbeats.
+    ///
+    /// Returns `None` if the connection closed or the client sent another
+    /// payload first.
+    pub async fn recv_identify(&mut self) -> Option<Identify> {
+        match self.recv_skipping_heartbeats().await? {
+            ClientPayload::Identify(identify) => Some(identify),
+            _ => None,
+        }
+    }
+
+    /// Wait for the client to resume a session, skipping its heartbeats.
+    ///
+    /// Returns `None` if the connection closed or the client sent another
+    /// payload first.
+    pub async fn recv_resume(&mut self) -> Option<Resume> {
+        match self.recv_skipping_heartbeats().await? {
+            ClientPayload::Resume(resume) => Some(resume),
+            _ => None,
+        }
+    }
+
+    async fn recv_skipping_heartbeats(&mut self) -> Option<ClientPayload> {
+        loop {
+            let payload = self.recv().await?;
+
+            if !matches!(payload, ClientPayload::Heartbeat(_)) {
+                return Some(payload);
+            }
+        }
+    }
+
+    /// Sequence of the last dispatched event.
+    pub fn sequence(&self) -> u64 {
+        self.sequence.load(Ordering::Relaxed)
+    }
+
+    /// Set the sequence of the last dispatched event, such as to continue the
+    /// sequence of a resumed session.
+    pub fn set_sequence(&self, sequence: u64) {
+        self.sequence.store(sequence, Ordering::Relaxed);
+    }
+
+    /// Dispatch an event, returning its sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data fails to serialize.
+    pub fn dispatch(&self, event_type: &str, data: &impl Serialize) -> u64 {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let data = serde_json::to_value(data).expect("event data serializes");
+
+        self.send_json(&json!({
+            "d": data,
+            "op": OpCode::Event as u8,
+            "s": sequence,
+            "t": event_type,
+        }));
+
+        sequence
+    }
+
+    /// Dispatch a `READY` event starting a session, returning its sequence.
+    ///
+    /// The guilds are sent as unavailable, to be followed by `GUILD_CREATE`
+    /// events.
+    pub fn ready(&self, session_id: &str, guilds: &[Id<GuildMarker>]) -> u64 {
+        let ready = Ready {
+            application: PartialApplication {
+                flags: ApplicationFlags::empty(),
+                id: Id::<ApplicationMarker>::new(BOT_ID),
+            },
+            guilds: guilds
+                .iter()
+                .map(|id| UnavailableGuild {
+                    id: *id,
+                    unavailable: true,
+                })
+                .collect(),
+            session_id: session_id.to_owned(),
+            shard: None,
+            user: CurrentUser {
+                accent_color: None,
+                avatar: None,
+                banner: None,
+                bot: true,
+                discriminator: 1,
+                email: None,
+                flags: None,
+                id: Id::<UserMarker>::new(BOT_ID),
+                locale: None,
+                mfa_enabled: false,
+                name: "twilight-mock".to_owned(),
+                premium_type: None,
+                public_flags: None,
+                verified: None,
+            },
+            version: 10,
+        };
+
+        self.dispatch("READY", &ready)
+    }
+
+    /// Dispatch a `RESUMED` event, completing a resume, and return its
+    /// sequence.
+    pub fn resumed(&self) -> u64 {
+        self.dispatch("RESUMED", &json!({}))
+    }
+
+    /// Request the client to send a heartbeat.
+    pub fn heartbeat(&self) {
+        self.send_json(&json!({
+            "d": self.sequence(),
+            "op": OpCode::Heartbeat as u8,
+        }));
+    }
+
+    /// Request the client to reconnect and resume its session.
+    pub fn reconnect(&self) {
+        self.send_json(&json!({
+            "d": null,
+            "op": OpCode::Reconnect as u8,
+        }));
+    }
+
+    /// Invalidate the client's session, indicating whether it may be
+    /// resumed.
+    pub fn invalid_session(&self, resumable: bool) {
+        self.send_json(&json!({
+            "d": resumable,
+            "op": OpCode::InvalidSession as u8,
+        }));
+    }
+
+    /// Close the connection with a close code, such as 4004 for an invalid
+    /// token.
+    pub fn close(&self, code: u16, reason: &str) {
+        let _res = self.outgoing.send(Outgoing::Close(CloseFrame {
+            code: CloseCode::from(code),
+            reason: reason.to_owned().into(),
+        }));
+    }
+
+    /// Send a raw payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the payload fails to serialize.
+    pub fn send_json(&self, payload: &impl Serialize) {
+        let json = serde_json::to_string(payload).expect("payload serializes");
+
+        let _res = self.outgoing.send(Outgoing::Json(json));
+    }
+}
+
+/// Writer of payloads to a connection, compressing them if requested.
+struct Writer {
+    compressor: Option<Compress>,
+}
+
+impl Writer {
+    async fn send(
+        &mut self,
+        websocket: &mut WebSocketStream<TcpStream>,
+        json: String,
+    ) -> Result<(), WsError> {
+        let message = if let Some(compressor) = self.compressor.as_mut() {
+            Message::Binary(compress(compressor, json.as_bytes()))
+        } else {
+            Message::Text(json)
+        };
+
+        websocket.send(message).await
+    }
+}
+
+/// Compress a payload onto a `zlib-stream`, ending it with a sync flush.
+fn compress(compressor: &mut Compress, input: &[u8]) -> Vec<u8> {
+    let start = compressor.total_in();
+    let mut output = Vec::with_capacity(input.len() + 64);
+
+    loop {
+        #[allow(clippy::cast_possible_truncation)]
+        let consumed = (compressor.total_in() - start) as usize;
+
+        compressor
+            .compress_vec(&input[consumed..], &mut output, FlushCompress::Sync)
+            .expect("compressing into memory succeeds");
+
+        // The flush is complete once all input has been consumed without
+        // filling the output.
+        #[allow(clippy::cast_possible_truncation)]
+        let consumed = (compressor.total_in() - start) as usize;
+
+        if consumed == input.len() && output.len() < output.capacity() {
+            return output;
+        }
+
+        output.reserve(output.capacity());
+    }
+}
+
+/// Read payloads from and write payloads to a connection until it closes.
+async fn run(
+    mut websocket: WebSocketStream<TcpStream>,
+    mut writer: Writer,
+    ack_heartbeats: bool,
+    mut outgoing: UnboundedReceiver<Outgoing>,
+    payloads: UnboundedSender<ClientPayload>,
+) {
+    loop {
+        tokio::select! {
+            maybe_outgoing = outgoing.recv() => match maybe_outgoing {
+                Some(Outgoing::Json(json)) => {
+                    if let Err(source) = writer.send(&mut websocket, json).await {
+                        tracing::debug!("sending payload failed: {source}");
+
+                        break;
+                    }
+                }
+                Some(Outgoing::Close(frame)) => {
+                    let _res = websocket.close(Some(frame)).await;
+
+                    break;
+                }
+                None => {
+                    let _res = websocket.close(None).await;
+
+                    break;
+                }
+            },
+            maybe_message = websocket.next() => {
+                let json = match maybe_message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Binary(bytes))) => match String::from_utf8(bytes) {
+                        Ok(text) => text,
+                        Err(_) => continue,
+                    },
+                    Some(Ok(Message::Close(frame))) => {
+                        let code = frame.map(|frame| u16::from(frame.code));
+                        let _res = payloads.send(ClientPayload::Close(code));
+
+                        break;
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(_)) | None => break,
+                };
+
+                let payload = if let Some(payload) = ClientPayload::parse(&json) {
+                    payload
+                } else {
+                    tracing::debug!("received malformed payload: {json}");
+
+                    continue;
+                };
+
+                if ack_heartbeats && matches!(payload, ClientPayload::Heartbeat(_)) {
+                    let ack = json!({ "op": OpCode::HeartbeatAck as u8 }).to_string();
+
+                    if writer.send(&mut websocket, ack).await.is_err() {
+                        break;
+                    }
+                }
+
+                let _res = payloads.send(payload);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, ClientPayload, Connection};
+    use flate2::{Compress, Compression, Decompress, FlushDecompress};
+    use serde_json::json;
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(ClientPayload: Clone, Debug, PartialEq, Send, Sync);
+    assert_impl_all!(Connection: Debug, Send, Sync);
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Some(ClientPayload::Heartbeat(Some(5))),
+            ClientPayload::parse(r#"{"op":1,"d":5}"#)
+        );
+        assert_eq!(
+            Some(ClientPayload::Heartbeat(None)),
+            ClientPayload::parse(r#"{"op":1,"d":null}"#)
+        );
+        assert!(matches!(
+            ClientPayload::parse(
+                r#"{"op":6,"d":{"seq":3,"session_id":"abc","token":"Bot a"}}"#
+            ),
+            Some(ClientPayload::Resume(resume)) if resume.d.seq == 3
+        ));
+        assert_eq!(
+            Some(ClientPayload::Other(json!({"op": 3, "d": {}}))),
+            ClientPayload::parse(r#"{"op":3,"d":{}}"#)
+        );
+        assert!(ClientPayload::parse("not json").is_none());
+    }
+
+    #[test]
+    fn compress_stream() {
+        let mut compressor = Compress::new(Compression::default(), true);
+        let mut decompressor = Decompress::new(true);
+        let payloads = [r#"{"op":11}"#.repeat(1000), r#"{"op":7}"#.to_owned()];
+
+        for payload in payloads {
+            let compressed = compress(&mut compressor, payload.as_bytes());
+            assert!(compressed.ends_with(&[0x00, 0x00, 0xff, 0xff]));
+
+            let mut output = Vec::with_capacity(payload.len() * 2);
+            decompressor
+                .decompress_vec(&compressed, &mut output, FlushDecompress::Sync)
+                .unwrap();
+            assert_eq!(payload.as_bytes(), output);
+        }
+    }
+}
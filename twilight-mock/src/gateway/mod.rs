@@ -0,0 +1,230 @@
+//! Mock gateway server speaking the gateway protocol.
+//!
+//! The [`MockGateway`] accepts websocket connections, sends each of them a
+//! Hello payload, and hands them to the test as [`Connection`]s to script the
+//! rest of the session.
+//!
+//! # Examples
+//!
+//! Identify a shard and dispatch an event to it:
+//!
+//! ```no_run
+//! # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use twilight_mock::gateway::MockGateway;
+//!
+//! let mut gateway = MockGateway::builder().heartbeat_interval(1_000).build().await?;
+//! println!("point shards at {}", gateway.url());
+//!
+//! let mut connection = gateway.accept().await.expect("gateway running");
+//! connection.recv_identify().await.expect("shard identified");
+//! connection.ready("session id", &[]);
+//! connection.dispatch(
+//!     "CHANNEL_PINS_UPDATE",
+//!     &serde_json::json!({ "channel_id": "1" }),
+//! );
+//! # Ok(()) }
+//! ```
+
+mod connection;
+
+pub use self::connection::{ClientPayload, Connection};
+
+use std::{
+    io::Error as IoError,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use tokio::{
+    net::TcpListener,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+/// Builder to configure and bind a [`MockGateway`].
+#[derive(Clone, Debug)]
+#[must_use = "has no effect if not built"]
+pub struct MockGatewayBuilder {
+    ack_heartbeats: bool,
+    address: SocketAddr,
+    heartbeat_interval: u64,
+}
+
+impl MockGatewayBuilder {
+    /// Default heartbeat interval sent in Hello payloads, in milliseconds.
+    pub const DEFAULT_HEARTBEAT_INTERVAL: u64 = 41_250;
+
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            ack_heartbeats: true,
+            address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+            heartbeat_interval: Self::DEFAULT_HEARTBEAT_INTERVAL,
+        }
+    }
+
+    /// Bind the mock gateway and start accepting connections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binding to the address failed.
+    pub async fn build(self) -> Result<MockGateway, IoError> {
+        let listener = TcpListener::bind(self.address).await?;
+        let address = listener.local_addr()?;
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(accept(listener, self, tx));
+
+        Ok(MockGateway {
+            address,
+            connections: rx,
+            task,
+        })
+    }
+
+    /// Set whether heartbeats are acknowledged automatically.
+    ///
+    /// Heartbeats are still received via [`Connection::recv`]. Disable this to
+    /// test how shards handle zombied connections.
+    ///
+    /// Defaults to true.
+    pub const fn ack_heartbeats(mut self, ack_heartbeats: bool) -> Self {
+        self.ack_heartbeats = ack_heartbeats;
+
+        self
+    }
+
+    /// Set the address to bind to.
+    ///
+    /// Defaults to a random port on the local host.
+    pub const fn address(mut self, address: SocketAddr) -> Self {
+        self.address = address;
+
+        self
+    }
+
+    /// Set the heartbeat interval sent in Hello payloads, in milliseconds.
+    ///
+    /// Defaults to [`DEFAULT_HEARTBEAT_INTERVAL`].
+    ///
+    /// [`DEFAULT_HEARTBEAT_INTERVAL`]: Self::DEFAULT_HEARTBEAT_INTERVAL
+    pub const fn heartbeat_interval(mut self, heartbeat_interval: u64) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+
+        self
+    }
+}
+
+impl Default for MockGatewayBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Local websocket server speaking the gateway protocol.
+///
+/// The server stops accepting connections when dropped; accepted connections
+/// remain open until their [`Connection`] is dropped.
+///
+/// Refer to the [module-level] documentation for more information.
+///
+/// [module-level]: self
+#[derive(Debug)]
+pub struct MockGateway {
+    address: SocketAddr,
+    connections: UnboundedReceiver<Connection>,
+    task: JoinHandle<()>,
+}
+
+impl MockGateway {
+    /// Bind a mock gateway with the default configuration to a random port on
+    /// the local host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binding to the address failed.
+    pub async fn bind() -> Result<Self, IoError> {
+        Self::builder().build().await
+    }
+
+    /// Create a builder to configure and bind a mock gateway.
+    pub fn builder() -> MockGatewayBuilder {
+        MockGatewayBuilder::new()
+    }
+
+    /// Wait for the next connection.
+    ///
+    /// The connection has already been sent a Hello payload.
+    ///
+    /// Returns `None` if the server stopped accepting connections.
+    pub async fn accept(&mut self) -> Option<Connection> {
+        self.connections.recv().await
+    }
+
+    /// Address the server is bound to.
+    pub const fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// URL of the server, to be provided to `ShardBuilder::gateway_url`.
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.address)
+    }
+}
+
+impl Drop for MockGateway {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Accept connections, performing their handshakes in their own tasks.
+async fn accept(
+    listener: TcpListener,
+    config: MockGatewayBuilder,
+    connections: UnboundedSender<Connection>,
+) {
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(source) => {
+                tracing::warn!("accepting connection failed: {source}");
+
+                continue;
+            }
+        };
+
+        tracing::debug!("accepted connection from {address}");
+
+        let connections = connections.clone();
+        let config = config.clone();
+
+        tokio::spawn(async move {
+            match Connection::accept(stream, config.heartbeat_interval, config.ack_heartbeats).await
+            {
+                Ok(connection) => {
+                    let _res = connections.send(connection);
+                }
+                Err(source) => tracing::warn!("handshake with {address} failed: {source}"),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockGateway, MockGatewayBuilder};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(MockGatewayBuilder: Clone, Debug, Default, Send, Sync);
+    assert_impl_all!(MockGateway: Debug, Send, Sync);
+
+    #[tokio::test]
+    async fn url() -> Result<(), Box<dyn std::error::Error>> {
+        let gateway = MockGateway::bind().await?;
+
+        assert_eq!(format!("ws://{}", gateway.address()), gateway.url());
+        assert!(gateway.address().port() > 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+//! Mock HTTP server serving canned responses of Discord's REST API.
+//!
+//! The [`MockHttp`] serves [`MockResponse`]s registered for [`Route`]s and
+//! records every request it receives, including its body, as a
+//! [`RecordedRequest`]. Clients are pointed at it via
+//! [`ClientBuilder::proxy`], which [`MockHttp::client_builder`] does.
+//!
+//! Requests to routes without a registered response receive a 404 response.
+//!
+//! # Examples
+//!
+//! Fail a request with a 429 once and succeed on the retry:
+//!
+//! ```no_run
+//! # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use twilight_http::routing::Route;
+//! use twilight_mock::http::{MockHttp, MockResponse};
+//!
+//! let server = MockHttp::bind().await?;
+//! let route = Route::CreateMessage { channel_id: 1 };
+//!
+//! server.mock_times(&route, MockResponse::ratelimited(0.1, false), 1);
+//! server.mock(&route, MockResponse::json(&serde_json::json!({ "id": "2" })));
+//!
+//! let client = server.client_builder().token("token".to_owned()).build();
+//!
+//! // Make requests with the client.
+//!
+//! for request in server.requests() {
+//!     println!("{:?} {}", request.method(), request.path());
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! [`ClientBuilder::proxy`]: twilight_http::client::ClientBuilder::proxy
+
+mod request;
+mod response;
+
+pub use self::{request::RecordedRequest, response::MockResponse};
+
+use hyper::{
+    body::{self, Body},
+    server::conn::Http,
+    service::service_fn,
+    Method as HyperMethod, Request, Response,
+};
+use std::{
+    convert::Infallible,
+    io::Error as IoError,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+use twilight_http::{
+    client::{Client, ClientBuilder},
+    request::Method,
+    routing::Route,
+};
+
+/// Response registered for a route.
+#[derive(Debug)]
+struct Mock {
+    method: Method,
+    path: String,
+    /// Number of times the response may still be served, if limited.
+    remaining: Option<usize>,
+    response: MockResponse,
+}
+
+/// State shared between the server and its connections.
+#[derive(Debug, Default)]
+struct State {
+    mocks: Vec<Mock>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Local HTTP server serving canned responses of Discord's REST API.
+///
+/// The server stops accepting connections when dropped.
+///
+/// Refer to the [module-level] documentation for more information.
+///
+/// [module-level]: self
+#[derive(Debug)]
+pub struct MockHttp {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockHttp {
+    /// Bind a mock HTTP server to a random port on the local host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binding to the address failed.
+    pub async fn bind() -> Result<Self, IoError> {
+        Self::bind_to(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)).await
+    }
+
+    /// Bind a mock HTTP server to an address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binding to the address failed.
+    pub async fn bind_to(address: SocketAddr) -> Result<Self, IoError> {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let task = tokio::spawn(accept(listener, Arc::clone(&state)));
+
+        Ok(Self {
+            address,
+            state,
+            task,
+        })
+    }
+
+    /// Address the server is bound to.
+    pub const fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Create a client builder with its proxy set to the server.
+    pub fn client_builder(&self) -> ClientBuilder {
+        Client::builder().proxy(self.proxy_url(), true)
+    }
+
+    /// Serve a response for every request to a route.
+    ///
+    /// Requests match a route if their method and path are equal to the
+    /// route's. Routes without query parameters match requests with any query
+    /// parameters. Responses are matched in the order they were registered,
+    /// so register responses limited via [`mock_times`] before fallback ones.
+    ///
+    /// [`mock_times`]: Self::mock_times
+    pub fn mock(&self, route: &Route<'_>, response: MockResponse) {
+        self.insert(route, response, None);
+    }
+
+    /// Serve a response for a number of requests to a route.
+    ///
+    /// Afterwards, requests to the route are matched against the other
+    /// responses. Useful for simulating 429 and 5xx responses before
+    /// successful ones.
+    ///
+    /// Refer to [`mock`] for how requests are matched.
+    ///
+    /// [`mock`]: Self::mock
+    pub fn mock_times(&self, route: &Route<'_>, response: MockResponse, times: usize) {
+        self.insert(route, response, Some(times));
+    }
+
+    /// Host and port of the server, to be provided to
+    /// [`ClientBuilder::proxy`] with `use_http` enabled.
+    ///
+    /// [`ClientBuilder::proxy`]: twilight_http::client::ClientBuilder::proxy
+    pub fn proxy_url(&self) -> String {
+        self.address.to_string()
+    }
+
+    /// Requests received by the server, in the order they were received.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server's state is poisoned.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().expect("state poisoned").requests.clone()
+    }
+
+    /// Clear registered responses and received requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server's state is poisoned.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().expect("state poisoned");
+        state.mocks.clear();
+        state.requests.clear();
+    }
+
+    fn insert(&self, route: &Route<'_>, response: MockResponse, remaining: Option<usize>) {
+        self.state.lock().expect("state poisoned").mocks.push(Mock {
+            method: route.method(),
+            path: route.to_string(),
+            remaining,
+            response,
+        });
+    }
+}
+
+impl Drop for MockHttp {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Accept connections, serving them in their own tasks.
+async fn accept(listener: TcpListener, state: Arc<Mutex<State>>) {
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(source) => {
+                tracing::warn!("accepting connection failed: {source}");
+
+                continue;
+            }
+        };
+
+        tracing::debug!("accepted connection from {address}");
+
+        let state = Arc::clone(&state);
+        let service = service_fn(move |request| handle(Arc::clone(&state), request));
+
+        tokio::spawn(async move {
+            if let Err(source) = Http::new().serve_connection(stream, service).await {
+                tracing::debug!("serving connection from {address} failed: {source}");
+            }
+        });
+    }
+}
+
+/// Record a request and serve the response registered for it.
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+
+    let method = match parts.method {
+        HyperMethod::DELETE => Method::Delete,
+        HyperMethod::GET => Method::Get,
+        HyperMethod::PATCH => Method::Patch,
+        HyperMethod::POST => Method::Post,
+        HyperMethod::PUT => Method::Put,
+        _ => return Ok(respond(&MockResponse::status(405))),
+    };
+
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path(), |path| path.as_str());
+    let path = strip_version(path).to_owned();
+
+    let headers = parts
+        .headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect();
+
+    let body = match body::to_bytes(body).await {
+        Ok(bytes) => bytes.to_vec(),
+        Err(source) => {
+            tracing::debug!("reading request body failed: {source}");
+
+            return Ok(respond(&MockResponse::status(400)));
+        }
+    };
+
+    tracing::debug!("received {method:?} request to {path}");
+
+    let mut state = state.lock().expect("state poisoned");
+
+    let response = state
+        .mocks
+        .iter_mut()
+        .find(|mock| {
+            mock.method == method
+                && mock.remaining != Some(0)
+                && request::path_matches(&mock.path, &path)
+        })
+        .map_or_else(not_found, |mock| {
+            if let Some(remaining) = mock.remaining.as_mut() {
+                *remaining -= 1;
+            }
+
+            mock.response.clone()
+        });
+
+    state.requests.push(RecordedRequest {
+        body,
+        headers,
+        method,
+        path,
+    });
+
+    Ok(respond(&response))
+}
+
+/// Response to requests of routes without a registered response, mirroring
+/// Discord's.
+fn not_found() -> MockResponse {
+    MockResponse::status(404).json_body(&serde_json::json!({
+        "code": 0,
+        "message": "404: Not Found",
+    }))
+}
+
+/// Convert a mock response into a response to send.
+fn respond(mock: &MockResponse) -> Response<Body> {
+    let mut builder = Response::builder().status(mock.status_code());
+
+    for (name, value) in mock.headers() {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+
+    builder
+        .body(Body::from(mock.body_bytes().to_vec()))
+        .unwrap_or_else(|source| {
+            tracing::warn!("building response failed: {source}");
+
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = hyper::StatusCode::INTERNAL_SERVER_ERROR;
+
+            response
+        })
+}
+
+/// Strip the leading slash and API version from the path of a request.
+fn strip_version(path: &str) -> &str {
+    let path = path.trim_start_matches('/');
+
+    path.strip_prefix("api/v")
+        .and_then(|rest| {
+            let (version, rest) = rest.split_once('/')?;
+
+            version
+                .bytes()
+                .all(|byte| byte.is_ascii_digit())
+                .then(|| rest)
+        })
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_version, MockHttp};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(MockHttp: Debug, Send, Sync);
+
+    #[test]
+    fn strip() {
+        assert_eq!("users/@me", strip_version("/api/v10/users/@me"));
+        assert_eq!(
+            "channels/1/messages?limit=5",
+            strip_version("/api/v9/channels/1/messages?limit=5")
+        );
+        assert_eq!("users/@me", strip_version("/users/@me"));
+    }
+}
@@ -0,0 +1,122 @@
+//! Requests received by the mock HTTP server.
+
+use serde::de::DeserializeOwned;
+use serde_json::Error as JsonError;
+use twilight_http::{request::Method, routing::Route};
+
+/// Request received by the mock HTTP server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedRequest {
+    pub(super) body: Vec<u8>,
+    pub(super) headers: Vec<(String, String)>,
+    pub(super) method: Method,
+    pub(super) path: String,
+}
+
+impl RecordedRequest {
+    /// Body of the request.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Value of a header of the request, if present.
+    ///
+    /// Header names are matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Headers of the request.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Deserialize the JSON body of the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the body is not valid JSON for the type.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, JsonError> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// Whether the request was made to a route.
+    ///
+    /// Refer to [`MockHttp::mock`] for how routes are matched.
+    ///
+    /// [`MockHttp::mock`]: super::MockHttp::mock
+    pub fn matches(&self, route: &Route<'_>) -> bool {
+        self.method == route.method() && path_matches(&route.to_string(), &self.path)
+    }
+
+    /// Method of the request.
+    pub const fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Path of the request relative to the API version, including the query.
+    ///
+    /// For example, `channels/1/messages?limit=5`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Whether the path of a route matches the path of a request.
+///
+/// Routes without a query match requests with any query.
+pub(super) fn path_matches(route: &str, path: &str) -> bool {
+    if route.contains('?') {
+        return route == path;
+    }
+
+    path.split('?').next() == Some(route)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{path_matches, RecordedRequest};
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+    use twilight_http::{request::Method, routing::Route};
+
+    assert_impl_all!(RecordedRequest: Clone, Debug, Eq, PartialEq, Send, Sync);
+
+    #[test]
+    fn matches() {
+        let request = RecordedRequest {
+            body: br#"{"content":"hi"}"#.to_vec(),
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            method: Method::Post,
+            path: "channels/1/messages".to_owned(),
+        };
+
+        assert!(request.matches(&Route::CreateMessage { channel_id: 1 }));
+        assert!(!request.matches(&Route::CreateMessage { channel_id: 2 }));
+        assert!(!request.matches(&Route::GetMessages {
+            after: None,
+            around: None,
+            before: None,
+            channel_id: 1,
+            limit: None,
+        }));
+        assert_eq!(Some("application/json"), request.header("content-type"));
+    }
+
+    #[test]
+    fn paths() {
+        assert!(path_matches("channels/1/messages", "channels/1/messages"));
+        assert!(path_matches(
+            "channels/1/messages",
+            "channels/1/messages?limit=5"
+        ));
+        assert!(!path_matches(
+            "channels/1/messages?limit=5",
+            "channels/1/messages?limit=6"
+        ));
+        assert!(!path_matches("channels/1", "channels/1/messages"));
+    }
+}
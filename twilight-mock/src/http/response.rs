@@ -0,0 +1,177 @@
+//! Canned responses served by the mock HTTP server.
+
+use serde::Serialize;
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Canned response to serve for a route.
+///
+/// # Examples
+///
+/// Serve a user with ratelimit headers:
+///
+/// ```
+/// use twilight_mock::http::MockResponse;
+///
+/// let response = MockResponse::json(&serde_json::json!({ "id": "1" }))
+///     .ratelimit("bucket", 5, 4, 2.5);
+///
+/// assert_eq!(200, response.status_code());
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use = "has no effect if not served"]
+pub struct MockResponse {
+    body: Vec<u8>,
+    headers: Vec<(String, String)>,
+    status: u16,
+}
+
+impl MockResponse {
+    /// Create a response with a status code and an empty body.
+    pub const fn status(status: u16) -> Self {
+        Self {
+            body: Vec::new(),
+            headers: Vec::new(),
+            status,
+        }
+    }
+
+    /// Create a 200 OK response with a JSON body.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value fails to serialize.
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Self {
+        Self::status(200).json_body(value)
+    }
+
+    /// Create a 204 No Content response.
+    pub const fn no_content() -> Self {
+        Self::status(204)
+    }
+
+    /// Create a 429 Too Many Requests response, telling the client to retry
+    /// after a number of seconds.
+    ///
+    /// Global ratelimits apply to all requests of the client, others only to
+    /// the route's bucket.
+    pub fn ratelimited(retry_after: f64, global: bool) -> Self {
+        let body = json!({
+            "global": global,
+            "message": "You are being rate limited.",
+            "retry_after": retry_after,
+        });
+
+        let mut response = Self::status(429)
+            .json_body(&body)
+            .header("retry-after", retry_after.ceil().to_string())
+            .header("x-ratelimit-scope", if global { "global" } else { "user" });
+
+        if global {
+            response = response.header("x-ratelimit-global", "true");
+        }
+
+        response
+    }
+
+    /// Set the body of the response.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+
+        self
+    }
+
+    /// Add a header to the response.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+
+        self
+    }
+
+    /// Set the body of the response to a JSON value.
+    ///
+    /// Useful for simulating API errors, such as a 403 with an error code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value fails to serialize.
+    pub fn json_body<T: Serialize + ?Sized>(self, value: &T) -> Self {
+        let body = serde_json::to_vec(value).expect("value serializes to JSON");
+
+        self.body(body).header("content-type", "application/json")
+    }
+
+    /// Add the ratelimit headers of a bucket to the response.
+    ///
+    /// The bucket's ratelimit resets after `reset_after` seconds.
+    pub fn ratelimit(self, bucket: &str, limit: u64, remaining: u64, reset_after: f64) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |duration| duration.as_secs_f64());
+
+        self.header("x-ratelimit-bucket", bucket)
+            .header("x-ratelimit-limit", limit.to_string())
+            .header("x-ratelimit-remaining", remaining.to_string())
+            .header("x-ratelimit-reset", format!("{:.3}", now + reset_after))
+            .header("x-ratelimit-reset-after", format!("{reset_after:.3}"))
+    }
+
+    /// Body of the response.
+    pub fn body_bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Headers of the response.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Status code of the response.
+    pub const fn status_code(&self) -> u16 {
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockResponse;
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(MockResponse: Clone, Debug, Eq, PartialEq, Send, Sync);
+
+    fn header<'a>(response: &'a MockResponse, name: &str) -> Option<&'a str> {
+        response
+            .headers()
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn ratelimited() {
+        let response = MockResponse::ratelimited(1.5, true);
+
+        assert_eq!(429, response.status_code());
+        assert_eq!(Some("2"), header(&response, "retry-after"));
+        assert_eq!(Some("true"), header(&response, "x-ratelimit-global"));
+
+        let body = serde_json::from_slice::<serde_json::Value>(response.body_bytes()).unwrap();
+        assert_eq!(Some(1.5), body["retry_after"].as_f64());
+
+        let response = MockResponse::ratelimited(1.0, false);
+        assert_eq!(None, header(&response, "x-ratelimit-global"));
+        assert_eq!(Some("user"), header(&response, "x-ratelimit-scope"));
+    }
+
+    #[test]
+    fn ratelimit_headers() {
+        let response = MockResponse::no_content().ratelimit("abcd", 5, 4, 2.5);
+
+        assert_eq!(204, response.status_code());
+        assert_eq!(Some("abcd"), header(&response, "x-ratelimit-bucket"));
+        assert_eq!(Some("5"), header(&response, "x-ratelimit-limit"));
+        assert_eq!(Some("4"), header(&response, "x-ratelimit-remaining"));
+        assert_eq!(Some("2.500"), header(&response, "x-ratelimit-reset-after"));
+    }
+}
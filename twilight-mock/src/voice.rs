@@ -0,0 +1,458 @@
+//! Mock voice server speaking the voice gateway protocol.
+//!
+//! The [`MockVoiceServer`] performs the voice handshake with each client,
+//! answers IP discovery requests and heartbeats, and reports the payloads and
+//! voice packets it receives as [`VoiceEvent`]s. Packets are left encrypted
+//! with the server's [secret key].
+//!
+//! # Examples
+//!
+//! Wait for a client to select its encryption mode and send a packet:
+//!
+//! ```no_run
+//! # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use twilight_mock::voice::{MockVoiceServer, VoiceEvent};
+//!
+//! let mut server = MockVoiceServer::bind().await?;
+//! println!("connect clients to {}", server.endpoint());
+//!
+//! while let Some(event) = server.next_event().await {
+//!     match event {
+//!         VoiceEvent::SelectProtocol { mode, .. } => println!("client selected {mode}"),
+//!         VoiceEvent::Packet(packet) => println!("received {} bytes", packet.len()),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! [secret key]: MockVoiceServer::secret_key
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{
+    io::Error as IoError,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+use tokio::{
+    net::{TcpListener, TcpStream, UdpSocket},
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+/// Length of IP discovery packets.
+const DISCOVERY_LEN: usize = 74;
+
+/// Voice gateway opcodes used by the mock voice server.
+mod op {
+    pub const IDENTIFY: u64 = 0;
+    pub const SELECT_PROTOCOL: u64 = 1;
+    pub const READY: u64 = 2;
+    pub const HEARTBEAT: u64 = 3;
+    pub const SESSION_DESCRIPTION: u64 = 4;
+    pub const SPEAKING: u64 = 5;
+    pub const HEARTBEAT_ACK: u64 = 6;
+    pub const HELLO: u64 = 8;
+}
+
+/// Event received by the mock voice server.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum VoiceEvent {
+    /// Client closed its connection, with a close code if provided.
+    Closed(Option<u16>),
+    /// Client sent a heartbeat with a nonce.
+    Heartbeat(Value),
+    /// Client identified with the data of its payload.
+    Identify(Value),
+    /// Client sent an encrypted voice packet.
+    Packet(Vec<u8>),
+    /// Client selected its protocol after discovering its external address.
+    SelectProtocol {
+        /// External address of the client.
+        address: String,
+        /// Name of the selected encryption mode.
+        mode: String,
+        /// External port of the client.
+        port: u16,
+    },
+    /// Client updated its speaking flags.
+    Speaking {
+        /// Raw speaking flags.
+        speaking: u8,
+        /// SSRC of the client's audio stream.
+        ssrc: u32,
+    },
+}
+
+/// Builder to configure and bind a [`MockVoiceServer`].
+#[derive(Clone, Debug)]
+#[must_use = "has no effect if not built"]
+pub struct MockVoiceServerBuilder {
+    heartbeat_interval: f64,
+    modes: Vec<String>,
+    secret_key: [u8; 32],
+    ssrc: u32,
+}
+
+impl MockVoiceServerBuilder {
+    /// Default heartbeat interval sent in Hello payloads, in milliseconds.
+    pub const DEFAULT_HEARTBEAT_INTERVAL: f64 = 13_750.0;
+
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            heartbeat_interval: Self::DEFAULT_HEARTBEAT_INTERVAL,
+            modes: [
+                "aead_xchacha20_poly1305_rtpsize",
+                "xsalsa20_poly1305_lite",
+                "xsalsa20_poly1305_suffix",
+                "xsalsa20_poly1305",
+            ]
+            .iter()
+            .map(|mode| (*mode).to_owned())
+            .collect(),
+            secret_key: [42; 32],
+            ssrc: 1,
+        }
+    }
+
+    /// Bind the mock voice server and start accepting connections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binding the TCP or UDP socket failed.
+    pub async fn build(self) -> Result<MockVoiceServer, IoError> {
+        let localhost = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+        let listener = TcpListener::bind(localhost).await?;
+        let udp = Arc::new(UdpSocket::bind(localhost).await?);
+        let address = listener.local_addr()?;
+        let udp_port = udp.local_addr()?.port();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let secret_key = self.secret_key;
+        let tasks = [
+            tokio::spawn(accept(listener, self, udp_port, tx.clone())),
+            tokio::spawn(receive_packets(udp, tx)),
+        ];
+
+        Ok(MockVoiceServer {
+            address,
+            events: rx,
+            secret_key,
+            tasks,
+        })
+    }
+
+    /// Set the heartbeat interval sent in Hello payloads, in milliseconds.
+    ///
+    /// Defaults to [`DEFAULT_HEARTBEAT_INTERVAL`].
+    ///
+    /// [`DEFAULT_HEARTBEAT_INTERVAL`]: Self::DEFAULT_HEARTBEAT_INTERVAL
+    pub const fn heartbeat_interval(mut self, heartbeat_interval: f64) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+
+        self
+    }
+
+    /// Set the names of the encryption modes offered to clients.
+    ///
+    /// Defaults to the modes supported by `twilight-voice`.
+    pub fn modes(mut self, modes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.modes = modes.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Set the secret key sent to clients.
+    pub const fn secret_key(mut self, secret_key: [u8; 32]) -> Self {
+        self.secret_key = secret_key;
+
+        self
+    }
+
+    /// Set the SSRC assigned to clients.
+    ///
+    /// Defaults to 1.
+    pub const fn ssrc(mut self, ssrc: u32) -> Self {
+        self.ssrc = ssrc;
+
+        self
+    }
+}
+
+impl Default for MockVoiceServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Local voice server speaking the voice gateway protocol.
+///
+/// The server stops when dropped.
+///
+/// Refer to the [module-level] documentation for more information.
+///
+/// [module-level]: self
+#[derive(Debug)]
+pub struct MockVoiceServer {
+    address: SocketAddr,
+    events: UnboundedReceiver<VoiceEvent>,
+    secret_key: [u8; 32],
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl MockVoiceServer {
+    /// Bind a mock voice server with the default configuration to random
+    /// ports on the local host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if binding the TCP or UDP socket failed.
+    pub async fn bind() -> Result<Self, IoError> {
+        Self::builder().build().await
+    }
+
+    /// Create a builder to configure and bind a mock voice server.
+    pub fn builder() -> MockVoiceServerBuilder {
+        MockVoiceServerBuilder::new()
+    }
+
+    /// Address the voice gateway is bound to.
+    pub const fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Endpoint of the server, to be provided to voice clients.
+    pub fn endpoint(&self) -> String {
+        format!("ws://{}", self.address)
+    }
+
+    /// Wait for the next event.
+    ///
+    /// Returns `None` if the server stopped.
+    pub async fn next_event(&mut self) -> Option<VoiceEvent> {
+        self.events.recv().await
+    }
+
+    /// Secret key sent to clients to encrypt their packets with.
+    pub const fn secret_key(&self) -> [u8; 32] {
+        self.secret_key
+    }
+}
+
+impl Drop for MockVoiceServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Accept connections, handling them in their own tasks.
+async fn accept(
+    listener: TcpListener,
+    config: MockVoiceServerBuilder,
+    udp_port: u16,
+    events: UnboundedSender<VoiceEvent>,
+) {
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(source) => {
+                tracing::warn!("accepting connection failed: {source}");
+
+                continue;
+            }
+        };
+
+        tracing::debug!("accepted voice connection from {address}");
+
+        let config = config.clone();
+        let events = events.clone();
+
+        tokio::spawn(async move {
+            match tokio_tungstenite::accept_async(stream).await {
+                Ok(websocket) => session(websocket, &config, udp_port, &events).await,
+                Err(source) => tracing::warn!("handshake with {address} failed: {source}"),
+            }
+        });
+    }
+}
+
+/// Perform the voice handshake with a client and answer its payloads.
+async fn session(
+    mut websocket: WebSocketStream<TcpStream>,
+    config: &MockVoiceServerBuilder,
+    udp_port: u16,
+    events: &UnboundedSender<VoiceEvent>,
+) {
+    let hello =
+        json!({ "op": op::HELLO, "d": { "heartbeat_interval": config.heartbeat_interval } });
+
+    if websocket
+        .send(Message::Text(hello.to_string()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    while let Some(message) = websocket.next().await {
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(frame)) => {
+                let code = frame.map(|frame| u16::from(frame.code));
+                let _res = events.send(VoiceEvent::Closed(code));
+
+                return;
+            }
+            Ok(_) => continue,
+            Err(_) => return,
+        };
+
+        let payload = match serde_json::from_str::<Value>(&text) {
+            Ok(payload) => payload,
+            Err(_) => continue,
+        };
+        let d = payload.get("d").cloned().unwrap_or(Value::Null);
+
+        let (event, reply) = match payload.get("op").and_then(Value::as_u64) {
+            Some(op::IDENTIFY) => {
+                let ready = json!({
+                    "op": op::READY,
+                    "d": {
+                        "ip": "127.0.0.1",
+                        "modes": config.modes,
+                        "port": udp_port,
+                        "ssrc": config.ssrc,
+                    },
+                });
+
+                (Some(VoiceEvent::Identify(d)), Some(ready))
+            }
+            Some(op::SELECT_PROTOCOL) => {
+                let data = &d["data"];
+                let mode = data["mode"].as_str().unwrap_or_default().to_owned();
+                let description = json!({
+                    "op": op::SESSION_DESCRIPTION,
+                    "d": { "mode": mode, "secret_key": config.secret_key },
+                });
+                let event = VoiceEvent::SelectProtocol {
+                    address: data["address"].as_str().unwrap_or_default().to_owned(),
+                    mode,
+                    port: data["port"]
+                        .as_u64()
+                        .and_then(|port| u16::try_from(port).ok())
+                        .unwrap_or_default(),
+                };
+
+                (Some(event), Some(description))
+            }
+            Some(op::HEARTBEAT) => {
+                let ack = json!({ "op": op::HEARTBEAT_ACK, "d": d });
+
+                (Some(VoiceEvent::Heartbeat(d)), Some(ack))
+            }
+            Some(op::SPEAKING) => {
+                let event = VoiceEvent::Speaking {
+                    speaking: d["speaking"]
+                        .as_u64()
+                        .and_then(|speaking| u8::try_from(speaking).ok())
+                        .unwrap_or_default(),
+                    ssrc: d["ssrc"]
+                        .as_u64()
+                        .and_then(|ssrc| u32::try_from(ssrc).ok())
+                        .unwrap_or_default(),
+                };
+
+                (Some(event), None)
+            }
+            _ => (None, None),
+        };
+
+        if let Some(event) = event {
+            let _res = events.send(event);
+        }
+
+        if let Some(reply) = reply {
+            if websocket
+                .send(Message::Text(reply.to_string()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Receive voice packets, answering IP discovery requests.
+async fn receive_packets(udp: Arc<UdpSocket>, events: UnboundedSender<VoiceEvent>) {
+    let mut buf = [0; 2048];
+
+    loop {
+        let (len, address) = match udp.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(source) => {
+                tracing::debug!("receiving packet failed: {source}");
+
+                continue;
+            }
+        };
+
+        let packet = &buf[..len];
+
+        if len == DISCOVERY_LEN && packet[..2] == [0, 1] {
+            let response = discovery_response(packet, address);
+
+            if let Err(source) = udp.send_to(&response, address).await {
+                tracing::debug!("answering IP discovery failed: {source}");
+            }
+        } else {
+            let _res = events.send(VoiceEvent::Packet(packet.to_vec()));
+        }
+    }
+}
+
+/// Create the response to an IP discovery request.
+fn discovery_response(request: &[u8], address: SocketAddr) -> [u8; DISCOVERY_LEN] {
+    let mut response = [0; DISCOVERY_LEN];
+    response[..2].copy_from_slice(&2_u16.to_be_bytes());
+    response[2..4].copy_from_slice(&70_u16.to_be_bytes());
+    response[4..8].copy_from_slice(&request[4..8]);
+
+    let ip = address.ip().to_string();
+    response[8..8 + ip.len()].copy_from_slice(ip.as_bytes());
+    response[72..].copy_from_slice(&address.port().to_be_bytes());
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        discovery_response, MockVoiceServer, MockVoiceServerBuilder, VoiceEvent, DISCOVERY_LEN,
+    };
+    use static_assertions::assert_impl_all;
+    use std::{fmt::Debug, net::SocketAddr};
+
+    assert_impl_all!(MockVoiceServerBuilder: Clone, Debug, Default, Send, Sync);
+    assert_impl_all!(MockVoiceServer: Debug, Send, Sync);
+    assert_impl_all!(VoiceEvent: Clone, Debug, PartialEq, Send, Sync);
+
+    #[test]
+    fn discovery() {
+        let mut request = [0; DISCOVERY_LEN];
+        request[..8].copy_from_slice(&[0, 1, 0, 70, 0, 0, 0, 42]);
+        let address = SocketAddr::from(([127, 0, 0, 1], 5000));
+
+        let response = discovery_response(&request, address);
+
+        assert_eq!([0, 2, 0, 70, 0, 0, 0, 42], response[..8]);
+        assert_eq!(b"127.0.0.1\0", &response[8..18]);
+        assert_eq!(5000_u16.to_be_bytes(), response[72..]);
+    }
+}
@@ -0,0 +1,509 @@
+//! Utilities for formatting URLs of assets hosted on Discord's CDN.
+//!
+//! Each function returns an [`ImageUrl`] for an asset, which can be
+//! customized with an [`ImageFormat`] and size before being formatted via its
+//! [`Display`] implementation.
+//!
+//! # Examples
+//!
+//! Format the URL of a user's avatar as a 256 pixel WebP image:
+//!
+//! ```
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use twilight_model::{id::Id, util::ImageHash};
+//! use twilight_util::link::cdn::{self, ImageFormat};
+//!
+//! let avatar = ImageHash::parse(b"b0e09d6697b11e9c79a89e5e3756ddee")?;
+//! let url = cdn::user_avatar(Id::new(123), avatar)
+//!     .format(ImageFormat::WebP)?
+//!     .size(256)?;
+//!
+//! assert_eq!(
+//!     "https://cdn.discordapp.com/avatars/123/b0e09d6697b11e9c79a89e5e3756ddee.webp?size=256",
+//!     url.to_string(),
+//! );
+//! # Ok(()) }
+//! ```
+
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+use twilight_model::{
+    channel::message::sticker::StickerFormatType,
+    id::{
+        marker::{
+            ApplicationMarker, EmojiMarker, GuildMarker, RoleMarker, ScheduledEventMarker,
+            StickerMarker, UserMarker,
+        },
+        Id,
+    },
+    util::ImageHash,
+};
+
+/// Base URL of Discord's CDN.
+pub const BASE_URL: &str = "https://cdn.discordapp.com";
+
+/// Formats supported by assets which may be animated.
+const ANIMATABLE_FORMATS: &[ImageFormat] = &[
+    ImageFormat::Gif,
+    ImageFormat::Jpeg,
+    ImageFormat::Png,
+    ImageFormat::WebP,
+];
+
+/// Formats supported by assets which are never animated.
+const STATIC_FORMATS: &[ImageFormat] = &[ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::WebP];
+
+/// Maximum size of an image.
+const SIZE_MAX: u16 = 4096;
+
+/// Minimum size of an image.
+const SIZE_MIN: u16 = 16;
+
+/// Error when configuring an [`ImageUrl`].
+#[derive(Debug)]
+pub struct ImageUrlError {
+    kind: ImageUrlErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ImageUrlError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ImageUrlErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (ImageUrlErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for ImageUrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            ImageUrlErrorType::FormatUnsupported { format } => {
+                f.write_str("format ")?;
+                f.write_str(format.extension())?;
+
+                f.write_str(" is not supported by the asset")
+            }
+            ImageUrlErrorType::SizeInvalid { size } => {
+                f.write_str("size ")?;
+                Display::fmt(size, f)?;
+                f.write_str(" is not a power of two between ")?;
+                Display::fmt(&SIZE_MIN, f)?;
+                f.write_str(" and ")?;
+
+                Display::fmt(&SIZE_MAX, f)
+            }
+        }
+    }
+}
+
+impl Error for ImageUrlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`ImageUrlError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ImageUrlErrorType {
+    /// Format is not supported by the asset.
+    ///
+    /// GIFs are only supported by animated assets.
+    FormatUnsupported {
+        /// Provided format.
+        format: ImageFormat,
+    },
+    /// Size is not a power of two between 16 and 4096.
+    SizeInvalid {
+        /// Provided size.
+        size: u16,
+    },
+}
+
+/// Format of an asset.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ImageFormat {
+    /// GIF format, only supported by animated assets.
+    Gif,
+    /// JPEG format.
+    Jpeg,
+    /// Lottie format, only supported by stickers.
+    Lottie,
+    /// PNG format.
+    Png,
+    /// WebP format.
+    WebP,
+}
+
+impl ImageFormat {
+    /// File extension of the format.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::Jpeg => "jpg",
+            Self::Lottie => "json",
+            Self::Png => "png",
+            Self::WebP => "webp",
+        }
+    }
+}
+
+/// URL of an asset on Discord's CDN.
+///
+/// The URL defaults to the GIF format for animated assets and the PNG format
+/// otherwise, without a size. Format the URL via its [`Display`]
+/// implementation.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[must_use = "image URLs have no effect if not formatted"]
+pub struct ImageUrl {
+    /// Whether the asset is animated, and may be formatted as a GIF.
+    animated: bool,
+    /// Format of the image.
+    format: ImageFormat,
+    /// Formats supported by the asset.
+    formats: &'static [ImageFormat],
+    /// Path of the asset, without the extension.
+    path: String,
+    /// Size of the image, if any.
+    size: Option<u16>,
+}
+
+impl ImageUrl {
+    /// Create a URL for an asset that may be animated.
+    const fn animatable(path: String, animated: bool) -> Self {
+        Self {
+            animated,
+            format: if animated {
+                ImageFormat::Gif
+            } else {
+                ImageFormat::Png
+            },
+            formats: ANIMATABLE_FORMATS,
+            path,
+            size: None,
+        }
+    }
+
+    /// Create a URL for an asset that is never animated.
+    const fn fixed(path: String) -> Self {
+        Self {
+            animated: false,
+            format: ImageFormat::Png,
+            formats: STATIC_FORMATS,
+            path,
+            size: None,
+        }
+    }
+
+    /// Set the format of the image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`FormatUnsupported`] if the asset does not
+    /// support the format. GIFs are only supported by animated assets.
+    ///
+    /// [`FormatUnsupported`]: ImageUrlErrorType::FormatUnsupported
+    pub fn format(mut self, format: ImageFormat) -> Result<Self, ImageUrlError> {
+        if !self.formats.contains(&format) || (format == ImageFormat::Gif && !self.animated) {
+            return Err(ImageUrlError {
+                kind: ImageUrlErrorType::FormatUnsupported { format },
+                source: None,
+            });
+        }
+
+        self.format = format;
+
+        Ok(self)
+    }
+
+    /// Set the size of the image in pixels.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`SizeInvalid`] if the size is not a power of
+    /// two between 16 and 4096.
+    ///
+    /// [`SizeInvalid`]: ImageUrlErrorType::SizeInvalid
+    pub fn size(mut self, size: u16) -> Result<Self, ImageUrlError> {
+        if !size.is_power_of_two() || !(SIZE_MIN..=SIZE_MAX).contains(&size) {
+            return Err(ImageUrlError {
+                kind: ImageUrlErrorType::SizeInvalid { size },
+                source: None,
+            });
+        }
+
+        self.size = Some(size);
+
+        Ok(self)
+    }
+
+    /// Format of the image.
+    pub const fn current_format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// Size of the image, if any.
+    pub const fn current_size(&self) -> Option<u16> {
+        self.size
+    }
+}
+
+impl Display for ImageUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(BASE_URL)?;
+        f.write_str("/")?;
+        f.write_str(&self.path)?;
+        f.write_str(".")?;
+        f.write_str(self.format.extension())?;
+
+        if let Some(size) = self.size {
+            f.write_str("?size=")?;
+            Display::fmt(&size, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// URL of an application's icon.
+pub fn application_icon(application_id: Id<ApplicationMarker>, icon: ImageHash) -> ImageUrl {
+    ImageUrl::fixed(format!("app-icons/{application_id}/{icon}"))
+}
+
+/// URL of a user's default avatar, based on their discriminator.
+///
+/// Default avatars only support the PNG format.
+pub fn default_avatar(discriminator: u16) -> ImageUrl {
+    let mut url = ImageUrl::fixed(format!("embed/avatars/{}", discriminator % 5));
+    url.formats = &[ImageFormat::Png];
+
+    url
+}
+
+/// URL of a custom emoji.
+pub fn emoji(emoji_id: Id<EmojiMarker>, animated: bool) -> ImageUrl {
+    ImageUrl::animatable(format!("emojis/{emoji_id}"), animated)
+}
+
+/// URL of a guild's banner.
+pub fn guild_banner(guild_id: Id<GuildMarker>, banner: ImageHash) -> ImageUrl {
+    ImageUrl::animatable(format!("banners/{guild_id}/{banner}"), banner.is_animated())
+}
+
+/// URL of a guild's discovery splash.
+pub fn guild_discovery_splash(guild_id: Id<GuildMarker>, splash: ImageHash) -> ImageUrl {
+    ImageUrl::fixed(format!("discovery-splashes/{guild_id}/{splash}"))
+}
+
+/// URL of a guild's icon.
+pub fn guild_icon(guild_id: Id<GuildMarker>, icon: ImageHash) -> ImageUrl {
+    ImageUrl::animatable(format!("icons/{guild_id}/{icon}"), icon.is_animated())
+}
+
+/// URL of a guild's invite splash.
+pub fn guild_splash(guild_id: Id<GuildMarker>, splash: ImageHash) -> ImageUrl {
+    ImageUrl::fixed(format!("splashes/{guild_id}/{splash}"))
+}
+
+/// URL of a member's guild-specific avatar.
+pub fn member_avatar(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    avatar: ImageHash,
+) -> ImageUrl {
+    ImageUrl::animatable(
+        format!("guilds/{guild_id}/users/{user_id}/avatars/{avatar}"),
+        avatar.is_animated(),
+    )
+}
+
+/// URL of a role's icon.
+pub fn role_icon(role_id: Id<RoleMarker>, icon: ImageHash) -> ImageUrl {
+    ImageUrl::fixed(format!("role-icons/{role_id}/{icon}"))
+}
+
+/// URL of a scheduled event's cover image.
+pub fn scheduled_event_cover(event_id: Id<ScheduledEventMarker>, cover: ImageHash) -> ImageUrl {
+    ImageUrl::fixed(format!("guild-events/{event_id}/{cover}"))
+}
+
+/// URL of a sticker.
+///
+/// Lottie stickers only support the [`ImageFormat::Lottie`] format, while
+/// other stickers only support the [`ImageFormat::Png`] format.
+pub fn sticker(sticker_id: Id<StickerMarker>, format_type: StickerFormatType) -> ImageUrl {
+    let mut url = ImageUrl::fixed(format!("stickers/{sticker_id}"));
+
+    if format_type == StickerFormatType::Lottie {
+        url.format = ImageFormat::Lottie;
+        url.formats = &[ImageFormat::Lottie];
+    } else {
+        url.formats = &[ImageFormat::Png];
+    }
+
+    url
+}
+
+/// URL of a user's avatar.
+pub fn user_avatar(user_id: Id<UserMarker>, avatar: ImageHash) -> ImageUrl {
+    ImageUrl::animatable(format!("avatars/{user_id}/{avatar}"), avatar.is_animated())
+}
+
+/// URL of a user's banner.
+pub fn user_banner(user_id: Id<UserMarker>, banner: ImageHash) -> ImageUrl {
+    ImageUrl::animatable(format!("banners/{user_id}/{banner}"), banner.is_animated())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ImageFormat, ImageUrl, ImageUrlError, ImageUrlErrorType};
+    use static_assertions::{assert_fields, assert_impl_all};
+    use std::{error::Error, fmt::Debug, hash::Hash};
+    use twilight_model::{channel::message::sticker::StickerFormatType, id::Id, util::ImageHash};
+
+    assert_fields!(ImageUrlErrorType::FormatUnsupported: format);
+    assert_fields!(ImageUrlErrorType::SizeInvalid: size);
+    assert_impl_all!(ImageFormat: Clone, Copy, Debug, Eq, Hash, PartialEq, Send, Sync);
+    assert_impl_all!(ImageUrl: Clone, Debug, Eq, Hash, PartialEq, Send, Sync);
+    assert_impl_all!(ImageUrlErrorType: Debug, Send, Sync);
+    assert_impl_all!(ImageUrlError: Debug, Error, Send, Sync);
+
+    const HASH: &str = "b0e09d6697b11e9c79a89e5e3756ddee";
+    const ANIMATED_HASH: &str = "a_b0e09d6697b11e9c79a89e5e3756ddee";
+
+    fn hash(value: &str) -> ImageHash {
+        ImageHash::parse(value.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn default_format() {
+        assert_eq!(
+            format!("https://cdn.discordapp.com/avatars/1/{HASH}.png"),
+            super::user_avatar(Id::new(1), hash(HASH)).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/avatars/1/{ANIMATED_HASH}.gif"),
+            super::user_avatar(Id::new(1), hash(ANIMATED_HASH)).to_string(),
+        );
+        assert_eq!(
+            "https://cdn.discordapp.com/stickers/2.json",
+            super::sticker(Id::new(2), StickerFormatType::Lottie).to_string(),
+        );
+        assert_eq!(
+            "https://cdn.discordapp.com/embed/avatars/3.png",
+            super::default_avatar(1238).to_string(),
+        );
+    }
+
+    #[test]
+    fn paths() {
+        let hash = hash(HASH);
+
+        assert_eq!(
+            format!("https://cdn.discordapp.com/guilds/1/users/2/avatars/{HASH}.png"),
+            super::member_avatar(Id::new(1), Id::new(2), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/icons/1/{HASH}.png"),
+            super::guild_icon(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/splashes/1/{HASH}.png"),
+            super::guild_splash(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/discovery-splashes/1/{HASH}.png"),
+            super::guild_discovery_splash(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/banners/1/{HASH}.png"),
+            super::guild_banner(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/banners/1/{HASH}.png"),
+            super::user_banner(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/role-icons/1/{HASH}.png"),
+            super::role_icon(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            "https://cdn.discordapp.com/emojis/1.gif",
+            super::emoji(Id::new(1), true).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/app-icons/1/{HASH}.png"),
+            super::application_icon(Id::new(1), hash).to_string(),
+        );
+        assert_eq!(
+            format!("https://cdn.discordapp.com/guild-events/1/{HASH}.png"),
+            super::scheduled_event_cover(Id::new(1), hash).to_string(),
+        );
+    }
+
+    #[test]
+    fn format() -> Result<(), Box<dyn Error>> {
+        let url = super::guild_icon(Id::new(1), hash(ANIMATED_HASH)).format(ImageFormat::Jpeg)?;
+        assert_eq!(ImageFormat::Jpeg, url.current_format());
+        assert_eq!(
+            format!("https://cdn.discordapp.com/icons/1/{ANIMATED_HASH}.jpg"),
+            url.to_string(),
+        );
+
+        assert!(matches!(
+            super::guild_icon(Id::new(1), hash(HASH))
+                .format(ImageFormat::Gif)
+                .unwrap_err()
+                .kind(),
+            ImageUrlErrorType::FormatUnsupported {
+                format: ImageFormat::Gif
+            },
+        ));
+        assert!(super::guild_splash(Id::new(1), hash(ANIMATED_HASH))
+            .format(ImageFormat::Gif)
+            .is_err());
+        assert!(super::default_avatar(0).format(ImageFormat::WebP).is_err());
+        assert!(super::sticker(Id::new(1), StickerFormatType::Png)
+            .format(ImageFormat::Lottie)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn size() -> Result<(), Box<dyn Error>> {
+        let url = super::emoji(Id::new(1), false).size(4096)?;
+        assert_eq!(Some(4096), url.current_size());
+        assert_eq!(
+            "https://cdn.discordapp.com/emojis/1.png?size=4096",
+            url.to_string(),
+        );
+        assert!(super::emoji(Id::new(1), false).size(16).is_ok());
+
+        for size in [0, 8, 100, 8192] {
+            assert!(matches!(
+                super::emoji(Id::new(1), false).size(size).unwrap_err().kind(),
+                ImageUrlErrorType::SizeInvalid { size: invalid } if *invalid == size,
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,841 @@
+//! Connection to a voice server.
+
+use crate::{
+    crypto::{Cipher, EncryptionMode},
+    payload::{self, Hello, Identify, Incoming, Ready, SelectProtocol, SelectProtocolData},
+    rtp::{self, Rtp, DISCOVERY_LEN},
+    SpeakingFlags,
+};
+use futures_util::{SinkExt, StreamExt};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    net::{TcpStream, UdpSocket},
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time,
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        protocol::{frame::coding::CloseCode as WsCloseCode, CloseFrame},
+        Message,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
+use twilight_model::{
+    gateway::payload::incoming::VoiceServerUpdate,
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+    voice::{OpCode, VoiceState},
+};
+
+/// Version of the voice gateway used by this crate.
+pub const VOICE_GATEWAY_VERSION: u8 = 4;
+
+/// Opus frame of silence.
+///
+/// Discord recommends sending five frames of silence when audio stops to
+/// avoid interpolation of the last frames.
+pub const SILENCE_FRAME: [u8; 3] = [0xF8, 0xFF, 0xFE];
+
+/// Duration of the audio of a frame.
+pub const FRAME_DURATION: Duration = Duration::from_millis(20);
+
+/// Maximum duration of the handshake before connecting is aborted.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Duration to wait for an IP discovery response before retrying.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Number of IP discovery requests to send before giving up.
+const DISCOVERY_ATTEMPTS: u8 = 3;
+
+type VoiceStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Connecting to a voice server failed.
+#[derive(Debug)]
+pub struct ConnectError {
+    kind: ConnectErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl ConnectError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ConnectErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (ConnectErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+
+    fn new(kind: ConnectErrorType, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self {
+            kind,
+            source: Some(source.into()),
+        }
+    }
+}
+
+impl Display for ConnectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            ConnectErrorType::Closed { code } => {
+                f.write_str("voice server closed the connection during the handshake")?;
+
+                if let Some(code) = code {
+                    f.write_str(" with close code ")?;
+                    Display::fmt(code, f)?;
+                }
+
+                Ok(())
+            }
+            ConnectErrorType::Connecting => f.write_str("connecting to the voice server failed"),
+            ConnectErrorType::Deserializing => {
+                f.write_str("payload from the voice server is invalid")
+            }
+            ConnectErrorType::Discovering => f.write_str("discovering the external IP failed"),
+            ConnectErrorType::EncryptionModeUnsupported { offered } => {
+                f.write_str("none of the encryption modes offered are supported: ")?;
+
+                f.write_str(&offered.join(", "))
+            }
+            ConnectErrorType::Sending => {
+                f.write_str("sending a payload to the voice server failed")
+            }
+            ConnectErrorType::Timeout => f.write_str("handshake with the voice server timed out"),
+            ConnectErrorType::Udp => f.write_str("setting up the UDP socket failed"),
+        }
+    }
+}
+
+impl Error for ConnectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`ConnectError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConnectErrorType {
+    /// Voice server closed the connection during the handshake.
+    Closed {
+        /// Close code, if any.
+        ///
+        /// Refer to [`CloseCode`] for the meaning of close codes.
+        ///
+        /// [`CloseCode`]: twilight_model::voice::CloseCode
+        code: Option<u16>,
+    },
+    /// Connecting to the voice gateway failed.
+    Connecting,
+    /// Payload from the voice server could not be deserialized.
+    Deserializing,
+    /// Discovering the external IP address and port failed.
+    Discovering,
+    /// None of the encryption modes offered by the voice server are
+    /// supported.
+    EncryptionModeUnsupported {
+        /// Names of the offered modes.
+        offered: Vec<String>,
+    },
+    /// Sending a payload to the voice server failed.
+    Sending,
+    /// Handshake did not complete in time.
+    Timeout,
+    /// Binding or connecting the UDP socket failed.
+    Udp,
+}
+
+/// Sending to a voice server failed.
+#[derive(Debug)]
+pub struct SendError {
+    kind: SendErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl SendError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &SendErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (SendErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for SendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            SendErrorType::Closed => f.write_str("connection to the voice server is closed"),
+            SendErrorType::Encrypting => f.write_str("encrypting the frame failed"),
+            SendErrorType::Sending => f.write_str("sending the frame failed"),
+        }
+    }
+}
+
+impl Error for SendError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`SendError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SendErrorType {
+    /// Connection to the voice gateway is closed.
+    Closed,
+    /// Encrypting the frame failed.
+    Encrypting,
+    /// Sending the frame over UDP failed.
+    Sending,
+}
+
+/// Information required to connect to a voice server.
+///
+/// The information is sent by the gateway after updating the voice state of
+/// the bot's user in a [`VoiceServerUpdate`] and [`VoiceState`] event.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionInfo {
+    endpoint: String,
+    guild_id: Id<GuildMarker>,
+    session_id: String,
+    token: String,
+    user_id: Id<UserMarker>,
+}
+
+impl ConnectionInfo {
+    /// Create connection information.
+    ///
+    /// The endpoint may include a scheme, such as `ws://` for local servers,
+    /// and otherwise defaults to `wss://`.
+    pub const fn new(
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        session_id: String,
+        endpoint: String,
+        token: String,
+    ) -> Self {
+        Self {
+            endpoint,
+            guild_id,
+            session_id,
+            token,
+            user_id,
+        }
+    }
+
+    /// Create connection information from the events received after updating
+    /// the voice state of the bot's user.
+    ///
+    /// Returns `None` if the voice server has not been allocated yet or the
+    /// events are not of a guild.
+    pub fn from_events(server: &VoiceServerUpdate, state: &VoiceState) -> Option<Self> {
+        Some(Self::new(
+            server.guild_id?,
+            state.user_id,
+            state.session_id.clone(),
+            server.endpoint.clone()?,
+            server.token.clone(),
+        ))
+    }
+
+    /// Endpoint of the voice server.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// ID of the guild.
+    pub const fn guild_id(&self) -> Id<GuildMarker> {
+        self.guild_id
+    }
+
+    /// ID of the gateway session of the bot's user.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Token of the voice server.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// ID of the bot's user.
+    pub const fn user_id(&self) -> Id<UserMarker> {
+        self.user_id
+    }
+
+    /// URL of the voice gateway.
+    fn url(&self) -> String {
+        let endpoint = self.endpoint.trim_end_matches('/');
+
+        if endpoint.contains("://") {
+            format!("{endpoint}/?v={VOICE_GATEWAY_VERSION}")
+        } else {
+            format!("wss://{endpoint}/?v={VOICE_GATEWAY_VERSION}")
+        }
+    }
+}
+
+/// State of the connection shared with its background task.
+#[derive(Debug, Default)]
+struct State {
+    /// Close code of the connection, if it was closed with one.
+    close_code: Option<u16>,
+    /// Whether the connection to the voice gateway is closed.
+    closed: bool,
+    /// Round trip time of the last acknowledged heartbeat.
+    latency: Option<Duration>,
+}
+
+/// Connection to a voice server, sending Opus frames of a guild.
+///
+/// Heartbeats are sent in a background task until the connection is dropped
+/// or closed.
+///
+/// # Examples
+///
+/// Connect to a voice server and play pre-encoded Opus frames:
+///
+/// ```no_run
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let (server, state): (twilight_model::gateway::payload::incoming::VoiceServerUpdate, twilight_model::voice::VoiceState) = unimplemented!();
+/// use twilight_voice::{Connection, ConnectionInfo, SpeakingFlags};
+///
+/// // Received after sending a voice state update over the gateway.
+/// let info = ConnectionInfo::from_events(&server, &state).expect("voice server allocated");
+///
+/// let mut connection = Connection::connect(&info).await?;
+/// connection.speaking(SpeakingFlags::MICROPHONE)?;
+///
+/// let frames: Vec<Vec<u8>> = Vec::new();
+/// connection.play(frames).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct Connection {
+    cipher: Cipher,
+    rtp: Rtp,
+    ssrc: u32,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+    tx: UnboundedSender<Outgoing>,
+    udp: UdpSocket,
+}
+
+impl Connection {
+    /// Connect to a voice server, performing the handshake and IP discovery.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConnectErrorType::Closed`] error type if the voice server
+    /// closed the connection, such as due to an invalid token.
+    ///
+    /// Returns a [`ConnectErrorType::EncryptionModeUnsupported`] error type if
+    /// none of the offered encryption modes are supported.
+    ///
+    /// Returns a [`ConnectErrorType::Timeout`] error type if the handshake
+    /// did not complete in time.
+    ///
+    /// Refer to [`ConnectErrorType`] for other errors.
+    pub async fn connect(info: &ConnectionInfo) -> Result<Self, ConnectError> {
+        time::timeout(HANDSHAKE_TIMEOUT, handshake(info))
+            .await
+            .map_err(|source| ConnectError::new(ConnectErrorType::Timeout, source))?
+    }
+
+    /// Close the connection to the voice gateway.
+    pub fn close(&self) {
+        let _res = self.tx.send(Outgoing::Close);
+    }
+
+    /// Close code of the connection, if the voice server closed it with one.
+    ///
+    /// Refer to [`CloseCode`] for the meaning of close codes.
+    ///
+    /// [`CloseCode`]: twilight_model::voice::CloseCode
+    pub fn close_code(&self) -> Option<u16> {
+        self.state().close_code
+    }
+
+    /// Whether the connection to the voice gateway is closed.
+    ///
+    /// Frames can't be sent once the connection is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state().closed
+    }
+
+    /// Round trip time of the last acknowledged heartbeat.
+    pub fn latency(&self) -> Option<Duration> {
+        self.state().latency
+    }
+
+    /// Encryption mode used for voice packets.
+    pub const fn mode(&self) -> EncryptionMode {
+        self.cipher.mode()
+    }
+
+    /// Send Opus frames paced at their duration, followed by five frames of
+    /// silence.
+    ///
+    /// Frames must be 20 milliseconds long.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by [`send_frame`].
+    ///
+    /// [`send_frame`]: Self::send_frame
+    pub async fn play<I>(&mut self, frames: I) -> Result<(), SendError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut interval = time::interval(FRAME_DURATION);
+        let silence = std::iter::repeat(SILENCE_FRAME.to_vec()).take(5);
+
+        for frame in frames
+            .into_iter()
+            .map(|frame| frame.as_ref().to_vec())
+            .chain(silence)
+        {
+            interval.tick().await;
+            self.send_frame(&frame).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Encrypt and send an Opus frame of 20 milliseconds.
+    ///
+    /// Frames are sent immediately, so they must be paced by the caller, such
+    /// as via [`play`]. Call [`skip_frame`] for frames that aren't sent to
+    /// keep the audio in sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SendErrorType::Closed`] error type if the connection to the
+    /// voice gateway is closed.
+    ///
+    /// Returns a [`SendErrorType::Encrypting`] error type if the frame failed
+    /// to be encrypted.
+    ///
+    /// Returns a [`SendErrorType::Sending`] error type if sending the packet
+    /// failed.
+    ///
+    /// [`play`]: Self::play
+    /// [`skip_frame`]: Self::skip_frame
+    pub async fn send_frame(&mut self, frame: &[u8]) -> Result<(), SendError> {
+        if self.is_closed() {
+            return Err(SendError {
+                kind: SendErrorType::Closed,
+                source: None,
+            });
+        }
+
+        let header = self.rtp.next_header();
+        let packet = self
+            .cipher
+            .encrypt(&header, frame)
+            .map_err(|source| SendError {
+                kind: SendErrorType::Encrypting,
+                source: Some(Box::new(source)),
+            })?;
+
+        self.udp.send(&packet).await.map_err(|source| SendError {
+            kind: SendErrorType::Sending,
+            source: Some(Box::new(source)),
+        })?;
+
+        Ok(())
+    }
+
+    /// Skip a frame, advancing the timestamp of the next frame.
+    pub fn skip_frame(&mut self) {
+        self.rtp.skip();
+    }
+
+    /// Set the speaking flags of the bot's user.
+    ///
+    /// Discord doesn't play audio from users who aren't speaking.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SendErrorType::Closed`] error type if the connection to the
+    /// voice gateway is closed.
+    pub fn speaking(&self, flags: SpeakingFlags) -> Result<(), SendError> {
+        let json = payload::to_json(
+            OpCode::Speaking,
+            payload::Speaking {
+                delay: 0,
+                flags,
+                ssrc: self.ssrc,
+            },
+        );
+
+        self.tx.send(Outgoing::Json(json)).map_err(|_| SendError {
+            kind: SendErrorType::Closed,
+            source: None,
+        })
+    }
+
+    /// SSRC of the bot's audio stream.
+    pub const fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("state poisoned")
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Message to the background task of a connection.
+#[derive(Debug)]
+enum Outgoing {
+    Close,
+    Json(String),
+}
+
+/// Connect to the voice gateway and perform the handshake.
+async fn handshake(info: &ConnectionInfo) -> Result<Connection, ConnectError> {
+    let url = info.url();
+    tracing::debug!(%url, "connecting to voice server");
+
+    let (mut websocket, _) = tokio_tungstenite::connect_async(&url)
+        .await
+        .map_err(|source| ConnectError::new(ConnectErrorType::Connecting, source))?;
+
+    let identify = payload::to_json(
+        OpCode::Identify,
+        Identify {
+            server_id: info.guild_id.to_string(),
+            session_id: &info.session_id,
+            token: &info.token,
+            user_id: info.user_id.to_string(),
+        },
+    );
+    send(&mut websocket, identify).await?;
+
+    let mut heartbeat_interval = None;
+
+    let ready = loop {
+        let incoming = next_payload(&mut websocket).await?;
+
+        match incoming.op {
+            OpCode::Hello => {
+                heartbeat_interval = Some(parse::<Hello>(incoming)?.heartbeat_interval);
+            }
+            OpCode::Ready => break parse::<Ready>(incoming)?,
+            op => tracing::debug!(?op, "ignoring payload during handshake"),
+        }
+    };
+
+    let mode = EncryptionMode::preferred(&ready.modes).ok_or_else(|| ConnectError {
+        kind: ConnectErrorType::EncryptionModeUnsupported {
+            offered: ready.modes.clone(),
+        },
+        source: None,
+    })?;
+
+    let udp = UdpSocket::bind(("0.0.0.0", 0))
+        .await
+        .map_err(|source| ConnectError::new(ConnectErrorType::Udp, source))?;
+    udp.connect((ready.ip.as_str(), ready.port))
+        .await
+        .map_err(|source| ConnectError::new(ConnectErrorType::Udp, source))?;
+
+    let (address, port) = discover(&udp, ready.ssrc).await?;
+    tracing::debug!(%address, port, ?mode, "discovered external address");
+
+    let select_protocol = payload::to_json(
+        OpCode::SelectProtocol,
+        SelectProtocol {
+            data: SelectProtocolData {
+                address,
+                mode: mode.name(),
+                port,
+            },
+            protocol: "udp",
+        },
+    );
+    send(&mut websocket, select_protocol).await?;
+
+    let description = loop {
+        let incoming = next_payload(&mut websocket).await?;
+
+        match incoming.op {
+            OpCode::Hello => {
+                heartbeat_interval = Some(parse::<Hello>(incoming)?.heartbeat_interval);
+            }
+            OpCode::SessionDescription => {
+                break parse::<payload::SessionDescription>(incoming)?;
+            }
+            op => tracing::debug!(?op, "ignoring payload during handshake"),
+        }
+    };
+
+    while heartbeat_interval.is_none() {
+        let incoming = next_payload(&mut websocket).await?;
+
+        if incoming.op == OpCode::Hello {
+            heartbeat_interval = Some(parse::<Hello>(incoming)?.heartbeat_interval);
+        }
+    }
+
+    let heartbeat_interval =
+        Duration::from_secs_f64(heartbeat_interval.unwrap_or_default().max(1.0) / 1000.0);
+    let mode = EncryptionMode::from_name(&description.mode).unwrap_or(mode);
+
+    let state = Arc::new(Mutex::new(State::default()));
+    let (tx, rx) = mpsc::unbounded_channel();
+    let task = tokio::spawn(run(websocket, heartbeat_interval, rx, Arc::clone(&state)));
+
+    Ok(Connection {
+        cipher: Cipher::new(mode, description.secret_key),
+        rtp: Rtp::new(ready.ssrc),
+        ssrc: ready.ssrc,
+        state,
+        task,
+        tx,
+        udp,
+    })
+}
+
+/// Discover the external address and port of the UDP socket.
+async fn discover(udp: &UdpSocket, ssrc: u32) -> Result<(String, u16), ConnectError> {
+    let request = rtp::discovery_request(ssrc);
+    let mut response = [0; DISCOVERY_LEN];
+
+    for _ in 0..DISCOVERY_ATTEMPTS {
+        udp.send(&request)
+            .await
+            .map_err(|source| ConnectError::new(ConnectErrorType::Discovering, source))?;
+
+        match time::timeout(DISCOVERY_TIMEOUT, udp.recv(&mut response)).await {
+            Ok(Ok(len)) => {
+                return rtp::parse_discovery_response(&response[..len]).ok_or(ConnectError {
+                    kind: ConnectErrorType::Discovering,
+                    source: None,
+                });
+            }
+            Ok(Err(source)) => {
+                return Err(ConnectError::new(ConnectErrorType::Discovering, source));
+            }
+            Err(_) => tracing::debug!("IP discovery timed out, retrying"),
+        }
+    }
+
+    Err(ConnectError {
+        kind: ConnectErrorType::Discovering,
+        source: None,
+    })
+}
+
+/// Receive the next payload during the handshake.
+async fn next_payload(websocket: &mut VoiceStream) -> Result<Incoming, ConnectError> {
+    loop {
+        match websocket.next().await {
+            Some(Ok(Message::Text(text))) => {
+                return serde_json::from_str(&text)
+                    .map_err(|source| ConnectError::new(ConnectErrorType::Deserializing, source));
+            }
+            Some(Ok(Message::Close(frame))) => {
+                return Err(ConnectError {
+                    kind: ConnectErrorType::Closed {
+                        code: frame.map(|frame| u16::from(frame.code)),
+                    },
+                    source: None,
+                });
+            }
+            Some(Ok(_)) => {}
+            Some(Err(source)) => {
+                return Err(ConnectError::new(
+                    ConnectErrorType::Closed { code: None },
+                    source,
+                ))
+            }
+            None => {
+                return Err(ConnectError {
+                    kind: ConnectErrorType::Closed { code: None },
+                    source: None,
+                })
+            }
+        }
+    }
+}
+
+/// Parse the data of a payload.
+fn parse<T: serde::de::DeserializeOwned>(incoming: Incoming) -> Result<T, ConnectError> {
+    serde_json::from_value(incoming.d)
+        .map_err(|source| ConnectError::new(ConnectErrorType::Deserializing, source))
+}
+
+/// Send a payload during the handshake.
+async fn send(websocket: &mut VoiceStream, json: String) -> Result<(), ConnectError> {
+    websocket
+        .send(Message::Text(json))
+        .await
+        .map_err(|source| ConnectError::new(ConnectErrorType::Sending, source))
+}
+
+/// Send heartbeats and payloads to the voice gateway and read its payloads
+/// until the connection closes.
+async fn run(
+    mut websocket: VoiceStream,
+    heartbeat_interval: Duration,
+    mut rx: UnboundedReceiver<Outgoing>,
+    state: Arc<Mutex<State>>,
+) {
+    let mut interval = time::interval(heartbeat_interval);
+    let mut heartbeat: Option<(u64, Instant)> = None;
+    let mut close_code = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let nonce = rand::random::<u64>() >> 12;
+                heartbeat = Some((nonce, Instant::now()));
+
+                let json = payload::to_json(OpCode::Heartbeat, nonce);
+
+                if websocket.send(Message::Text(json)).await.is_err() {
+                    break;
+                }
+            }
+            maybe_outgoing = rx.recv() => match maybe_outgoing {
+                Some(Outgoing::Json(json)) => {
+                    if websocket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Outgoing::Close) | None => {
+                    let frame = CloseFrame {
+                        code: WsCloseCode::Normal,
+                        reason: "".into(),
+                    };
+                    let _res = websocket.close(Some(frame)).await;
+
+                    break;
+                }
+            },
+            maybe_message = websocket.next() => match maybe_message {
+                Some(Ok(Message::Text(text))) => {
+                    let incoming = match serde_json::from_str::<Incoming>(&text) {
+                        Ok(incoming) => incoming,
+                        Err(source) => {
+                            tracing::debug!("received invalid payload: {source}");
+
+                            continue;
+                        }
+                    };
+
+                    if incoming.op == OpCode::HeartbeatAck {
+                        if let Some((nonce, sent)) = heartbeat {
+                            if incoming.d.as_u64() == Some(nonce) {
+                                state.lock().expect("state poisoned").latency =
+                                    Some(sent.elapsed());
+                            }
+                        }
+                    } else {
+                        tracing::trace!(op = ?incoming.op, "received payload");
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    close_code = frame.map(|frame| u16::from(frame.code));
+
+                    break;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(source)) => {
+                    tracing::debug!("voice connection errored: {source}");
+
+                    break;
+                }
+                None => break,
+            },
+        }
+    }
+
+    tracing::debug!(?close_code, "voice connection closed");
+
+    let mut state = state.lock().expect("state poisoned");
+    state.close_code = close_code;
+    state.closed = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConnectError, ConnectErrorType, Connection, ConnectionInfo, SendError, SendErrorType,
+    };
+    use static_assertions::{assert_fields, assert_impl_all};
+    use std::{error::Error, fmt::Debug};
+    use twilight_model::id::Id;
+
+    assert_impl_all!(ConnectErrorType: Debug, Send, Sync);
+    assert_fields!(ConnectErrorType::Closed: code);
+    assert_fields!(ConnectErrorType::EncryptionModeUnsupported: offered);
+    assert_impl_all!(ConnectError: Error, Send, Sync);
+    assert_impl_all!(SendErrorType: Debug, Send, Sync);
+    assert_impl_all!(SendError: Error, Send, Sync);
+    assert_impl_all!(ConnectionInfo: Clone, Debug, Eq, PartialEq, Send, Sync);
+    assert_impl_all!(Connection: Debug, Send, Sync);
+
+    fn info(endpoint: &str) -> ConnectionInfo {
+        ConnectionInfo::new(
+            Id::new(1),
+            Id::new(2),
+            "session".to_owned(),
+            endpoint.to_owned(),
+            "token".to_owned(),
+        )
+    }
+
+    #[test]
+    fn url() {
+        assert_eq!(
+            "wss://us-east1.discord.media:443/?v=4",
+            info("us-east1.discord.media:443").url()
+        );
+        assert_eq!(
+            "ws://127.0.0.1:5000/?v=4",
+            info("ws://127.0.0.1:5000/").url()
+        );
+    }
+}
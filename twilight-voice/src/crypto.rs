@@ -0,0 +1,399 @@
+//! Encryption of voice packets.
+//!
+//! Voice packets are RTP packets whose payloads are encrypted with the secret
+//! key of the session via one of the [`EncryptionMode`]s offered by the voice
+//! server.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305,
+};
+use crypto_secretbox::XSalsa20Poly1305;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Length of a nonce of the supported ciphers.
+const NONCE_LEN: usize = 24;
+
+/// Length of the nonce appended to packets of modes using a counter.
+const COUNTER_LEN: usize = 4;
+
+/// Length of an RTP header without CSRCs or extensions.
+pub(crate) const RTP_HEADER_LEN: usize = 12;
+
+/// Encrypting or decrypting a voice packet failed.
+#[derive(Debug)]
+pub struct CryptoError {
+    kind: CryptoErrorType,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl CryptoError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &CryptoErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (CryptoErrorType, Option<Box<dyn Error + Send + Sync>>) {
+        (self.kind, self.source)
+    }
+
+    const fn new(kind: CryptoErrorType) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+impl Display for CryptoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            CryptoErrorType::Decrypting => f.write_str("packet could not be decrypted"),
+            CryptoErrorType::Encrypting => f.write_str("packet could not be encrypted"),
+            CryptoErrorType::PacketInvalid => f.write_str("packet is not a valid RTP packet"),
+        }
+    }
+}
+
+impl Error for CryptoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`CryptoError`] that occurred.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CryptoErrorType {
+    /// Packet failed authentication, such as due to a wrong key or mode.
+    Decrypting,
+    /// Payload could not be encrypted.
+    Encrypting,
+    /// Packet is too short to be an encrypted RTP packet of the mode.
+    PacketInvalid,
+}
+
+/// Encryption mode of voice packets.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum EncryptionMode {
+    /// XChaCha20-Poly1305 AEAD with the RTP header as associated data and an
+    /// incrementing 32-bit nonce appended to the packet.
+    AeadXChaCha20Poly1305RtpSize,
+    /// XSalsa20-Poly1305 with the RTP header as the nonce.
+    Xsalsa20Poly1305,
+    /// XSalsa20-Poly1305 with an incrementing 32-bit nonce appended to the
+    /// packet.
+    Xsalsa20Poly1305Lite,
+    /// XSalsa20-Poly1305 with a random 24 byte nonce appended to the packet.
+    Xsalsa20Poly1305Suffix,
+}
+
+impl EncryptionMode {
+    /// Supported modes, from most to least preferred.
+    pub const PREFERENCE: [Self; 4] = [
+        Self::AeadXChaCha20Poly1305RtpSize,
+        Self::Xsalsa20Poly1305Lite,
+        Self::Xsalsa20Poly1305Suffix,
+        Self::Xsalsa20Poly1305,
+    ];
+
+    /// Parse a mode from its name used by the voice gateway.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::PREFERENCE
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+
+    /// Name of the mode used by the voice gateway.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::AeadXChaCha20Poly1305RtpSize => "aead_xchacha20_poly1305_rtpsize",
+            Self::Xsalsa20Poly1305 => "xsalsa20_poly1305",
+            Self::Xsalsa20Poly1305Lite => "xsalsa20_poly1305_lite",
+            Self::Xsalsa20Poly1305Suffix => "xsalsa20_poly1305_suffix",
+        }
+    }
+
+    /// Most preferred supported mode of modes offered by a voice server.
+    pub fn preferred(offered: &[impl AsRef<str>]) -> Option<Self> {
+        Self::PREFERENCE
+            .into_iter()
+            .find(|mode| offered.iter().any(|name| name.as_ref() == mode.name()))
+    }
+}
+
+/// Key of a session's cipher.
+#[derive(Clone)]
+enum Key {
+    XChaCha20(XChaCha20Poly1305),
+    Xsalsa20(XSalsa20Poly1305),
+}
+
+/// Cipher encrypting and decrypting the voice packets of a session.
+#[derive(Clone)]
+pub struct Cipher {
+    key: Key,
+    mode: EncryptionMode,
+    /// Nonce of the next packet for modes using a counter.
+    nonce: u32,
+}
+
+impl Cipher {
+    /// Create a cipher from the session's mode and secret key.
+    pub fn new(mode: EncryptionMode, secret_key: [u8; 32]) -> Self {
+        let key = match mode {
+            EncryptionMode::AeadXChaCha20Poly1305RtpSize => {
+                Key::XChaCha20(XChaCha20Poly1305::new(&secret_key.into()))
+            }
+            EncryptionMode::Xsalsa20Poly1305
+            | EncryptionMode::Xsalsa20Poly1305Lite
+            | EncryptionMode::Xsalsa20Poly1305Suffix => {
+                Key::Xsalsa20(XSalsa20Poly1305::new(&secret_key.into()))
+            }
+        };
+
+        Self {
+            key,
+            mode,
+            nonce: 0,
+        }
+    }
+
+    /// Mode of the cipher.
+    pub const fn mode(&self) -> EncryptionMode {
+        self.mode
+    }
+
+    /// Decrypt a voice packet, returning its payload.
+    ///
+    /// The payload includes the body of the RTP header extension, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CryptoErrorType::PacketInvalid`] error type if the packet is
+    /// too short.
+    ///
+    /// Returns a [`CryptoErrorType::Decrypting`] error type if the packet
+    /// failed authentication.
+    pub fn decrypt(&self, packet: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let header_len = self.header_len(packet)?;
+        let mut nonce = [0; NONCE_LEN];
+
+        let (header, body) = packet.split_at(header_len);
+        let ciphertext = match self.mode {
+            EncryptionMode::Xsalsa20Poly1305 => {
+                nonce[..RTP_HEADER_LEN].copy_from_slice(&header[..RTP_HEADER_LEN]);
+
+                body
+            }
+            EncryptionMode::AeadXChaCha20Poly1305RtpSize | EncryptionMode::Xsalsa20Poly1305Lite => {
+                let (ciphertext, counter) = split_suffix(body, COUNTER_LEN)?;
+                nonce[..COUNTER_LEN].copy_from_slice(counter);
+
+                ciphertext
+            }
+            EncryptionMode::Xsalsa20Poly1305Suffix => {
+                let (ciphertext, suffix) = split_suffix(body, NONCE_LEN)?;
+                nonce.copy_from_slice(suffix);
+
+                ciphertext
+            }
+        };
+
+        let result = match &self.key {
+            Key::XChaCha20(key) => key.decrypt(
+                &nonce.into(),
+                Payload {
+                    aad: header,
+                    msg: ciphertext,
+                },
+            ),
+            Key::Xsalsa20(key) => key.decrypt(&nonce.into(), ciphertext),
+        };
+
+        result.map_err(|_| CryptoError::new(CryptoErrorType::Decrypting))
+    }
+
+    /// Encrypt the payload of a voice packet, returning the packet.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CryptoErrorType::Encrypting`] error type if the payload
+    /// could not be encrypted.
+    pub fn encrypt(
+        &mut self,
+        header: &[u8; RTP_HEADER_LEN],
+        payload: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let mut nonce = [0; NONCE_LEN];
+
+        let suffix = match self.mode {
+            EncryptionMode::Xsalsa20Poly1305 => {
+                nonce[..RTP_HEADER_LEN].copy_from_slice(header);
+
+                Vec::new()
+            }
+            EncryptionMode::AeadXChaCha20Poly1305RtpSize | EncryptionMode::Xsalsa20Poly1305Lite => {
+                let counter = self.nonce.to_be_bytes();
+                self.nonce = self.nonce.wrapping_add(1);
+                nonce[..COUNTER_LEN].copy_from_slice(&counter);
+
+                counter.to_vec()
+            }
+            EncryptionMode::Xsalsa20Poly1305Suffix => {
+                nonce = rand::random();
+
+                nonce.to_vec()
+            }
+        };
+
+        let result = match &self.key {
+            Key::XChaCha20(key) => key.encrypt(
+                &nonce.into(),
+                Payload {
+                    aad: header,
+                    msg: payload,
+                },
+            ),
+            Key::Xsalsa20(key) => key.encrypt(&nonce.into(), payload),
+        };
+        let ciphertext = result.map_err(|_| CryptoError::new(CryptoErrorType::Encrypting))?;
+
+        let mut packet = Vec::with_capacity(RTP_HEADER_LEN + ciphertext.len() + suffix.len());
+        packet.extend_from_slice(header);
+        packet.extend_from_slice(&ciphertext);
+        packet.extend_from_slice(&suffix);
+
+        Ok(packet)
+    }
+
+    /// Length of the unencrypted header of a packet.
+    ///
+    /// Only the RTP size modes leave the header of an extension unencrypted.
+    fn header_len(&self, packet: &[u8]) -> Result<usize, CryptoError> {
+        let first = *packet
+            .first()
+            .ok_or_else(|| CryptoError::new(CryptoErrorType::PacketInvalid))?;
+        let csrc_count = usize::from(first & 0x0F);
+        let has_extension = first & 0x10 != 0;
+
+        let mut len = RTP_HEADER_LEN + 4 * csrc_count;
+
+        if has_extension && self.mode == EncryptionMode::AeadXChaCha20Poly1305RtpSize {
+            len += 4;
+        }
+
+        if packet.len() < len {
+            return Err(CryptoError::new(CryptoErrorType::PacketInvalid));
+        }
+
+        Ok(len)
+    }
+}
+
+impl std::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Cipher")
+            .field("key", &"<redacted>")
+            .field("mode", &self.mode)
+            .field("nonce", &self.nonce)
+            .finish()
+    }
+}
+
+/// Split a suffix of a length off of a packet's body.
+fn split_suffix(body: &[u8], len: usize) -> Result<(&[u8], &[u8]), CryptoError> {
+    let at = body
+        .len()
+        .checked_sub(len)
+        .ok_or_else(|| CryptoError::new(CryptoErrorType::PacketInvalid))?;
+
+    Ok(body.split_at(at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cipher, CryptoError, CryptoErrorType, EncryptionMode, RTP_HEADER_LEN};
+    use static_assertions::assert_impl_all;
+    use std::{error::Error, fmt::Debug, hash::Hash};
+
+    assert_impl_all!(Cipher: Clone, Debug, Send, Sync);
+    assert_impl_all!(CryptoErrorType: Debug, Send, Sync);
+    assert_impl_all!(CryptoError: Error, Send, Sync);
+    assert_impl_all!(EncryptionMode: Clone, Copy, Debug, Eq, Hash, PartialEq, Send, Sync);
+
+    const HEADER: [u8; RTP_HEADER_LEN] = [0x80, 0x78, 0, 1, 0, 0, 3, 192, 0, 0, 0, 42];
+    const KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn names() {
+        for mode in EncryptionMode::PREFERENCE {
+            assert_eq!(Some(mode), EncryptionMode::from_name(mode.name()));
+        }
+
+        assert_eq!(None, EncryptionMode::from_name("aead_aes256_gcm_rtpsize"));
+        assert_eq!(
+            Some(EncryptionMode::Xsalsa20Poly1305Lite),
+            EncryptionMode::preferred(&["xsalsa20_poly1305", "xsalsa20_poly1305_lite"])
+        );
+        assert_eq!(None, EncryptionMode::preferred(&["aead_aes256_gcm"]));
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), CryptoError> {
+        for mode in EncryptionMode::PREFERENCE {
+            let mut cipher = Cipher::new(mode, KEY);
+            let packet = cipher.encrypt(&HEADER, b"opus")?;
+
+            assert_eq!(&HEADER, &packet[..RTP_HEADER_LEN]);
+            assert_ne!(b"opus", &packet[RTP_HEADER_LEN..RTP_HEADER_LEN + 4]);
+            assert_eq!(b"opus".to_vec(), Cipher::new(mode, KEY).decrypt(&packet)?);
+
+            let wrong_key = Cipher::new(mode, [8; 32]).decrypt(&packet);
+            assert!(matches!(
+                wrong_key.unwrap_err().kind(),
+                CryptoErrorType::Decrypting
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn counter_nonces() -> Result<(), CryptoError> {
+        let mut cipher = Cipher::new(EncryptionMode::Xsalsa20Poly1305Lite, KEY);
+        let first = cipher.encrypt(&HEADER, b"opus")?;
+        let second = cipher.encrypt(&HEADER, b"opus")?;
+
+        assert_eq!([0, 0, 0, 0], first[first.len() - 4..]);
+        assert_eq!([0, 0, 0, 1], second[second.len() - 4..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_packets() {
+        let cipher = Cipher::new(EncryptionMode::Xsalsa20Poly1305Suffix, KEY);
+
+        assert!(matches!(
+            cipher.decrypt(&[]).unwrap_err().kind(),
+            CryptoErrorType::PacketInvalid
+        ));
+        assert!(matches!(
+            cipher.decrypt(&HEADER).unwrap_err().kind(),
+            CryptoErrorType::PacketInvalid
+        ));
+    }
+}
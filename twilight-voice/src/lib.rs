@@ -0,0 +1,30 @@
+#![deny(
+    clippy::all,
+    clippy::missing_const_for_fn,
+    clippy::pedantic,
+    future_incompatible,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    rustdoc::broken_intra_doc_links,
+    unsafe_code,
+    unused
+)]
+#![allow(clippy::module_name_repetitions, clippy::must_use_candidate)]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![doc = include_str!("../README.md")]
+
+pub mod crypto;
+
+mod connection;
+mod payload;
+mod rtp;
+mod speaking;
+
+pub use self::{
+    connection::{
+        ConnectError, ConnectErrorType, Connection, ConnectionInfo, SendError, SendErrorType,
+        FRAME_DURATION, SILENCE_FRAME, VOICE_GATEWAY_VERSION,
+    },
+    speaking::SpeakingFlags,
+};
@@ -0,0 +1,107 @@
+//! Payloads of the voice gateway.
+
+use crate::SpeakingFlags;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use twilight_model::voice::OpCode;
+
+/// Payload received from the voice gateway, with its data left unparsed.
+#[derive(Debug, Deserialize)]
+pub struct Incoming {
+    pub d: Value,
+    pub op: OpCode,
+}
+
+/// Payload sent to the voice gateway.
+#[derive(Debug, Serialize)]
+pub struct Outgoing<T> {
+    pub d: T,
+    pub op: OpCode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Hello {
+    pub heartbeat_interval: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Identify<'a> {
+    pub server_id: String,
+    pub session_id: &'a str,
+    pub token: &'a str,
+    pub user_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Ready {
+    pub ip: String,
+    pub modes: Vec<String>,
+    pub port: u16,
+    pub ssrc: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelectProtocol<'a> {
+    pub data: SelectProtocolData<'a>,
+    pub protocol: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelectProtocolData<'a> {
+    pub address: String,
+    pub mode: &'a str,
+    pub port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionDescription {
+    pub mode: String,
+    pub secret_key: [u8; 32],
+}
+
+#[derive(Debug, Serialize)]
+pub struct Speaking {
+    pub delay: u32,
+    #[serde(rename = "speaking")]
+    pub flags: SpeakingFlags,
+    pub ssrc: u32,
+}
+
+/// Serialize a payload to JSON.
+pub fn to_json<T: Serialize>(op: OpCode, d: T) -> String {
+    serde_json::to_string(&Outgoing { d, op }).expect("payloads serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_json, Incoming, SessionDescription, Speaking};
+    use crate::SpeakingFlags;
+    use twilight_model::voice::OpCode;
+
+    #[test]
+    fn speaking() {
+        let json = to_json(
+            OpCode::Speaking,
+            Speaking {
+                delay: 0,
+                flags: SpeakingFlags::MICROPHONE,
+                ssrc: 42,
+            },
+        );
+
+        assert_eq!(r#"{"d":{"delay":0,"speaking":1,"ssrc":42},"op":5}"#, json);
+    }
+
+    #[test]
+    fn session_description() {
+        let key = (0..32).map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+        let json = format!(r#"{{"op":4,"d":{{"mode":"xsalsa20_poly1305","secret_key":[{key}]}}}}"#);
+
+        let incoming = serde_json::from_str::<Incoming>(&json).unwrap();
+        assert_eq!(OpCode::SessionDescription, incoming.op);
+
+        let description = serde_json::from_value::<SessionDescription>(incoming.d).unwrap();
+        assert_eq!("xsalsa20_poly1305", description.mode);
+        assert_eq!(31, description.secret_key[31]);
+    }
+}
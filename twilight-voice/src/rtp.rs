@@ -0,0 +1,123 @@
+//! RTP headers and IP discovery packets.
+
+use crate::crypto::RTP_HEADER_LEN;
+
+/// Length of IP discovery packets.
+pub const DISCOVERY_LEN: usize = 74;
+
+/// Length of the address field of IP discovery packets.
+const ADDRESS_LEN: usize = 64;
+
+/// RTP payload type of Opus audio.
+const PAYLOAD_TYPE: u8 = 0x78;
+
+/// RTP version 2 without padding, extensions or CSRCs.
+const VERSION: u8 = 0x80;
+
+/// Samples per channel of a 20 millisecond frame at 48 kHz.
+const SAMPLES_PER_FRAME: u32 = 960;
+
+/// Sequence and timestamp of the RTP packets of a stream.
+#[derive(Debug)]
+pub struct Rtp {
+    sequence: u16,
+    ssrc: u32,
+    timestamp: u32,
+}
+
+impl Rtp {
+    /// Create the state of a stream, starting at a random sequence and
+    /// timestamp as recommended by RFC 3550.
+    pub fn new(ssrc: u32) -> Self {
+        Self {
+            sequence: rand::random(),
+            ssrc,
+            timestamp: rand::random(),
+        }
+    }
+
+    /// Header of the next packet, advancing the stream by a frame.
+    pub fn next_header(&mut self) -> [u8; RTP_HEADER_LEN] {
+        let mut header = [0; RTP_HEADER_LEN];
+        header[0] = VERSION;
+        header[1] = PAYLOAD_TYPE;
+        header[2..4].copy_from_slice(&self.sequence.to_be_bytes());
+        header[4..8].copy_from_slice(&self.timestamp.to_be_bytes());
+        header[8..].copy_from_slice(&self.ssrc.to_be_bytes());
+
+        self.sequence = self.sequence.wrapping_add(1);
+        self.timestamp = self.timestamp.wrapping_add(SAMPLES_PER_FRAME);
+
+        header
+    }
+
+    /// Skip a frame, such as one that wasn't sent in time.
+    pub fn skip(&mut self) {
+        self.timestamp = self.timestamp.wrapping_add(SAMPLES_PER_FRAME);
+    }
+}
+
+/// Create an IP discovery request of a stream.
+pub fn discovery_request(ssrc: u32) -> [u8; DISCOVERY_LEN] {
+    let mut packet = [0; DISCOVERY_LEN];
+    packet[..2].copy_from_slice(&1_u16.to_be_bytes());
+    packet[2..4].copy_from_slice(&70_u16.to_be_bytes());
+    packet[4..8].copy_from_slice(&ssrc.to_be_bytes());
+
+    packet
+}
+
+/// Parse the external address and port from an IP discovery response.
+pub fn parse_discovery_response(packet: &[u8]) -> Option<(String, u16)> {
+    if packet.len() < DISCOVERY_LEN || packet[..2] != 2_u16.to_be_bytes() {
+        return None;
+    }
+
+    let address = &packet[8..8 + ADDRESS_LEN];
+    let end = address.iter().position(|byte| *byte == 0)?;
+    let address = std::str::from_utf8(&address[..end]).ok()?.to_owned();
+
+    let port = u16::from_be_bytes([packet[72], packet[73]]);
+
+    Some((address, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{discovery_request, parse_discovery_response, Rtp, DISCOVERY_LEN};
+
+    #[test]
+    fn headers() {
+        let mut rtp = Rtp::new(42);
+        let first = rtp.next_header();
+        rtp.skip();
+        let second = rtp.next_header();
+
+        assert_eq!([0x80, 0x78], first[..2]);
+        assert_eq!([0, 0, 0, 42], first[8..]);
+
+        let sequence = |header: &[u8]| u16::from_be_bytes([header[2], header[3]]);
+        let timestamp =
+            |header: &[u8]| u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+
+        assert_eq!(sequence(&first).wrapping_add(1), sequence(&second));
+        assert_eq!(timestamp(&first).wrapping_add(1920), timestamp(&second));
+    }
+
+    #[test]
+    fn discovery() {
+        let request = discovery_request(42);
+        assert_eq!([0, 1, 0, 70, 0, 0, 0, 42], request[..8]);
+
+        let mut response = [0; DISCOVERY_LEN];
+        response[..2].copy_from_slice(&[0, 2]);
+        response[8..17].copy_from_slice(b"127.0.0.1");
+        response[72..].copy_from_slice(&5000_u16.to_be_bytes());
+
+        assert_eq!(
+            Some(("127.0.0.1".to_owned(), 5000)),
+            parse_discovery_response(&response)
+        );
+        assert_eq!(None, parse_discovery_response(&request));
+    }
+}
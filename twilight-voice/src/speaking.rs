@@ -0,0 +1,57 @@
+use bitflags::bitflags;
+use serde::{
+    de::{Deserialize, Deserializer},
+    ser::{Serialize, Serializer},
+};
+
+bitflags! {
+    /// Modes a user is speaking with.
+    ///
+    /// Discord doesn't play audio from users who aren't speaking, so
+    /// [`Connection::speaking`] must be called before sending audio.
+    ///
+    /// [`Connection::speaking`]: crate::Connection::speaking
+    pub struct SpeakingFlags: u8 {
+        /// Normal transmission of voice audio.
+        const MICROPHONE = 1;
+        /// Transmission of context audio for video, no speaking indicator.
+        const SOUNDSHARE = 1 << 1;
+        /// Priority speaker, lowering the audio of other speakers.
+        const PRIORITY = 1 << 2;
+    }
+}
+
+impl<'de> Deserialize<'de> for SpeakingFlags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_bits_truncate(u8::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for SpeakingFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(self.bits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpeakingFlags;
+    use static_assertions::{assert_impl_all, const_assert_eq};
+    use std::fmt::Debug;
+
+    assert_impl_all!(SpeakingFlags: Copy, Debug, Eq, Send, Sync);
+    const_assert_eq!(1, SpeakingFlags::MICROPHONE.bits());
+    const_assert_eq!(2, SpeakingFlags::SOUNDSHARE.bits());
+    const_assert_eq!(4, SpeakingFlags::PRIORITY.bits());
+
+    #[test]
+    fn serde() {
+        let flags = SpeakingFlags::MICROPHONE | SpeakingFlags::PRIORITY;
+
+        assert_eq!("5", serde_json::to_string(&flags).unwrap());
+        assert_eq!(flags, serde_json::from_str("5").unwrap());
+    }
+}
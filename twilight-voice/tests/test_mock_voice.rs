@@ -0,0 +1,123 @@
+use std::{error::Error, time::Duration};
+use tokio::time;
+use twilight_mock::voice::{MockVoiceServer, VoiceEvent};
+use twilight_model::id::Id;
+use twilight_voice::{
+    crypto::{Cipher, EncryptionMode},
+    Connection, ConnectionInfo, SpeakingFlags,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn info(server: &MockVoiceServer) -> ConnectionInfo {
+    ConnectionInfo::new(
+        Id::new(1),
+        Id::new(2),
+        "session".to_owned(),
+        server.endpoint(),
+        "token".to_owned(),
+    )
+}
+
+/// Wait for the next event of the server.
+async fn next_event(server: &mut MockVoiceServer) -> VoiceEvent {
+    time::timeout(TIMEOUT, server.next_event())
+        .await
+        .expect("event received in time")
+        .expect("server running")
+}
+
+#[tokio::test]
+async fn test_handshake_and_send_frame() -> Result<(), Box<dyn Error>> {
+    let mut server = MockVoiceServer::builder().ssrc(42).build().await?;
+    let mut connection = Connection::connect(&info(&server)).await?;
+
+    assert_eq!(42, connection.ssrc());
+    assert_eq!(
+        EncryptionMode::AeadXChaCha20Poly1305RtpSize,
+        connection.mode()
+    );
+
+    match next_event(&mut server).await {
+        VoiceEvent::Identify(data) => {
+            assert_eq!("1", data["server_id"]);
+            assert_eq!("session", data["session_id"]);
+            assert_eq!("token", data["token"]);
+            assert_eq!("2", data["user_id"]);
+        }
+        other => panic!("expected identify, got {other:?}"),
+    }
+
+    match next_event(&mut server).await {
+        VoiceEvent::SelectProtocol { address, mode, .. } => {
+            assert_eq!("127.0.0.1", address);
+            assert_eq!(connection.mode().name(), mode);
+        }
+        other => panic!("expected select protocol, got {other:?}"),
+    }
+
+    connection.speaking(SpeakingFlags::MICROPHONE)?;
+    assert_eq!(
+        VoiceEvent::Speaking {
+            speaking: SpeakingFlags::MICROPHONE.bits(),
+            ssrc: 42,
+        },
+        next_event(&mut server).await
+    );
+
+    let frame = [1, 2, 3, 4, 5];
+    connection.send_frame(&frame).await?;
+
+    let packet = match next_event(&mut server).await {
+        VoiceEvent::Packet(packet) => packet,
+        other => panic!("expected packet, got {other:?}"),
+    };
+    let cipher = Cipher::new(connection.mode(), server.secret_key());
+    assert_eq!(frame.to_vec(), cipher.decrypt(&packet)?);
+
+    connection.close();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_modes_unsupported() -> Result<(), Box<dyn Error>> {
+    let server = MockVoiceServer::builder()
+        .modes(["unknown"])
+        .build()
+        .await?;
+
+    let error = Connection::connect(&info(&server))
+        .await
+        .expect_err("no supported modes");
+
+    assert!(matches!(
+        error.kind(),
+        twilight_voice::ConnectErrorType::EncryptionModeUnsupported { offered }
+            if offered == &["unknown".to_owned()]
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_legacy_mode() -> Result<(), Box<dyn Error>> {
+    let mut server = MockVoiceServer::builder()
+        .modes(["xsalsa20_poly1305"])
+        .build()
+        .await?;
+    let mut connection = Connection::connect(&info(&server)).await?;
+    assert_eq!(EncryptionMode::Xsalsa20Poly1305, connection.mode());
+
+    connection.send_frame(&[9, 9, 9]).await?;
+
+    let packet = loop {
+        if let VoiceEvent::Packet(packet) = next_event(&mut server).await {
+            break packet;
+        }
+    };
+    let cipher = Cipher::new(connection.mode(), server.secret_key());
+    assert_eq!(vec![9, 9, 9], cipher.decrypt(&packet)?);
+
+    Ok(())
+}